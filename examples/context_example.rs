@@ -2,10 +2,10 @@
 use crow::{
     glutin::{
         event::{Event, WindowEvent},
-        event_loop::{ControlFlow, EventLoop},
+        event_loop::EventLoop,
         window::WindowBuilder,
     },
-    Context, DrawConfig, Texture,
+    Context, DrawConfig, Flow, Texture,
 };
 
 fn main() -> Result<(), crow::Error> {
@@ -14,20 +14,22 @@ fn main() -> Result<(), crow::Error> {
 
     let texture = Texture::load(&mut ctx, "./textures/player.png")?;
 
-    event_loop.run(
-        move |event: Event<()>, _window_target: _, control_flow: &mut ControlFlow| match event {
-            Event::WindowEvent {
+    ctx.run(event_loop, move |ctx, surface, events| {
+        ctx.clear_color(surface, (0.4, 0.4, 0.8, 1.0));
+        ctx.draw(surface, &texture, (100, 150), &DrawConfig::default());
+
+        for event in events {
+            if let Event::WindowEvent {
                 event: WindowEvent::CloseRequested,
                 ..
-            } => *control_flow = ControlFlow::Exit,
-            Event::MainEventsCleared => ctx.window().request_redraw(),
-            Event::RedrawRequested(_) => {
-                let mut surface = ctx.surface();
-                ctx.clear_color(&mut surface, (0.4, 0.4, 0.8, 1.0));
-                ctx.draw(&mut surface, &texture, (100, 150), &DrawConfig::default());
-                ctx.present(surface).unwrap();
+            } = event
+            {
+                return Flow::Exit(());
             }
-            _ => (),
-        },
-    )
+        }
+
+        Flow::Continue
+    })?;
+
+    Ok(())
 }
@@ -5,7 +5,7 @@ use crow::{
         event_loop::{ControlFlow, EventLoop},
         window::WindowBuilder,
     },
-    Context,
+    Context, IRect,
 };
 
 fn main() -> Result<(), crow::Error> {
@@ -26,8 +26,16 @@ fn main() -> Result<(), crow::Error> {
                 ctx.debug_line(&mut surface, (50, 50), (150, 100), (1.0, 0.0, 0.0, 1.0));
                 ctx.debug_line(&mut surface, (150, 200), (50, 150), (1.0, 0.0, 0.0, 1.0));
 
-                ctx.debug_rectangle(&mut surface, (50, 250), (150, 300), (1.0, 0.0, 0.0, 1.0));
-                ctx.debug_rectangle(&mut surface, (150, 400), (50, 350), (1.0, 0.0, 0.0, 1.0));
+                ctx.debug_rectangle(
+                    &mut surface,
+                    IRect::new((50, 250), (101, 51)),
+                    (1.0, 0.0, 0.0, 1.0),
+                );
+                ctx.debug_rectangle(
+                    &mut surface,
+                    IRect::from_corners((150, 400), (50, 350)),
+                    (1.0, 0.0, 0.0, 1.0),
+                );
                 ctx.present(surface).unwrap();
             }
             _ => (),
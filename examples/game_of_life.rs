@@ -46,12 +46,11 @@ fn main() -> Result<(), crow::Error> {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                 WindowEvent::CursorMoved { position, .. } => mouse_position = position.into(),
-                WindowEvent::KeyboardInput { input, .. } => {
+                WindowEvent::KeyboardInput { input, .. }
                     if input.state == ElementState::Pressed
-                        && input.virtual_keycode == Some(VirtualKeyCode::Space)
-                    {
-                        step(&mut cells);
-                    }
+                        && input.virtual_keycode == Some(VirtualKeyCode::Space) =>
+                {
+                    step(&mut cells);
                 }
                 WindowEvent::MouseInput {
                     state: ElementState::Pressed,
@@ -143,7 +142,7 @@ fn step(
 
     for (x, row) in cells.iter().enumerate() {
         for (y, &cell) in row.iter().enumerate() {
-            let n = neighbors(&cells, x as isize, y as isize);
+            let n = neighbors(cells, x as isize, y as isize);
             if cell {
                 if n != 2 && n != 3 {
                     diffs.push((x, y));
@@ -0,0 +1,85 @@
+//! Conway's Game Of Life simulated on the GPU using `CellularAutomaton`.
+//!
+//! To place a cell press the left mouse button,
+//! to pause or resume the simulation press space.
+
+use crow::{
+    glutin::{
+        dpi::LogicalSize,
+        event::{ElementState, Event, MouseButton, VirtualKeyCode, WindowEvent},
+        event_loop::EventLoop,
+        window::WindowBuilder,
+    },
+    target::Scaled,
+    CellularAutomaton, Context, DrawConfig, Flow, Rule,
+};
+
+const WINDOW_WIDTH: u32 = 1080;
+const WINDOW_HEIGHT: u32 = 720;
+const CELL_SIZE: u32 = 4;
+
+fn main() -> Result<(), crow::Error> {
+    let event_loop = EventLoop::new();
+    let mut ctx = Context::new(
+        WindowBuilder::new().with_inner_size(LogicalSize::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
+        &event_loop,
+    )?;
+
+    let mut life = CellularAutomaton::new(
+        &mut ctx,
+        (WINDOW_WIDTH / CELL_SIZE, WINDOW_HEIGHT / CELL_SIZE),
+        Rule::GameOfLife,
+    )?;
+    // an r-pentomino in the center of the window
+    let (x, y) = (
+        (WINDOW_WIDTH / CELL_SIZE / 2) as i32,
+        (WINDOW_HEIGHT / CELL_SIZE / 2) as i32,
+    );
+    for &(dx, dy) in [(1, 0), (0, 1), (1, 1), (1, 2), (2, 2)].iter() {
+        life.set_cell(&mut ctx, (x + dx, y + dy), (1.0, 1.0, 1.0, 1.0));
+    }
+
+    let mut mouse_position = (0, 0);
+    let mut running = true;
+    ctx.run(event_loop, move |ctx, surface, events| {
+        for event in events {
+            if let Event::WindowEvent { event, .. } = event {
+                match event {
+                    WindowEvent::CloseRequested => return Flow::Exit(()),
+                    WindowEvent::CursorMoved { position, .. } => {
+                        mouse_position = (*position).into()
+                    }
+                    WindowEvent::KeyboardInput { input, .. }
+                        if input.state == ElementState::Pressed
+                            && input.virtual_keycode == Some(VirtualKeyCode::Space) =>
+                    {
+                        running = !running
+                    }
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        let (x, y): (i32, i32) = mouse_position;
+                        let position = (
+                            x / CELL_SIZE as i32,
+                            (WINDOW_HEIGHT as i32 - 1 - y) / CELL_SIZE as i32,
+                        );
+                        life.set_cell(ctx, position, (1.0, 1.0, 1.0, 1.0));
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        if running {
+            life.step(ctx);
+        }
+
+        let mut surface = Scaled::new(surface, (CELL_SIZE, CELL_SIZE));
+        ctx.draw(&mut surface, life.state(), (0, 0), &DrawConfig::default());
+        Flow::Continue
+    })?;
+
+    Ok(())
+}
@@ -19,7 +19,7 @@ use crow::{
         window::WindowBuilder,
     },
     target::{Offset, Scaled},
-    Context, DrawConfig, DrawTarget, Texture,
+    Context, DrawConfig, DrawTarget, Rect, Texture,
 };
 
 use rand::Rng;
@@ -99,7 +99,7 @@ fn main() -> Result<(), crow::Error> {
                 position += 1;
 
                 if let Some(first) = rectangles.front() {
-                    if (first.position.0 + first.size.0 as i32) < position as i32 {
+                    if (first.position.0 + first.size.0 as i32) < position {
                         rectangles.pop_front();
                     }
                 }
@@ -164,8 +164,10 @@ pub fn draw_rectangles(
             );
         }
 
-        let vertical_section =
-            vertical.get_section((0, vertical.height() - height), (vertical.width(), height));
+        let vertical_section = vertical.get_section(Rect::new(
+            (0, vertical.height() - height),
+            (vertical.width(), height),
+        ));
         ctx.draw(
             surface,
             &vertical_section,
@@ -219,10 +221,10 @@ pub fn draw_rectangles(
             );
         }
 
-        let horizontal_section = horizontal.get_section(
+        let horizontal_section = horizontal.get_section(Rect::new(
             (horizontal.width() - horizontal_pos, 0),
             (horizontal_pos, horizontal.height()),
-        );
+        ));
         ctx.draw(
             surface,
             &horizontal_section,
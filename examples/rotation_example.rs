@@ -23,7 +23,7 @@ fn main() -> Result<(), crow::Error> {
     // But be aware, a rotation that isn't a multiple of 90 will make
     // the texture look distorted and thus not pixel perfect.
     let mut a: f32 = 0.0;
-    let mut rotation: i32 = 0;
+    let mut rotation: f32 = 0.0;
 
     let mut delta = Instant::now();
     event_loop.run(
@@ -41,7 +41,7 @@ fn main() -> Result<(), crow::Error> {
                     &cat1,
                     (200, 300),
                     &DrawConfig {
-                        rotation: rotation,
+                        rotation,
                         ..DrawConfig::default()
                     },
                 );
@@ -52,6 +52,8 @@ fn main() -> Result<(), crow::Error> {
                     (400, 50),
                     &DrawConfig {
                         rotation: -rotation,
+                        // rotate around the lower left corner
+                        rotation_center: Some((0.0, 0.0)),
                         ..DrawConfig::default()
                     },
                 );
@@ -61,8 +63,8 @@ fn main() -> Result<(), crow::Error> {
                 let duration = delta.elapsed();
                 a += (duration.as_micros()) as f32 / 16666.0;
 
-                // Pixel perfect rotation
-                rotation = a as i32;
+                // Rotate in whole degree steps
+                rotation = a.floor();
 
                 // Reset delta
                 delta = Instant::now();
@@ -0,0 +1,108 @@
+use crate::{Context, DrawConfig, NewTextureError, Texture};
+
+/// Keeps a fading copy of the previous frames, which can be used for trails and motion blur.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Afterimage, Context, DrawConfig, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let player = Texture::load(&mut ctx, "./textures/player.png")?;
+///
+/// let mut afterimage = Afterimage::new();
+/// afterimage.enable(0.8);
+///
+/// let frame = afterimage.render(&mut ctx, (320, 240), (0.0, 0.0, 0.0, 1.0), |ctx, frame| {
+///     ctx.draw(frame, &player, (100, 100), &DrawConfig::default());
+/// })?;
+///
+/// let mut surface = ctx.surface();
+/// ctx.draw(&mut surface, frame, (0, 0), &DrawConfig::default());
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Afterimage {
+    decay: Option<f32>,
+    /// The result of the last call to `render`.
+    current: Option<Texture>,
+    /// The texture used by the call to `render` before that, which is reused for the next frame.
+    spare: Option<Texture>,
+}
+
+impl Afterimage {
+    /// Creates a new `Afterimage`, which is disabled by default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables the afterimage, drawing the previous frame with an opacity
+    /// of `decay` before each new frame.
+    ///
+    /// A `decay` of `0.0` causes the previous frame to immediately disappear,
+    /// while a `decay` close to `1.0` results in long trails.
+    pub fn enable(&mut self, decay: f32) {
+        self.decay = Some(decay);
+    }
+
+    /// Disables the afterimage, forgetting all previous frames.
+    pub fn disable(&mut self) {
+        self.decay = None;
+        self.current = None;
+    }
+
+    /// Returns the current `decay`, or `None` if the afterimage is disabled.
+    pub fn decay(&self) -> Option<f32> {
+        self.decay
+    }
+
+    /// Renders a new frame of the given `dimensions`.
+    ///
+    /// The frame is first cleared using `background`, after which the fading
+    /// previous frame is drawn in case the afterimage is enabled. Then `f`
+    /// is called to draw the content of this frame.
+    ///
+    /// The alpha channel of the frame is only set by `background` and `f`, so
+    /// the returned texture can be drawn using the default `DrawConfig`.
+    pub fn render<F>(
+        &mut self,
+        ctx: &mut Context,
+        dimensions: impl Into<(u32, u32)>,
+        background: (f32, f32, f32, f32),
+        f: F,
+    ) -> Result<&Texture, NewTextureError>
+    where
+        F: FnOnce(&mut Context, &mut Texture),
+    {
+        let dimensions = dimensions.into();
+        let mut frame = match self.spare.take() {
+            Some(texture) if texture.dimensions() == dimensions => texture,
+            _ => Texture::new(ctx, dimensions)?,
+        };
+
+        ctx.clear_color(&mut frame, background);
+        match (self.decay, &self.current) {
+            (Some(decay), Some(previous)) if previous.dimensions() == dimensions => {
+                let config = DrawConfig {
+                    color_modulation: [
+                        [1.0, 0.0, 0.0, 0.0],
+                        [0.0, 1.0, 0.0, 0.0],
+                        [0.0, 0.0, 1.0, 0.0],
+                        [0.0, 0.0, 0.0, decay],
+                    ],
+                    // blending would otherwise also apply to the alpha channel
+                    color_mask: (true, true, true, false),
+                    ..Default::default()
+                };
+                ctx.draw(&mut frame, previous, (0, 0), &config);
+            }
+            _ => (),
+        }
+
+        f(ctx, &mut frame);
+
+        self.spare = self.current.take();
+        Ok(self.current.get_or_insert(frame))
+    }
+}
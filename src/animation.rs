@@ -0,0 +1,219 @@
+use std::time::Duration;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Rect, Texture};
+
+/// How an [`Animation`] continues after its last frame.
+///
+/// [`Animation`]: struct.Animation.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LoopMode {
+    /// Stops at the last frame.
+    Once,
+    /// Starts again at the first frame.
+    #[default]
+    Loop,
+    /// Plays the frames in reverse order before starting again,
+    /// without repeating the first and last frame.
+    PingPong,
+}
+
+/// A single frame of an [`Animation`].
+///
+/// [`Animation`]: struct.Animation.html
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    /// The texture shown during this frame, usually a section of a sprite sheet.
+    pub texture: Texture,
+    /// How long this frame is shown.
+    pub duration: Duration,
+}
+
+/// A sequence of textures, each shown for a given duration.
+///
+/// An animation does not store how much of it has already been played,
+/// so it can be shared between multiple [`AnimationState`]s.
+///
+/// ```rust, no_run
+/// use std::time::Duration;
+///
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Animation, AnimationState, Context, DrawConfig, LoopMode, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+///
+/// let sheet = Texture::load(&mut ctx, "./textures/player_walk.png")?;
+/// let walk = Animation::from_grid(&sheet, (16, 16), Duration::from_millis(100), LoopMode::Loop)
+///     .expect("the sheet is smaller than a single frame");
+/// let mut state = AnimationState::new();
+///
+/// ctx.frame(|ctx, surface| {
+///     let texture = state.tick(&walk, Duration::from_millis(16));
+///     ctx.draw(surface, texture, (100, 100), &DrawConfig::default());
+/// })?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`AnimationState`]: struct.AnimationState.html
+#[derive(Debug, Clone)]
+pub struct Animation {
+    /// All frames of the animation in order, which is never empty.
+    frames: Vec<AnimationFrame>,
+    mode: LoopMode,
+}
+
+impl Animation {
+    /// Creates an animation showing `frames` in order.
+    ///
+    /// Returns `None` if `frames` is empty.
+    pub fn new(frames: Vec<AnimationFrame>, mode: LoopMode) -> Option<Self> {
+        if frames.is_empty() {
+            None
+        } else {
+            Some(Animation { frames, mode })
+        }
+    }
+
+    /// Creates an animation using each cell of `texture` with the size `frame_size` as a frame,
+    /// starting at the top left and going from left to right and from top to bottom.
+    ///
+    /// Each frame is shown for `frame_duration`. Incomplete cells at the right and bottom
+    /// of `texture` are ignored.
+    ///
+    /// Returns `None` if `frame_size` is greater than the size of `texture`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `frame_size` is zero.
+    pub fn from_grid(
+        texture: &Texture,
+        frame_size: impl Into<(u32, u32)>,
+        frame_duration: Duration,
+        mode: LoopMode,
+    ) -> Option<Self> {
+        let (width, height) = frame_size.into();
+        assert!(width != 0 && height != 0, "the frame size must not be zero");
+        let (columns, rows) = (texture.width() / width, texture.height() / height);
+
+        let mut frames = Vec::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                let section = Rect::new((column * width, row * height), (width, height));
+                frames.push(AnimationFrame {
+                    texture: texture.get_section(section.flip_y(texture.height())),
+                    duration: frame_duration,
+                });
+            }
+        }
+        Self::new(frames, mode)
+    }
+
+    /// Returns all frames of the animation in order, which is never empty.
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    /// Returns how the animation continues after its last frame.
+    pub fn mode(&self) -> LoopMode {
+        self.mode
+    }
+
+    /// Sets how the animation continues after its last frame.
+    pub fn set_mode(&mut self, mode: LoopMode) {
+        self.mode = mode;
+    }
+
+    /// Returns the indices of the frames of a single cycle in the order they are shown.
+    fn sequence(&self) -> impl Iterator<Item = usize> {
+        let len = self.frames.len();
+        let back = match self.mode {
+            LoopMode::Once | LoopMode::Loop => 1..1,
+            LoopMode::PingPong => 1..len.saturating_sub(1),
+        };
+        (0..len).chain(back.rev())
+    }
+
+    /// Returns the time needed to show each frame once, or each frame
+    /// except the first and last one twice when using `LoopMode::PingPong`.
+    pub fn cycle_duration(&self) -> Duration {
+        self.sequence().map(|i| self.frames[i].duration).sum()
+    }
+
+    /// Returns the frame shown at `time` after starting the animation.
+    ///
+    /// If the duration of each frame is zero, the first frame is returned,
+    /// or the last one when using `LoopMode::Once`.
+    pub fn frame_at(&self, time: Duration) -> &AnimationFrame {
+        let cycle = self.cycle_duration();
+        let time = match self.mode {
+            LoopMode::Once if time >= cycle => return self.frames.last().unwrap(),
+            LoopMode::Once => time.as_nanos(),
+            LoopMode::Loop | LoopMode::PingPong if cycle == Duration::from_secs(0) => {
+                return &self.frames[0]
+            }
+            LoopMode::Loop | LoopMode::PingPong => time.as_nanos() % cycle.as_nanos(),
+        };
+
+        let mut remaining = time;
+        for i in self.sequence() {
+            let duration = self.frames[i].duration.as_nanos();
+            if remaining < duration {
+                return &self.frames[i];
+            }
+            remaining -= duration;
+        }
+        bug!("the time is always smaller than the length of a cycle")
+    }
+
+    /// Returns `true` if the animation uses `LoopMode::Once` and has been completed at `time`.
+    pub fn is_finished(&self, time: Duration) -> bool {
+        self.mode == LoopMode::Once && time >= self.cycle_duration()
+    }
+}
+
+/// How much of an [`Animation`] has already been played.
+///
+/// [`Animation`]: struct.Animation.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AnimationState {
+    time: Duration,
+}
+
+impl AnimationState {
+    /// Creates a state at the start of an animation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the animation by `delta`, returning the texture which should be drawn.
+    pub fn tick<'a>(&mut self, animation: &'a Animation, delta: Duration) -> &'a Texture {
+        self.time += delta;
+        self.current(animation)
+    }
+
+    /// Returns the texture which should currently be drawn.
+    pub fn current<'a>(&self, animation: &'a Animation) -> &'a Texture {
+        &animation.frame_at(self.time).texture
+    }
+
+    /// Returns the time since the start of the animation.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns `true` if `animation` uses `LoopMode::Once` and has been completed.
+    pub fn is_finished(&self, animation: &Animation) -> bool {
+        animation.is_finished(self.time)
+    }
+
+    /// Restarts the animation.
+    pub fn reset(&mut self) {
+        self.time = Duration::from_secs(0);
+    }
+}
@@ -0,0 +1,523 @@
+//! Sprites created using the [Aseprite] pixel art editor.
+//!
+//! [`Sprite`] reads `.aseprite` and `.ase` files directly, so sprite sheets do not have to be
+//! exported by hand. The visible layers of each frame are combined into a single image using
+//! the `Normal` blend mode, other blend modes are treated as `Normal`. Tilemap layers are not
+//! supported.
+//!
+//! Sprites exported as a sprite sheet together with a JSON file can be loaded
+//! using `texture_packer::SpriteSheet` instead.
+//!
+//! ```rust, no_run
+//! use std::time::Duration;
+//!
+//! use crow::{
+//!     aseprite::Sprite,
+//!     glutin::{event_loop::EventLoop, window::WindowBuilder},
+//!     AnimationState, Context, DrawConfig,
+//! };
+//!
+//! let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+//!
+//! let player = Sprite::load("./sprites/player.aseprite")?.build_animation(&mut ctx)?;
+//! let walk = player.animation(Some("walk")).unwrap();
+//! let mut state = AnimationState::new();
+//!
+//! ctx.frame(|ctx, surface| {
+//!     let texture = state.tick(&walk, Duration::from_millis(16));
+//!     ctx.draw(surface, texture, (100, 100), &DrawConfig::default());
+//! })?;
+//! # Ok::<(), crow::Error>(())
+//! ```
+//!
+//! [Aseprite]: https://www.aseprite.org
+//! [`Sprite`]: struct.Sprite.html
+use std::{fs, path::Path, time::Duration};
+
+use image::{Rgba, RgbaImage};
+
+use crate::{
+    texture_packer::{Direction, FrameTag, TaggedAnimation},
+    AnimationFrame, Context, LoadAsepriteError, NewTextureError, Rect, Texture,
+};
+
+/// The frames and tags of an Aseprite file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Sprite {
+    /// The width of each frame.
+    pub width: u32,
+    /// The height of each frame.
+    pub height: u32,
+    /// All frames in order.
+    pub frames: Vec<Frame>,
+    /// Named ranges of `frames`.
+    pub tags: Vec<FrameTag>,
+}
+
+/// A single frame of a [`Sprite`].
+///
+/// [`Sprite`]: struct.Sprite.html
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Frame {
+    /// All visible layers of this frame combined into a single image.
+    pub image: RgbaImage,
+    /// How long this frame is shown.
+    pub duration: Duration,
+}
+
+impl Sprite {
+    /// Loads the `.aseprite` or `.ase` file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Sprite, LoadAsepriteError> {
+        Sprite::parse(&fs::read(path)?)
+    }
+
+    /// Parses the content of an `.aseprite` or `.ase` file.
+    pub fn parse(bytes: &[u8]) -> Result<Sprite, LoadAsepriteError> {
+        let mut header = Reader::new(bytes).take(HEADER_SIZE)?;
+        header.skip(4)?;
+        if header.u16()? != HEADER_MAGIC {
+            return Err(invalid("the file is not an Aseprite file"));
+        }
+        let frame_count = header.u16()?;
+        let width = u32::from(header.u16()?);
+        let height = u32::from(header.u16()?);
+        let depth = match header.u16()? {
+            32 => ColorDepth::Rgba,
+            16 => ColorDepth::Grayscale,
+            8 => ColorDepth::Indexed,
+            depth => return Err(invalid(&format!("unsupported color depth: {}", depth))),
+        };
+        let uses_layer_opacity = header.u32()? & 1 != 0;
+        header.skip(10)?;
+        let transparent_index = header.u8()?;
+        if width == 0 || height == 0 {
+            return Err(invalid("the sprite must not be empty"));
+        }
+
+        let mut parser = Parser {
+            depth,
+            transparent_index,
+            uses_layer_opacity,
+            palette: vec![[0; 4]; 256],
+            layers: Vec::new(),
+            groups: Vec::new(),
+            tags: Vec::new(),
+        };
+        let mut frames = Vec::with_capacity(usize::from(frame_count));
+        let mut cels: Vec<Vec<Cel>> = Vec::with_capacity(usize::from(frame_count));
+        let mut reader = Reader::new(&bytes[HEADER_SIZE..]);
+        for _ in 0..frame_count {
+            let size = reader.u32()? as usize;
+            let mut frame = reader.take(size.saturating_sub(4))?;
+            if frame.u16()? != FRAME_MAGIC {
+                return Err(invalid("invalid frame header"));
+            }
+            let old_chunk_count = frame.u16()?;
+            let duration = Duration::from_millis(u64::from(frame.u16()?));
+            frame.skip(2)?;
+            let chunk_count = match frame.u32()? {
+                0 => u32::from(old_chunk_count),
+                count => count,
+            };
+
+            let mut frame_cels = Vec::new();
+            for _ in 0..chunk_count {
+                let size = frame.u32()? as usize;
+                let mut chunk = frame.take(size.saturating_sub(4))?;
+                let kind = chunk.u16()?;
+                if let Some(cel) = parser.chunk(kind, &mut chunk, &cels)? {
+                    frame_cels.push(cel);
+                }
+            }
+            cels.push(frame_cels);
+            frames.push(duration);
+        }
+
+        let frames = frames
+            .into_iter()
+            .zip(cels.iter())
+            .map(|(duration, cels)| Frame {
+                image: parser.compose(width, height, cels),
+                duration,
+            })
+            .collect();
+
+        let tags = parser.tags;
+        if tags
+            .iter()
+            .any(|tag| tag.from > tag.to || tag.to >= usize::from(frame_count))
+        {
+            return Err(invalid("a tag contains frames which do not exist"));
+        }
+
+        Ok(Sprite {
+            width,
+            height,
+            frames,
+            tags,
+        })
+    }
+
+    /// Stores all frames in a single texture and returns each frame as a section of
+    /// this texture, together with the `tags` of this sprite.
+    pub fn build_animation(&self, ctx: &mut Context) -> Result<TaggedAnimation, NewTextureError> {
+        let count = self.frames.len() as u32;
+        let mut columns = 1;
+        while columns * columns < count {
+            columns += 1;
+        }
+        let rows = (count + columns - 1) / columns;
+
+        let mut sheet = RgbaImage::new(self.width * columns, self.height * rows.max(1));
+        let mut sections = Vec::with_capacity(self.frames.len());
+        for (i, frame) in self.frames.iter().enumerate() {
+            let (column, row) = (i as u32 % columns, i as u32 / columns);
+            let (x, y) = (column * self.width, row * self.height);
+            for (px, py, &pixel) in frame.image.enumerate_pixels() {
+                sheet.put_pixel(x + px, y + py, pixel);
+            }
+            sections.push(Rect::new((x, y), (self.width, self.height)));
+        }
+
+        let height = sheet.height();
+        let texture = Texture::from_image(ctx, sheet)?;
+        let frames = self
+            .frames
+            .iter()
+            .zip(sections)
+            .map(|(frame, section)| AnimationFrame {
+                texture: texture.get_section(section.flip_y(height)),
+                duration: frame.duration,
+            })
+            .collect();
+        Ok(TaggedAnimation {
+            frames,
+            tags: self.tags.clone(),
+        })
+    }
+}
+
+const HEADER_SIZE: usize = 128;
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+
+const OLD_PALETTE_CHUNK: u16 = 0x0004;
+const LAYER_CHUNK: u16 = 0x2004;
+const CEL_CHUNK: u16 = 0x2005;
+const TAGS_CHUNK: u16 = 0x2018;
+const PALETTE_CHUNK: u16 = 0x2019;
+
+fn invalid(reason: &str) -> LoadAsepriteError {
+    LoadAsepriteError::InvalidFile {
+        reason: reason.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorDepth {
+    Rgba,
+    Grayscale,
+    Indexed,
+}
+
+impl ColorDepth {
+    fn bytes_per_pixel(self) -> usize {
+        match self {
+            ColorDepth::Rgba => 4,
+            ColorDepth::Grayscale => 2,
+            ColorDepth::Indexed => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Layer {
+    /// If this layer and all of its parents are visible.
+    visible: bool,
+    opacity: u8,
+    /// Indexed background layers do not have a transparent color.
+    background: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Cel {
+    layer: usize,
+    position: (i32, i32),
+    opacity: u8,
+    z_index: i16,
+    image: RgbaImage,
+}
+
+struct Parser {
+    depth: ColorDepth,
+    transparent_index: u8,
+    uses_layer_opacity: bool,
+    palette: Vec<[u8; 4]>,
+    layers: Vec<Layer>,
+    /// The visibility of the groups containing the next layer.
+    groups: Vec<bool>,
+    tags: Vec<FrameTag>,
+}
+
+impl Parser {
+    /// Parses a single chunk, returning the contained cel, if any.
+    ///
+    /// `previous` contains the cels of all previous frames, which may be used by linked cels.
+    fn chunk(
+        &mut self,
+        kind: u16,
+        chunk: &mut Reader<'_>,
+        previous: &[Vec<Cel>],
+    ) -> Result<Option<Cel>, LoadAsepriteError> {
+        match kind {
+            LAYER_CHUNK => {
+                let flags = chunk.u16()?;
+                let layer_type = chunk.u16()?;
+                let level = usize::from(chunk.u16()?);
+                chunk.skip(6)?;
+                let opacity = chunk.u8()?;
+                if layer_type == 2 {
+                    return Err(invalid("tilemap layers are not supported"));
+                }
+
+                self.groups.truncate(level);
+                let visible = flags & 1 != 0 && self.groups.iter().all(|&visible| visible);
+                if layer_type == 1 {
+                    self.groups.push(visible);
+                }
+                self.layers.push(Layer {
+                    visible,
+                    opacity: if self.uses_layer_opacity {
+                        opacity
+                    } else {
+                        255
+                    },
+                    background: flags & 8 != 0,
+                });
+                Ok(None)
+            }
+            CEL_CHUNK => {
+                let layer = usize::from(chunk.u16()?);
+                let position = (i32::from(chunk.i16()?), i32::from(chunk.i16()?));
+                let opacity = chunk.u8()?;
+                let cel_type = chunk.u16()?;
+                let z_index = chunk.i16()?;
+                chunk.skip(5)?;
+                if layer >= self.layers.len() {
+                    return Err(invalid("a cel uses a layer which does not exist"));
+                }
+
+                let image = match cel_type {
+                    0 | 2 => {
+                        let width = u32::from(chunk.u16()?);
+                        let height = u32::from(chunk.u16()?);
+                        let data = chunk.rest();
+                        if cel_type == 0 {
+                            self.image(layer, width, height, data)?
+                        } else {
+                            let data = miniz_oxide::inflate::decompress_to_vec_zlib(data)
+                                .map_err(|_| invalid("invalid compressed cel"))?;
+                            self.image(layer, width, height, &data)?
+                        }
+                    }
+                    1 => {
+                        let frame = usize::from(chunk.u16()?);
+                        let linked = previous
+                            .get(frame)
+                            .and_then(|cels| cels.iter().find(|cel| cel.layer == layer))
+                            .ok_or_else(|| {
+                                invalid("a linked cel uses a cel which does not exist")
+                            })?;
+                        linked.image.clone()
+                    }
+                    3 => return Err(invalid("tilemap cels are not supported")),
+                    _ => return Err(invalid("unknown cel type")),
+                };
+                Ok(Some(Cel {
+                    layer,
+                    position,
+                    opacity,
+                    z_index,
+                    image,
+                }))
+            }
+            TAGS_CHUNK => {
+                let count = chunk.u16()?;
+                chunk.skip(8)?;
+                for _ in 0..count {
+                    let from = usize::from(chunk.u16()?);
+                    let to = usize::from(chunk.u16()?);
+                    let direction = match chunk.u8()? {
+                        0 => Direction::Forward,
+                        1 => Direction::Reverse,
+                        2 => Direction::PingPong,
+                        3 => Direction::PingPongReverse,
+                        _ => return Err(invalid("unknown tag direction")),
+                    };
+                    chunk.skip(12)?;
+                    let name = chunk.string()?;
+                    self.tags.push(FrameTag {
+                        name,
+                        from,
+                        to,
+                        direction,
+                    });
+                }
+                Ok(None)
+            }
+            PALETTE_CHUNK => {
+                chunk.skip(4)?;
+                let first = chunk.u32()? as usize;
+                let last = chunk.u32()? as usize;
+                chunk.skip(8)?;
+                for index in first..=last {
+                    let flags = chunk.u16()?;
+                    let color = [chunk.u8()?, chunk.u8()?, chunk.u8()?, chunk.u8()?];
+                    if flags & 1 != 0 {
+                        chunk.string()?;
+                    }
+                    if let Some(entry) = self.palette.get_mut(index) {
+                        *entry = color;
+                    }
+                }
+                Ok(None)
+            }
+            OLD_PALETTE_CHUNK => {
+                let packets = chunk.u16()?;
+                let mut index = 0;
+                for _ in 0..packets {
+                    index += usize::from(chunk.u8()?);
+                    let count = match chunk.u8()? {
+                        0 => 256,
+                        count => usize::from(count),
+                    };
+                    for _ in 0..count {
+                        let color = [chunk.u8()?, chunk.u8()?, chunk.u8()?, 255];
+                        if let Some(entry) = self.palette.get_mut(index) {
+                            *entry = color;
+                        }
+                        index += 1;
+                    }
+                }
+                Ok(None)
+            }
+            // user data, slices, color profiles and other chunks are not needed to draw the sprite
+            _ => Ok(None),
+        }
+    }
+
+    /// Converts the pixels of a cel on `layer` to an image.
+    fn image(
+        &self,
+        layer: usize,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Result<RgbaImage, LoadAsepriteError> {
+        let bytes_per_pixel = self.depth.bytes_per_pixel();
+        if data.len() != width as usize * height as usize * bytes_per_pixel {
+            return Err(invalid("the size of a cel does not match its pixel data"));
+        }
+
+        let background = self.layers[layer].background;
+        let pixels = data
+            .chunks_exact(bytes_per_pixel)
+            .flat_map(|pixel| match *pixel {
+                [r, g, b, a] => [r, g, b, a],
+                [value, a] => [value, value, value, a],
+                [index] if index == self.transparent_index && !background => [0; 4],
+                [index] => self.palette[usize::from(index)],
+                _ => unreachable!(),
+            })
+            .collect();
+        Ok(RgbaImage::from_raw(width, height, pixels).unwrap())
+    }
+
+    /// Combines the `cels` of a single frame into one image.
+    fn compose(&self, width: u32, height: u32, cels: &[Cel]) -> RgbaImage {
+        let mut cels: Vec<&Cel> = cels
+            .iter()
+            .filter(|cel| self.layers[cel.layer].visible)
+            .collect();
+        // cels with a higher z-index are drawn above the layers following their own
+        cels.sort_by_key(|cel| (cel.layer as i64 + i64::from(cel.z_index), cel.z_index));
+
+        let mut image = RgbaImage::new(width, height);
+        for cel in cels {
+            let opacity =
+                f32::from(cel.opacity) / 255.0 * f32::from(self.layers[cel.layer].opacity) / 255.0;
+            for (x, y, &Rgba(source)) in cel.image.enumerate_pixels() {
+                let (x, y) = (cel.position.0 + x as i32, cel.position.1 + y as i32);
+                if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                    continue;
+                }
+
+                let target = image.get_pixel_mut(x as u32, y as u32);
+                let alpha = f32::from(source[3]) / 255.0 * opacity;
+                let below = f32::from(target[3]) / 255.0 * (1.0 - alpha);
+                let result = alpha + below;
+                if result > 0.0 {
+                    for c in 0..3 {
+                        let color = f32::from(source[c]) * alpha + f32::from(target[c]) * below;
+                        target[c] = (color / result).round() as u8;
+                    }
+                }
+                target[3] = (result * 255.0).round() as u8;
+            }
+        }
+        image
+    }
+}
+
+/// Reads the little endian values of an Aseprite file.
+#[derive(Debug, Clone, Copy)]
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    fn take(&mut self, len: usize) -> Result<Reader<'a>, LoadAsepriteError> {
+        if len > self.bytes.len() {
+            return Err(invalid("unexpected end of file"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(Reader::new(taken))
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), LoadAsepriteError> {
+        self.take(len).map(drop)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, LoadAsepriteError> {
+        Ok(self.take(1)?.bytes[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, LoadAsepriteError> {
+        let bytes = self.take(2)?.bytes;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, LoadAsepriteError> {
+        self.u16().map(|value| value as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, LoadAsepriteError> {
+        let bytes = self.take(4)?.bytes;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, LoadAsepriteError> {
+        let len = usize::from(self.u16()?);
+        let bytes = self.take(len)?.bytes;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("a string is not valid UTF-8"))
+    }
+}
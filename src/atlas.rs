@@ -0,0 +1,219 @@
+use std::path::Path;
+
+use image::{imageops, RgbaImage};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Context, LoadTextureError, NewTextureError, Rect, Subsystem, Texture};
+
+/// The layout used by [`Atlas`] and `Texture::load_sprite_sheet`.
+///
+/// [`Atlas`]: struct.Atlas.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct AtlasConfig {
+    /// The number of transparent pixels between two entries of the atlas.
+    pub padding: u32,
+    /// The number of times the outermost pixels of each entry are duplicated around it.
+    ///
+    /// Draw calls which sample slightly outside of an entry, for example when
+    /// using a fractional `transform`, then use the color of the closest edge
+    /// instead of the neighboring entry.
+    pub extrusion: u32,
+}
+
+impl Default for AtlasConfig {
+    fn default() -> Self {
+        AtlasConfig {
+            padding: 1,
+            extrusion: 1,
+        }
+    }
+}
+
+/// Combines multiple images into a single texture, returning each image as a section of it.
+///
+/// Drawing sections of the same texture is faster than using separate textures,
+/// especially when using a [`SpriteBatch`].
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Atlas, AtlasConfig, Context,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+///
+/// let mut atlas = Atlas::new(AtlasConfig::default());
+/// let player = atlas.push(crow::image::open("./textures/player.png")?.to_rgba8());
+/// let tree = atlas.push(crow::image::open("./textures/tree.png")?.to_rgba8());
+/// let textures = atlas.build(&mut ctx)?;
+///
+/// let (player, tree) = (&textures[player], &textures[tree]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`SpriteBatch`]: struct.SpriteBatch.html
+#[derive(Debug, Clone, Default)]
+pub struct Atlas {
+    config: AtlasConfig,
+    images: Vec<RgbaImage>,
+}
+
+impl Atlas {
+    /// Creates an empty atlas using the given `config`.
+    pub fn new(config: AtlasConfig) -> Self {
+        Atlas {
+            config,
+            images: Vec::new(),
+        }
+    }
+
+    /// Adds `image` to the atlas, returning its index in the result of `Atlas::build`.
+    pub fn push(&mut self, image: RgbaImage) -> usize {
+        self.images.push(image);
+        self.images.len() - 1
+    }
+
+    /// Returns the number of images in this atlas.
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Returns `true` if this atlas does not contain any images.
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Creates the texture containing all images, returning each image as a section of it.
+    pub fn build(self, ctx: &mut Context) -> Result<Vec<Texture>, NewTextureError> {
+        let AtlasConfig { padding, extrusion } = self.config;
+        let border = 2 * extrusion + padding;
+        let cell = |image: &RgbaImage| (image.width() + border, image.height() + border);
+
+        // the entries are placed in rows, starting with the tallest entries
+        let area: u64 = self
+            .images
+            .iter()
+            .map(|image| {
+                let (w, h) = cell(image);
+                u64::from(w) * u64::from(h)
+            })
+            .sum();
+        let widest = self.images.iter().map(|image| cell(image).0).max();
+        let max_width = ctx.maximum_texture_size().0 + padding;
+        let row_width = ((area as f64).sqrt().ceil() as u32)
+            .max(widest.unwrap_or(1))
+            .min(max_width);
+
+        let mut order = (0..self.images.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| std::cmp::Reverse(self.images[i].height()));
+
+        let mut positions = vec![(0, 0); self.images.len()];
+        let (mut x, mut y, mut row_height, mut width) = (0, 0, 0, 0);
+        for &i in order.iter() {
+            let (w, h) = cell(&self.images[i]);
+            if x != 0 && x + w > row_width {
+                x = 0;
+                y += row_height;
+                row_height = 0;
+            }
+
+            positions[i] = (x + extrusion, y + extrusion);
+            x += w;
+            width = width.max(x);
+            row_height = row_height.max(h);
+        }
+
+        // the padding after the last entry of each row and column is not needed
+        let dimensions = (
+            width.saturating_sub(padding).max(1),
+            (y + row_height).saturating_sub(padding).max(1),
+        );
+        let mut atlas = RgbaImage::new(dimensions.0, dimensions.1);
+        for (image, &position) in self.images.iter().zip(positions.iter()) {
+            extrude(&mut atlas, image, position, extrusion);
+        }
+
+        let texture = ctx.in_subsystem(Subsystem::Atlas, |ctx| Texture::from_image(ctx, atlas))?;
+        Ok(self
+            .images
+            .iter()
+            .zip(positions)
+            .map(|(image, position)| {
+                // textures start at the bottom left while images start at the top left
+                texture.get_section(Rect::new(position, image.dimensions()).flip_y(dimensions.1))
+            })
+            .collect())
+    }
+}
+
+/// Copies `image` into `atlas` at `position`, duplicating its outermost pixels `extrusion` times.
+fn extrude(atlas: &mut RgbaImage, image: &RgbaImage, position: (u32, u32), extrusion: u32) {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let extrusion = i64::from(extrusion);
+    for y in -extrusion..i64::from(height) + extrusion {
+        for x in -extrusion..i64::from(width) + extrusion {
+            let source = (
+                x.max(0).min(i64::from(width) - 1) as u32,
+                y.max(0).min(i64::from(height) - 1) as u32,
+            );
+            atlas.put_pixel(
+                (i64::from(position.0) + x) as u32,
+                (i64::from(position.1) + y) as u32,
+                *image.get_pixel(source.0, source.1),
+            );
+        }
+    }
+}
+
+impl Texture {
+    /// Loads a sprite sheet located at `path`, returning each frame of the given `frame_size`
+    /// as a section of a texture laid out according to `config`.
+    ///
+    /// The frames are returned in reading order, starting at the top left of the image.
+    /// Incomplete frames at the right and bottom edge of the image are ignored.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if either the width or height of `frame_size` is zero.
+    pub fn load_sprite_sheet<P: AsRef<Path>>(
+        ctx: &mut Context,
+        path: P,
+        frame_size: impl Into<(u32, u32)>,
+        config: &AtlasConfig,
+    ) -> Result<Vec<Texture>, LoadTextureError> {
+        let frame_size = frame_size.into();
+        assert!(
+            frame_size.0 != 0 && frame_size.1 != 0,
+            "invalid frame size: {:?}",
+            frame_size
+        );
+
+        let image = image::open(path)
+            .map_err(LoadTextureError::ImageError)?
+            .to_rgba8();
+        let (columns, rows) = (image.width() / frame_size.0, image.height() / frame_size.1);
+
+        let mut atlas = Atlas::new(*config);
+        for row in 0..rows {
+            for column in 0..columns {
+                let frame = imageops::crop_imm(
+                    &image,
+                    column * frame_size.0,
+                    row * frame_size.1,
+                    frame_size.0,
+                    frame_size.1,
+                );
+                atlas.push(frame.to_image());
+            }
+        }
+
+        Ok(atlas.build(ctx)?)
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(target_os = "macos")]
+use glutin::platform::macos::{RequestUserAttentionType, WindowExtMacOS};
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+use glutin::platform::unix::WindowExtUnix;
+
+use crate::Context;
+
+/// How urgently the attention of the user is requested, see [`Context::request_attention`].
+///
+/// [`Context::request_attention`]: struct.Context.html#method.request_attention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Attention {
+    /// Notifies the user once, for example by bouncing the dock icon a single time on macOS.
+    #[default]
+    Informational,
+    /// Notifies the user until the window is focused.
+    Critical,
+}
+
+impl Context {
+    /// Requests the attention of the user, for example after a long running task finished.
+    ///
+    /// This does nothing if the window is already focused. Whether the window is focused
+    /// is updated by [`Context::handle_event`], which is called by [`Context::run`]. Until
+    /// the first `WindowEvent::Focused`, the window is assumed to be focused. The request
+    /// is cancelled once the window is focused again.
+    ///
+    /// The platforms differ in how the request is shown:
+    ///
+    /// - macOS bounces the dock icon, either once or until the window is focused.
+    /// - X11 sets the urgency hint of the window, which is usually shown by highlighting
+    ///   its taskbar entry. This does not differentiate between the types of `attention`.
+    /// - All other platforms, including Windows and Wayland, currently ignore this request,
+    ///   as the used version of the windowing library does not support it there. Flashing
+    ///   the taskbar entry on Windows would require `FlashWindowEx`.
+    ///
+    /// Showing progress in the taskbar entry of the window is not supported on any
+    /// platform for the same reason.
+    ///
+    /// This does nothing if the context was created using `Context::new_headless`.
+    ///
+    /// [`Context::handle_event`]: struct.Context.html#method.handle_event
+    /// [`Context::run`]: struct.Context.html#method.run
+    pub fn request_attention(&mut self, attention: Attention) {
+        let window = match self.try_window() {
+            Some(window) if !self.focused => window,
+            _ => return,
+        };
+
+        #[cfg(target_os = "macos")]
+        window.request_user_attention(match attention {
+            Attention::Informational => RequestUserAttentionType::Informational,
+            Attention::Critical => RequestUserAttentionType::Critical,
+        });
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+        ))]
+        window.set_urgent(true);
+        // unused on platforms which ignore this request
+        let _ = (window, attention);
+        self.attention_requested = true;
+    }
+
+    /// Cancels a previous `Context::request_attention` once the window is focused.
+    pub(crate) fn clear_attention(&mut self) {
+        if self.attention_requested {
+            self.attention_requested = false;
+            // macOS stops bouncing the dock icon by itself
+            #[cfg(any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+            ))]
+            if let Some(window) = self.try_window() {
+                window.set_urgent(false);
+            }
+        }
+    }
+}
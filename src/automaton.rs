@@ -0,0 +1,181 @@
+use std::rc::Rc;
+
+use crate::{backend::Uniform, Context, DrawConfig, Error, ShaderError, Texture};
+
+const HEADER: &str = include_str!("backend/shader/fragment_automaton.glsl");
+const GAME_OF_LIFE: &str = include_str!("backend/shader/automaton_life.glsl");
+
+/// The rule used by a [`CellularAutomaton`] to compute the next state of each cell.
+///
+/// [`CellularAutomaton`]: struct.CellularAutomaton.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// Conway's Game of Life, cells are alive if their red channel is greater than `0.5`.
+    ///
+    /// Living cells are white and dead cells are black.
+    GameOfLife,
+    /// A GLSL function `vec4 rule()` returning the next state of the current cell.
+    ///
+    /// It can use the functions `vec4 cell(ivec2 offset)`, which returns the current
+    /// state of the cell at `offset` relative to the current cell, and `int alive_neighbors()`,
+    /// which returns the number of neighbors whose red channel is greater than `0.5`.
+    /// All `ShaderGlobals` of the context can be used by declaring them as uniforms.
+    ///
+    /// ```rust
+    /// use crow::Rule;
+    ///
+    /// // cells spread to all neighbors and never die
+    /// let rule = Rule::Custom(String::from(
+    ///     "vec4 rule() {
+    ///         bool alive = cell(ivec2(0)).r > 0.5 || alive_neighbors() > 0;
+    ///         return alive ? vec4(1.0) : vec4(0.0, 0.0, 0.0, 1.0);
+    ///     }",
+    /// ));
+    /// ```
+    Custom(String),
+}
+
+/// A cellular automaton, where each pixel of a texture is a cell which
+/// is updated on the GPU using a [`Rule`].
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     target::Scaled,
+///     CellularAutomaton, Context, DrawConfig, Rule,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let mut life = CellularAutomaton::new(&mut ctx, (64, 64), Rule::GameOfLife)?;
+/// // a glider
+/// for &cell in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter() {
+///     life.set_cell(&mut ctx, cell, (1.0, 1.0, 1.0, 1.0));
+/// }
+///
+/// life.step(&mut ctx);
+/// let mut surface = Scaled::new(ctx.surface(), (8, 8));
+/// ctx.draw(&mut surface, life.state(), (0, 0), &DrawConfig::default());
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`Rule`]: enum.Rule.html
+#[derive(Debug)]
+pub struct CellularAutomaton {
+    current: Texture,
+    next: Texture,
+    /// The complete fragment shader of `rule`, keeping its program alive.
+    shader: Rc<str>,
+    rule: Rule,
+    wrap: bool,
+}
+
+fn shader(rule: &Rule) -> String {
+    let rule = match rule {
+        Rule::GameOfLife => GAME_OF_LIFE,
+        Rule::Custom(rule) => rule,
+    };
+    format!("{}\n{}", HEADER, rule)
+}
+
+impl CellularAutomaton {
+    /// Creates a new automaton of the given `dimensions`, in which all cells are dead.
+    ///
+    /// The edges of the automaton wrap around by default.
+    /// Returns `Error::ShaderError` if `rule` is not valid GLSL.
+    pub fn new(ctx: &mut Context, dimensions: (u32, u32), rule: Rule) -> Result<Self, Error> {
+        let shader = ctx.backend.retain_pass_program(&shader(&rule))?;
+        let mut current = Texture::new(ctx, dimensions)?;
+        ctx.clear_color(&mut current, (0.0, 0.0, 0.0, 1.0));
+        let next = Texture::new(ctx, dimensions)?;
+        Ok(CellularAutomaton {
+            current,
+            next,
+            shader,
+            rule,
+            wrap: true,
+        })
+    }
+
+    /// Creates a new automaton, copying its initial state from `state`.
+    pub fn from_texture(ctx: &mut Context, state: &Texture, rule: Rule) -> Result<Self, Error> {
+        let mut automaton = Self::new(ctx, state.dimensions(), rule)?;
+        ctx.draw(
+            &mut automaton.current,
+            state,
+            (0, 0),
+            &DrawConfig::default(),
+        );
+        Ok(automaton)
+    }
+
+    /// Returns the texture containing the current state of all cells.
+    pub fn state(&self) -> &Texture {
+        &self.current
+    }
+
+    /// Returns the dimensions of this automaton.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.current.dimensions()
+    }
+
+    /// Returns the rule used by this automaton.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Changes the rule used for all following steps.
+    ///
+    /// The previous rule is kept if `rule` is not valid GLSL.
+    pub fn set_rule(&mut self, ctx: &mut Context, rule: Rule) -> Result<(), ShaderError> {
+        self.shader = ctx.backend.retain_pass_program(&shader(&rule))?;
+        self.rule = rule;
+        Ok(())
+    }
+
+    /// Sets whether cells at the edges of this automaton are neighbors of the cells at the
+    /// opposite edge. If disabled, cells outside of this automaton are `(0.0, 0.0, 0.0, 0.0)`.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Returns whether cells at the edges of this automaton are neighbors
+    /// of the cells at the opposite edge.
+    pub fn wrap(&self) -> bool {
+        self.wrap
+    }
+
+    /// Sets the state of the cell at `position`.
+    pub fn set_cell(
+        &mut self,
+        ctx: &mut Context,
+        position: (i32, i32),
+        state: (f32, f32, f32, f32),
+    ) {
+        ctx.clear_region(&mut self.current, (position, (1, 1)), state);
+    }
+
+    /// Advances the automaton by a single step.
+    ///
+    /// All `ShaderGlobals` of the context are available to the rule.
+    pub fn step(&mut self, ctx: &mut Context) {
+        if let Some(next) = self.next.prepare_as_draw_target(ctx) {
+            let mut uniforms = vec![("wrap", Uniform::UInt(u32::from(self.wrap)))];
+            uniforms.extend(ctx.shader_globals.pass_uniforms(|name| name == "wrap"));
+            ctx.backend.run_pass(
+                &self.shader,
+                next.framebuffer_id,
+                next.dimensions,
+                Some(&self.current.inner),
+                &uniforms,
+            );
+            std::mem::swap(&mut self.current, &mut self.next);
+        }
+    }
+
+    /// Advances the automaton by `steps` steps.
+    pub fn steps(&mut self, ctx: &mut Context, steps: u32) {
+        for _ in 0..steps {
+            self.step(ctx);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::{cmp, mem};
+
+use crate::backend::{draw::BatchSprite, ring::SpriteVertex};
+
+/// The maximum number of unused sprite buffers kept after each frame.
+const MAX_SPRITE_BUFFERS: usize = 4;
+
+/// Buffers for transient allocations while drawing a frame.
+///
+/// Buffers are taken from the arena and returned once they are no longer needed,
+/// keeping their capacity. This means that drawing does not allocate once the
+/// buffers are large enough for the most expensive frame.
+#[derive(Debug, Default)]
+pub struct FrameArena {
+    /// Unused buffers for queued sprites, all of which are empty.
+    sprites: Vec<Vec<BatchSprite>>,
+    /// The buffer used to build the vertices of a batch, empty while not in use.
+    vertices: Vec<SpriteVertex>,
+}
+
+impl FrameArena {
+    pub fn take_sprites(&mut self) -> Vec<BatchSprite> {
+        self.sprites.pop().unwrap_or_default()
+    }
+
+    pub fn recycle_sprites(&mut self, mut sprites: Vec<BatchSprite>) {
+        sprites.clear();
+        self.sprites.push(sprites);
+    }
+
+    pub fn take_vertices(&mut self) -> Vec<SpriteVertex> {
+        mem::take(&mut self.vertices)
+    }
+
+    pub fn recycle_vertices(&mut self, mut vertices: Vec<SpriteVertex>) {
+        if vertices.capacity() > self.vertices.capacity() {
+            vertices.clear();
+            self.vertices = vertices;
+        }
+    }
+
+    /// Called at the end of each frame, releasing excess sprite buffers.
+    ///
+    /// Returns the number of bytes which remain reserved.
+    pub fn reset(&mut self) -> usize {
+        // prefer keeping the largest buffers
+        self.sprites
+            .sort_by_key(|sprites| cmp::Reverse(sprites.capacity()));
+        self.sprites.truncate(MAX_SPRITE_BUFFERS);
+        self.sprites
+            .iter()
+            .map(|sprites| sprites.capacity() * mem::size_of::<BatchSprite>())
+            .sum::<usize>()
+            + self.vertices.capacity() * mem::size_of::<SpriteVertex>()
+    }
+}
@@ -1,29 +1,304 @@
+use std::{mem, rc::Rc};
+
 use gl::types::*;
 
 use crate::{
-    backend::{tex::RawTexture, Backend},
-    DrawConfig,
+    backend::{
+        pass::Uniform,
+        ring::{SpriteVertex, Vertex, SEGMENT_SIZE},
+        tex::RawTexture,
+        Backend,
+    },
+    BlendMode, DrawConfig, DrawError, Sampler,
 };
 
+/// A section of the source texture drawn by `Backend::draw_batch`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchSprite {
+    pub texture_offset: (u32, u32),
+    pub dimensions: (u32, u32),
+    pub position: (i32, i32),
+    pub config: DrawConfig,
+}
+
+/// Draw calls which have not yet been submitted to OpenGL.
+#[derive(Debug)]
+pub struct QueuedDraws {
+    target_framebuffer: GLuint,
+    target_dimensions: (u32, u32),
+    hidpi: u32,
+    /// Keeps the source texture alive until the draw calls are submitted.
+    source_texture: Rc<RawTexture>,
+    sprites: Vec<BatchSprite>,
+}
+
+/// The state which has to be equal for sprites drawn in the same draw call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BatchState {
+    blend_mode: BlendMode,
+    color_mask: (bool, bool, bool, bool),
+    depth_active: bool,
+    sampler: Option<Sampler>,
+}
+
+fn check_draw(
+    target_dimensions: (u32, u32),
+    source_dimensions: (u32, u32),
+    draw_config: &DrawConfig,
+) -> Result<(), DrawError> {
+    if source_dimensions.0 == 0 || source_dimensions.1 == 0 {
+        Err(DrawError::ZeroSizedTexture)
+    } else if target_dimensions.0 == 0 || target_dimensions.1 == 0 {
+        Err(DrawError::TargetTooSmall {
+            width: target_dimensions.0,
+            height: target_dimensions.1,
+        })
+    } else {
+        match draw_config.validate().into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
 impl Backend {
+    /// Handles the result of validating a draw call, returning `false` if it should be skipped.
+    fn should_draw(&mut self, res: Result<(), DrawError>) -> bool {
+        if let Err(err) = res {
+            match self.try_draw {
+                Some(_) => {
+                    self.report_error(err);
+                    return false;
+                }
+                // These draw calls would not change the target, so we can ignore them.
+                None if matches!(
+                    err,
+                    DrawError::ZeroSizedTexture | DrawError::TargetTooSmall { .. }
+                ) =>
+                {
+                    return false
+                }
+                None if err == DrawError::ForeignTexture => {
+                    self.report_error(err);
+                    return false;
+                }
+                None => {
+                    if cfg!(debug_assertions) {
+                        warn!("{}, drawing using `DrawConfig::sanitize` instead", err);
+                    } else {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn start_try_draw(&mut self) {
+        self.try_draw = Some(Ok(()));
+    }
+
+    pub fn finish_try_draw(&mut self) -> Result<(), DrawError> {
+        self.try_draw.take().unwrap_or(Ok(()))
+    }
+
+    /// Queues a draw call, which is submitted once a different
+    /// target or source texture is used, or `Backend::flush_draws` is called.
     #[allow(clippy::too_many_arguments)]
     pub fn draw(
         &mut self,
         target_framebuffer: GLuint,
         target_dimensions: (u32, u32),
         hidpi: u32,
-        source_texture: &RawTexture,
+        source_texture: &Rc<RawTexture>,
         source_texture_offset: (u32, u32),
         source_dimensions: (u32, u32),
         source_position: (i32, i32),
         draw_config: &DrawConfig,
     ) {
+        self.assert_current();
+        let res = self
+            .validate(source_texture)
+            .and_then(|()| check_draw(target_dimensions, source_dimensions, draw_config));
+
+        if !self.should_draw(res) {
+            return;
+        }
+
+        self.queue(target_framebuffer, target_dimensions, hidpi, source_texture)
+            .push(BatchSprite {
+                texture_offset: source_texture_offset,
+                dimensions: source_dimensions,
+                position: source_position,
+                config: draw_config.sanitize(),
+            });
+        self.counts().draws += 1;
+    }
+
+    /// Queues all `sprites`, which are sections of `source_texture`.
+    pub fn draw_batch(
+        &mut self,
+        target_framebuffer: GLuint,
+        target_dimensions: (u32, u32),
+        hidpi: u32,
+        source_texture: &Rc<RawTexture>,
+        sprites: &[BatchSprite],
+    ) {
+        self.assert_current();
+        let res = self.validate(source_texture);
+        if !self.should_draw(res) {
+            return;
+        }
+
+        for sprite in sprites {
+            let res = check_draw(target_dimensions, sprite.dimensions, &sprite.config);
+            if self.should_draw(res) {
+                self.queue(target_framebuffer, target_dimensions, hidpi, source_texture)
+                    .push(BatchSprite {
+                        config: sprite.config.sanitize(),
+                        ..sprite.clone()
+                    });
+                self.counts().draws += 1;
+            }
+        }
+    }
+
+    /// Returns the queue for draw calls with the given target and source,
+    /// submitting all previously queued draw calls if they differ.
+    fn queue(
+        &mut self,
+        target_framebuffer: GLuint,
+        target_dimensions: (u32, u32),
+        hidpi: u32,
+        source_texture: &Rc<RawTexture>,
+    ) -> &mut Vec<BatchSprite> {
+        // the name of a deleted framebuffer may be reused by `target_framebuffer`
+        self.delete_dropped_framebuffers();
+        let matches = matches!(&self.queued, Some(queued)
+            if queued.target_framebuffer == target_framebuffer
+                && queued.target_dimensions == target_dimensions
+                && queued.hidpi == hidpi
+                && Rc::ptr_eq(&queued.source_texture, source_texture));
+
+        if !matches {
+            self.flush_draws();
+            self.queued = Some(QueuedDraws {
+                target_framebuffer,
+                target_dimensions,
+                hidpi,
+                source_texture: Rc::clone(source_texture),
+                sprites: self.arena.take_sprites(),
+            });
+        }
+
+        // Masks are drawn into the stencil buffer of the first target.
+        self.prepare_stencil(target_framebuffer);
+        self.prepare_clip(target_framebuffer);
+        &mut self.queued.as_mut().unwrap().sprites
+    }
+
+    /// Submits all queued draw calls.
+    ///
+    /// This must be called before any other operation which
+    /// reads or modifies a framebuffer or texture.
+    pub fn flush_draws(&mut self) {
+        self.delete_dropped_framebuffers();
+        if let Some(queued) = self.queued.take() {
+            self.submit(queued);
+        }
+    }
+
+    /// Deletes the framebuffers of all dropped textures.
+    ///
+    /// Draw calls queued for one of them can't be observed anymore and are discarded.
+    /// Framebuffers are only deleted here, so their names can't be reused
+    /// by a different target while draw calls are still queued for them.
+    pub(super) fn delete_dropped_framebuffers(&mut self) {
+        let dropped = mem::take(&mut *self.dropped_framebuffers.borrow_mut());
+        if dropped.is_empty() {
+            return;
+        }
+
+        for (framebuffer, depth) in dropped {
+            if matches!(&self.queued, Some(queued) if queued.target_framebuffer == framebuffer) {
+                let queued = self.queued.take().unwrap();
+                self.arena.recycle_sprites(queued.sprites);
+            }
+
+            // deleting a bound framebuffer binds the default framebuffer
+            self.state.update_framebuffer(0);
+            // SAFETY: `n` is one and both objects were created by OpenGL
+            unsafe {
+                gl::DeleteFramebuffers(1, &framebuffer);
+                gl::DeleteRenderbuffers(1, &depth);
+            }
+        }
+    }
+
+    /// Submits all queued draw calls if they use `texture` as their source.
+    pub fn flush_draws_using(&mut self, texture: &Rc<RawTexture>) {
+        if let Some(queued) = &self.queued {
+            if Rc::ptr_eq(&queued.source_texture, texture) {
+                self.flush_draws();
+            }
+        }
+    }
+
+    fn submit(&mut self, queued: QueuedDraws) {
+        let QueuedDraws {
+            target_framebuffer,
+            target_dimensions,
+            hidpi,
+            source_texture,
+            sprites,
+        } = queued;
+
+        if let [sprite] = sprites.as_slice() {
+            self.draw_single(
+                target_framebuffer,
+                target_dimensions,
+                hidpi,
+                &source_texture,
+                sprite,
+            );
+        } else {
+            self.draw_sprites(
+                target_framebuffer,
+                target_dimensions,
+                hidpi,
+                &source_texture,
+                &sprites,
+            );
+        }
+        self.arena.recycle_sprites(sprites);
+    }
+
+    /// Draws a single sprite using the main program.
+    fn draw_single(
+        &mut self,
+        target_framebuffer: GLuint,
+        target_dimensions: (u32, u32),
+        hidpi: u32,
+        source_texture: &RawTexture,
+        sprite: &BatchSprite,
+    ) {
+        let draw_config = &sprite.config;
+        self.prepare_stencil(target_framebuffer);
+        self.prepare_clip(target_framebuffer);
+        let sampler = self.sampler_object(draw_config.sampler, source_texture.options.mipmaps);
         let s = &mut self.state;
         s.update_program(self.program.id);
         s.update_vao(self.program.vao);
         s.update_blend_mode(draw_config.blend_mode);
+        if draw_config.depth_only {
+            s.update_color_mask((false, false, false, false));
+        } else {
+            s.update_color_mask(draw_config.color_mask);
+        }
         s.update_framebuffer(target_framebuffer);
         s.update_texture(source_texture.id);
+        s.update_sampler(sampler);
         s.update_depth(draw_config.depth);
 
         s.update_color_modulation(draw_config.color_modulation);
@@ -32,11 +307,15 @@ impl Backend {
         s.update_viewport_dimensions(viewport_dimensions);
         s.update_source_scale(draw_config.scale);
         s.update_source_rotation(draw_config.rotation);
+        s.update_rotation_center(draw_config.rotation_center(sprite.dimensions));
+        s.update_transform(draw_config.effective_transform());
         s.update_source_texture_dimensions(source_texture.dimensions);
-        s.update_source_texture_offset(source_texture_offset);
-        s.update_source_position(source_position);
-        s.update_source_dimensions(source_dimensions);
+        s.update_source_texture_offset(sprite.texture_offset);
+        s.update_source_position(sprite.position);
+        s.update_source_dimensions(sprite.dimensions);
         s.update_invert_color(draw_config.invert_color);
+        s.update_premultiplied(draw_config.blend_mode == BlendMode::PremultipliedAlpha);
+        s.update_border_color(source_texture.border_color);
         s.update_flip_vertically(draw_config.flip_vertically);
         s.update_flip_horizontally(draw_config.flip_horizontally);
         unsafe {
@@ -49,6 +328,148 @@ impl Backend {
         }
     }
 
+    /// Draws all `sprites`, which are sections of `source_texture`, using as few draw calls as possible.
+    ///
+    /// Consecutive sprites are drawn together as long as they use the same blend mode,
+    /// color mask, sampler and either all or none of them use the depth buffer.
+    fn draw_sprites(
+        &mut self,
+        target_framebuffer: GLuint,
+        target_dimensions: (u32, u32),
+        hidpi: u32,
+        source_texture: &RawTexture,
+        sprites: &[BatchSprite],
+    ) {
+        self.prepare_stencil(target_framebuffer);
+        self.prepare_clip(target_framebuffer);
+        let program = self.batch_program.id;
+        let s = &mut self.state;
+        s.update_program(program);
+        s.update_vao(self.batch_program.vertices.vao);
+        s.update_framebuffer(target_framebuffer);
+        s.update_texture(source_texture.id);
+        let viewport_dimensions = (target_dimensions.0 * hidpi, target_dimensions.1 * hidpi);
+        s.update_viewport_dimensions(viewport_dimensions);
+        let border_color = source_texture.border_color;
+        s.update_pass_uniform(
+            program,
+            self.batch_program.use_border,
+            Uniform::UInt(border_color.is_some().into()),
+        );
+        if let Some((r, g, b, a)) = border_color {
+            s.update_pass_uniform(
+                program,
+                self.batch_program.border_color,
+                Uniform::Vec4(r, g, b, a),
+            );
+        }
+
+        let texture_dimensions = (
+            source_texture.dimensions.0 as f32,
+            source_texture.dimensions.1 as f32,
+        );
+        let mipmaps = source_texture.options.mipmaps;
+        let mut vertices = self.arena.take_vertices();
+        vertices.reserve((sprites.len() * 6).min(SEGMENT_SIZE));
+        let mut state = None;
+        for sprite in sprites {
+            let config = &sprite.config;
+            let sprite_state = BatchState {
+                blend_mode: config.blend_mode,
+                color_mask: if config.depth_only {
+                    (false, false, false, false)
+                } else {
+                    config.color_mask
+                },
+                depth_active: config.depth.is_some(),
+                sampler: config.sampler,
+            };
+            if state != Some(sprite_state) || vertices.len() + 6 > SEGMENT_SIZE {
+                self.flush_batch(&mut vertices);
+                let sampler = self.sampler_object(sprite_state.sampler, mipmaps);
+                let s = &mut self.state;
+                s.update_sampler(sampler);
+                s.update_blend_mode(sprite_state.blend_mode);
+                s.update_color_mask(sprite_state.color_mask);
+                if sprite_state.depth_active {
+                    s.enable_depth();
+                } else {
+                    s.disable_depth();
+                }
+                let premultiplied = sprite_state.blend_mode == BlendMode::PremultipliedAlpha;
+                s.update_pass_uniform(
+                    program,
+                    self.batch_program.premultiplied,
+                    Uniform::UInt(premultiplied.into()),
+                );
+                state = Some(sprite_state);
+            }
+
+            let (offset, size) = (
+                (
+                    sprite.texture_offset.0 as f32,
+                    sprite.texture_offset.1 as f32,
+                ),
+                (sprite.dimensions.0 as f32, sprite.dimensions.1 as f32),
+            );
+            let section = [
+                offset.0 / texture_dimensions.0,
+                offset.1 / texture_dimensions.1,
+                (offset.0 + size.0) / texture_dimensions.0,
+                (offset.1 + size.1) / texture_dimensions.1,
+            ];
+            // mirrors the computation in `vertex.glsl`
+            let vertex = |x: f32, y: f32| {
+                let (px, py) = config.corner(sprite.dimensions, (x, y));
+                let tx = if config.flip_horizontally { 1.0 - x } else { x };
+                let ty = if config.flip_vertically { 1.0 - y } else { y };
+                SpriteVertex {
+                    position: [
+                        (px + sprite.position.0 as f32) / target_dimensions.0 as f32 * 2.0 - 1.0,
+                        (py + sprite.position.1 as f32) / target_dimensions.1 as f32 * 2.0 - 1.0,
+                    ],
+                    tex_coords: [
+                        (offset.0 + size.0 * tx) / texture_dimensions.0,
+                        (offset.1 + size.1 * ty) / texture_dimensions.1,
+                    ],
+                    section,
+                    color_modulation: config.color_modulation,
+                    depth: config.depth.unwrap_or(0.0),
+                    invert_color: if config.invert_color { 1.0 } else { 0.0 },
+                }
+            };
+            let (a, b, c, d) = (
+                vertex(0.0, 0.0),
+                vertex(1.0, 0.0),
+                vertex(0.0, 1.0),
+                vertex(1.0, 1.0),
+            );
+            vertices.extend_from_slice(&[a, b, c, c, b, d]);
+        }
+
+        self.flush_batch(&mut vertices);
+        self.arena.recycle_vertices(vertices);
+    }
+
+    /// Draws and clears the `vertices` of a batch.
+    fn flush_batch(&mut self, vertices: &mut Vec<SpriteVertex>) {
+        if vertices.is_empty() {
+            return;
+        }
+
+        let first = self.batch_program.vertices.push(vertices);
+        unsafe {
+            // SAFETY:
+            // `gl::TRIANGLES` is an accepted value
+            // `count` is positive
+            // `first..first + count` are valid vertices of the bound vertex array
+            // The mapped vertex buffer is mapped using `gl::MAP_PERSISTENT_BIT`
+            // No geometry shader is active
+            gl::DrawArrays(gl::TRIANGLES, first, vertices.len() as GLsizei);
+        }
+        vertices.clear();
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn debug_draw(
         &mut self,
@@ -60,28 +481,122 @@ impl Backend {
         to: (i32, i32),
         color: (f32, f32, f32, f32),
     ) {
+        self.assert_current();
+        self.flush_draws();
+        self.prepare_stencil(target_framebuffer);
+        self.prepare_clip(target_framebuffer);
         let s = &mut self.state;
         s.update_program(self.debug_program.id);
-        s.update_vao(self.debug_program.vao[rectangle as usize]);
+        s.update_vao(self.debug_program.vertices.vao);
+        s.update_color_mask((true, true, true, true));
         s.update_framebuffer(target_framebuffer);
         let viewport_dimensions = (target_dimensions.0 * hidpi, target_dimensions.1 * hidpi);
         s.update_viewport_dimensions(viewport_dimensions);
         s.disable_depth();
-        s.update_debug_color(color);
-        let data = (
+
+        let (x1, y1) = (
             (from.0 as f32 + 0.5) / target_dimensions.0 as f32 * 2.0 - 1.0,
             (from.1 as f32 + 0.5) / target_dimensions.1 as f32 * 2.0 - 1.0,
+        );
+        let (x2, y2) = (
             (to.0 as f32 + 0.75) / target_dimensions.0 as f32 * 2.0 - 1.0,
             (to.1 as f32 + 0.75) / target_dimensions.1 as f32 * 2.0 - 1.0,
         );
-        s.update_debug_start_end(data);
+        let color = [color.0, color.1, color.2, color.3];
+        let vertex = |x, y| Vertex {
+            position: [x, y],
+            color,
+        };
+        let first;
+        let count;
+        if rectangle {
+            let vertices = [
+                vertex(x1, y1),
+                vertex(x1, y2),
+                vertex(x2, y2),
+                vertex(x2, y1),
+                vertex(x1, y1),
+            ];
+            first = self.debug_program.vertices.push(&vertices);
+            count = vertices.len();
+        } else {
+            let vertices = [vertex(x1, y1), vertex(x2, y2)];
+            first = self.debug_program.vertices.push(&vertices);
+            count = vertices.len();
+        }
+
         unsafe {
             // SAFETY:
             // `gl::LINE_STRIP` is an accepted value
             // `count` is positive
-            // We never map the data store of a buffer object
+            // `first..first + count` are valid vertices of the bound vertex array
+            // The mapped vertex buffer is mapped using `gl::MAP_PERSISTENT_BIT`
+            // No geometry shader is active
+            gl::DrawArrays(gl::LINE_STRIP, first, count as GLsizei);
+        }
+    }
+
+    /// Draws a filled quad covering the area which would be changed by drawing
+    /// a texture of the given size using `draw_config`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn debug_quad(
+        &mut self,
+        target_framebuffer: GLuint,
+        target_dimensions: (u32, u32),
+        hidpi: u32,
+        source_dimensions: (u32, u32),
+        source_position: (i32, i32),
+        draw_config: &DrawConfig,
+        color: (f32, f32, f32, f32),
+    ) {
+        self.assert_current();
+        self.flush_draws();
+        if let Err(err) = check_draw(target_dimensions, source_dimensions, draw_config) {
+            if self.try_draw.is_some() {
+                self.report_error(err);
+            }
+            return;
+        }
+
+        self.prepare_stencil(target_framebuffer);
+        self.prepare_clip(target_framebuffer);
+        let s = &mut self.state;
+        s.update_program(self.debug_program.id);
+        s.update_vao(self.debug_program.vertices.vao);
+        s.update_blend_mode(draw_config.blend_mode);
+        s.update_color_mask((true, true, true, true));
+        s.update_framebuffer(target_framebuffer);
+        let viewport_dimensions = (target_dimensions.0 * hidpi, target_dimensions.1 * hidpi);
+        s.update_viewport_dimensions(viewport_dimensions);
+        s.disable_depth();
+
+        let color = [color.0, color.1, color.2, color.3];
+        let vertex = |x: f32, y: f32| {
+            let (x, y) = draw_config.corner(source_dimensions, (x, y));
+            Vertex {
+                position: [
+                    (x + source_position.0 as f32) / target_dimensions.0 as f32 * 2.0 - 1.0,
+                    (y + source_position.1 as f32) / target_dimensions.1 as f32 * 2.0 - 1.0,
+                ],
+                color,
+            }
+        };
+        let vertices = [
+            vertex(0.0, 0.0),
+            vertex(1.0, 0.0),
+            vertex(0.0, 1.0),
+            vertex(1.0, 1.0),
+        ];
+        let first = self.debug_program.vertices.push(&vertices);
+
+        unsafe {
+            // SAFETY:
+            // `gl::TRIANGLE_STRIP` is an accepted value
+            // `count` is positive
+            // `first..first + count` are valid vertices of the bound vertex array
+            // The mapped vertex buffer is mapped using `gl::MAP_PERSISTENT_BIT`
             // No geometry shader is active
-            gl::DrawArrays(gl::LINE_STRIP, 0, if rectangle { 5 } else { 2 });
+            gl::DrawArrays(gl::TRIANGLE_STRIP, first, vertices.len() as GLsizei);
         }
     }
 }
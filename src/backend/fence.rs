@@ -0,0 +1,99 @@
+use std::{ptr, time::Duration};
+
+use gl::types::*;
+
+use crate::{backend::Backend, Context};
+
+/// A point in the GPU command stream, created using [`Context::insert_fence`].
+///
+/// Once all commands issued before the fence have been completed by the GPU,
+/// the fence is signaled.
+///
+/// [`Context::insert_fence`]: struct.Context.html#method.insert_fence
+#[derive(Debug)]
+pub struct Fence {
+    sync: GLsync,
+    generation: u64,
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        // All objects of a dropped context have already been freed.
+        if super::is_active(self.generation) {
+            // SAFETY: `self.sync` is a valid sync object
+            unsafe { gl::DeleteSync(self.sync) }
+        }
+    }
+}
+
+impl Fence {
+    pub(crate) fn new(backend: &mut Backend) -> Self {
+        backend.assert_current();
+        backend.flush_draws();
+        // SAFETY:
+        // `condition` is `gl::SYNC_GPU_COMMANDS_COMPLETE` and `flags` is zero
+        let sync = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+        if sync.is_null() {
+            bug!("unexpected error: {}", unsafe { gl::GetError() });
+        }
+
+        Fence {
+            sync,
+            generation: backend.generation(),
+        }
+    }
+
+    fn check_context(&self, ctx: &Context) {
+        ctx.backend.assert_current();
+        if self.generation != ctx.backend.generation() {
+            panic!(
+                "Tried to use a `Fence` with a different `Context` than the one which created it"
+            );
+        }
+    }
+
+    /// Blocks until all GPU commands issued before this fence have been completed.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `self` was created by a different `Context`.
+    pub fn wait(&self, ctx: &mut Context) {
+        while !self.wait_timeout(ctx, Duration::from_millis(1)) {}
+    }
+
+    /// Blocks until either all GPU commands issued before this fence have been completed
+    /// or `timeout` has passed, returning `true` if the fence has been signaled.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `self` was created by a different `Context`.
+    pub fn wait_timeout(&self, ctx: &mut Context, timeout: Duration) -> bool {
+        self.check_context(ctx);
+        let timeout = timeout.as_nanos().min(u128::from(u64::MAX)) as GLuint64;
+        // SAFETY:
+        // `self.sync` is a valid sync object
+        // `gl::SYNC_FLUSH_COMMANDS_BIT` is a valid flag
+        match unsafe { gl::ClientWaitSync(self.sync, gl::SYNC_FLUSH_COMMANDS_BIT, timeout) } {
+            gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => true,
+            gl::TIMEOUT_EXPIRED => false,
+            _ => bug!("unexpected error: {}", unsafe { gl::GetError() }),
+        }
+    }
+
+    /// Returns `true` if all GPU commands issued before this fence have been completed,
+    /// without blocking.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `self` was created by a different `Context`.
+    pub fn is_signaled(&self, ctx: &mut Context) -> bool {
+        self.check_context(ctx);
+        let mut status = 0;
+        // SAFETY:
+        // `self.sync` is a valid sync object
+        // `gl::SYNC_STATUS` is a valid `pname`
+        // `bufSize` is 1 and `values` points to a single `GLint`
+        unsafe { gl::GetSynciv(self.sync, gl::SYNC_STATUS, 1, ptr::null_mut(), &mut status) };
+        status == gl::SIGNALED as GLint
+    }
+}
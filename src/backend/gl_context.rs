@@ -0,0 +1,248 @@
+use std::ffi::c_void;
+
+use gl::types::*;
+use glutin::{
+    dpi::PhysicalSize, window::Window, ContextError, ContextWrapper, PossiblyCurrent, Rect,
+};
+
+use crate::{NewContextError, VsyncError};
+
+/// The OpenGL context used by the backend.
+#[derive(Debug)]
+pub enum GlContext {
+    Windowed(ContextWrapper<PossiblyCurrent, Window>),
+    /// A context without a window, see `Context::new_headless`.
+    ///
+    /// OSMesa ignores the requested depth and stencil bits, so the
+    /// backend renders into an offscreen framebuffer with the given size instead,
+    /// see `offscreen_framebuffer`.
+    Headless(glutin::Context<PossiblyCurrent>, PhysicalSize<u32>),
+}
+
+impl GlContext {
+    pub fn window(&self) -> Option<&Window> {
+        match self {
+            GlContext::Windowed(context) => Some(context.window()),
+            GlContext::Headless(..) => None,
+        }
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        match self {
+            GlContext::Windowed(context) => context.window().scale_factor(),
+            GlContext::Headless(..) => 1.0,
+        }
+    }
+
+    /// The size of the default framebuffer in physical pixels.
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        match self {
+            GlContext::Windowed(context) => context.window().inner_size(),
+            &GlContext::Headless(_, size) => size,
+        }
+    }
+
+    pub fn get_proc_address(&self, symbol: &str) -> *const c_void {
+        match self {
+            GlContext::Windowed(context) => context.get_proc_address(symbol),
+            GlContext::Headless(context, _) => context.get_proc_address(symbol),
+        }
+    }
+
+    pub fn is_current(&self) -> bool {
+        match self {
+            GlContext::Windowed(context) => context.is_current(),
+            GlContext::Headless(context, _) => context.is_current(),
+        }
+    }
+
+    /// Changes the swap interval of the window, which can not be done using glutin
+    /// after creating the context.
+    pub fn set_vsync(&self, vsync: bool) -> Result<(), VsyncError> {
+        match self {
+            GlContext::Windowed(context) => set_swap_interval(context, vsync),
+            GlContext::Headless(..) => Err(VsyncError::Unsupported),
+        }
+    }
+
+    /// Presents the default framebuffer, only redrawing `damage` if it is not empty
+    /// and this is supported by the platform.
+    ///
+    /// Headless contexts do not have anything to present to.
+    pub fn swap_buffers(&self, damage: &[Rect]) -> Result<(), ContextError> {
+        match self {
+            GlContext::Windowed(context) => {
+                if damage.is_empty() || !context.swap_buffers_with_damage_supported() {
+                    context.swap_buffers()
+                } else {
+                    context.swap_buffers_with_damage(damage)
+                }
+            }
+            GlContext::Headless(..) => Ok(()),
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+fn set_swap_interval(
+    context: &ContextWrapper<PossiblyCurrent, Window>,
+    vsync: bool,
+) -> Result<(), VsyncError> {
+    use glutin::platform::{unix::RawHandle, ContextTraitExt};
+    use glutin_egl_sys::egl;
+    use glutin_glx_sys::glx_extra;
+
+    let interval = i32::from(vsync);
+    // SAFETY: the handle is only used to check which API is used by the context
+    match unsafe { context.raw_handle() } {
+        RawHandle::Glx(_) => {
+            let glx = glx_extra::Glx::load_with(|symbol| context.get_proc_address(symbol));
+            // SAFETY: each function is only called if it was loaded and the context is current
+            unsafe {
+                if glx.SwapIntervalEXT.is_loaded()
+                    && glx.GetCurrentDisplay.is_loaded()
+                    && glx.GetCurrentDrawable.is_loaded()
+                {
+                    glx.SwapIntervalEXT(
+                        glx.GetCurrentDisplay(),
+                        glx.GetCurrentDrawable(),
+                        interval,
+                    );
+                    Ok(())
+                } else if glx.SwapIntervalMESA.is_loaded() {
+                    match glx.SwapIntervalMESA(interval as u32) {
+                        0 => Ok(()),
+                        _ => Err(VsyncError::Rejected),
+                    }
+                } else if glx.SwapIntervalSGI.is_loaded() {
+                    // `glXSwapIntervalSGI` does not accept an interval of zero,
+                    // so vsync can only be enabled using it.
+                    if !vsync {
+                        Err(VsyncError::Unsupported)
+                    } else if glx.SwapIntervalSGI(interval) == 0 {
+                        Ok(())
+                    } else {
+                        Err(VsyncError::Rejected)
+                    }
+                } else {
+                    Err(VsyncError::Unsupported)
+                }
+            }
+        }
+        RawHandle::Egl(_) => {
+            let egl = egl::Egl::load_with(|symbol| context.get_proc_address(symbol));
+            // SAFETY: the display is only used while the context exists
+            let display = unsafe { context.get_egl_display() };
+            match display {
+                Some(display) if egl.SwapInterval.is_loaded() => {
+                    // SAFETY: `display` is the display of the current context
+                    match unsafe { egl.SwapInterval(display as _, interval) } {
+                        egl::TRUE => Ok(()),
+                        _ => Err(VsyncError::Rejected),
+                    }
+                }
+                _ => Err(VsyncError::Unsupported),
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_swap_interval(
+    context: &ContextWrapper<PossiblyCurrent, Window>,
+    vsync: bool,
+) -> Result<(), VsyncError> {
+    use glutin_wgl_sys::wgl_extra;
+
+    let wgl = wgl_extra::Wgl::load_with(|symbol| context.get_proc_address(symbol));
+    if wgl.SwapIntervalEXT.is_loaded() {
+        // SAFETY: the function was loaded and the context is current
+        match unsafe { wgl.SwapIntervalEXT(i32::from(vsync)) } {
+            0 => Err(VsyncError::Rejected),
+            _ => Ok(()),
+        }
+    } else {
+        Err(VsyncError::Unsupported)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows",
+)))]
+fn set_swap_interval(
+    _: &ContextWrapper<PossiblyCurrent, Window>,
+    _: bool,
+) -> Result<(), VsyncError> {
+    Err(VsyncError::Unsupported)
+}
+
+/// Creates a framebuffer with a color and a depth and stencil renderbuffer,
+/// used in place of the default framebuffer of headless contexts.
+///
+/// The renderbuffers are freed together with the OpenGL context.
+pub fn offscreen_framebuffer(size: PhysicalSize<u32>) -> Result<GLuint, NewContextError> {
+    let mut framebuffer = 0;
+    let mut renderbuffers = [0; 2];
+    unsafe {
+        // SAFETY: `n` is 1
+        gl::GenFramebuffers(1, &mut framebuffer);
+        // SAFETY:
+        // `gl::FRAMEBUFFER` is a valid target
+        // `framebuffer` was just returned from `glGenFramebuffers`
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+        // SAFETY: `n` is 2
+        gl::GenRenderbuffers(2, renderbuffers.as_mut_ptr());
+
+        let attachments = [
+            (gl::RGBA8, gl::COLOR_ATTACHMENT0),
+            (gl::DEPTH24_STENCIL8, gl::DEPTH_STENCIL_ATTACHMENT),
+        ];
+        for (&renderbuffer, &(format, attachment)) in renderbuffers.iter().zip(&attachments) {
+            // SAFETY:
+            // `target` is `gl::RENDERBUFFER`
+            // `renderbuffer` was returned from `gl::GenRenderbuffers`
+            gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+            // SAFETY:
+            // `target` is `gl::RENDERBUFFER`
+            // `width` and `height` are in the range `0..=gl::MAX_RENDERBUFFER_SIZE`
+            // `format` is color-renderable or depth- and stencil-renderable
+            gl::RenderbufferStorage(gl::RENDERBUFFER, format, size.width as _, size.height as _);
+            // SAFETY:
+            // `gl::FRAMEBUFFER` is a valid `target` and `framebuffer` is not zero
+            // `attachment` is a valid `attachment`
+            // `renderbuffer` has type `gl::RENDERBUFFER`
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                attachment,
+                gl::RENDERBUFFER,
+                renderbuffer,
+            );
+        }
+
+        // SAFETY: this function is always safe
+        match gl::GetError() {
+            gl::NO_ERROR => (),
+            gl::OUT_OF_MEMORY => return Err(super::out_of_memory(NewContextError::OutOfMemory)),
+            e => bug!("unexpected error: {}", e),
+        }
+
+        // SAFETY:
+        // `gl::FRAMEBUFFER` is a valid `target`
+        if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+            bug!("incomplete offscreen framebuffer");
+        }
+    }
+
+    Ok(framebuffer)
+}
@@ -0,0 +1,75 @@
+//! Tracks all textures of the current context in debug builds,
+//! so textures which are still alive when dropping it can be reported.
+use std::{backtrace::Backtrace, cell::RefCell, collections::HashMap};
+
+use gl::types::*;
+
+struct TextureInfo {
+    dimensions: (u32, u32),
+    label: Option<String>,
+    /// Where the texture was created, only captured with the feature `leak-backtraces`.
+    backtrace: Option<Backtrace>,
+}
+
+thread_local! {
+    static TEXTURES: RefCell<HashMap<GLuint, TextureInfo>> = RefCell::new(HashMap::new());
+}
+
+pub fn register(id: GLuint, dimensions: (u32, u32)) {
+    if cfg!(debug_assertions) {
+        let backtrace = if cfg!(feature = "leak-backtraces") {
+            Some(Backtrace::force_capture())
+        } else {
+            None
+        };
+        let info = TextureInfo {
+            dimensions,
+            label: None,
+            backtrace,
+        };
+        TEXTURES.with(|textures| textures.borrow_mut().insert(id, info));
+    }
+}
+
+pub fn set_label(id: GLuint, label: &str) {
+    if cfg!(debug_assertions) {
+        TEXTURES.with(|textures| {
+            if let Some(info) = textures.borrow_mut().get_mut(&id) {
+                info.label = Some(label.to_owned());
+            }
+        })
+    }
+}
+
+pub fn unregister(id: GLuint) {
+    if cfg!(debug_assertions) {
+        TEXTURES.with(|textures| textures.borrow_mut().remove(&id));
+    }
+}
+
+/// Warns about all textures which have not yet been dropped and forgets them.
+pub fn report_leaks() {
+    let mut leaked: Vec<_> = TEXTURES.with(|textures| textures.borrow_mut().drain().collect());
+    if leaked.is_empty() {
+        return;
+    }
+
+    leaked.sort_by_key(|&(id, _)| id);
+    warn!(
+        "{} textures are still alive while dropping the context, their content is lost",
+        leaked.len()
+    );
+    for (_, info) in leaked {
+        let (width, height) = info.dimensions;
+        let label = info
+            .label
+            .map_or(String::new(), |label| format!(" {:?}", label));
+        match info.backtrace {
+            Some(backtrace) => warn!(
+                "leaked {}x{} texture{}, created at:\n{}",
+                width, height, label, backtrace
+            ),
+            None => warn!("leaked {}x{} texture{}", width, height, label),
+        }
+    }
+}
@@ -1,41 +1,153 @@
-use std::{cmp, convert::TryFrom, ffi::CStr};
+use std::{
+    cell::RefCell,
+    cmp,
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    ffi::CStr,
+    mem,
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+    thread::{self, ThreadId},
+    time::Instant,
+};
 
 use static_assertions::{assert_type_eq_all, const_assert_eq};
 
 use gl::types::*;
 use glutin::{
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalSize},
     event_loop::EventLoop,
     window::{Window, WindowBuilder},
-    ContextWrapper, PossiblyCurrent,
+    Rect,
 };
 
-use crate::{FinalizeError, NewContextError};
+use crate::{
+    Counters, Counts, DrawError, FinalizeError, IRect, NewContextError, Sampler, ShaderError,
+    Subsystem, VsyncError,
+};
 
+mod arena;
 mod draw;
+mod fence;
+mod gl_context;
+mod leak;
+mod pass;
+mod query;
+mod ring;
+mod sampler;
 mod shader;
 mod state;
 pub(crate) mod tex;
 
+use arena::FrameArena;
+pub use draw::BatchSprite;
+use draw::QueuedDraws;
+pub use fence::Fence;
+use gl_context::GlContext;
+pub use pass::Uniform;
+pub use query::{QueryHandle, TimerQuery};
 use tex::RawTexture;
 
-use shader::{DebugProgram, Program};
-use state::OpenGlState;
+use pass::PassProgram;
+use shader::{BatchProgram, DebugProgram, Program};
+use state::{OpenGlState, StencilMode};
 
 assert_type_eq_all!(GLfloat, f32);
 const_assert_eq!(true as GLboolean, gl::TRUE);
 const_assert_eq!(false as GLboolean, gl::FALSE);
 
-#[allow(non_upper_case_globals)]
-const ARB_framebuffer_no_attachments: &[u8] = b"GL_ARB_framebuffer_no_attachments\0";
+/// The generation of the currently existing context, `0` if there is none.
+static ACTIVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
 
-#[derive(Debug)]
+/// The framebuffers of dropped textures, see `Backend::delete_dropped_framebuffers`.
+pub type DroppedFramebuffers = Rc<RefCell<Vec<(GLuint, GLuint)>>>;
+
+/// Returns `true` if the context of the given `generation` still exists.
+pub fn is_active(generation: u64) -> bool {
+    ACTIVE_GENERATION.load(Ordering::Acquire) == generation
+}
+
+/// Optional OpenGL extensions used by crow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct Extensions {
+    /// `GL_ARB_framebuffer_no_attachments`
+    pub framebuffer_no_attachments: bool,
+    /// `GL_ARB_texture_storage`
+    pub texture_storage: bool,
+    /// `GL_ARB_direct_state_access`
+    pub direct_state_access: bool,
+    /// `GL_ARB_buffer_storage`
+    pub buffer_storage: bool,
+    /// `GL_ARB_texture_filter_anisotropic` or `GL_EXT_texture_filter_anisotropic`
+    pub texture_filter_anisotropic: bool,
+    /// `GL_KHR_debug`
+    pub debug: bool,
+}
+
+impl Extensions {
+    fn load() -> Self {
+        let mut extensions = Extensions::default();
+        let mut count = 0;
+        unsafe {
+            // SAFETY: `gl::NUM_EXTENSIONS` is a valid `pname`
+            gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        }
+
+        for i in 0..count.max(0) as GLuint {
+            let extension = unsafe {
+                // SAFETY:
+                // `gl::EXTENSIONS` is a valid `name`
+                // `i` is in the range `0..gl::NUM_EXTENSIONS`
+                let extension = gl::GetStringi(gl::EXTENSIONS, i);
+                if extension.is_null() {
+                    bug!("unexpected error: {}", gl::GetError());
+                }
+                CStr::from_ptr(extension.cast())
+            };
+
+            match extension.to_bytes() {
+                b"GL_ARB_framebuffer_no_attachments" => {
+                    extensions.framebuffer_no_attachments = true
+                }
+                b"GL_ARB_texture_storage" => extensions.texture_storage = true,
+                b"GL_ARB_direct_state_access" => extensions.direct_state_access = true,
+                b"GL_ARB_buffer_storage" => extensions.buffer_storage = true,
+                b"GL_ARB_texture_filter_anisotropic" | b"GL_EXT_texture_filter_anisotropic" => {
+                    extensions.texture_filter_anisotropic = true
+                }
+                b"GL_KHR_debug" => extensions.debug = true,
+                _ => (),
+            }
+        }
+
+        extensions
+    }
+}
+
+/// Limits and capabilities of the used OpenGL implementation.
+///
+/// Can be accessed using `Context::gl_constants`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct GlConstants {
+    /// The maximum size of a texture which can be used as a draw target.
     pub max_texture_size: (u32, u32),
+    /// The maximum number of textures which can be used in a single draw call.
+    pub max_texture_units: u32,
+    /// `GL_MAX_RENDERBUFFER_SIZE`
+    pub max_renderbuffer_size: u32,
+    /// `GL_MAX_VERTEX_ATTRIBS`
+    pub max_vertex_attribs: u32,
+    /// The maximum degree of anisotropic filtering, `1` if it is not supported.
+    pub max_anisotropy: u32,
+    /// The supported optional extensions.
+    pub extensions: Extensions,
 }
 
 impl GlConstants {
-    pub fn load() -> Self {
+    pub(crate) fn load() -> Self {
         fn get(pname: GLenum, name: &str) -> u32 {
             let mut v = 0;
             unsafe {
@@ -50,65 +162,136 @@ impl GlConstants {
             }
         }
 
+        let extensions = Extensions::load();
+        trace!("{:?}", extensions);
+
         // must be at least 1024
         let texture_size = get(gl::MAX_TEXTURE_SIZE, "texture_size");
         trace!("MAX_TEXTURE_SIZE: {}", texture_size);
-        let renderbuffer_size = get(gl::MAX_RENDERBUFFER_SIZE, "renderbuffer_size");
-        trace!("MAX_RENDERBUFFER_SIZE: {}", renderbuffer_size);
-        let size = cmp::min(texture_size, renderbuffer_size);
+        let max_renderbuffer_size = get(gl::MAX_RENDERBUFFER_SIZE, "renderbuffer_size");
+        trace!("MAX_RENDERBUFFER_SIZE: {}", max_renderbuffer_size);
+        let size = cmp::min(texture_size, max_renderbuffer_size);
 
         // FIXES https://github.com/lcnr/crow/issues/15
         // only check the max framebuffer size if the extension
         // `ARB_framebuffer_no_attachments` exists
-        unsafe {
-            // TODO: change the constant to `&CStr` once `CStr::from_bytes_with_nul_unchecked` is const
-            let expected_extension =
-                CStr::from_bytes_with_nul(ARB_framebuffer_no_attachments).unwrap();
-            for i in 0.. {
-                let extension = gl::GetStringi(gl::EXTENSIONS, i);
-                let err = gl::GetError();
-                match err {
-                    gl::NO_ERROR => {
-                        let extension = CStr::from_ptr(extension.cast());
-                        if expected_extension == extension {
-                            let framebuffer_width =
-                                get(gl::MAX_FRAMEBUFFER_WIDTH, "framebuffer_width");
-                            let framebuffer_height =
-                                get(gl::MAX_FRAMEBUFFER_HEIGHT, "framebuffer_height");
-                            trace!(
-                                "MAX_FRAMBUFFER_SIZE: {}x{}",
-                                framebuffer_width,
-                                framebuffer_height
-                            );
-
-                            return GlConstants {
-                                max_texture_size: (
-                                    cmp::min(size, framebuffer_width),
-                                    cmp::min(size, framebuffer_height),
-                                ),
-                            };
-                        }
-                    }
-                    gl::INVALID_VALUE => break,
-                    err => bug!("unexpected error: {:?}", err),
-                }
+        let max_texture_size = if extensions.framebuffer_no_attachments {
+            let framebuffer_width = get(gl::MAX_FRAMEBUFFER_WIDTH, "framebuffer_width");
+            let framebuffer_height = get(gl::MAX_FRAMEBUFFER_HEIGHT, "framebuffer_height");
+            trace!(
+                "MAX_FRAMBUFFER_SIZE: {}x{}",
+                framebuffer_width,
+                framebuffer_height
+            );
+            (
+                cmp::min(size, framebuffer_width),
+                cmp::min(size, framebuffer_height),
+            )
+        } else {
+            (size, size)
+        };
+
+        // must be at least 16
+        let max_texture_units = get(gl::MAX_TEXTURE_IMAGE_UNITS, "texture_units");
+        trace!("MAX_TEXTURE_IMAGE_UNITS: {}", max_texture_units);
+        // must be at least 16
+        let max_vertex_attribs = get(gl::MAX_VERTEX_ATTRIBS, "vertex_attribs");
+        trace!("MAX_VERTEX_ATTRIBS: {}", max_vertex_attribs);
+        // must be at least 2 if supported
+        let max_anisotropy = if extensions.texture_filter_anisotropic {
+            let mut v = 0.0;
+            unsafe {
+                // SAFETY: `pname` is valid as `texture_filter_anisotropic` is supported
+                gl::GetFloatv(sampler::MAX_TEXTURE_MAX_ANISOTROPY, &mut v);
             }
-        }
+            trace!("MAX_TEXTURE_MAX_ANISOTROPY: {}", v);
+            (v as u32).max(1)
+        } else {
+            1
+        };
 
         GlConstants {
-            max_texture_size: (size, size),
+            max_texture_size,
+            max_texture_units,
+            max_renderbuffer_size,
+            max_vertex_attribs,
+            max_anisotropy,
+            extensions,
         }
     }
 }
 
+/// The progress of `Context::masked`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Masking {
+    Disabled,
+    /// The mask is currently being drawn, contains the
+    /// framebuffer of the target once it is known.
+    Write(Option<GLuint>),
+    /// Draw calls to the given framebuffer are masked.
+    Test(GLuint),
+}
+
 #[derive(Debug)]
 pub struct Backend {
     state: OpenGlState,
-    gl_context: ContextWrapper<PossiblyCurrent, Window>,
+    masking: Masking,
+    /// The region of the target outside of which nothing is changed, see `target::Clipped`.
+    clip: Option<IRect>,
+    /// `Some` while inside of `Context::try_draw`, contains the first error.
+    try_draw: Option<Result<(), DrawError>>,
+    generation: u64,
+    /// The thread on which the context was created.
+    thread: ThreadId,
     constants: GlConstants,
     program: Program,
     debug_program: DebugProgram,
+    batch_program: BatchProgram,
+    /// Programs used by `Backend::run_pass`, compiled on first use.
+    pass_programs: Vec<PassProgram>,
+    /// Fragment shaders which failed to compile.
+    failed_programs: HashMap<String, ShaderError>,
+    /// Failed fragment shaders which have already been logged by `run_pass`.
+    reported_programs: HashSet<String>,
+    /// Sampler objects used by `DrawConfig::sampler`, created on first use.
+    samplers: HashMap<(Sampler, bool), GLuint>,
+    /// The framebuffer from which `Backend::blit` reads, created on first use.
+    blit_framebuffer: Option<GLuint>,
     dpi: u32,
+    /// The damaged regions of the current frame in physical pixels.
+    damage: Vec<Rect>,
+    /// Draw calls which are submitted together, see `Backend::flush_draws`.
+    queued: Option<QueuedDraws>,
+    /// The framebuffers and depth buffers of dropped textures, which still have to be deleted.
+    dropped_framebuffers: DroppedFramebuffers,
+    /// Buffers reused for transient allocations, see `FrameArena`.
+    arena: FrameArena,
+    /// The operations of the current frame.
+    counters: Counters,
+    /// The operations of the last presented frame.
+    last_frame_counters: Counters,
+    /// The subsystem to which operations are currently attributed.
+    subsystem: Subsystem,
+    /// The time at which the context was created.
+    start: Instant,
+    /// The value of the uniform `u_time`, updated once per frame.
+    time: f32,
+    // The context must be dropped after all other OpenGL objects.
+    gl_context: GlContext,
+}
+
+impl Drop for Backend {
+    fn drop(&mut self) {
+        // SAFETY: this function is always safe
+        let gl_error = unsafe { gl::GetError() };
+        if gl_error != gl::NO_ERROR {
+            error!("unexpected error while dropping the context: {}", gl_error);
+        }
+
+        leak::report_leaks();
+
+        ACTIVE_GENERATION.store(0, Ordering::Release);
+    }
 }
 
 impl Backend {
@@ -116,8 +299,68 @@ impl Backend {
         window: WindowBuilder,
         event_loop: &EventLoop<T>,
     ) -> Result<Self, NewContextError> {
+        let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+        if ACTIVE_GENERATION
+            .compare_exchange(0, generation, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            panic!("Tried to initialize a second Context");
+        }
+
+        let res = Self::create_windowed(window, event_loop)
+            .and_then(|gl_context| Self::initialize_generation(gl_context, generation));
+        if res.is_err() {
+            ACTIVE_GENERATION.store(0, Ordering::Release);
+        }
+        res
+    }
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    pub fn initialize_headless(dimensions: (u32, u32)) -> Result<Self, NewContextError> {
+        use glutin::platform::unix::HeadlessContextExt;
+
+        let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+        if ACTIVE_GENERATION
+            .compare_exchange(0, generation, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            panic!("Tried to initialize a second Context");
+        }
+
+        let size = PhysicalSize::new(dimensions.0, dimensions.1);
+        let res = glutin::ContextBuilder::new()
+            .with_depth_buffer(16)
+            .with_stencil_buffer(8)
+            .build_osmesa(size)
+            .map_err(NewContextError::CreationError)
+            .and_then(|gl_context| {
+                // SAFETY: the context is only used on this thread
+                let gl_context = unsafe {
+                    gl_context
+                        .make_current()
+                        .map_err(|(_, e)| NewContextError::ContextError(e))?
+                };
+                Self::initialize_generation(GlContext::Headless(gl_context, size), generation)
+            });
+        if res.is_err() {
+            ACTIVE_GENERATION.store(0, Ordering::Release);
+        }
+        res
+    }
+
+    fn create_windowed<T>(
+        window: WindowBuilder,
+        event_loop: &EventLoop<T>,
+    ) -> Result<GlContext, NewContextError> {
         let gl_context = glutin::ContextBuilder::new()
             .with_depth_buffer(16)
+            .with_stencil_buffer(8)
             .with_vsync(false)
             .build_windowed(window, event_loop)
             .map_err(NewContextError::CreationError)?;
@@ -128,8 +371,14 @@ impl Backend {
                 .make_current()
                 .map_err(|(_, e)| NewContextError::ContextError(e))?
         };
+        Ok(GlContext::Windowed(gl_context))
+    }
 
-        let dpi = gl_context.window().scale_factor();
+    fn initialize_generation(
+        gl_context: GlContext,
+        generation: u64,
+    ) -> Result<Self, NewContextError> {
+        let dpi = gl_context.scale_factor();
         if dpi < 0.5 {
             bug!("unexpected dpi: {}", dpi);
         } else if dpi.fract().min(1.0 - dpi.fract()) > f64::EPSILON {
@@ -149,56 +398,84 @@ impl Backend {
             gl::Enable(gl::BLEND);
         }
 
-        let (program, uniforms) = Program::new();
-        let (debug_program, debug_uniforms) = DebugProgram::new();
+        let constants = GlConstants::load();
+        info!(
+            "Maximum supported texture size: {}x{}",
+            constants.max_texture_size.0, constants.max_texture_size.1
+        );
+
+        let (program, uniforms) = Program::new()?;
+        let debug_program = DebugProgram::new(&constants.extensions)?;
+        let batch_program = BatchProgram::new(&constants.extensions)?;
 
-        let window_size: LogicalSize<u32> =
-            gl_context.window().inner_size().to_logical(f64::from(dpi));
+        let window_size: LogicalSize<u32> = gl_context.inner_size().to_logical(f64::from(dpi));
         let window_size: (u32, u32) = window_size.into();
         info!("Logical window size: {}x{}", window_size.0, window_size.1);
 
+        let default_framebuffer = if gl_context.window().is_some() {
+            0
+        } else {
+            gl_context::offscreen_framebuffer(gl_context.inner_size())?
+        };
         let state = OpenGlState::new(
             uniforms,
-            debug_uniforms,
             (program.id, program.vao),
+            default_framebuffer,
             window_size,
         );
 
-        let constants = GlConstants::load();
-        info!(
-            "Maximum supported texture size: {}x{}",
-            constants.max_texture_size.0, constants.max_texture_size.1
-        );
-
         Ok(Self {
             state,
-            gl_context,
+            masking: Masking::Disabled,
+            clip: None,
+            try_draw: None,
+            generation,
+            thread: thread::current().id(),
             constants,
             program,
             debug_program,
+            batch_program,
+            pass_programs: Vec::new(),
+            failed_programs: HashMap::new(),
+            reported_programs: HashSet::new(),
+            samplers: HashMap::new(),
+            blit_framebuffer: None,
             dpi,
+            damage: Vec::new(),
+            queued: None,
+            dropped_framebuffers: DroppedFramebuffers::default(),
+            arena: FrameArena::default(),
+            counters: Counters::default(),
+            last_frame_counters: Counters::default(),
+            subsystem: Subsystem::Other,
+            start: Instant::now(),
+            time: 0.0,
+            gl_context,
         })
     }
 
     pub fn resize_window(&mut self, width: u32, height: u32) {
         let size: LogicalSize<u32> = From::from((width, height));
-        self.gl_context.window().set_inner_size(size);
+        self.window().set_inner_size(size);
     }
 
     pub fn window(&self) -> &Window {
+        self.try_window()
+            .unwrap_or_else(|| panic!("Tried to access the window of a headless `Context`"))
+    }
+
+    pub fn try_window(&self) -> Option<&Window> {
         self.gl_context.window()
     }
 
     pub fn window_dimensions(&self) -> (u32, u32) {
-        let size: LogicalSize<u32> = self
-            .gl_context
-            .window()
-            .inner_size()
-            .to_logical(f64::from(self.dpi));
+        let size: LogicalSize<u32> = self.gl_context.inner_size().to_logical(f64::from(self.dpi));
         size.into()
     }
 
     pub fn take_screenshot(&mut self, (width, height): (u32, u32)) -> Vec<u8> {
+        self.assert_current();
+        self.flush_draws();
         let byte_count = usize::checked_mul(height as usize, width as usize)
             .and_then(|p| p.checked_mul(4))
             .unwrap_or_else(|| {
@@ -231,10 +508,17 @@ impl Backend {
             data.set_len(byte_count);
         }
 
+        self.counts().readbacks += 1;
         data
     }
 
     pub fn get_image_data(&mut self, texture: &RawTexture) -> Vec<u8> {
+        self.assert_current();
+        self.flush_draws();
+        if let Err(err) = self.validate(texture) {
+            panic!("{}", err);
+        }
+
         let (width, height) = texture.dimensions;
 
         // FIXME: this could theoretically overflow, leading to memory unsafety.
@@ -250,29 +534,51 @@ impl Backend {
         let mut data: Vec<u8> = Vec::with_capacity(byte_count);
 
         unsafe {
-            self.state.update_texture(texture.id);
-            // SAFETY:
-            // `gl::TEXTURE_2D` is an accepted target
-            // `gl::RGBA` is an accepted format
-            // `gl::UNSIGNED_BYTE` is an accepted type
-            // `level` is set to 0
-            // `GL_PIXEL_PACK_BUFFER` is never used and zero by default.
-            gl::GetTexImage(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                data.as_mut_ptr() as *mut _,
-            );
+            if self.constants.extensions.direct_state_access {
+                // SAFETY:
+                // `texture.id` is the name of an existing texture
+                // `gl::RGBA` is an accepted format
+                // `gl::UNSIGNED_BYTE` is an accepted type
+                // `level` is set to 0
+                // `bufSize` is the capacity of `data`
+                // `GL_PIXEL_PACK_BUFFER` is never used and zero by default.
+                gl::GetTextureImage(
+                    texture.id,
+                    0,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    byte_count as _,
+                    data.as_mut_ptr() as *mut _,
+                );
+            } else {
+                self.state.update_texture(texture.id);
+                // SAFETY:
+                // `gl::TEXTURE_2D` is an accepted target
+                // `gl::RGBA` is an accepted format
+                // `gl::UNSIGNED_BYTE` is an accepted type
+                // `level` is set to 0
+                // `GL_PIXEL_PACK_BUFFER` is never used and zero by default.
+                gl::GetTexImage(
+                    gl::TEXTURE_2D,
+                    0,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    data.as_mut_ptr() as *mut _,
+                );
+            }
 
             // SAFETY: the buffer has the correct capacity and has been initialized by gl::GetTexImage
             data.set_len(byte_count);
         }
 
+        self.counts().readbacks += 1;
         data
     }
 
     pub fn clear_depth(&mut self, framebuffer: GLuint) {
+        self.assert_current();
+        self.flush_draws();
+        self.prepare_clip(framebuffer);
         self.state.update_framebuffer(framebuffer);
         unsafe {
             // SAFETY:
@@ -283,7 +589,17 @@ impl Backend {
     }
 
     pub fn clear_color(&mut self, buffer_id: GLuint, color: (f32, f32, f32, f32)) {
+        self.assert_current();
+        self.flush_draws();
+        self.prepare_clip(buffer_id);
+        self.clear(buffer_id, color)
+    }
+
+    /// Sets each pixel inside of the current scissor rectangle to `color`.
+    fn clear(&mut self, buffer_id: GLuint, color: (f32, f32, f32, f32)) {
         self.state.update_framebuffer(buffer_id);
+        // `glClear` respects the current color mask
+        self.state.update_color_mask((true, true, true, true));
         unsafe {
             // SAFETY: this function is always safe
             gl::ClearColor(color.0, color.1, color.2, color.3);
@@ -294,22 +610,366 @@ impl Backend {
         }
     }
 
+    /// Sets each pixel in `rect` to `color`.
+    pub fn clear_region(
+        &mut self,
+        buffer_id: GLuint,
+        hidpi: u32,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        let rect = match self.clip {
+            Some(clip) => match rect.intersection(clip) {
+                Some(rect) => rect,
+                None => return,
+            },
+            None => rect,
+        };
+        if rect.is_empty() {
+            return;
+        }
+
+        self.assert_current();
+        self.flush_draws();
+        let position = (rect.x * hidpi as i32, rect.y * hidpi as i32);
+        let size = (rect.width * hidpi, rect.height * hidpi);
+
+        self.state.update_scissor(Some((position, size)));
+        self.clear(buffer_id, color);
+        self.state.update_scissor(None);
+    }
+
+    /// Copies the pixels of `source` in the rectangle at `source_position` with `source_size`
+    /// to the rectangle at `target_position` with `target_size` of the framebuffer `target`,
+    /// scaling them using nearest neighbor filtering.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit(
+        &mut self,
+        source: &RawTexture,
+        source_position: (u32, u32),
+        source_size: (u32, u32),
+        target: GLuint,
+        target_position: (i32, i32),
+        target_size: (u32, u32),
+    ) {
+        self.assert_current();
+        if let Err(err) = self.validate(source) {
+            return self.report_error(err);
+        }
+
+        self.flush_draws();
+        self.state.update_framebuffer(target);
+        // blits are only used for internal copies, which are never clipped
+        self.state.update_scissor(None);
+        // `glBlitFramebuffer` respects the current color mask
+        self.state.update_color_mask((true, true, true, true));
+        let (sx, sy) = (source_position.0 as GLint, source_position.1 as GLint);
+        let (sw, sh) = (source_size.0 as GLint, source_size.1 as GLint);
+        let (tx, ty) = target_position;
+        let (tw, th) = (target_size.0 as GLint, target_size.1 as GLint);
+        let read_framebuffer = *self.blit_framebuffer.get_or_insert_with(|| {
+            let mut id = 0;
+            // SAFETY: `n` is 1
+            unsafe { gl::GenFramebuffers(1, &mut id) };
+            id
+        });
+        unsafe {
+            // SAFETY:
+            // `gl::READ_FRAMEBUFFER` is a valid target
+            // `read_framebuffer` was returned from `glGenFramebuffers`
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, read_framebuffer);
+            // SAFETY:
+            // `gl::READ_FRAMEBUFFER` is a valid `target` and `read_framebuffer` is not zero
+            // `gl::COLOR_ATTACHMENT0` is a valid `attachment`
+            // `source.id` is a valid `gl::TEXTURE_2D` which supports the `level` zero
+            gl::FramebufferTexture(gl::READ_FRAMEBUFFER, gl::COLOR_ATTACHMENT0, source.id, 0);
+            // SAFETY:
+            // `mask` only contains `gl::COLOR_BUFFER_BIT` and `filter` is `gl::NEAREST`
+            // both framebuffers are complete and use the same color format
+            gl::BlitFramebuffer(
+                sx,
+                sy,
+                sx + sw,
+                sy + sh,
+                tx,
+                ty,
+                tx + tw,
+                ty + th,
+                gl::COLOR_BUFFER_BIT,
+                gl::NEAREST,
+            );
+            // detach `source` so that it does not outlive its `RawTexture`
+            // SAFETY: using `0` as `texture` detaches the current attachment
+            gl::FramebufferTexture(gl::READ_FRAMEBUFFER, gl::COLOR_ATTACHMENT0, 0, 0);
+            // SAFETY: `target` is the currently bound framebuffer
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.state.bound_framebuffer());
+        }
+    }
+
+    /// Copies the window, which has the physical size `size`, into the framebuffer `target`.
+    pub fn copy_window(&mut self, target: GLuint, size: (u32, u32)) {
+        self.assert_current();
+        self.flush_draws();
+        self.state.update_framebuffer(0);
+        let window = self.state.bound_framebuffer();
+        self.state.update_framebuffer(target);
+        // `glBlitFramebuffer` respects the current scissor rectangle and color mask
+        self.state.update_scissor(None);
+        self.state.update_color_mask((true, true, true, true));
+        let (width, height) = (size.0 as GLint, size.1 as GLint);
+        unsafe {
+            // SAFETY:
+            // `gl::READ_FRAMEBUFFER` is a valid target
+            // `window` is either zero or was returned from `glGenFramebuffers`
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, window);
+            // SAFETY:
+            // `mask` only contains `gl::COLOR_BUFFER_BIT` and `filter` is `gl::NEAREST`
+            // both framebuffers are complete and have the same size
+            gl::BlitFramebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                gl::COLOR_BUFFER_BIT,
+                gl::NEAREST,
+            );
+            // SAFETY: `target` is the currently bound framebuffer
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.state.bound_framebuffer());
+        }
+    }
+
+    pub fn masking(&self) -> Masking {
+        self.masking
+    }
+
+    pub fn set_masking(&mut self, masking: Masking) {
+        self.flush_draws();
+        self.masking = masking;
+    }
+
+    /// Checks that `texture` was created by this context.
+    pub fn validate(&self, texture: &RawTexture) -> Result<(), DrawError> {
+        if texture.generation == self.generation {
+            Ok(())
+        } else {
+            if let Some(label) = texture.label.as_deref() {
+                error!("the texture {:?} was created by a different context", label);
+            }
+            Err(DrawError::ForeignTexture)
+        }
+    }
+
+    /// Reports an error which prevented a draw call.
+    ///
+    /// Using a texture from a different context panics
+    /// outside of `Context::try_draw`, as its id may refer
+    /// to an unrelated object.
+    pub fn report_error(&mut self, err: DrawError) {
+        match &mut self.try_draw {
+            Some(res) => {
+                if res.is_ok() {
+                    *res = Err(err);
+                }
+            }
+            None if err == DrawError::ForeignTexture => panic!("{}", err),
+            None => error!("{}", err),
+        }
+    }
+
+    /// Runs `f` while ignoring the active mask and clip, used for internal copies.
+    pub fn without_mask<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.flush_draws();
+        let masking = mem::replace(&mut self.masking, Masking::Disabled);
+        let clip = self.clip.take();
+        self.state.update_scissor(None);
+        let res = f(self);
+        self.flush_draws();
+        self.masking = masking;
+        self.clip = clip;
+        res
+    }
+
+    pub fn clip(&self) -> Option<IRect> {
+        self.clip
+    }
+
+    pub fn set_clip(&mut self, clip: Option<IRect>) {
+        self.flush_draws();
+        self.clip = clip;
+        // the scissor rectangle is updated by the next operation using the clip
+        self.state.update_scissor(None);
+    }
+
+    /// Updates the scissor rectangle before changing `framebuffer`.
+    fn prepare_clip(&mut self, framebuffer: GLuint) {
+        let dpi = if framebuffer == 0 { self.dpi } else { 1 };
+        self.state.update_scissor(self.clip.map(|clip| {
+            (
+                (clip.x * dpi as i32, clip.y * dpi as i32),
+                (clip.width * dpi, clip.height * dpi),
+            )
+        }));
+    }
+
+    /// Updates the stencil state before drawing to `framebuffer`.
+    fn prepare_stencil(&mut self, framebuffer: GLuint) {
+        match self.masking {
+            Masking::Disabled => self.state.update_stencil_mode(StencilMode::Disabled),
+            Masking::Write(target) => {
+                if target != Some(framebuffer) {
+                    self.masking = Masking::Write(Some(framebuffer));
+                    self.state.update_framebuffer(framebuffer);
+                    unsafe {
+                        // SAFETY:
+                        // no undefined bit is set in `mask`
+                        // `glBegin` and `glEnd` are never used
+                        gl::Clear(gl::STENCIL_BUFFER_BIT);
+                    }
+                }
+                self.state.update_stencil_mode(StencilMode::Write)
+            }
+            Masking::Test(target) => {
+                if target == framebuffer {
+                    self.state.update_stencil_mode(StencilMode::Test)
+                } else {
+                    self.state.update_stencil_mode(StencilMode::Disabled)
+                }
+            }
+        }
+    }
+
     pub fn finalize_frame(&mut self) -> Result<(), FinalizeError> {
+        self.assert_current();
+        self.flush_draws();
         self.gl_context
-            .swap_buffers()
+            .swap_buffers(&self.damage)
             .map_err(FinalizeError::ContextError)?;
+        self.end_frame();
+        self.time = self.start.elapsed().as_secs_f32();
+        Ok(())
+    }
+
+    /// Resets the state of the current frame without presenting it,
+    /// used by `finalize_frame` and `Context::render_frames`.
+    pub fn end_frame(&mut self) {
+        self.flush_draws();
+        self.damage.clear();
+        let arena_size = self.arena.reset();
+        self.counters.set_frame_arena_size(arena_size);
+        self.last_frame_counters = self.counters;
+        self.reset_counters();
         self.state.update_framebuffer(0);
         self.clear_depth(0);
-        Ok(())
+    }
+
+    pub fn add_damage(&mut self, rect: IRect) {
+        let dpi = self.dpi;
+        let x = cmp::max(rect.left(), 0);
+        let y = cmp::max(rect.bottom(), 0);
+        if rect.right() > x && rect.top() > y {
+            self.damage.push(Rect {
+                x: x as u32 * dpi,
+                y: y as u32 * dpi,
+                width: (rect.right() - x) as u32 * dpi,
+                height: (rect.top() - y) as u32 * dpi,
+            });
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.assert_current();
+        self.flush_draws();
+        // SAFETY: this function is always safe
+        unsafe { gl::Flush() }
+    }
+
+    pub fn finish(&mut self) {
+        self.assert_current();
+        self.flush_draws();
+        // SAFETY: this function is always safe
+        unsafe { gl::Finish() }
+    }
+
+    /// Checks that the context is used on the thread which created
+    /// it and is still current, which is only done in debug builds.
+    pub fn assert_current(&self) {
+        if cfg!(debug_assertions) {
+            if thread::current().id() != self.thread {
+                panic!("Tried to use the `Context` on a different thread than the one which created it");
+            } else if !self.gl_context.is_current() {
+                panic!("Tried to use the `Context` while its OpenGL context is not current");
+            }
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
     }
 
     pub fn dpi_factor(&self) -> u32 {
         self.dpi
     }
 
+    /// Changes the swap interval of the window.
+    pub fn set_vsync(&mut self, vsync: bool) -> Result<(), VsyncError> {
+        self.assert_current();
+        self.gl_context.set_vsync(vsync)
+    }
+
     pub fn constants(&self) -> &GlConstants {
         &self.constants
     }
+
+    /// The seconds between creating the context and finalizing the last frame.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    pub fn counters(&self) -> &Counters {
+        &self.counters
+    }
+
+    pub fn last_frame_counters(&self) -> &Counters {
+        &self.last_frame_counters
+    }
+
+    pub fn reset_counters(&mut self) {
+        let arena_size = self.counters.frame_arena_size();
+        self.counters = Counters::default();
+        self.counters.set_frame_arena_size(arena_size);
+    }
+
+    /// Attributes all following operations to `subsystem`, returning the previous one.
+    pub fn set_subsystem(&mut self, subsystem: Subsystem) -> Subsystem {
+        std::mem::replace(&mut self.subsystem, subsystem)
+    }
+
+    /// The counts of the subsystem to which operations are currently attributed.
+    pub fn counts(&mut self) -> &mut Counts {
+        self.counters.get_mut(self.subsystem)
+    }
+}
+
+/// Called if OpenGL is out of memory.
+///
+/// Panics unless the feature `strict-errors` is enabled, in which case `err` is returned.
+fn out_of_memory<E>(err: E) -> E {
+    if cfg!(feature = "strict-errors") {
+        err
+    } else {
+        // TODO: OpenGl is now in an undefined state,
+        // consider aborting instead, as it is possible
+        // to catch a panic
+        panic!("OpenGl is out of memory and in an invalid state");
+    }
 }
 
 /// Sets the currently active program to `program`.
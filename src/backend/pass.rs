@@ -0,0 +1,199 @@
+use std::{collections::HashMap, ffi::CString, rc::Rc};
+
+use gl::types::*;
+
+use crate::{
+    backend::{shader, tex::RawTexture, Backend},
+    ShaderError,
+};
+
+/// The vertex shader used by all passes, covering the whole target.
+const VERTEX: &str = include_str!("shader/vertex_pass.glsl");
+
+/// The value of a uniform used by a pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Uniform {
+    Float(f32),
+    Vec2(f32, f32),
+    Vec3(f32, f32, f32),
+    Vec4(f32, f32, f32, f32),
+    /// A row-major matrix.
+    Mat4([[f32; 4]; 4]),
+    Int(i32),
+    IVec2(i32, i32),
+    UInt(u32),
+    /// A texture bound to the next free texture unit,
+    /// the uniform must be a `sampler2D`.
+    Texture(GLuint),
+}
+
+/// A program which computes each pixel of its target, used
+/// for effects which can not be expressed using draw calls.
+#[derive(Debug)]
+pub struct PassProgram {
+    id: GLuint,
+    /// The fragment shader, used to identify the program.
+    fragment: Rc<str>,
+    /// Whether this program is freed once `fragment` is only used by the backend,
+    /// see `Backend::retain_pass_program`.
+    retained: bool,
+    locations: HashMap<String, GLint>,
+}
+
+impl PassProgram {
+    /// Returns the location of the uniform `name`, which is `-1` if it is unused.
+    fn location(&mut self, name: &str) -> GLint {
+        let id = self.id;
+        *self
+            .locations
+            .entry(name.to_owned())
+            .or_insert_with(|| unsafe {
+                let name = CString::new(name).unwrap();
+                // SAFETY: `id` is a valid and linked program object
+                gl::GetUniformLocation(id, name.as_ptr())
+            })
+    }
+}
+
+impl Drop for PassProgram {
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY: `id` was generated by OpenGL
+            gl::DeleteProgram(self.id);
+        }
+    }
+}
+
+impl Backend {
+    /// Returns the index of the program using `fragment`, compiling it on first use.
+    ///
+    /// Fragment shaders which failed to compile are remembered and not compiled again.
+    pub fn pass_program(&mut self, fragment: &str) -> Result<usize, ShaderError> {
+        if let Some(index) = self
+            .pass_programs
+            .iter()
+            .position(|program| &*program.fragment == fragment)
+        {
+            return Ok(index);
+        } else if let Some(err) = self.failed_programs.get(fragment) {
+            return Err(err.clone());
+        }
+
+        self.assert_current();
+        self.free_unused_pass_programs();
+        // `try_compile_program` uses the new program, which is fine
+        // as `run_pass` updates the current program afterwards
+        let id = match shader::try_compile_program(VERTEX, fragment) {
+            Ok(id) => id,
+            Err(err) => {
+                self.failed_programs
+                    .insert(fragment.to_owned(), err.clone());
+                return Err(err);
+            }
+        };
+        self.state.update_program(id);
+        // the name of a deleted program may be reused
+        self.state.forget_program(id);
+        self.pass_programs.push(PassProgram {
+            id,
+            fragment: fragment.into(),
+            retained: false,
+            locations: HashMap::new(),
+        });
+        Ok(self.pass_programs.len() - 1)
+    }
+
+    /// Compiles `fragment` and returns a handle to it. The program
+    /// is freed once all handles to it have been dropped.
+    pub fn retain_pass_program(&mut self, fragment: &str) -> Result<Rc<str>, ShaderError> {
+        let index = self.pass_program(fragment)?;
+        let program = &mut self.pass_programs[index];
+        program.retained = true;
+        Ok(Rc::clone(&program.fragment))
+    }
+
+    /// Frees all retained programs without a handle, which happens lazily
+    /// as handles do not have access to the backend.
+    fn free_unused_pass_programs(&mut self) {
+        let state = &mut self.state;
+        self.pass_programs.retain(|program| {
+            if program.retained && Rc::strong_count(&program.fragment) == 1 {
+                state.forget_program(program.id);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Overwrites each pixel of the target using the fragment shader `fragment`.
+    ///
+    /// `source` is bound to the sampler `source` if it exists. Uniforms which
+    /// are not used by `fragment` are ignored.
+    ///
+    /// Does nothing if `fragment` does not compile, which is logged once.
+    pub fn run_pass(
+        &mut self,
+        fragment: &str,
+        target_framebuffer: GLuint,
+        target_dimensions: (u32, u32),
+        source: Option<&RawTexture>,
+        uniforms: &[(&str, Uniform)],
+    ) {
+        self.assert_current();
+        self.flush_draws();
+        let index = match self.pass_program(fragment) {
+            Ok(index) => index,
+            Err(err) => {
+                if self.reported_programs.insert(fragment.to_owned()) {
+                    error!("skipping pass: {}", err);
+                }
+                return;
+            }
+        };
+        let program = self.pass_programs[index].id;
+
+        self.prepare_stencil(target_framebuffer);
+        self.prepare_clip(target_framebuffer);
+        let s = &mut self.state;
+        s.update_program(program);
+        s.update_vao(self.program.vao);
+        s.disable_blending();
+        s.update_color_mask((true, true, true, true));
+        s.update_framebuffer(target_framebuffer);
+        s.update_viewport_dimensions(target_dimensions);
+        s.disable_depth();
+        if let Some(source) = source {
+            s.update_texture(source.id);
+            s.update_sampler(0);
+        }
+
+        // texture unit 0 is used by `source`
+        let mut unit = 1;
+        for &(name, value) in uniforms.iter() {
+            let location = self.pass_programs[index].location(name);
+            if location == -1 {
+                // unused textures do not need a texture unit
+                continue;
+            }
+
+            let value = if let Uniform::Texture(texture) = value {
+                self.state.bind_texture_unit(unit, texture);
+                unit += 1;
+                Uniform::Int(unit as i32 - 1)
+            } else {
+                value
+            };
+            self.state.update_pass_uniform(program, location, value);
+        }
+
+        unsafe {
+            // SAFETY:
+            // `gl::TRIANGLE_STRIP` is an accepted value
+            // `count` is positive
+            // We never map the data store of a buffer object
+            // No geometry shader is active
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+        }
+    }
+}
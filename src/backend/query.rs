@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use gl::types::*;
+
+use crate::{
+    backend::{ring::Vertex, Backend},
+    Context, IRect,
+};
+
+/// Returns the result of the query `id` if it is already available, without blocking.
+fn query_result(id: GLuint) -> Option<GLuint64> {
+    let mut available = 0;
+    // SAFETY:
+    // `id` is a valid query object which has been ended
+    // `params` points to a single `GLint`
+    unsafe { gl::GetQueryObjectiv(id, gl::QUERY_RESULT_AVAILABLE, &mut available) };
+    if available == 0 {
+        return None;
+    }
+
+    let mut result = 0;
+    // SAFETY:
+    // the result of `id` is available
+    // `params` points to a single `GLuint64`
+    unsafe { gl::GetQueryObjectui64v(id, gl::QUERY_RESULT, &mut result) };
+    Some(result)
+}
+
+/// Measures the time the GPU spent executing the commands
+/// issued between `TimerQuery::begin` and `TimerQuery::end`.
+#[derive(Debug)]
+pub struct TimerQuery {
+    id: GLuint,
+    generation: u64,
+}
+
+impl Drop for TimerQuery {
+    fn drop(&mut self) {
+        // All objects of a dropped context have already been freed.
+        if super::is_active(self.generation) {
+            // SAFETY: `self.id` is a valid query object
+            unsafe { gl::DeleteQueries(1, &self.id) }
+        }
+    }
+}
+
+impl TimerQuery {
+    /// Starts measuring, no other timer query may be active until `end` is called.
+    pub fn begin(backend: &mut Backend) -> Self {
+        backend.assert_current();
+        backend.flush_draws();
+        let mut id = 0;
+        unsafe {
+            // SAFETY: `n` is 1 and `ids` points to a single `GLuint`
+            gl::GenQueries(1, &mut id);
+            // SAFETY: `id` is a newly generated query and no other timer query is active
+            gl::BeginQuery(gl::TIME_ELAPSED, id);
+        }
+
+        TimerQuery {
+            id,
+            generation: backend.generation(),
+        }
+    }
+
+    /// Stops measuring.
+    pub fn end(&self, backend: &mut Backend) {
+        backend.assert_current();
+        backend.flush_draws();
+        // SAFETY: `self` is the currently active timer query
+        unsafe { gl::EndQuery(gl::TIME_ELAPSED) }
+    }
+
+    /// Returns the measured duration if it is already available, without blocking.
+    pub fn result(&self, backend: &mut Backend) -> Option<Duration> {
+        backend.assert_current();
+        query_result(self.id).map(Duration::from_nanos)
+    }
+}
+
+/// Whether any pixel of a rectangle would be drawn, created using [`Context::query_visible`].
+///
+/// The GPU answers the query asynchronously, so the result is usually only
+/// available a frame later. Waiting for it using `QueryHandle::wait` stalls the CPU.
+///
+/// [`Context::query_visible`]: struct.Context.html#method.query_visible
+#[derive(Debug)]
+pub struct QueryHandle {
+    /// `None` if the query could not be issued, in which case the rectangle counts as visible.
+    id: Option<GLuint>,
+    generation: u64,
+}
+
+impl Drop for QueryHandle {
+    fn drop(&mut self) {
+        // All objects of a dropped context have already been freed.
+        if let Some(id) = self.id.filter(|_| super::is_active(self.generation)) {
+            // SAFETY: `id` is a valid query object
+            unsafe { gl::DeleteQueries(1, &id) }
+        }
+    }
+}
+
+impl QueryHandle {
+    /// A query which could not be issued, for example because its target is invalid.
+    pub(crate) fn visible(backend: &Backend) -> Self {
+        QueryHandle {
+            id: None,
+            generation: backend.generation(),
+        }
+    }
+
+    fn check_context(&self, ctx: &Context) {
+        ctx.backend.assert_current();
+        if self.generation != ctx.backend.generation() {
+            panic!(
+                "Tried to use a `QueryHandle` with a different `Context` than the one which created it"
+            );
+        }
+    }
+
+    /// Returns `Some(true)` if any pixel of the queried rectangle would have been drawn,
+    /// or `None` if the result is not yet available, without blocking.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `self` was created by a different `Context`.
+    pub fn is_visible(&self, ctx: &mut Context) -> Option<bool> {
+        self.check_context(ctx);
+        match self.id {
+            Some(id) => query_result(id).map(|samples| samples != 0),
+            None => Some(true),
+        }
+    }
+
+    /// Blocks until the result of the query is available and returns it.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `self` was created by a different `Context`.
+    pub fn wait(&self, ctx: &mut Context) -> bool {
+        self.check_context(ctx);
+        if let Some(id) = self.id {
+            ctx.backend.flush();
+            let mut samples = 0;
+            // SAFETY:
+            // `id` is a valid query object which has been ended
+            // `params` points to a single `GLuint`
+            unsafe { gl::GetQueryObjectuiv(id, gl::QUERY_RESULT, &mut samples) };
+            samples != 0
+        } else {
+            true
+        }
+    }
+}
+
+impl Backend {
+    /// Checks if any pixel of `rect` passes the depth and stencil test
+    /// at the given `depth`, without modifying the target.
+    pub fn query_visible(
+        &mut self,
+        target_framebuffer: GLuint,
+        target_dimensions: (u32, u32),
+        hidpi: u32,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        self.assert_current();
+        self.flush_draws();
+        self.prepare_stencil(target_framebuffer);
+        self.prepare_clip(target_framebuffer);
+        let s = &mut self.state;
+        s.update_program(self.debug_program.id);
+        s.update_vao(self.debug_program.vertices.vao);
+        s.update_color_mask((false, false, false, false));
+        s.update_framebuffer(target_framebuffer);
+        let viewport_dimensions = (target_dimensions.0 * hidpi, target_dimensions.1 * hidpi);
+        s.update_viewport_dimensions(viewport_dimensions);
+        s.enable_depth();
+
+        let (x1, y1) = (
+            rect.x as f32 / target_dimensions.0 as f32 * 2.0 - 1.0,
+            rect.y as f32 / target_dimensions.1 as f32 * 2.0 - 1.0,
+        );
+        let (x2, y2) = (
+            rect.right() as f32 / target_dimensions.0 as f32 * 2.0 - 1.0,
+            rect.top() as f32 / target_dimensions.1 as f32 * 2.0 - 1.0,
+        );
+        let vertex = |x, y| Vertex {
+            position: [x, y],
+            color: [0.0; 4],
+        };
+        let vertices = [
+            vertex(x1, y1),
+            vertex(x2, y1),
+            vertex(x2, y2),
+            vertex(x1, y2),
+        ];
+        let first = self.debug_program.vertices.push(&vertices);
+
+        let mut id = 0;
+        unsafe {
+            // SAFETY: `depth` is declared as a `float`
+            gl::Uniform1f(self.debug_program.depth, depth);
+            // SAFETY: `n` is 1 and `ids` points to a single `GLuint`
+            gl::GenQueries(1, &mut id);
+            // the query must not modify the depth buffer
+            gl::DepthMask(gl::FALSE);
+            // SAFETY: `id` is a newly generated query and no other occlusion query is active
+            gl::BeginQuery(gl::ANY_SAMPLES_PASSED, id);
+            // SAFETY:
+            // `gl::TRIANGLE_FAN` is an accepted value
+            // `first..first + 4` are valid vertices of the bound vertex array
+            // No geometry shader is active
+            gl::DrawArrays(gl::TRIANGLE_FAN, first, vertices.len() as GLsizei);
+            gl::EndQuery(gl::ANY_SAMPLES_PASSED);
+            gl::DepthMask(gl::TRUE);
+            // debug primitives are drawn at depth `0.0`
+            gl::Uniform1f(self.debug_program.depth, 0.0);
+        }
+
+        QueryHandle {
+            id: Some(id),
+            generation: self.generation(),
+        }
+    }
+}
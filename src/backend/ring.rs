@@ -0,0 +1,300 @@
+use std::{mem, ptr};
+
+use gl::types::*;
+
+use crate::{backend::Extensions, NewContextError};
+
+/// `position` is at location 0
+const POSITION_ATTR: GLuint = 0;
+/// `color` is at location 1
+const COLOR_ATTR: GLuint = 1;
+
+/// The location, size and column count of each attribute of `SpriteVertex` in order.
+///
+/// `color_modulation` is a `mat4`, which uses one location for each of its four columns.
+const SPRITE_ATTRS: [(GLuint, GLint, GLuint); 6] = [
+    (0, 2, 1),
+    (1, 2, 1),
+    (2, 4, 1),
+    (3, 4, 4),
+    (7, 1, 1),
+    (8, 1, 1),
+];
+
+/// The number of segments the buffer is split into, each
+/// segment is guarded by its own fence.
+const SEGMENT_COUNT: usize = 4;
+/// The number of vertices which fit into a single segment.
+pub const SEGMENT_SIZE: usize = 4096;
+
+/// A vertex type which can be stored in a `VertexRing`.
+pub trait RingVertex: Copy {
+    /// Enables and specifies the attributes of the currently bound vertex array.
+    ///
+    /// SAFETY: the vertex buffer storing `Self` must be bound to `gl::ARRAY_BUFFER`.
+    unsafe fn specify_attributes();
+}
+
+/// A vertex used to draw primitives.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    /// The position in normalized device coordinates.
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl RingVertex for Vertex {
+    unsafe fn specify_attributes() {
+        // SAFETY:
+        // `position` was specified with `layout (location = 0) in vec2`
+        // `color` was specified with `layout (location = 1) in vec4`
+        // both attributes are less than `GL_MAX_VERTEX_ATTRIBS`
+        gl::EnableVertexAttribArray(POSITION_ATTR);
+        gl::EnableVertexAttribArray(COLOR_ATTR);
+        // SAFETY:
+        // `size` is two and four respectively
+        // `gl::FLOAT` is an accepted value
+        // `stride` is the size of `Vertex`, which is `repr(C)`
+        // the offsets are the offsets of `position` and `color` in `Vertex`
+        gl::VertexAttribPointer(
+            POSITION_ATTR,
+            2,
+            gl::FLOAT,
+            gl::FALSE,
+            mem::size_of::<Vertex>() as GLsizei,
+            ptr::null(),
+        );
+        gl::VertexAttribPointer(
+            COLOR_ATTR,
+            4,
+            gl::FLOAT,
+            gl::FALSE,
+            mem::size_of::<Vertex>() as GLsizei,
+            mem::size_of::<[f32; 2]>() as *const _,
+        );
+    }
+}
+
+/// A vertex of a sprite drawn using `Backend::draw_batch`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteVertex {
+    /// The position in normalized device coordinates.
+    pub position: [f32; 2],
+    pub tex_coords: [f32; 2],
+    /// The start and end of the drawn section in texture coordinates.
+    pub section: [f32; 4],
+    pub color_modulation: [[f32; 4]; 4],
+    pub depth: f32,
+    /// `1.0` if the color should be inverted, `0.0` otherwise.
+    pub invert_color: f32,
+}
+
+impl RingVertex for SpriteVertex {
+    unsafe fn specify_attributes() {
+        let mut offset = 0;
+        for &(location, size, columns) in SPRITE_ATTRS.iter() {
+            for column in 0..columns {
+                // SAFETY:
+                // the locations were specified in `vertex_batch.glsl`
+                // and are less than `GL_MAX_VERTEX_ATTRIBS`
+                gl::EnableVertexAttribArray(location + column);
+                // SAFETY:
+                // `size` is one, two or four
+                // `gl::FLOAT` is an accepted value
+                // `stride` is the size of `SpriteVertex`, which is `repr(C)`
+                // `offset` is the offset of the attribute in `SpriteVertex`
+                gl::VertexAttribPointer(
+                    location + column,
+                    size,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    mem::size_of::<SpriteVertex>() as GLsizei,
+                    (offset * mem::size_of::<f32>()) as *const _,
+                );
+                offset += size as usize;
+            }
+        }
+    }
+}
+
+/// A ring buffer storing the vertices of all drawn primitives or sprites.
+///
+/// In case `ARB_buffer_storage` is supported, the buffer is persistently mapped
+/// and each segment is guarded by a fence, making sure that we never overwrite vertices
+/// which are still used by the GPU. Otherwise the vertices are uploaded using `glBufferSubData`,
+/// which handles synchronization itself.
+#[derive(Debug)]
+pub struct VertexRing<V: RingVertex = Vertex> {
+    pub vao: GLuint,
+    vbo: GLuint,
+    /// The persistently mapped buffer, null if `ARB_buffer_storage` is not supported.
+    mapping: *mut V,
+    fences: [GLsync; SEGMENT_COUNT],
+    /// The segment which is currently written to.
+    segment: usize,
+    /// The number of vertices already used in the current segment.
+    offset: usize,
+}
+
+impl<V: RingVertex> VertexRing<V> {
+    pub fn new(extensions: &Extensions) -> Result<Self, NewContextError> {
+        let capacity = SEGMENT_COUNT * SEGMENT_SIZE;
+        let size = (capacity * mem::size_of::<V>()) as GLsizeiptr;
+
+        let mut vao = 0;
+        let mut vbo = 0;
+        let mut mapping: *mut V = ptr::null_mut();
+        unsafe {
+            // SAFETY: `n` is positive
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+
+            // SAFETY: `vao` was just returned from `gl::GenVertexArrays`
+            gl::BindVertexArray(vao);
+            // SAFETY: `gl::ARRAY_BUFFER` is a valid `target` and `vbo` is valid
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+
+            if extensions.buffer_storage {
+                let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+                // SAFETY:
+                // `gl::ARRAY_BUFFER` is a valid buffer target
+                // `size` is positive
+                // `flags` only contains valid bits and `gl::MAP_PERSISTENT_BIT`
+                // is only used together with `gl::MAP_WRITE_BIT`
+                // `vbo` is bound to `target` and does not yet have immutable storage
+                gl::BufferStorage(gl::ARRAY_BUFFER, size, ptr::null(), flags);
+                check_error()?;
+
+                // SAFETY:
+                // the range covers the whole buffer, which is not yet mapped
+                // the buffer was created using the same `flags`
+                mapping = gl::MapBufferRange(gl::ARRAY_BUFFER, 0, size, flags).cast();
+                check_error()?;
+                if mapping.is_null() {
+                    bug!("failed to map the vertex buffer");
+                }
+            } else {
+                // SAFETY:
+                // `gl::ARRAY_BUFFER` is a valid buffer target
+                // `gl::STREAM_DRAW` is a valid usage
+                // `size` is positive
+                // `vbo` is bound to `target`
+                // `GL_BUFFER_IMMUTABLE_STORAGE` is not set
+                gl::BufferData(gl::ARRAY_BUFFER, size, ptr::null(), gl::STREAM_DRAW);
+                check_error()?;
+            }
+
+            // SAFETY: `vbo` is bound to `gl::ARRAY_BUFFER`
+            V::specify_attributes();
+        }
+
+        Ok(VertexRing {
+            vao,
+            vbo,
+            mapping,
+            fences: [ptr::null(); SEGMENT_COUNT],
+            segment: 0,
+            offset: 0,
+        })
+    }
+
+    /// Writes `vertices` into the buffer, returning the index of the first vertex.
+    ///
+    /// The returned index may only be used for draw calls issued before the next call to `push`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `vertices` contains more than `SEGMENT_SIZE` elements.
+    pub fn push(&mut self, vertices: &[V]) -> GLint {
+        let len = vertices.len();
+        assert!(len <= SEGMENT_SIZE, "too many vertices: {}", len);
+
+        if self.offset + len > SEGMENT_SIZE {
+            let next = (self.segment + 1) % SEGMENT_COUNT;
+            if !self.mapping.is_null() {
+                unsafe {
+                    // SAFETY:
+                    // `condition` is `gl::SYNC_GPU_COMMANDS_COMPLETE` and `flags` is zero
+                    self.fences[self.segment] = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+                    self.wait_for(next);
+                }
+            }
+            self.segment = next;
+            self.offset = 0;
+        }
+
+        let first = self.segment * SEGMENT_SIZE + self.offset;
+        unsafe {
+            if self.mapping.is_null() {
+                // SAFETY:
+                // `gl::ARRAY_BUFFER` is a valid `target` and `self.vbo` is valid
+                // the range `first..first + len` is inside of the buffer
+                // the buffer is not mapped
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+                gl::BufferSubData(
+                    gl::ARRAY_BUFFER,
+                    (first * mem::size_of::<V>()) as GLintptr,
+                    mem::size_of_val(vertices) as GLsizeiptr,
+                    vertices.as_ptr().cast(),
+                );
+            } else {
+                // SAFETY:
+                // the range `first..first + len` is inside of the mapped buffer
+                // and the GPU is done with the current segment
+                ptr::copy_nonoverlapping(vertices.as_ptr(), self.mapping.add(first), len);
+            }
+        }
+
+        self.offset += len;
+        first as GLint
+    }
+
+    /// Waits until the GPU is done using the given `segment`.
+    unsafe fn wait_for(&mut self, segment: usize) {
+        let fence = mem::replace(&mut self.fences[segment], ptr::null());
+        if !fence.is_null() {
+            loop {
+                // SAFETY:
+                // `fence` is a valid sync object
+                // `gl::SYNC_FLUSH_COMMANDS_BIT` is a valid flag
+                match gl::ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, 1_000_000) {
+                    gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => break,
+                    gl::TIMEOUT_EXPIRED => continue,
+                    _ => bug!("unexpected error: {}", gl::GetError()),
+                }
+            }
+
+            // SAFETY: `fence` is a valid sync object
+            gl::DeleteSync(fence);
+        }
+    }
+}
+
+impl<V: RingVertex> Drop for VertexRing<V> {
+    fn drop(&mut self) {
+        unsafe {
+            for &fence in self.fences.iter() {
+                if !fence.is_null() {
+                    // SAFETY: `fence` is a valid sync object
+                    gl::DeleteSync(fence);
+                }
+            }
+
+            // SAFETY: `n` is one and both objects were generated by OpenGL,
+            // deleting a mapped buffer also unmaps it
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+/// Checks if OpenGL is out of memory.
+unsafe fn check_error() -> Result<(), NewContextError> {
+    match gl::GetError() {
+        gl::NO_ERROR => Ok(()),
+        gl::OUT_OF_MEMORY => Err(super::out_of_memory(NewContextError::OutOfMemory)),
+        e => bug!("unexpected error: {}", e),
+    }
+}
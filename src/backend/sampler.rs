@@ -0,0 +1,71 @@
+use gl::types::*;
+
+use crate::{backend::Backend, Filter, Sampler, Wrap};
+
+// Only part of the core profile since OpenGL 4.6 and therefore missing from `gl`.
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+pub(super) const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+pub(super) fn filter(filter: Filter) -> GLint {
+    match filter {
+        Filter::Nearest => gl::NEAREST as _,
+        Filter::Linear => gl::LINEAR as _,
+    }
+}
+
+/// The minifying filter, which also blends between mipmap levels if `mipmaps` is set.
+pub(super) fn min_filter(min_filter: Filter, mipmaps: bool) -> GLint {
+    match (min_filter, mipmaps) {
+        (min_filter, false) => filter(min_filter),
+        (Filter::Nearest, true) => gl::NEAREST_MIPMAP_NEAREST as _,
+        (Filter::Linear, true) => gl::LINEAR_MIPMAP_LINEAR as _,
+    }
+}
+
+pub(super) fn wrap(wrap: Wrap) -> GLint {
+    match wrap {
+        Wrap::ClampToEdge => gl::CLAMP_TO_EDGE as _,
+        Wrap::Repeat => gl::REPEAT as _,
+        Wrap::MirroredRepeat => gl::MIRRORED_REPEAT as _,
+    }
+}
+
+impl Backend {
+    /// Returns the sampler object for `sampler`, `0` if the parameters of the texture should be used.
+    ///
+    /// `mipmaps` is set if the drawn texture has mipmaps, which are then also used by the sampler.
+    pub(super) fn sampler_object(&mut self, sampler: Option<Sampler>, mipmaps: bool) -> GLuint {
+        let sampler = if let Some(sampler) = sampler {
+            sampler
+        } else {
+            return 0;
+        };
+
+        let constants = &self.constants;
+        *self.samplers.entry((sampler, mipmaps)).or_insert_with(|| {
+            let mut id = 0;
+            unsafe {
+                // SAFETY: `n` is one
+                gl::GenSamplers(1, &mut id);
+                // SAFETY:
+                // `id` was just returned from `glGenSamplers`
+                // `gl::TEXTURE_WRAP_(S|T)` and `gl::TEXTURE_(MIN|MAG)_FILTER` are valid `pname`
+                // all used `param` are valid for their `pname`
+                let min_filter = min_filter(sampler.min_filter, mipmaps);
+                gl::SamplerParameteri(id, gl::TEXTURE_MIN_FILTER, min_filter);
+                gl::SamplerParameteri(id, gl::TEXTURE_MAG_FILTER, filter(sampler.mag_filter));
+                gl::SamplerParameteri(id, gl::TEXTURE_WRAP_S, wrap(sampler.wrap.0));
+                gl::SamplerParameteri(id, gl::TEXTURE_WRAP_T, wrap(sampler.wrap.1));
+                if constants.extensions.texture_filter_anisotropic {
+                    let anisotropy = sampler.anisotropy.max(1).min(constants.max_anisotropy);
+                    // SAFETY:
+                    // `TEXTURE_MAX_ANISOTROPY` is a valid `pname` as
+                    // `texture_filter_anisotropic` is supported
+                    // `anisotropy` is at least 1
+                    gl::SamplerParameterf(id, TEXTURE_MAX_ANISOTROPY, anisotropy as f32);
+                }
+            }
+            id
+        })
+    }
+}
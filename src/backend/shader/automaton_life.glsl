@@ -0,0 +1,9 @@
+vec4 rule() {
+    int neighbors = alive_neighbors();
+    bool alive = cell(ivec2(0)).r > 0.5;
+    if (neighbors == 3 || (alive && neighbors == 2)) {
+        return vec4(1.0);
+    } else {
+        return vec4(0.0, 0.0, 0.0, 1.0);
+    }
+}
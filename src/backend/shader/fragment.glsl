@@ -5,16 +5,31 @@ out vec4 color;
 
 uniform mat4 color_modulation;
 uniform bool invert_color;
+uniform bool premultiplied;
 
 uniform sampler2D source;
+uniform vec2 source_texture_dimensions;
+uniform uvec2 source_texture_offset;
+uniform uvec2 source_dimensions;
+
+uniform bool use_border;
+uniform vec4 border_color;
 
 void main() {
-    color = color_modulation * texture(source, tex_coords);
+    vec2 section_start = vec2(source_texture_offset) / source_texture_dimensions;
+    vec2 section_end = vec2(source_texture_offset + source_dimensions) / source_texture_dimensions;
+    // only sample the drawn section, even if it is part of a larger texture
+    if (use_border && (any(lessThan(tex_coords, section_start)) || any(greaterThanEqual(tex_coords, section_end)))) {
+        color = color_modulation * border_color;
+    } else {
+        color = color_modulation * texture(source, tex_coords);
+    }
     if (color.a == 0.0) {
         discard;
     }
 
     if (invert_color) {
-        color.rgb = 1.0 - color.rgb;
+        // premultiplied colors are in the range `0.0..=color.a`
+        color.rgb = (premultiplied ? color.a : 1.0) - color.rgb;
     }
 }
\ No newline at end of file
@@ -0,0 +1,40 @@
+#version 330
+
+out vec4 color;
+
+uniform sampler2D source;
+uniform bool wrap;
+
+// Returns the state of the cell at `offset` relative to the current cell.
+vec4 cell(ivec2 offset) {
+    ivec2 size = textureSize(source, 0);
+    ivec2 position = ivec2(gl_FragCoord.xy) + offset;
+    if (wrap) {
+        // `%` is undefined for negative operands
+        position -= size * ivec2(floor(vec2(position) / vec2(size)));
+    } else if (any(lessThan(position, ivec2(0))) || any(greaterThanEqual(position, size))) {
+        return vec4(0.0);
+    }
+
+    return texelFetch(source, position, 0);
+}
+
+// Returns the number of the 8 neighbors of the current cell
+// whose red channel is greater than `0.5`.
+int alive_neighbors() {
+    int count = 0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            if ((x != 0 || y != 0) && cell(ivec2(x, y)).r > 0.5) {
+                count++;
+            }
+        }
+    }
+    return count;
+}
+
+vec4 rule();
+
+void main() {
+    color = rule();
+}
@@ -0,0 +1,32 @@
+#version 330
+
+in vec2 tex_coords;
+flat in vec4 section;
+flat in mat4 color_modulation;
+flat in float invert_color;
+out vec4 color;
+
+uniform bool premultiplied;
+
+uniform sampler2D source;
+
+uniform bool use_border;
+uniform vec4 border_color;
+
+void main() {
+    // only sample the drawn section, even if it is part of a larger texture
+    if (use_border && (any(lessThan(tex_coords, section.xy)) || any(greaterThanEqual(tex_coords, section.zw)))) {
+        color = color_modulation * border_color;
+    } else {
+        color = color_modulation * texture(source, tex_coords);
+    }
+
+    if (color.a == 0.0) {
+        discard;
+    }
+
+    if (invert_color != 0.0) {
+        // premultiplied colors are in the range `0.0..=color.a`
+        color.rgb = (premultiplied ? color.a : 1.0) - color.rgb;
+    }
+}
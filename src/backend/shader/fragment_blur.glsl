@@ -0,0 +1,45 @@
+#version 330
+
+out vec4 color;
+
+uniform sampler2D source;
+// the lower left corner and size of the used part of `source`
+uniform ivec2 source_position;
+uniform ivec2 source_size;
+// the position of `source` relative to the target
+uniform ivec2 offset;
+// either `(1, 0)` or `(0, 1)`
+uniform ivec2 direction;
+uniform int radius;
+// if the result should be colored using `shadow_color`,
+// otherwise the blurred alpha is written to all channels
+uniform bool colorize;
+uniform vec4 shadow_color;
+
+float alpha(ivec2 position) {
+    position -= offset;
+    if (any(lessThan(position, ivec2(0))) || any(greaterThanEqual(position, source_size))) {
+        return 0.0;
+    }
+
+    return texelFetch(source, source_position + position, 0).a;
+}
+
+void main() {
+    ivec2 position = ivec2(gl_FragCoord.xy);
+    float sigma = max(float(radius) / 2.0, 0.5);
+    float sum = 0.0;
+    float total = 0.0;
+    for (int i = -radius; i <= radius; i++) {
+        float weight = exp(-float(i * i) / (2.0 * sigma * sigma));
+        sum += alpha(position + direction * i) * weight;
+        total += weight;
+    }
+
+    float blurred = sum / total;
+    if (colorize) {
+        color = vec4(shadow_color.rgb, shadow_color.a * blurred);
+    } else {
+        color = vec4(blurred);
+    }
+}
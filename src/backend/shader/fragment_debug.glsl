@@ -1,8 +1,8 @@
 #version 330
 
-out vec4 color;
+in vec4 line_color;
 
-uniform vec4 line_color;
+out vec4 color;
 
 void main() {
     color = line_color;
@@ -0,0 +1,90 @@
+#version 330
+
+out vec4 color;
+
+// 0: value noise, 1: perlin noise, 2: linear gradient, 3: radial gradient
+uniform uint mode;
+uniform uint seed;
+uniform vec2 offset;
+uniform float scale;
+uniform uint octaves;
+uniform float persistence;
+// the start and end of a linear gradient,
+// or the center and radius of a radial gradient
+uniform vec2 from;
+uniform vec2 to;
+uniform vec4 low;
+uniform vec4 high;
+
+// integer hashing is used instead of `sin` based hashes, so
+// the result is the same on all GPUs
+uint hash(uint x) {
+    x ^= x >> 16u;
+    x *= 0x7feb352du;
+    x ^= x >> 15u;
+    x *= 0x846ca68bu;
+    x ^= x >> 16u;
+    return x;
+}
+
+float random(ivec2 cell, uint octave) {
+    uint h = hash(uint(cell.x) ^ hash(uint(cell.y) ^ hash(seed + octave)));
+    return float(h) / 4294967295.0;
+}
+
+float value_noise(vec2 p, uint octave) {
+    ivec2 cell = ivec2(floor(p));
+    vec2 f = smoothstep(0.0, 1.0, fract(p));
+    float a = random(cell, octave);
+    float b = random(cell + ivec2(1, 0), octave);
+    float c = random(cell + ivec2(0, 1), octave);
+    float d = random(cell + ivec2(1, 1), octave);
+    return mix(mix(a, b, f.x), mix(c, d, f.x), f.y);
+}
+
+float gradient(ivec2 cell, vec2 f, uint octave) {
+    float angle = random(cell, octave) * 6.2831853;
+    return dot(vec2(cos(angle), sin(angle)), f);
+}
+
+float perlin_noise(vec2 p, uint octave) {
+    ivec2 cell = ivec2(floor(p));
+    vec2 f = fract(p);
+    // quintic fade curve
+    vec2 u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    float a = gradient(cell, f, octave);
+    float b = gradient(cell + ivec2(1, 0), f - vec2(1.0, 0.0), octave);
+    float c = gradient(cell + ivec2(0, 1), f - vec2(0.0, 1.0), octave);
+    float d = gradient(cell + ivec2(1, 1), f - vec2(1.0, 1.0), octave);
+    // the result is roughly in the range `-0.7..=0.7`
+    return clamp(mix(mix(a, b, u.x), mix(c, d, u.x), u.y) / 1.4 + 0.5, 0.0, 1.0);
+}
+
+float fractal_noise(vec2 p) {
+    float sum = 0.0;
+    float amplitude = 1.0;
+    float total = 0.0;
+    for (uint octave = 0u; octave < max(octaves, 1u); octave++) {
+        float n = mode == 0u ? value_noise(p, octave) : perlin_noise(p, octave);
+        sum += n * amplitude;
+        total += amplitude;
+        amplitude *= persistence;
+        p *= 2.0;
+    }
+    return sum / total;
+}
+
+void main() {
+    vec2 p = gl_FragCoord.xy + offset;
+    float t;
+    if (mode <= 1u) {
+        t = fractal_noise(p / scale);
+    } else if (mode == 2u) {
+        vec2 direction = to - from;
+        t = clamp(dot(p - from, direction) / dot(direction, direction), 0.0, 1.0);
+    } else {
+        t = clamp(length(p - from) / to.x, 0.0, 1.0);
+    }
+
+    color = mix(low, high, t);
+}
@@ -0,0 +1,25 @@
+#version 330
+
+out vec4 color;
+
+// each texel of a plane contains 4 consecutive bytes of a row,
+// the first row of each plane is the top of the frame
+uniform sampler2D y_plane;
+uniform sampler2D u_plane;
+uniform sampler2D v_plane;
+uniform int frame_height;
+
+float byte(sampler2D plane, ivec2 position) {
+    vec4 texel = texelFetch(plane, ivec2(position.x / 4, position.y), 0);
+    return texel[position.x % 4] * 255.0;
+}
+
+void main() {
+    ivec2 position = ivec2(int(gl_FragCoord.x), frame_height - 1 - int(gl_FragCoord.y));
+    // BT.601 with limited range
+    float luma = (byte(y_plane, position) - 16.0) * 1.164;
+    float u = byte(u_plane, position / 2) - 128.0;
+    float v = byte(v_plane, position / 2) - 128.0;
+    vec3 rgb = vec3(luma + 1.596 * v, luma - 0.392 * u - 0.813 * v, luma + 2.017 * u);
+    color = vec4(rgb / 255.0, 1.0);
+}
@@ -5,6 +5,14 @@ use std::{
 
 use gl::types::*;
 
+use crate::{
+    backend::{
+        ring::{SpriteVertex, VertexRing},
+        Extensions,
+    },
+    NewContextError, ShaderError,
+};
+
 /// `position` is at location 0 in both programs
 const POSITION_ATTR: GLuint = 0;
 /// We never use an offset into the vertex buffer
@@ -21,7 +29,8 @@ static VERTEX_DATA: [GLfloat; 8] = [
 const VERTEX: &str = include_str!("vertex.glsl");
 const FRAGMENT: &str = include_str!("fragment.glsl");
 
-fn compile_shader(src: &str, ty: GLenum) -> GLuint {
+/// Compiles a shader of type `ty`, deleting it again if it does not compile.
+fn compile_shader(src: &str, ty: GLenum) -> Result<GLuint, String> {
     let shader;
     unsafe {
         // SAFETY: `ty` is either `gl::VERTEX_SHADER` or `gl::FRAGMENT_SHADER`
@@ -53,21 +62,36 @@ fn compile_shader(src: &str, ty: GLenum) -> GLuint {
             // SAFETY: `maxLength` is the value of `gl::INFO_LOG_LENGTH`
             gl::GetShaderInfoLog(shader, len, ptr::null_mut(), buf.as_mut_ptr().cast());
             // SAFETY: the content has been written by `gl::GetShaderInfoLog`
-            buf.set_len((len as usize) - 1);
-            bug!(
-                "{}",
-                str::from_utf8(&buf).expect("ShaderInfoLog not valid utf8")
-            );
+            buf.set_len((len as usize).saturating_sub(1));
+            // SAFETY: `shader` is a shader object created by OpenGL
+            gl::DeleteShader(shader);
+            return Err(String::from_utf8_lossy(&buf).into_owned());
         }
     }
 
-    shader
+    Ok(shader)
 }
 
+/// Compiles one of the internal programs of crow, which must not fail.
+///
 /// uses the created program
-fn compile_program(vertex: &str, fragment: &str) -> GLuint {
-    let vs = compile_shader(vertex, gl::VERTEX_SHADER);
-    let fs = compile_shader(fragment, gl::FRAGMENT_SHADER);
+pub fn compile_program(vertex: &str, fragment: &str) -> GLuint {
+    try_compile_program(vertex, fragment).unwrap_or_else(|err| bug!("{}", err))
+}
+
+/// Compiles and links a program, deleting all created objects on failure.
+///
+/// uses the created program if successful
+pub fn try_compile_program(vertex: &str, fragment: &str) -> Result<GLuint, ShaderError> {
+    let vs = compile_shader(vertex, gl::VERTEX_SHADER).map_err(ShaderError::Compile)?;
+    let fs = match compile_shader(fragment, gl::FRAGMENT_SHADER) {
+        Ok(fs) => fs,
+        Err(err) => {
+            // SAFETY: `vs` is a shader object created by OpenGL
+            unsafe { gl::DeleteShader(vs) };
+            return Err(ShaderError::Compile(err));
+        }
+    };
     unsafe {
         // SAFETY: can not fail
         let program = gl::CreateProgram();
@@ -81,6 +105,11 @@ fn compile_program(vertex: &str, fragment: &str) -> GLuint {
         gl::AttachShader(program, vs);
         gl::AttachShader(program, fs);
 
+        // SAFETY: `colorNumber` is zero, which is less than `GL_MAX_DRAW_BUFFERS`,
+        // the binding only takes effect once the program is linked
+        let color_str = CString::new("color").unwrap();
+        gl::BindFragDataLocation(program, 0, color_str.as_ptr());
+
         // SAFETY:
         // `program` is a valid program object and not active
         gl::LinkProgram(program);
@@ -104,11 +133,15 @@ fn compile_program(vertex: &str, fragment: &str) -> GLuint {
                 buf.as_mut_ptr() as *mut GLchar,
             );
             // SAFETY: the content has been written by `gl::GetProgramInfoLog`
-            buf.set_len(len as usize - 1);
-            bug!(
-                "{}",
-                str::from_utf8(&buf).expect("ProgramInfoLog not valid utf8")
-            );
+            buf.set_len((len as usize).saturating_sub(1));
+            // SAFETY: all objects were created by OpenGL and are not in use,
+            // the shaders are only freed once they are detached from `program`
+            gl::DeleteShader(fs);
+            gl::DeleteShader(vs);
+            gl::DeleteProgram(program);
+            return Err(ShaderError::Link(
+                String::from_utf8_lossy(&buf).into_owned(),
+            ));
         }
 
         // SAFETY:
@@ -121,15 +154,11 @@ fn compile_program(vertex: &str, fragment: &str) -> GLuint {
 
         // SAFETY: no OpenGlState is currently alive
         super::update_program(program);
-
-        // SAFETY: `colorNumber` is zero, which is less than `GL_MAX_DRAW_BUFFERS`
-        let color_str = CString::new("color").unwrap();
-        gl::BindFragDataLocation(program, 0, color_str.as_ptr());
-        program
+        Ok(program)
     }
 }
 
-fn init_vertex_buffer(vbo: GLuint, data: &[GLfloat]) {
+fn init_vertex_buffer(vbo: GLuint, data: &[GLfloat]) -> Result<(), NewContextError> {
     unsafe {
         // SAFETY: `gl::ARRAY_BUFFER` is a valid `target` and `vbo` is valid
         gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
@@ -148,13 +177,8 @@ fn init_vertex_buffer(vbo: GLuint, data: &[GLfloat]) {
         // check for oom
         let gl_error = gl::GetError();
         match gl_error {
-            gl::NO_ERROR => (),
-            gl::OUT_OF_MEMORY => {
-                // TODO: OpenGl is now in an undefined state,
-                // consider aborting instead, as it is possible
-                // to catch a panic
-                panic!("OpenGl is out of memory and in an invalid state");
-            }
+            gl::NO_ERROR => Ok(()),
+            gl::OUT_OF_MEMORY => Err(super::out_of_memory(NewContextError::OutOfMemory)),
             e => bug!("unexpected error: {}", e),
         }
     }
@@ -180,7 +204,7 @@ pub struct Program {
 }
 
 impl Program {
-    pub fn new() -> (Self, Uniforms) {
+    pub fn new() -> Result<(Self, Uniforms), NewContextError> {
         let program = compile_program(VERTEX, FRAGMENT);
         let mut vao = 0;
         let mut vbo = 0;
@@ -193,7 +217,7 @@ impl Program {
             // SAFETY: `vao` was just returned from `gl::GenVertexArrays`
             gl::BindVertexArray(vao);
 
-            init_vertex_buffer(vbo, &VERTEX_DATA);
+            init_vertex_buffer(vbo, &VERTEX_DATA)?;
 
             // SAFETY:
             // `vao` is the currently bound vertex array
@@ -223,7 +247,7 @@ impl Program {
         };
 
         let uniforms = prog.get_uniforms();
-        (prog, uniforms)
+        Ok((prog, uniforms))
     }
 
     pub fn get_uniforms(&self) -> Uniforms {
@@ -231,6 +255,9 @@ impl Program {
             source: get_uniform_id(self.id, "source"),
             color_modulation: get_uniform_id(self.id, "color_modulation"),
             invert_color: get_uniform_id(self.id, "invert_color"),
+            premultiplied: get_uniform_id(self.id, "premultiplied"),
+            use_border: get_uniform_id(self.id, "use_border"),
+            border_color: get_uniform_id(self.id, "border_color"),
             flip_vertically: get_uniform_id(self.id, "flip_vertically"),
             flip_horizontally: get_uniform_id(self.id, "flip_horizontally"),
             target_dimensions: get_uniform_id(self.id, "target_dimensions"),
@@ -240,6 +267,9 @@ impl Program {
             source_position: get_uniform_id(self.id, "source_position"),
             source_scale: get_uniform_id(self.id, "source_scale"),
             source_rotation: get_uniform_id(self.id, "source_rotation"),
+            rotation_center: get_uniform_id(self.id, "rotation_center"),
+            use_transform: get_uniform_id(self.id, "use_transform"),
+            transform: get_uniform_id(self.id, "transform"),
             depth: get_uniform_id(self.id, "depth"),
         }
     }
@@ -261,6 +291,7 @@ pub struct Uniforms {
     pub source: GLint,
     pub color_modulation: GLint,
     pub invert_color: GLint,
+    pub premultiplied: GLint,
     pub flip_vertically: GLint,
     pub flip_horizontally: GLint,
     pub target_dimensions: GLint,
@@ -270,127 +301,79 @@ pub struct Uniforms {
     pub source_position: GLint,
     pub source_scale: GLint,
     pub source_rotation: GLint,
+    pub rotation_center: GLint,
+    pub use_transform: GLint,
+    pub transform: GLint,
+    pub use_border: GLint,
+    pub border_color: GLint,
     pub depth: GLint,
 }
 
-#[rustfmt::skip]
-static LINES_VERTEX_DATA: [GLfloat; 8] = [
-    1.0, 1.0, 0.0, 0.0,
-    0.0, 0.0, 1.0, 1.0,
-];
-
-#[rustfmt::skip]
-static RECTANGLES_VERTEX_DATA: [GLfloat; 20] = [
-    1.0, 1.0, 0.0, 0.0,
-    1.0, 0.0, 0.0, 1.0,
-    0.0, 0.0, 1.0, 1.0,
-    0.0, 1.0, 1.0, 0.0,
-    1.0, 1.0, 0.0, 0.0,
-];
-
-/// vao 0 is for drawing lines
-/// vao 1 for drawing rectangles
+/// The program used to draw primitives, their vertices are stored in `vertices`.
 #[derive(Debug)]
 pub struct DebugProgram {
     pub id: GLuint,
-    pub vao: [GLuint; 2],
-    pub vbo: [GLuint; 2],
+    pub vertices: VertexRing,
+    /// Only used by occlusion queries, all other primitives use a depth of `0.0`.
+    pub depth: GLint,
 }
 
 impl DebugProgram {
-    pub fn new() -> (Self, DebugUniforms) {
+    pub fn new(extensions: &Extensions) -> Result<Self, NewContextError> {
         let program = compile_program(
             include_str!("vertex_debug.glsl"),
             include_str!("fragment_debug.glsl"),
         );
 
-        let mut vao = [0; 2];
-        let mut vbo = [0; 2];
-
-        unsafe {
-            // SAFETY: `n` is positive
-            gl::GenVertexArrays(2, vao.as_mut_ptr());
-            gl::GenBuffers(2, vbo.as_mut_ptr());
+        Ok(Self {
+            id: program,
+            vertices: VertexRing::new(extensions)?,
+            depth: get_uniform_id(program, "depth"),
+        })
+    }
+}
 
-            // SAFETY: `vao` was just returned from `gl::GenVertexArrays`
-            gl::BindVertexArray(vao[0]);
-            init_vertex_buffer(vbo[0], &LINES_VERTEX_DATA);
+/// The program used to draw sprite batches, their vertices are stored in `vertices`.
+#[derive(Debug)]
+pub struct BatchProgram {
+    pub id: GLuint,
+    pub vertices: VertexRing<SpriteVertex>,
+    pub premultiplied: GLint,
+    pub use_border: GLint,
+    pub border_color: GLint,
+}
 
-            // SAFETY:
-            // `vao[0]` is the currently bound vertex array
-            // `position` was specified with `layout (location = 0) in vec4`
-            // `POSITION_ATTR` is less than `GL_MAX_VERTEX_ATTRIBS`
-            gl::EnableVertexAttribArray(POSITION_ATTR);
-            // SAFETY:
-            // `POSITION_ATTR` is less than `GL_MAX_VERTEX_ATTRIBS`
-            // `size` is four
-            // `gl::FLOAT` is an accepted value
-            // `stride` is zero
-            // the offset into `vbo` is zero
-            gl::VertexAttribPointer(
-                POSITION_ATTR,
-                4,
-                gl::FLOAT,
-                gl::FALSE as GLboolean,
-                0,
-                ptr::null(),
-            );
+impl BatchProgram {
+    pub fn new(extensions: &Extensions) -> Result<Self, NewContextError> {
+        let id = compile_program(
+            include_str!("vertex_batch.glsl"),
+            include_str!("fragment_batch.glsl"),
+        );
 
-            // SAFETY: `vao` was just returned from `gl::GenVertexArrays`
-            gl::BindVertexArray(vao[1]);
-            init_vertex_buffer(vbo[1], &RECTANGLES_VERTEX_DATA);
+        Ok(Self {
+            id,
+            vertices: VertexRing::new(extensions)?,
+            premultiplied: get_uniform_id(id, "premultiplied"),
+            use_border: get_uniform_id(id, "use_border"),
+            border_color: get_uniform_id(id, "border_color"),
+        })
+    }
+}
 
-            // SAFETY:
-            // `vao[1]` is the currently bound vertex array
-            // `position` was specified with `layout (location = 0) in vec4`
-            // `POSITION_ATTR` is less than `GL_MAX_VERTEX_ATTRIBS`
-            gl::EnableVertexAttribArray(POSITION_ATTR);
-            // SAFETY:
-            // `POSITION_ATTR` is less than `GL_MAX_VERTEX_ATTRIBS`
-            // `size` is two
-            // `gl::FLOAT` is an accepted value
-            // `stride` is zero
-            // the offset into `vbo` is zero
-            gl::VertexAttribPointer(
-                POSITION_ATTR,
-                4,
-                gl::FLOAT,
-                gl::FALSE as GLboolean,
-                0,
-                ptr::null(),
-            );
+impl Drop for BatchProgram {
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY: `id` was generated by OpenGL
+            gl::DeleteProgram(self.id);
         }
-
-        let line_color_uniform = get_uniform_id(program, "line_color");
-        let start_end = get_uniform_id(program, "start_end");
-
-        (
-            Self {
-                id: program,
-                vao,
-                vbo,
-            },
-            DebugUniforms {
-                line_color: line_color_uniform,
-                start_end,
-            },
-        )
     }
 }
 
 impl Drop for DebugProgram {
     fn drop(&mut self) {
         unsafe {
-            // SAFETY: `id` was generated by OpenGL and `n` is two
+            // SAFETY: `id` was generated by OpenGL
             gl::DeleteProgram(self.id);
-            gl::DeleteBuffers(2, self.vbo.as_ptr());
-            gl::DeleteVertexArrays(2, self.vao.as_ptr());
         }
     }
 }
-
-#[derive(Debug)]
-pub struct DebugUniforms {
-    pub line_color: GLint,
-    pub start_end: GLint,
-}
@@ -16,6 +16,9 @@ uniform bool flip_horizontally;
 
 uniform uvec2 source_scale;
 uniform mat2 source_rotation;
+uniform vec2 rotation_center;
+uniform bool use_transform;
+uniform mat3 transform;
 uniform float depth;
 
 void main() {
@@ -36,14 +39,18 @@ void main() {
     // All sprites don't have such dimension
     vec2 scale = vec2(source_scale * source_dimensions);
     vec2 pos = position * scale;
-    // [0.5, 0.5] is currently the center of the square
-    // It has to be [0.0, 0.0] for a correct rotation
-    // Relatively to current dimension, "re-center" the square
-    vec2 trick = vec2(pos.x - scale.x / 2.0, pos.y - scale.y / 2.0);
+    // The rotation center has to be [0.0, 0.0] for a correct rotation
+    // so we move the square relative to it
+    vec2 trick = pos - rotation_center;
     // Then rotate it
     trick *= source_rotation;
-    // Then "re-re-center" it
-    trick = vec2(trick.x + scale.x / 2.0, trick.y + scale.y / 2.0);
+    // And move it back
+    trick += rotation_center;
+    // Apply the user defined transform, skipped for the identity
+    // to keep the pixel-perfect default path
+    if (use_transform) {
+        trick = (transform * vec3(trick, 1.0)).xy;
+    }
     
     vec2 target_pos = (trick + source_position) / target_dimensions;
 
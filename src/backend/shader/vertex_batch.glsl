@@ -0,0 +1,25 @@
+#version 330
+// the position in normalized device coordinates
+layout (location = 0) in vec2 position;
+layout (location = 1) in vec2 sprite_tex_coords;
+// the start and end of the drawn section in texture coordinates
+layout (location = 2) in vec4 sprite_section;
+// the rows of `DrawConfig::color_modulation`, which is row-major
+layout (location = 3) in mat4 sprite_color_modulation;
+layout (location = 7) in float depth;
+layout (location = 8) in float sprite_invert_color;
+
+out vec2 tex_coords;
+flat out vec4 section;
+flat out mat4 color_modulation;
+flat out float invert_color;
+
+void main() {
+    tex_coords = sprite_tex_coords;
+    section = sprite_section;
+    // each column of the attribute is a row of the modulation matrix
+    color_modulation = transpose(sprite_color_modulation);
+    invert_color = sprite_invert_color;
+
+    gl_Position = vec4(position, depth, 1.0);
+}
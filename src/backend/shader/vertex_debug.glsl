@@ -1,15 +1,14 @@
 #version 330
 
-// select (x1, y1, x2, y2)
-layout (location = 0) in vec4 position;
+// in normalized device coordinates
+layout (location = 0) in vec2 position;
+layout (location = 1) in vec4 vertex_color;
 
-// (x1, y1, x2, y2)
-uniform vec4 start_end;
+out vec4 line_color;
+
+uniform float depth;
 
 void main() {
-    gl_Position = vec4(
-        start_end.x * position.x + start_end.z * position.z,
-        start_end.y * position.y + start_end.w * position.w,
-        0.0, 1.0
-    );
+    gl_Position = vec4(position, depth, 1.0);
+    line_color = vertex_color;
 }
\ No newline at end of file
@@ -0,0 +1,10 @@
+#version 330
+layout (location = 0) in vec2 position;
+
+out vec2 v_uv;
+
+void main() {
+    // the unit square covers the whole target
+    v_uv = position;
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
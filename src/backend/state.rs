@@ -1,55 +1,107 @@
+use std::collections::HashMap;
+
 use gl::types::*;
 
 use crate::{
-    backend::shader::{DebugUniforms, Uniforms},
+    backend::{pass::Uniform, shader::Uniforms},
     BlendMode,
 };
 
-fn update_blend_mode(blend_mode: BlendMode) {
+/// Sets the blend function, `None` overwrites the target.
+fn update_blend_mode(blend_mode: Option<BlendMode>) {
     unsafe {
         // SAFETY:
-        // `gl::SRC_ALPHA` is a valid `sfactor`
-        // both `gl::ONE_MINUS_SRC_ALPHA` is a valid `dfactor`
+        // `gl::SRC_ALPHA` and `gl::ONE` are valid `sfactor`s
+        // `gl::ONE_MINUS_SRC_ALPHA`, `gl::ONE` and `gl::ZERO` are valid `dfactor`s
         match blend_mode {
-            BlendMode::Alpha => gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA),
-            BlendMode::Additive => gl::BlendFunc(gl::SRC_ALPHA, gl::ONE),
+            Some(BlendMode::Alpha) => gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA),
+            Some(BlendMode::Additive) => gl::BlendFunc(gl::SRC_ALPHA, gl::ONE),
+            Some(BlendMode::PremultipliedAlpha) => gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA),
+            None => gl::BlendFunc(gl::ONE, gl::ZERO),
         }
     }
 }
+/// How the stencil buffer of the current framebuffer is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StencilMode {
+    /// The stencil buffer is ignored.
+    Disabled,
+    /// Each drawn pixel sets the stencil buffer to `1`.
+    Write,
+    /// Only pixels with a stencil value of `1` are drawn.
+    Test,
+}
+
+fn update_stencil_mode(stencil_mode: StencilMode) {
+    unsafe {
+        // SAFETY:
+        // `gl::STENCIL_TEST` is a valid `cap`
+        // `gl::ALWAYS` and `gl::EQUAL` are valid stencil functions
+        // `gl::KEEP` and `gl::REPLACE` are valid stencil operations
+        match stencil_mode {
+            StencilMode::Disabled => gl::Disable(gl::STENCIL_TEST),
+            StencilMode::Write => {
+                gl::Enable(gl::STENCIL_TEST);
+                gl::StencilFunc(gl::ALWAYS, 1, 0xff);
+                gl::StencilOp(gl::KEEP, gl::KEEP, gl::REPLACE);
+            }
+            StencilMode::Test => {
+                gl::Enable(gl::STENCIL_TEST);
+                gl::StencilFunc(gl::EQUAL, 1, 0xff);
+                gl::StencilOp(gl::KEEP, gl::KEEP, gl::KEEP);
+            }
+        }
+    }
+}
+
 /// TODO: in case `update_program` fails, there might not be a current program object, meaning
 /// that `glUniform` can error.
 #[derive(Debug)]
 pub struct OpenGlState {
     uniforms: Uniforms,
-    debug_uniforms: DebugUniforms,
     program: GLuint,
     vao: GLuint,
     target_dimensions: (u32, u32),
     viewport_dimensions: (u32, u32),
-    blend_mode: BlendMode,
+    /// `None` if the target is overwritten.
+    blend_mode: Option<BlendMode>,
+    color_mask: (bool, bool, bool, bool),
+    stencil_mode: StencilMode,
+    /// The lower left corner and size of the scissor rectangle, `None` if the scissor test is disabled.
+    scissor: Option<((i32, i32), (u32, u32))>,
     depth_active: bool,
     depth: f32,
+    /// The framebuffer used for `0`, only differs from `0` for headless contexts.
+    default_framebuffer: GLuint,
     framebuffer: GLuint,
     texture: GLuint,
+    /// The sampler object bound to texture unit 0, `0` if the texture parameters are used.
+    sampler: GLuint,
     source_scale: (u32, u32),
-    source_rotation: i32,
+    source_rotation: f32,
+    rotation_center: (f32, f32),
+    /// `None` if `use_transform` is false.
+    transform: Option<[[f32; 3]; 3]>,
     color_modulation: [[f32; 4]; 4],
     source_texture_dimensions: (u32, u32),
     source_texture_offset: (u32, u32),
     source_position: (i32, i32),
     source_dimensions: (u32, u32),
     invert_color: bool,
+    premultiplied: bool,
+    /// `None` if `use_border` is false.
+    border_color: Option<(f32, f32, f32, f32)>,
     flip_vertically: bool,
     flip_horizontally: bool,
-    debug_color: (f32, f32, f32, f32),
-    debug_start_end: (f32, f32, f32, f32),
+    /// The uniforms of all programs except the main program, indexed by program and location.
+    pass_uniforms: HashMap<(GLuint, GLint), Uniform>,
 }
 
 impl OpenGlState {
     pub fn new(
         uniforms: Uniforms,
-        debug_uniforms: DebugUniforms,
         (program, vao): (GLuint, GLuint),
+        default_framebuffer: GLuint,
         window_dimensions: (u32, u32),
     ) -> Self {
         unsafe {
@@ -71,9 +123,20 @@ impl OpenGlState {
             // SAFETY: both `width` and `height` are positive
             gl::Viewport(0, 0, viewport_dimensions.0 as _, viewport_dimensions.1 as _);
 
-            let blend_mode = BlendMode::Alpha;
+            let blend_mode = Some(BlendMode::Alpha);
             update_blend_mode(blend_mode);
 
+            let color_mask = (true, true, true, true);
+            // SAFETY: this function is always safe
+            gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+
+            let stencil_mode = StencilMode::Disabled;
+            update_stencil_mode(stencil_mode);
+
+            let scissor = None;
+            // SAFETY: `gl::SCISSOR_TEST` is a valid `cap`
+            gl::Disable(gl::SCISSOR_TEST);
+
             let depth_active = false;
             let depth = 0.0;
 
@@ -85,8 +148,11 @@ impl OpenGlState {
             let framebuffer = 0;
             // SAFETY:
             // `gl::FRAMEBUFFER` is a valid target
-            // `framebuffer` was previously returned from `glGenFramebuffers`
-            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+            // `default_framebuffer` was previously returned from `glGenFramebuffers`
+            gl::BindFramebuffer(gl::FRAMEBUFFER, default_framebuffer);
+
+            // SAFETY: `source` is declared as a `sampler2D` and we only use texture unit 0
+            gl::Uniform1i(uniforms.source, 0);
 
             let texture = 0;
             // SAFETY:
@@ -95,11 +161,14 @@ impl OpenGlState {
             gl::BindTexture(gl::TEXTURE_2D, texture);
             assert_eq!(gl::NO_ERROR, gl::GetError());
 
+            // no sampler object is bound by default
+            let sampler = 0;
+
             let source_scale = (1, 1);
             // SAFETY: `source_scale` is declared as a `uvec2`
             gl::Uniform2ui(uniforms.source_scale, source_scale.0, source_scale.1);
 
-            let source_rotation = 0;
+            let source_rotation = 0.0;
             // An angle of 0 means identity matrix
             // SAFETY: `source_rotation` is declared as a `mat2`
             let rot_mat: [[f32; 2]; 2] = [[1.0, 0.0], [0.0, 1.0]];
@@ -111,6 +180,8 @@ impl OpenGlState {
             );
 
             // By default, all uniforms are 0
+            let rotation_center = (0.0, 0.0);
+            let transform = None;
             let color_modulation = [
                 [0.0, 0.0, 0.0, 0.0],
                 [0.0, 0.0, 0.0, 0.0],
@@ -152,41 +223,54 @@ impl OpenGlState {
 
             let invert_color = false;
             // SAFETY: `invert_color` is declared as a `bool`
-            gl::Uniform1ui(uniforms.invert_color, invert_color as _);
+            gl::Uniform1ui(uniforms.invert_color, GLuint::from(invert_color));
+
+            let premultiplied = false;
+            // SAFETY: `premultiplied` is declared as a `bool`
+            gl::Uniform1ui(uniforms.premultiplied, GLuint::from(premultiplied));
+
+            // By default, all uniforms are 0
+            let border_color = None;
 
             let flip_vertically = false;
             // SAFETY: `flip_vertically` is declared as a `bool`
-            gl::Uniform1ui(uniforms.flip_vertically, flip_vertically as _);
+            gl::Uniform1ui(uniforms.flip_vertically, GLuint::from(flip_vertically));
 
             let flip_horizontally = false;
             // SAFETY: `flip_horizontally` is declared as a `bool`
-            gl::Uniform1ui(uniforms.flip_horizontally, flip_horizontally as _);
+            gl::Uniform1ui(uniforms.flip_horizontally, GLuint::from(flip_horizontally));
 
             Self {
                 uniforms,
                 vao,
-                debug_uniforms,
                 program,
                 target_dimensions,
                 viewport_dimensions,
                 blend_mode,
+                color_mask,
+                stencil_mode,
+                scissor,
                 depth_active,
                 depth,
+                default_framebuffer,
                 framebuffer,
                 texture,
+                sampler,
                 source_scale,
                 source_rotation,
+                rotation_center,
+                transform,
                 color_modulation,
                 source_texture_dimensions,
                 source_texture_offset,
                 source_position,
                 source_dimensions,
                 invert_color,
+                premultiplied,
+                border_color,
                 flip_vertically,
                 flip_horizontally,
-                // set `debug_color` and `debug_start_end` to the default value
-                debug_color: (0.0, 0.0, 0.0, 0.0),
-                debug_start_end: (0.0, 0.0, 0.0, 0.0),
+                pass_uniforms: HashMap::new(),
             }
         }
     }
@@ -238,12 +322,71 @@ impl OpenGlState {
     }
 
     pub fn update_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.update_blend_function(Some(blend_mode))
+    }
+
+    /// Overwrites the target with each drawn pixel, ignoring its previous value.
+    pub fn disable_blending(&mut self) {
+        self.update_blend_function(None)
+    }
+
+    fn update_blend_function(&mut self, blend_mode: Option<BlendMode>) {
         if blend_mode != self.blend_mode {
             self.blend_mode = blend_mode;
             update_blend_mode(self.blend_mode);
         }
     }
 
+    pub fn update_color_mask(&mut self, color_mask: (bool, bool, bool, bool)) {
+        if color_mask != self.color_mask {
+            self.color_mask = color_mask;
+            unsafe {
+                // SAFETY: this function is always safe
+                gl::ColorMask(
+                    GLboolean::from(color_mask.0),
+                    GLboolean::from(color_mask.1),
+                    GLboolean::from(color_mask.2),
+                    GLboolean::from(color_mask.3),
+                );
+            }
+        }
+    }
+
+    pub fn update_stencil_mode(&mut self, stencil_mode: StencilMode) {
+        if stencil_mode != self.stencil_mode {
+            self.stencil_mode = stencil_mode;
+            update_stencil_mode(self.stencil_mode);
+        }
+    }
+
+    pub fn update_scissor(&mut self, scissor: Option<((i32, i32), (u32, u32))>) {
+        if scissor != self.scissor {
+            self.scissor = scissor;
+            unsafe {
+                if let Some((position, size)) = scissor {
+                    // SAFETY: `gl::SCISSOR_TEST` is a valid `cap`
+                    gl::Enable(gl::SCISSOR_TEST);
+                    // SAFETY: both `width` and `height` are positive
+                    gl::Scissor(position.0, position.1, size.0 as _, size.1 as _);
+                } else {
+                    // SAFETY: `gl::SCISSOR_TEST` is a valid `cap`
+                    gl::Disable(gl::SCISSOR_TEST);
+                }
+            }
+        }
+    }
+
+    /// Enables the depth test without changing the `depth` uniform of the main program.
+    pub fn enable_depth(&mut self) {
+        if !self.depth_active {
+            self.depth_active = true;
+            unsafe {
+                // SAFETY: `gl::DEPTH_TEST` is a valid `cap`.
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
     pub fn disable_depth(&mut self) {
         if self.depth_active {
             self.depth_active = false;
@@ -284,11 +427,20 @@ impl OpenGlState {
                 // SAFETY:
                 // `gl::FRAMEBUFFER` is a valid target
                 // `framebuffer` was previously returned from `glGenFramebuffers`
-                gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+                gl::BindFramebuffer(gl::FRAMEBUFFER, self.bound_framebuffer());
             }
         }
     }
 
+    /// The OpenGL name of the current framebuffer, replacing `0` with the default framebuffer.
+    pub fn bound_framebuffer(&self) -> GLuint {
+        if self.framebuffer == 0 {
+            self.default_framebuffer
+        } else {
+            self.framebuffer
+        }
+    }
+
     pub fn update_texture(&mut self, texture: GLuint) {
         if texture != self.texture {
             self.texture = texture;
@@ -302,6 +454,18 @@ impl OpenGlState {
         }
     }
 
+    pub fn update_sampler(&mut self, sampler: GLuint) {
+        if sampler != self.sampler {
+            self.sampler = sampler;
+            unsafe {
+                // SAFETY:
+                // `0` is a valid texture unit
+                // `self.sampler` is either zero or was created using `glGenSamplers`
+                gl::BindSampler(0, self.sampler);
+            }
+        }
+    }
+
     pub fn update_source_scale(&mut self, source_scale: (u32, u32)) {
         if source_scale != self.source_scale {
             self.source_scale = source_scale;
@@ -316,10 +480,11 @@ impl OpenGlState {
         }
     }
 
-    pub fn update_source_rotation(&mut self, source_rotation: i32) {
+    #[allow(clippy::float_cmp)]
+    pub fn update_source_rotation(&mut self, source_rotation: f32) {
         if source_rotation != self.source_rotation {
             // Build rotation matrices
-            let angle = (source_rotation as f32).to_radians();
+            let angle = source_rotation.to_radians();
             let rot_mat: [[f32; 2]; 2] = [[angle.cos(), -angle.sin()], [angle.sin(), angle.cos()]];
             self.source_rotation = source_rotation;
             unsafe {
@@ -333,6 +498,44 @@ impl OpenGlState {
         }
     }
 
+    #[allow(clippy::float_cmp)]
+    pub fn update_rotation_center(&mut self, rotation_center: (f32, f32)) {
+        if rotation_center != self.rotation_center {
+            self.rotation_center = rotation_center;
+            // SAFETY: `rotation_center` is declared as a `vec2`
+            unsafe {
+                gl::Uniform2f(
+                    self.uniforms.rotation_center,
+                    rotation_center.0,
+                    rotation_center.1,
+                )
+            }
+        }
+    }
+
+    pub fn update_transform(&mut self, transform: Option<[[f32; 3]; 3]>) {
+        if transform != self.transform {
+            unsafe {
+                if self.transform.is_some() != transform.is_some() {
+                    // SAFETY: `use_transform` is declared as a `bool`
+                    gl::Uniform1ui(
+                        self.uniforms.use_transform,
+                        GLuint::from(transform.is_some()),
+                    );
+                }
+
+                if let Some(transform) = &transform {
+                    let transform: *const [[f32; 3]; 3] = transform;
+                    // SAFETY:
+                    // `transform` is declared as a `mat3`
+                    // `transform` is an array of 9 `GLfloat`.
+                    gl::UniformMatrix3fv(self.uniforms.transform, 1, gl::TRUE, transform.cast())
+                }
+            }
+            self.transform = transform;
+        }
+    }
+
     pub fn update_color_modulation(&mut self, color_modulation: [[f32; 4]; 4]) {
         if color_modulation != self.color_modulation {
             self.color_modulation = color_modulation;
@@ -412,17 +615,53 @@ impl OpenGlState {
             self.invert_color = invert_color;
             unsafe {
                 // SAFETY: `invert_color` is declared as a `bool`
-                gl::Uniform1ui(self.uniforms.invert_color, self.invert_color as _);
+                gl::Uniform1ui(self.uniforms.invert_color, GLuint::from(self.invert_color));
+            }
+        }
+    }
+
+    pub fn update_premultiplied(&mut self, premultiplied: bool) {
+        if premultiplied != self.premultiplied {
+            self.premultiplied = premultiplied;
+            unsafe {
+                // SAFETY: `premultiplied` is declared as a `bool`
+                gl::Uniform1ui(
+                    self.uniforms.premultiplied,
+                    GLuint::from(self.premultiplied),
+                );
             }
         }
     }
 
+    pub fn update_border_color(&mut self, border_color: Option<(f32, f32, f32, f32)>) {
+        if border_color != self.border_color {
+            unsafe {
+                if self.border_color.is_some() != border_color.is_some() {
+                    // SAFETY: `use_border` is declared as a `bool`
+                    gl::Uniform1ui(
+                        self.uniforms.use_border,
+                        GLuint::from(border_color.is_some()),
+                    );
+                }
+
+                if let Some((r, g, b, a)) = border_color {
+                    // SAFETY: `border_color` is declared as a `vec4`
+                    gl::Uniform4f(self.uniforms.border_color, r, g, b, a);
+                }
+            }
+            self.border_color = border_color;
+        }
+    }
+
     pub fn update_flip_vertically(&mut self, flip_vertically: bool) {
         if flip_vertically != self.flip_vertically {
             self.flip_vertically = flip_vertically;
             unsafe {
                 // SAFETY: `flip_vertically` is declared as a `bool`
-                gl::Uniform1ui(self.uniforms.flip_vertically, self.flip_vertically as _);
+                gl::Uniform1ui(
+                    self.uniforms.flip_vertically,
+                    GLuint::from(self.flip_vertically),
+                );
             }
         }
     }
@@ -432,39 +671,61 @@ impl OpenGlState {
             self.flip_horizontally = flip_horizontally;
             unsafe {
                 // SAFETY: `flip_horizontally` is declared as a `bool`
-                gl::Uniform1ui(self.uniforms.flip_horizontally, self.flip_horizontally as _);
+                gl::Uniform1ui(
+                    self.uniforms.flip_horizontally,
+                    GLuint::from(self.flip_horizontally),
+                );
             }
         }
     }
 
-    pub fn update_debug_color(&mut self, debug_color: (f32, f32, f32, f32)) {
-        if debug_color != self.debug_color {
-            self.debug_color = debug_color;
-            unsafe {
-                // SAFETY: `line_color` is declared as `vec4`
-                gl::Uniform4f(
-                    self.debug_uniforms.line_color,
-                    debug_color.0,
-                    debug_color.1,
-                    debug_color.2,
-                    debug_color.3,
-                );
+    /// Sets the uniform at `location` of the current program `program`,
+    /// which must not be the main program.
+    /// Forgets the cached uniforms of `program`, which is required
+    /// before the name of a deleted program is used again.
+    pub fn forget_program(&mut self, program: GLuint) {
+        self.pass_uniforms.retain(|&(p, _), _| p != program);
+    }
+
+    pub fn update_pass_uniform(&mut self, program: GLuint, location: GLint, value: Uniform) {
+        if location == -1 || self.pass_uniforms.get(&(program, location)) == Some(&value) {
+            return;
+        }
+
+        self.pass_uniforms.insert((program, location), value);
+        unsafe {
+            // SAFETY:
+            // `program` is the current program
+            // `location` is the location of a uniform of the given type
+            match value {
+                Uniform::Float(x) => gl::Uniform1f(location, x),
+                Uniform::Vec2(x, y) => gl::Uniform2f(location, x, y),
+                Uniform::Vec3(x, y, z) => gl::Uniform3f(location, x, y, z),
+                Uniform::Vec4(x, y, z, w) => gl::Uniform4f(location, x, y, z, w),
+                Uniform::Mat4(ref matrix) => {
+                    let matrix: *const [[f32; 4]; 4] = matrix;
+                    gl::UniformMatrix4fv(location, 1, gl::TRUE, matrix.cast())
+                }
+                Uniform::Int(x) => gl::Uniform1i(location, x),
+                Uniform::IVec2(x, y) => gl::Uniform2i(location, x, y),
+                Uniform::UInt(x) => gl::Uniform1ui(location, x),
+                Uniform::Texture(_) => bug!("textures must be bound to a texture unit"),
             }
         }
     }
-    pub fn update_debug_start_end(&mut self, debug_start_end: (f32, f32, f32, f32)) {
-        if debug_start_end != self.debug_start_end {
-            self.debug_start_end = debug_start_end;
-        }
+
+    /// Binds `texture` to the texture unit `unit`, which must not be `0`.
+    ///
+    /// Only the binding of unit `0` is tracked, so this always rebinds `texture`.
+    pub fn bind_texture_unit(&mut self, unit: u32, texture: GLuint) {
         unsafe {
-            // SAFETY: `start_end` is declared as `vec4`
-            gl::Uniform4f(
-                self.debug_uniforms.start_end,
-                debug_start_end.0,
-                debug_start_end.1,
-                debug_start_end.2,
-                debug_start_end.3,
-            );
+            // SAFETY:
+            // `unit` is less than `gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS`
+            // `texture` was created using `glGenTexture`
+            //      and is only ever bound to `gl::TEXTURE_2D`
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::ActiveTexture(gl::TEXTURE0);
         }
     }
 }
@@ -1,10 +1,13 @@
-use std::{ffi::c_void, ptr};
+use std::{ffi::c_void, ptr, rc::Rc};
 
 use gl::types::*;
 
 use image::RgbaImage;
 
-use crate::{backend::Backend, DrawConfig, NewTextureError, UnwrapBug};
+use crate::{
+    backend::{sampler, Backend, DroppedFramebuffers},
+    DrawConfig, DrawError, NewTextureError, TextureOptions,
+};
 
 #[derive(Debug)]
 pub struct RawTexture {
@@ -13,25 +16,123 @@ pub struct RawTexture {
     pub depth_id: GLuint,
     pub dimensions: (u32, u32),
     pub has_framebuffer: bool,
+    pub options: TextureOptions,
+    /// The color used when sampling outside of the drawn section,
+    /// `None` if the closest edge is used instead.
+    pub border_color: Option<(f32, f32, f32, f32)>,
+    /// The name set using `Texture::set_label`, used in debug output.
+    pub label: Option<String>,
+    /// The generation of the context which created this texture.
+    pub generation: u64,
+    /// Shared with the backend, see `Backend::delete_dropped_framebuffers`.
+    pub dropped_framebuffers: DroppedFramebuffers,
 }
 
 impl Drop for RawTexture {
     fn drop(&mut self) {
-        // SAFETY: `n` is `1` for all functions
+        // All objects of a dropped context have already been freed,
+        // their ids may now be used by the current context.
+        if !super::is_active(self.generation) {
+            return;
+        }
+
+        super::leak::unregister(self.id);
+
+        // draw calls may still be queued for the framebuffer,
+        // so it is deleted by `Backend::delete_dropped_framebuffers`
         if self.has_framebuffer {
-            unsafe { gl::DeleteFramebuffers(1, &self.framebuffer_id) }
-            unsafe { gl::DeleteRenderbuffers(1, &self.depth_id) }
+            self.dropped_framebuffers
+                .borrow_mut()
+                .push((self.framebuffer_id, self.depth_id));
         }
+        // SAFETY: `n` is `1`
         unsafe { gl::DeleteTextures(1, &self.id) }
     }
 }
 
+/// Deletes a partially initialized framebuffer after an error.
+unsafe fn delete_framebuffer(backend: &mut Backend, buffer: GLuint, depth: GLuint) {
+    backend.state.update_framebuffer(0);
+    // SAFETY: `n` is one and both objects were created by OpenGL
+    gl::DeleteRenderbuffers(1, &depth);
+    gl::DeleteFramebuffers(1, &buffer);
+}
+
+/// Sets the sampling parameters of a new texture using `set`.
+///
+/// Textures use the wrap mode of their options until a border color
+/// is set using `RawTexture::set_border_color`.
+unsafe fn set_texture_parameters(options: TextureOptions, set: impl Fn(GLenum, GLint)) {
+    let min_filter = sampler::min_filter(options.min_filter, options.mipmaps);
+    // SAFETY:
+    // `gl::TEXTUREWRAP_(S|T)` and `gl::TEXTURE_(MIN|MAG)_FILTER` are valid `pname`
+    // all used `param` are valid for their `pname`
+    set(gl::TEXTURE_WRAP_S, sampler::wrap(options.wrap.0));
+    set(gl::TEXTURE_WRAP_T, sampler::wrap(options.wrap.1));
+    set(gl::TEXTURE_MIN_FILTER, min_filter);
+    set(gl::TEXTURE_MAG_FILTER, sampler::filter(options.mag_filter));
+}
+
+/// The number of mipmap levels of a texture, `1` if it does not use mipmaps.
+fn levels(dimensions: (u32, u32), options: TextureOptions) -> GLsizei {
+    if options.mipmaps {
+        (32 - dimensions.0.max(dimensions.1).leading_zeros()) as GLsizei
+    } else {
+        1
+    }
+}
+
+/// Creates a texture with immutable storage without changing the current bindings.
+///
+/// SAFETY: `ARB_direct_state_access` must be supported and `data`
+/// must either be null or point to `dimensions.0 * dimensions.1` pixels.
+unsafe fn create_texture_dsa(
+    id: &mut GLuint,
+    dimensions: (u32, u32),
+    options: TextureOptions,
+    data: *const c_void,
+) {
+    // SAFETY: `gl::TEXTURE_2D` is a valid `target` and `n` is one
+    gl::CreateTextures(gl::TEXTURE_2D, 1, id);
+    let id = *id;
+    set_texture_parameters(options, |pname, param| {
+        gl::TextureParameteri(id, pname, param)
+    });
+
+    // SAFETY:
+    // `id` is the name of a texture without immutable storage
+    // `levels` is at most `log2(max(width, height)) + 1`
+    // `gl::RGBA8` is a valid sized `internalformat`
+    // `width` and `height` are both in the range `1..=GL_MAX_TEXTURE_SIZE`
+    let levels = levels(dimensions, options);
+    gl::TextureStorage2D(id, levels, gl::RGBA8, dimensions.0 as _, dimensions.1 as _);
+    if !data.is_null() {
+        // SAFETY:
+        // the specified region is exactly the size of the texture
+        // `gl::RGBA` and `gl::UNSIGNED_BYTE` are a valid format and type
+        // We never bind something to `GL_PIXEL_UNPACK_BUFFER`
+        gl::TextureSubImage2D(
+            id,
+            0,
+            0,
+            0,
+            dimensions.0 as _,
+            dimensions.1 as _,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data,
+        );
+    }
+}
+
 impl RawTexture {
     fn internal_new(
         backend: &mut Backend,
         dimensions: (u32, u32),
+        options: TextureOptions,
         data: *const c_void,
     ) -> Result<RawTexture, NewTextureError> {
+        backend.assert_current();
         let (max_width, max_height) = backend.constants().max_texture_size;
         if (dimensions.0 == 0 || dimensions.1 == 0)
             || (dimensions.0 > max_width || dimensions.1 > max_height)
@@ -47,63 +148,135 @@ impl RawTexture {
             dimensions.0, dimensions.1
         );
 
+        let extensions = backend.constants().extensions;
         let mut id = 0;
         unsafe {
-            // SAFETY: `n` is one.
-            gl::GenTextures(1, &mut id);
-            backend.state.update_texture(id);
+            if extensions.direct_state_access {
+                create_texture_dsa(&mut id, dimensions, options, data);
+            } else {
+                // SAFETY: `n` is one.
+                gl::GenTextures(1, &mut id);
+                backend.state.update_texture(id);
+                set_texture_parameters(options, |pname, param| {
+                    gl::TexParameteri(gl::TEXTURE_2D, pname, param)
+                });
 
-            // TODO: consider using `gl::CLAMP_TO_BORDER` with an invisible border instead.
+                if extensions.texture_storage {
+                    // SAFETY:
+                    // `gl::TEXTURE_2D` is a valid `target`
+                    // `levels` is at most `log2(max(width, height)) + 1`
+                    // `gl::RGBA8` is a valid sized `internalformat`
+                    // `width` and `height` are both in the range `1..=GL_MAX_TEXTURE_SIZE`
+                    // the currently bound texture does not yet have immutable storage
+                    gl::TexStorage2D(
+                        gl::TEXTURE_2D,
+                        levels(dimensions, options),
+                        gl::RGBA8,
+                        dimensions.0 as _,
+                        dimensions.1 as _,
+                    );
+                    if !data.is_null() {
+                        // SAFETY:
+                        // `gl::TEXTURE_2D` is a valid `target`
+                        // the specified region is exactly the size of the texture
+                        // `gl::RGBA` and `gl::UNSIGNED_BYTE` are a valid format and type
+                        // `data` points to `width * height` pixels
+                        // We never bind something to `GL_PIXEL_UNPACK_BUFFER`
+                        gl::TexSubImage2D(
+                            gl::TEXTURE_2D,
+                            0,
+                            0,
+                            0,
+                            dimensions.0 as _,
+                            dimensions.1 as _,
+                            gl::RGBA,
+                            gl::UNSIGNED_BYTE,
+                            data,
+                        );
+                    }
+                } else {
+                    // SAFETY:
+                    // `gl::TEXTURE_2D` is a valid `target`
+                    // `gl::UNSIGNED_BYTE` is a valid `type` constant
+                    // `width` and `height` are both in the range `0..=GL_MAX_TEXTURE_SIZE`
+                    // `gl::RGBA8` is a valid sized `internalformat`
+                    // `level` and `border` are 0
+                    // We never bind something to `GL_PIXEL_UNPACK_BUFFER`
+                    gl::TexImage2D(
+                        gl::TEXTURE_2D,
+                        0,
+                        gl::RGBA8 as _,
+                        dimensions.0 as _,
+                        dimensions.1 as _,
+                        0,
+                        gl::RGBA,
+                        gl::UNSIGNED_BYTE,
+                        data,
+                    );
+                }
+            }
 
-            // SAFETY:
-            // `gl::TEXTURE_2D` is a valid target
-            // `gl::TEXTUREWRAP_(S|T)` and `gl::TEXTURE_(MIN|MAG)_FILTER` are valid `pname`
-            // `gl::CLAMP_TO_EDGE` is a valid `param` for `gl::TEXTURE_WRAP_(S|T)`
-            // `gl::NEAREST` is a valid `param` for `gl::TEXTURE_(MIN|MAG)_FILTER`
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as _);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as _);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as _);
+            match gl::GetError() {
+                gl::NO_ERROR => (),
+                gl::OUT_OF_MEMORY => {
+                    backend.state.update_texture(0);
+                    // SAFETY: `n` is one and `id` was returned from `glGenTextures` or `glCreateTextures`
+                    gl::DeleteTextures(1, &id);
+                    return Err(super::out_of_memory(NewTextureError::OutOfMemory));
+                }
+                e => bug!("unexpected error: {}", e),
+            }
+        }
 
-            // SAFETY:
-            // `gl::TEXTURE_2D` is a valid `target`
-            // `gl::UNSIGNED_BYTE` is a valid `type` constant
-            // `width` and `height` are both in the range `0..=GL_MAX_TEXTURE_SIZE`
-            // `gl::RGBA8` is a valid sized `internalformat`
-            // `level` and `border` are 0
-            // We never bind something to `GL_PIXEL_UNPACK_BUFFER`
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA8 as _,
-                dimensions.0 as _,
-                dimensions.1 as _,
-                0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                data,
-            );
+        if !data.is_null() {
+            backend.counts().uploads += 1;
         }
+        super::leak::register(id, dimensions);
 
-        Ok(Self {
+        let texture = Self {
             id,
             framebuffer_id: 0,
             depth_id: 0,
             dimensions,
             has_framebuffer: false,
-        })
+            options,
+            border_color: None,
+            label: None,
+            generation: backend.generation(),
+            dropped_framebuffers: Rc::clone(&backend.dropped_framebuffers),
+        };
+        if !data.is_null() {
+            texture.generate_mipmaps(backend);
+        }
+        Ok(texture)
     }
 
     pub fn new(
         backend: &mut Backend,
         dimensions: (u32, u32),
     ) -> Result<RawTexture, NewTextureError> {
-        Self::internal_new(backend, dimensions, ptr::null())
+        Self::new_with(backend, dimensions, TextureOptions::default())
+    }
+
+    pub fn new_with(
+        backend: &mut Backend,
+        dimensions: (u32, u32),
+        options: TextureOptions,
+    ) -> Result<RawTexture, NewTextureError> {
+        Self::internal_new(backend, dimensions, options, ptr::null())
     }
 
     pub fn from_image(
         backend: &mut Backend,
         image: RgbaImage,
+    ) -> Result<RawTexture, NewTextureError> {
+        Self::from_image_with(backend, image, TextureOptions::default())
+    }
+
+    pub fn from_image_with(
+        backend: &mut Backend,
+        image: RgbaImage,
+        options: TextureOptions,
     ) -> Result<RawTexture, NewTextureError> {
         let dimensions = image.dimensions();
         // open gl presents images upside down,
@@ -116,10 +289,198 @@ impl RawTexture {
             .copied()
             .collect();
 
-        Self::internal_new(backend, dimensions, reversed_data.as_ptr() as *const _)
+        Self::internal_new(
+            backend,
+            dimensions,
+            options,
+            reversed_data.as_ptr() as *const _,
+        )
+    }
+
+    /// Recomputes all mipmap levels from the content of the texture,
+    /// does nothing if the texture does not use mipmaps.
+    pub fn generate_mipmaps(&self, backend: &mut Backend) {
+        if !self.options.mipmaps {
+            return;
+        }
+
+        backend.assert_current();
+        backend.flush_draws();
+        unsafe {
+            // SAFETY:
+            // `self.id` is the name of an existing texture
+            // the texture has either immutable storage for all levels or mutable storage
+            if backend.constants().extensions.direct_state_access {
+                gl::GenerateTextureMipmap(self.id);
+            } else {
+                backend.state.update_texture(self.id);
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+        }
+    }
+
+    /// Replaces the content of the texture with `data`, which
+    /// contains the rows of the texture starting at the bottom.
+    pub fn write(&self, backend: &mut Backend, data: &[u8]) {
+        self.write_region(backend, (0, 0), self.dimensions, data)
+    }
+
+    /// Replaces the region of the texture with the given `offset` and `dimensions`
+    /// with `data`, which contains the rows of the region starting at the bottom.
+    pub fn write_region(
+        &self,
+        backend: &mut Backend,
+        offset: (u32, u32),
+        dimensions: (u32, u32),
+        data: &[u8],
+    ) {
+        backend.assert_current();
+        backend.flush_draws();
+        assert!(
+            offset.0 + dimensions.0 <= self.dimensions.0
+                && offset.1 + dimensions.1 <= self.dimensions.1
+        );
+        assert_eq!(
+            data.len(),
+            dimensions.0 as usize * dimensions.1 as usize * 4
+        );
+
+        let data = data.as_ptr() as *const c_void;
+        unsafe {
+            if backend.constants().extensions.direct_state_access {
+                // SAFETY:
+                // `self.id` is the name of an existing texture
+                // the specified region is inside of the texture
+                // `gl::RGBA` and `gl::UNSIGNED_BYTE` are a valid format and type
+                // `data` points to `width * height` pixels
+                // We never bind something to `GL_PIXEL_UNPACK_BUFFER`
+                gl::TextureSubImage2D(
+                    self.id,
+                    0,
+                    offset.0 as _,
+                    offset.1 as _,
+                    dimensions.0 as _,
+                    dimensions.1 as _,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    data,
+                );
+            } else {
+                backend.state.update_texture(self.id);
+                // SAFETY:
+                // `gl::TEXTURE_2D` is a valid `target`
+                // the specified region is inside of the texture
+                // `gl::RGBA` and `gl::UNSIGNED_BYTE` are a valid format and type
+                // `data` points to `width * height` pixels
+                // We never bind something to `GL_PIXEL_UNPACK_BUFFER`
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    offset.0 as _,
+                    offset.1 as _,
+                    dimensions.0 as _,
+                    dimensions.1 as _,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    data,
+                );
+            }
+        }
+        backend.counts().uploads += 1;
     }
 
-    pub fn add_framebuffer(&mut self, backend: &mut Backend) {
+    /// Uses `color` when sampling outside of the drawn section, or the closest edge if it is `None`.
+    ///
+    /// The border is used both by `gl::CLAMP_TO_BORDER` and when drawing a section of `self`.
+    pub fn set_border_color(&mut self, backend: &mut Backend, color: Option<(f32, f32, f32, f32)>) {
+        backend.assert_current();
+        backend.flush_draws();
+        self.border_color = color;
+
+        // a border color replaces the wrap mode of the texture until it is removed again
+        let (wrap, (r, g, b, a)) = match color {
+            Some(color) => ((gl::CLAMP_TO_BORDER as _, gl::CLAMP_TO_BORDER as _), color),
+            None => (
+                (
+                    sampler::wrap(self.options.wrap.0),
+                    sampler::wrap(self.options.wrap.1),
+                ),
+                (0.0, 0.0, 0.0, 0.0),
+            ),
+        };
+        let border = [r, g, b, a];
+        unsafe {
+            // SAFETY:
+            // `gl::TEXTURE_WRAP_(S|T)` and `gl::TEXTURE_BORDER_COLOR` are valid `pname`
+            // `gl::CLAMP_TO_BORDER` and the wrap modes of `self.options` are valid
+            // `param`s for `gl::TEXTURE_WRAP_(S|T)`
+            // `border` contains 4 `GLfloat`s
+            if backend.constants().extensions.direct_state_access {
+                gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_S, wrap.0);
+                gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_T, wrap.1);
+                gl::TextureParameterfv(self.id, gl::TEXTURE_BORDER_COLOR, border.as_ptr());
+            } else {
+                backend.state.update_texture(self.id);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrap.0);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrap.1);
+                gl::TexParameterfv(gl::TEXTURE_2D, gl::TEXTURE_BORDER_COLOR, border.as_ptr());
+            }
+        }
+    }
+
+    pub fn set_label(&mut self, backend: &mut Backend, label: &str) {
+        backend.assert_current();
+        self.label = Some(label.to_owned());
+        super::leak::set_label(self.id, label);
+        self.apply_label(backend);
+    }
+
+    /// Names the OpenGL objects of this texture, so they can be identified in graphics debuggers.
+    fn apply_label(&self, backend: &Backend) {
+        let label = match self.label.as_deref() {
+            Some(label) if backend.constants().extensions.debug => label,
+            _ => return,
+        };
+
+        // `GL_MAX_LABEL_LENGTH` is at least 256, including the null terminator
+        let mut len = label.len().min(255);
+        while !label.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        unsafe {
+            // SAFETY:
+            // `gl::TEXTURE` and `gl::FRAMEBUFFER` are valid `identifier`s
+            // `self.id` and `self.framebuffer_id` are names of existing objects
+            // `label` points to `len` bytes, which is less than `GL_MAX_LABEL_LENGTH`
+            gl::ObjectLabel(gl::TEXTURE, self.id, len as _, label.as_ptr().cast());
+            if self.has_framebuffer {
+                gl::ObjectLabel(
+                    gl::FRAMEBUFFER,
+                    self.framebuffer_id,
+                    len as _,
+                    label.as_ptr().cast(),
+                );
+            }
+        }
+    }
+
+    pub fn from_image_premultiplied(
+        backend: &mut Backend,
+        mut image: RgbaImage,
+    ) -> Result<RawTexture, NewTextureError> {
+        for pixel in image.pixels_mut() {
+            let alpha = u16::from(pixel[3]);
+            for channel in pixel.0[..3].iter_mut() {
+                *channel = ((u16::from(*channel) * alpha + 127) / 255) as u8;
+            }
+        }
+
+        Self::from_image(backend, image)
+    }
+
+    pub fn add_framebuffer(&mut self, backend: &mut Backend) -> Result<(), DrawError> {
+        backend.assert_current();
         assert!(!self.has_framebuffer);
         let mut buffer = 0;
         let mut depth = 0;
@@ -149,10 +510,10 @@ impl RawTexture {
             // SAFETY:
             // `target` is `gl::RENDERBUFFER`
             // `width` and `height` in the range `0..=gl::MAX_RENDERBUFFER_SIZE`
-            // `gl::DEPTH_COMPONENT16` is a depth-renderable format
+            // `gl::DEPTH24_STENCIL8` is a depth- and stencil-renderable format
             gl::RenderbufferStorage(
                 gl::RENDERBUFFER,
-                gl::DEPTH_COMPONENT16,
+                gl::DEPTH24_STENCIL8,
                 self.dimensions.0 as _,
                 self.dimensions.1 as _,
             );
@@ -161,10 +522,9 @@ impl RawTexture {
             match gl_error {
                 gl::NO_ERROR => (),
                 gl::OUT_OF_MEMORY => {
-                    // TODO: OpenGl is now in an undefined state,
-                    // consider aborting instead, as it is possible
-                    // to catch a panic
-                    panic!("OpenGl is out of memory and in an invalid state");
+                    let err = super::out_of_memory(DrawError::OutOfMemory);
+                    delete_framebuffer(backend, buffer, depth);
+                    return Err(err);
                 }
                 e => bug!("unexpected error: {}", e),
             }
@@ -172,12 +532,12 @@ impl RawTexture {
             // SAFETY:
             // `gl::FRAMEBUFFER` is a valid `target`
             // We just bound `buffer` to `target` meaning that buffer is not zero
-            // `gl::DEPTH_ATTACHMENT` is a valid `attachment`
+            // `gl::DEPTH_STENCIL_ATTACHMENT` is a valid `attachment`
             // the `renderbuffertarget` is `gl::RENDERBUFFER`
             // `depth` has type `gl::RENDERBUFFER` and was returned from `gl::GenRenderbuffers`
             gl::FramebufferRenderbuffer(
                 gl::FRAMEBUFFER,
-                gl::DEPTH_ATTACHMENT,
+                gl::DEPTH_STENCIL_ATTACHMENT,
                 gl::RENDERBUFFER,
                 depth,
             );
@@ -202,9 +562,16 @@ impl RawTexture {
             // SAFETY:
             // `gl::FRAMEBUFFER` is a valid `target`
             if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
-                bug!("incomplete framebuffer");
+                if cfg!(feature = "strict-errors") {
+                    delete_framebuffer(backend, buffer, depth);
+                    return Err(DrawError::IncompleteFramebuffer);
+                } else {
+                    bug!("incomplete framebuffer");
+                }
             }
 
+            // the new depth buffer is always cleared completely
+            backend.state.update_scissor(None);
             // SAFETY:
             // no undefined bit is set in `mask`
             // `glBegin` and `glEnd` are never used
@@ -215,23 +582,29 @@ impl RawTexture {
         self.framebuffer_id = buffer;
 
         self.has_framebuffer = true;
+        self.apply_label(backend);
+        Ok(())
     }
 
-    pub fn clone_as_target(previous: &Self, backend: &mut Backend) -> Self {
-        let mut clone = Self::new(backend, previous.dimensions).unwrap_bug();
-        clone.add_framebuffer(backend);
+    pub fn clone_as_target(previous: &Rc<Self>, backend: &mut Backend) -> Result<Self, DrawError> {
+        let mut clone = Self::new_with(backend, previous.dimensions, previous.options)
+            .map_err(DrawError::from_new)?;
+        clone.add_framebuffer(backend)?;
         backend.clear_color(clone.framebuffer_id, (0.0, 0.0, 0.0, 0.0));
-        backend.draw(
-            clone.framebuffer_id,
-            previous.dimensions,
-            1,
-            previous,
-            (0, 0),
-            previous.dimensions,
-            (0, 0),
-            &DrawConfig::default(),
-        );
+        backend.without_mask(|backend| {
+            backend.draw(
+                clone.framebuffer_id,
+                previous.dimensions,
+                1,
+                previous,
+                (0, 0),
+                previous.dimensions,
+                (0, 0),
+                &DrawConfig::default(),
+            )
+        });
+        clone.generate_mipmaps(backend);
 
-        clone
+        Ok(clone)
     }
 }
@@ -0,0 +1,133 @@
+use std::rc::Rc;
+
+use crate::{backend::BatchSprite, DrawConfig, Texture};
+
+/// A list of sprites using sections of the same texture, which are drawn
+/// together using [`Context::draw_batch`].
+///
+/// Drawing a batch uses a single draw call for all consecutive sprites with the same
+/// `blend_mode` and `color_mask` which either all or none use `depth`. While consecutive
+/// calls to `Context::draw` using the same target and texture are combined as well,
+/// a batch can be built once and reused, for example for the tiles of a tilemap.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, DrawConfig, Rect, SpriteBatch, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let tiles = Texture::load(&mut ctx, "./textures/tiles.png")?;
+/// let grass = tiles.get_section(Rect::new((0, 0), (16, 16)));
+///
+/// let mut batch = SpriteBatch::new(&tiles);
+/// for x in 0..40 {
+///     for y in 0..30 {
+///         batch.push(&grass, (x * 16, y * 16), &DrawConfig::default());
+///     }
+/// }
+///
+/// let mut surface = ctx.surface();
+/// ctx.draw_batch(&mut surface, &batch);
+/// ctx.present(surface)?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`Context::draw_batch`]: struct.Context.html#method.draw_batch
+#[derive(Debug, Clone)]
+pub struct SpriteBatch {
+    pub(crate) texture: Texture,
+    pub(crate) sprites: Vec<BatchSprite>,
+}
+
+impl SpriteBatch {
+    /// Creates an empty batch for sections of `texture`.
+    pub fn new(texture: &Texture) -> Self {
+        SpriteBatch {
+            texture: texture.clone(),
+            sprites: Vec::new(),
+        }
+    }
+
+    /// Adds `texture` to the batch, which is drawn at `position` using `config`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `texture` is not a section of the texture used to create this batch.
+    pub fn push(
+        &mut self,
+        texture: &Texture,
+        position: impl Into<(i32, i32)>,
+        config: &DrawConfig,
+    ) {
+        assert!(
+            Rc::ptr_eq(&self.texture.inner, &texture.inner),
+            "the texture is not a section of the texture of this batch"
+        );
+
+        self.sprites.push(BatchSprite {
+            texture_offset: texture.position,
+            dimensions: texture.size,
+            position: position.into(),
+            config: config.clone(),
+        });
+    }
+
+    /// Returns the texture used to create this batch.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns the number of sprites in this batch.
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    /// Returns `true` if this batch does not contain any sprites.
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// Removes all sprites, keeping the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.sprites.clear()
+    }
+
+    /// Returns a copy of this batch, changing the position and config of each sprite using `f`,
+    /// which also receives the dimensions of the sprite.
+    ///
+    /// This is used by draw modifiers to forward batches without splitting them up.
+    pub(crate) fn map<F>(&self, mut f: F) -> SpriteBatch
+    where
+        F: FnMut((u32, u32), (i32, i32), &DrawConfig) -> ((i32, i32), DrawConfig),
+    {
+        SpriteBatch {
+            texture: self.texture.clone(),
+            sprites: self
+                .sprites
+                .iter()
+                .map(|sprite| {
+                    let (position, config) = f(sprite.dimensions, sprite.position, &sprite.config);
+                    BatchSprite {
+                        position,
+                        config,
+                        ..sprite.clone()
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns each sprite of this batch as a `Texture` together with its position and config.
+    pub(crate) fn sprites(&self) -> impl Iterator<Item = (Texture, (i32, i32), &DrawConfig)> {
+        let inner = &self.texture.inner;
+        self.sprites.iter().map(move |sprite| {
+            let texture = Texture {
+                inner: Rc::clone(inner),
+                position: sprite.texture_offset,
+                size: sprite.dimensions,
+            };
+            (texture, sprite.position, &sprite.config)
+        })
+    }
+}
@@ -0,0 +1,284 @@
+use std::{fmt::Write, time::Duration};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Context, DrawConfig, Texture};
+
+/// Whether draw calls to the window are captured, see [`Context::set_draw_capture`].
+///
+/// [`Context::set_draw_capture`]: struct.Context.html#method.set_draw_capture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DrawCapture {
+    /// Draw calls are not captured.
+    #[default]
+    Disabled,
+    /// Draw calls are recorded and can be accessed using `Context::captured_draws`.
+    Record,
+    /// Draw calls are recorded and the outline of each draw call is drawn
+    /// on top of the frame right before it is presented.
+    ///
+    /// The timings of the `RenderGraph` passes of the previous frame are drawn in the
+    /// lower left corner, see `RenderGraph::draw_timings`. The graph is 64 pixels high,
+    /// which corresponds to a frame budget of 1/60th of a second.
+    Overlay,
+}
+
+/// The kind of a [`CapturedDraw`].
+///
+/// [`CapturedDraw`]: struct.CapturedDraw.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum CapturedKind {
+    /// A texture drawn using `Context::draw`.
+    Texture,
+    /// A line drawn using `Context::debug_line`.
+    Line,
+    /// A rectangle drawn using `Context::debug_rectangle`.
+    Rectangle,
+}
+
+/// A draw call to the window surface recorded while draw capture is enabled.
+///
+/// With the `serde1` feature enabled, captured draws can be serialized,
+/// which allows external tools to inspect the content of each frame.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct CapturedDraw {
+    /// The kind of draw call.
+    pub kind: CapturedKind,
+    /// The lower left corner of the area changed by this draw call,
+    /// the start of the line in case `kind` is `CapturedKind::Line`.
+    pub lower_left: (i32, i32),
+    /// The upper right corner of the area changed by this draw call,
+    /// the end of the line in case `kind` is `CapturedKind::Line`.
+    pub upper_right: (i32, i32),
+    /// The color used to draw this call in the overlay.
+    ///
+    /// Textures sharing the same underlying texture have the same color,
+    /// while debug primitives use their own color.
+    pub color: (f32, f32, f32, f32),
+    /// The label set using `Context::set_capture_label` when this call was drawn.
+    pub label: Option<String>,
+    /// The label of the drawn texture set using `Texture::set_label`.
+    pub texture_label: Option<String>,
+}
+
+impl CapturedDraw {
+    pub(crate) fn texture(texture: &Texture, position: (i32, i32), config: &DrawConfig) -> Self {
+        // the bounding box of all corners
+        let (mut left, mut bottom) = (f64::INFINITY, f64::INFINITY);
+        let (mut right, mut top) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &corner in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)].iter() {
+            let (x, y) = config.corner(texture.size, corner);
+            let x = f64::from(x) + f64::from(position.0);
+            let y = f64::from(y) + f64::from(position.1);
+            left = left.min(x);
+            right = right.max(x);
+            bottom = bottom.min(y);
+            top = top.max(y);
+        }
+
+        CapturedDraw {
+            kind: CapturedKind::Texture,
+            lower_left: (left.floor() as i32, bottom.floor() as i32),
+            upper_right: (right.ceil() as i32 - 1, top.ceil() as i32 - 1),
+            color: texture_color(texture.inner.id),
+            label: None,
+            texture_label: texture.label(),
+        }
+    }
+}
+
+/// The height in pixels of the pass timings drawn by `DrawCapture::Overlay`.
+const OVERLAY_TIMINGS_HEIGHT: u32 = 64;
+
+/// The duration corresponding to `OVERLAY_TIMINGS_HEIGHT`.
+const OVERLAY_TIMINGS_BUDGET: Duration = Duration::from_micros(16_667);
+
+/// Picks a distinct and fully saturated color for each texture id.
+fn texture_color(id: u32) -> (f32, f32, f32, f32) {
+    // use the golden ratio to spread consecutive ids over all hues
+    let hue = (f64::from(id) * 0.618_033_988_75).fract() * 6.0;
+    let x = (1.0 - (hue % 2.0 - 1.0).abs()) as f32;
+    let (r, g, b) = match hue as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    (r, g, b, 1.0)
+}
+
+/// Escapes all characters of `text` which have a special meaning in XML.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Context {
+    /// Sets whether draw calls to the window surface are captured.
+    ///
+    /// This is meant to be used while debugging, for example to
+    /// find misplaced sprites or areas which are drawn over multiple times.
+    pub fn set_draw_capture(&mut self, capture: DrawCapture) {
+        self.draw_capture = capture;
+        if capture == DrawCapture::Disabled {
+            self.current_capture.clear();
+            self.captured_draws.clear();
+        }
+    }
+
+    /// Returns whether draw calls to the window surface are captured.
+    pub fn draw_capture(&self) -> DrawCapture {
+        self.draw_capture
+    }
+
+    /// Sets the label of all following draw calls to the window surface,
+    /// `None` removes the current label.
+    ///
+    /// Labels are stored in `CapturedDraw::label` and are added as titles
+    /// to `Context::captured_draws_svg`, so they can be read by screen readers.
+    pub fn set_capture_label(&mut self, label: Option<String>) {
+        self.capture_label = label;
+    }
+
+    /// Returns the label of draw calls to the window surface.
+    pub fn capture_label(&self) -> Option<&str> {
+        self.capture_label.as_deref()
+    }
+
+    /// Returns all draw calls to the window surface of the last presented frame.
+    ///
+    /// This is empty unless draw capture is enabled using `Context::set_draw_capture`.
+    pub fn captured_draws(&self) -> &[CapturedDraw] {
+        &self.captured_draws
+    }
+
+    /// Returns an SVG image containing the outline of each draw call of the last presented frame.
+    ///
+    /// This is empty unless draw capture is enabled using `Context::set_draw_capture`.
+    pub fn captured_draws_svg(&self) -> String {
+        let (width, height) = self.window_dimensions();
+        let height = height as i32;
+        let color = |(r, g, b, a): (f32, f32, f32, f32)| {
+            format!(
+                "stroke=\"rgb({},{},{})\" stroke-opacity=\"{}\"",
+                (r * 255.0) as u8,
+                (g * 255.0) as u8,
+                (b * 255.0) as u8,
+                a
+            )
+        };
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+            width, height
+        );
+        for draw in self.captured_draws.iter() {
+            let (from, to) = (draw.lower_left, draw.upper_right);
+            // svg uses the top left corner as its origin
+            let _ = match draw.kind {
+                CapturedKind::Line => write!(
+                    svg,
+                    "  <line x1=\"{}.5\" y1=\"{}.5\" x2=\"{}.5\" y2=\"{}.5\" {}",
+                    from.0,
+                    height - 1 - from.1,
+                    to.0,
+                    height - 1 - to.1,
+                    color(draw.color)
+                ),
+                CapturedKind::Texture | CapturedKind::Rectangle => {
+                    let (left, right) = (from.0.min(to.0), from.0.max(to.0));
+                    let (bottom, top) = (from.1.min(to.1), from.1.max(to.1));
+                    write!(
+                        svg,
+                        "  <rect x=\"{}.5\" y=\"{}.5\" width=\"{}\" height=\"{}\" fill=\"none\" {}",
+                        left,
+                        height - 1 - top,
+                        right - left,
+                        top - bottom,
+                        color(draw.color)
+                    )
+                }
+            };
+            let title = match (&draw.label, &draw.texture_label) {
+                (Some(label), Some(texture)) => Some(format!("{}: {}", label, texture)),
+                (Some(label), None) | (None, Some(label)) => Some(label.clone()),
+                (None, None) => None,
+            };
+            let _ = match (draw.kind, title) {
+                (kind, Some(title)) => writeln!(
+                    svg,
+                    "><title>{}</title></{}>",
+                    escape_xml(&title),
+                    if kind == CapturedKind::Line {
+                        "line"
+                    } else {
+                        "rect"
+                    }
+                ),
+                (_, None) => writeln!(svg, "/>"),
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Records `draw` in case draw capture is enabled.
+    pub(crate) fn capture(&mut self, draw: impl FnOnce() -> CapturedDraw) {
+        if self.draw_capture != DrawCapture::Disabled {
+            let draw = CapturedDraw {
+                label: self.capture_label.clone(),
+                ..draw()
+            };
+            self.current_capture.push(draw);
+        }
+    }
+
+    /// Finishes capturing the current frame, drawing the overlay if requested.
+    pub(crate) fn finish_capture(&mut self) {
+        if self.draw_capture == DrawCapture::Overlay {
+            let dim = self.backend.window_dimensions();
+            let dpi = self.backend.dpi_factor();
+            let draws = &self.current_capture;
+            let timings = &mut self.last_pass_timings;
+            self.backend.without_mask(|backend| {
+                timings.resolve(backend);
+                timings.draw(
+                    (0, 0),
+                    OVERLAY_TIMINGS_HEIGHT,
+                    OVERLAY_TIMINGS_BUDGET,
+                    |from, to, color| backend.debug_draw(false, 0, dim, dpi, from, to, color),
+                );
+                for draw in draws.iter() {
+                    let rectangle = draw.kind != CapturedKind::Line;
+                    backend.debug_draw(
+                        rectangle,
+                        0,
+                        dim,
+                        dpi,
+                        draw.lower_left,
+                        draw.upper_right,
+                        draw.color,
+                    );
+                }
+            });
+        }
+
+        self.captured_draws.clear();
+        self.captured_draws.append(&mut self.current_capture);
+    }
+}
@@ -1,4 +1,9 @@
-//! A collection of useful color matrices.
+//! A collection of useful color matrices and the [`Palette`] used to restrict colors.
+//!
+//! [`Palette`]: struct.Palette.html
+use std::collections::HashSet;
+
+use image::{Rgba, RgbaImage};
 
 /// The identity matrix.
 pub const IDENTITY: [[f32; 4]; 4] = [
@@ -39,3 +44,172 @@ pub const BLUE: [[f32; 4]; 4] = [
     [0.0, 0.0, 1.0, 0.0],
     [0.0, 0.0, 0.0, 1.0],
 ];
+
+/// Simulates how an image is perceived with protanopia, the absence of red cones.
+pub const PROTANOPIA: [[f32; 4]; 4] = [
+    [0.1523, 1.0526, -0.2049, 0.0],
+    [0.1145, 0.7863, 0.0992, 0.0],
+    [-0.0039, -0.0481, 1.052, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Simulates how an image is perceived with deuteranopia, the absence of green cones.
+pub const DEUTERANOPIA: [[f32; 4]; 4] = [
+    [0.3673, 0.8606, -0.228, 0.0],
+    [0.2801, 0.6725, 0.0474, 0.0],
+    [-0.0118, 0.0429, 0.9689, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Simulates how an image is perceived with tritanopia, the absence of blue cones.
+pub const TRITANOPIA: [[f32; 4]; 4] = [
+    [1.2555, -0.0767, -0.1788, 0.0],
+    [-0.0784, 0.9308, 0.1476, 0.0],
+    [0.0047, 0.6914, 0.3039, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Shifts the colors of an image which are lost with protanopia to ones
+/// which can still be distinguished, also known as daltonization.
+pub const PROTANOPIA_ASSIST: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.4789, 0.4769, 0.0442, 0.0],
+    [0.5973, -0.6887, 1.0914, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Shifts the colors of an image which are lost with deuteranopia to ones
+/// which can still be distinguished, also known as daltonization.
+pub const DEUTERANOPIA_ASSIST: [[f32; 4]; 4] = [
+    [1.4366, -0.6314, 0.1948, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [-0.1842, 0.1863, 0.9979, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Shifts the colors of an image which are lost with tritanopia to ones
+/// which can still be distinguished, also known as daltonization.
+pub const TRITANOPIA_ASSIST: [[f32; 4]; 4] = [
+    [0.7412, -0.4072, 0.666, 0.0],
+    [0.0751, 0.5852, 0.3397, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Changes the contrast of an image, a `factor` greater than `1.0` increases the contrast.
+///
+/// As color matrices are unable to add a constant offset, this uses the alpha
+/// channel instead, so it is only fully accurate for opaque pixels.
+/// Combined with `Context::set_color_filter` this can be used to
+/// improve the readability of the window for low-vision users.
+pub fn contrast(factor: f32) -> [[f32; 4]; 4] {
+    let offset = 0.5 - 0.5 * factor;
+    [
+        [factor, 0.0, 0.0, offset],
+        [0.0, factor, 0.0, offset],
+        [0.0, 0.0, factor, offset],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+/// Combines two color matrices, the result is equal to first applying
+/// `inner` and then applying `outer`.
+pub fn combine(outer: [[f32; 4]; 4], inner: [[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut result = [[0.0; 4]; 4];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| outer[i][k] * inner[k][j]).sum();
+        }
+    }
+    result
+}
+
+/// Applies `matrix` to a single `color`.
+pub fn apply(matrix: [[f32; 4]; 4], color: (f32, f32, f32, f32)) -> (f32, f32, f32, f32) {
+    let color = [color.0, color.1, color.2, color.3];
+    let row = |i: usize| (0..4).map(|k| matrix[i][k] * color[k]).sum();
+    (row(0), row(1), row(2), row(3))
+}
+
+/// A fixed set of colors, used to map arbitrary colors to the closest one of them.
+///
+/// Using `Context::set_primitive_palette`, colors passed to `Context::debug_line` and
+/// `Context::debug_rectangle` are replaced by the closest color of the palette,
+/// so debug output respects the fixed palette of a game.
+///
+/// ```rust
+/// use crow::color::Palette;
+///
+/// let palette = Palette::new(vec![(0.0, 0.0, 0.0, 1.0), (1.0, 0.5, 0.0, 1.0)]);
+/// assert_eq!(palette.nearest((0.9, 0.2, 0.1, 1.0)), (1.0, 0.5, 0.0, 1.0));
+/// assert_eq!(palette.nearest((0.2, 0.2, 0.2, 1.0)), (0.0, 0.0, 0.0, 1.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colors: Vec<(f32, f32, f32, f32)>,
+}
+
+impl Palette {
+    /// Creates a palette containing the given `colors`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `colors` is empty.
+    pub fn new(colors: Vec<(f32, f32, f32, f32)>) -> Self {
+        assert!(
+            !colors.is_empty(),
+            "a palette must contain at least one color"
+        );
+        Palette { colors }
+    }
+
+    /// Creates a palette containing each distinct color of `image`,
+    /// in the order in which they first occur.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `image` is empty.
+    pub fn from_image(image: &RgbaImage) -> Self {
+        let mut seen = HashSet::new();
+        let pixels: Vec<Rgba<u8>> = image
+            .pixels()
+            .copied()
+            .filter(|pixel| seen.insert(pixel.0))
+            .collect();
+
+        let channel = |c: u8| f32::from(c) / 255.0;
+        Self::new(
+            pixels
+                .into_iter()
+                .map(|Rgba([r, g, b, a])| (channel(r), channel(g), channel(b), channel(a)))
+                .collect(),
+        )
+    }
+
+    /// Returns all colors of this palette.
+    pub fn colors(&self) -> &[(f32, f32, f32, f32)] {
+        &self.colors
+    }
+
+    /// Returns the color of this palette which is closest to `color`,
+    /// using the euclidean distance of all four channels.
+    ///
+    /// If multiple colors are equally close, the first of them is used.
+    pub fn nearest(&self, color: (f32, f32, f32, f32)) -> (f32, f32, f32, f32) {
+        let distance = |c: &(f32, f32, f32, f32)| {
+            (c.0 - color.0).powi(2)
+                + (c.1 - color.1).powi(2)
+                + (c.2 - color.2).powi(2)
+                + (c.3 - color.3).powi(2)
+        };
+
+        let mut colors = self.colors.iter();
+        let mut nearest = colors.next().unwrap();
+        for c in colors {
+            if distance(c) < distance(nearest) {
+                nearest = c;
+            }
+        }
+        *nearest
+    }
+}
@@ -0,0 +1,143 @@
+use std::mem;
+
+use crate::{color, Context, DrawConfig, NewTextureError, Texture};
+
+/// A single layer of a [`Composite`].
+///
+/// [`Composite`]: struct.Composite.html
+#[derive(Debug, Clone)]
+pub struct Layer {
+    /// The texture of this layer.
+    pub texture: Texture,
+    /// The position of `texture` relative to the lower left corner of the composite.
+    pub offset: (i32, i32),
+    /// The color modulation applied to `texture`, see `DrawConfig::color_modulation`.
+    pub color_modulation: [[f32; 4]; 4],
+}
+
+impl Layer {
+    /// Creates a new layer drawing `texture` without an offset or tint.
+    pub fn new(texture: Texture) -> Self {
+        Layer {
+            texture,
+            offset: (0, 0),
+            color_modulation: color::IDENTITY,
+        }
+    }
+}
+
+/// Multiple textures layered on top of each other, for example the body,
+/// armor and weapon of a character.
+///
+/// The layers are rendered into a cached texture, which is only
+/// updated once a layer changes.
+///
+/// ```rust, no_run
+/// use crow::{
+///     color,
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Composite, Context, DrawConfig, Layer, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let body = Texture::load(&mut ctx, "./textures/player.png")?;
+/// let armor = Texture::load(&mut ctx, "./textures/armor.png")?;
+///
+/// let mut player = Composite::new((32, 32))
+///     .with_layer(Layer::new(body))
+///     .with_layer(Layer {
+///         color_modulation: color::RED,
+///         ..Layer::new(armor)
+///     });
+///
+/// let mut surface = ctx.surface();
+/// let texture = player.texture(&mut ctx)?;
+/// ctx.draw(&mut surface, texture, (100, 100), &DrawConfig::default());
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Composite {
+    size: (u32, u32),
+    layers: Vec<Layer>,
+    cache: Option<Texture>,
+}
+
+impl Composite {
+    /// Creates a new composite of the given `size` without any layers.
+    pub fn new(size: impl Into<(u32, u32)>) -> Self {
+        Composite {
+            size: size.into(),
+            layers: Vec::new(),
+            cache: None,
+        }
+    }
+
+    /// Adds `layer` on top of all existing layers.
+    pub fn with_layer(mut self, layer: Layer) -> Self {
+        self.push_layer(layer);
+        self
+    }
+
+    /// Adds `layer` on top of all existing layers, returning its index.
+    pub fn push_layer(&mut self, layer: Layer) -> usize {
+        self.cache = None;
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    /// Replaces the layer at `index`, returning the previous one.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if there is no layer at `index`.
+    pub fn set_layer(&mut self, index: usize, layer: Layer) -> Layer {
+        self.cache = None;
+        mem::replace(&mut self.layers[index], layer)
+    }
+
+    /// Removes the layer at `index`, moving all layers above it down by one.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if there is no layer at `index`.
+    pub fn remove_layer(&mut self, index: usize) -> Layer {
+        self.cache = None;
+        self.layers.remove(index)
+    }
+
+    /// Returns all layers, starting with the bottom one.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Returns a mutable reference to the layer at `index`,
+    /// which causes the composite to be rendered again.
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut Layer> {
+        self.cache = None;
+        self.layers.get_mut(index)
+    }
+
+    /// Returns the size of this composite.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the rendered composite, only rendering
+    /// it again if a layer changed since the last call.
+    pub fn texture(&mut self, ctx: &mut Context) -> Result<&Texture, NewTextureError> {
+        if self.cache.is_none() {
+            let mut texture = Texture::new(ctx, self.size)?;
+            ctx.clear_color(&mut texture, (0.0, 0.0, 0.0, 0.0));
+            for layer in self.layers.iter() {
+                let config = DrawConfig {
+                    color_modulation: layer.color_modulation,
+                    ..Default::default()
+                };
+                ctx.draw(&mut texture, &layer.texture, layer.offset, &config);
+            }
+            self.cache = Some(texture);
+        }
+
+        Ok(self.cache.as_ref().unwrap())
+    }
+}
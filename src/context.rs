@@ -1,46 +1,131 @@
-use std::{
-    marker::PhantomData,
-    mem,
-    sync::atomic::{AtomicBool, Ordering},
-};
+use std::{backtrace::Backtrace, borrow::Cow, cmp, marker::PhantomData, mem, time::Instant};
 
 use glutin::{
+    dpi::LogicalPosition,
+    event::{Event, WindowEvent},
     event_loop::EventLoop,
-    window::{Window, WindowBuilder},
+    window::{Theme, Window, WindowBuilder},
 };
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+use glutin::{event_loop::ControlFlow, platform::desktop::EventLoopExtDesktop};
 
 use image::RgbaImage;
 
 use crate::{
-    backend::Backend, Context, DrawConfig, DrawTarget, FinalizeError, NewContextError, Texture,
-    WindowSurface,
+    backend::{tex::RawTexture, Backend, Fence, GlConstants, Masking, QueryHandle},
+    color, noise,
+    render_graph::PassTimings,
+    BlendMode, CapturedDraw, CapturedKind, Context, CopyReason, Counters, CursorGrab, DebugView,
+    DrawCapture, DrawConfig, DrawError, DrawTarget, FinalizeError, Flow, IRect, Mask,
+    NewContextError, NewTextureError, PassTiming, Rect, RenderProfile, Sampler, Shader,
+    ShaderGlobals, SkipDebug, SpriteBatch, Subsystem, Texture, TextureCopy, UniformValue,
+    WindowSurface, Wrap,
 };
 
-static INITIALIZED: AtomicBool = AtomicBool::new(false);
-
 impl Context {
     /// Creates a new `Context`. It is not possible to have more
-    /// than one `Context` in a program.
+    /// than one `Context` at the same time.
+    ///
+    /// Once the previous context has been dropped, a new one can be created.
+    /// Textures of the previous context can not be used with the new one.
     ///
-    /// To create a new `Context` after a previous context was used,
-    /// The previous context has to be dropped using the method
-    /// `Context::unlock_unchecked()`. This is a workaround and
-    /// will probably be fixed in a future release.
+    /// # Panics
+    ///
+    /// This function panics if a `Context` already exists.
     pub fn new<T>(
         window: WindowBuilder,
         event_loop: &EventLoop<T>,
     ) -> Result<Self, NewContextError> {
-        if INITIALIZED.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
-            != Ok(false)
-        {
-            panic!("Tried to initialize a second Context");
-        }
+        Self::from_backend(Backend::initialize(window, event_loop)?)
+    }
 
-        let backend = Backend::initialize(window, &event_loop)?;
+    /// Creates a new `Context` without a window, which renders into
+    /// an offscreen buffer with the given `dimensions` instead.
+    ///
+    /// This uses OSMesa and does not require a display server, allowing
+    /// tests using `Context::image_data` to run on CI machines.
+    /// Presenting a frame does not do anything apart from finishing it.
+    ///
+    /// Headless contexts do not have a window, so `Context::window`, `Context::resize_window`
+    /// and `Context::run` must not be used. The window surface can still be drawn to.
+    ///
+    /// ```rust, no_run
+    /// use crow::Context;
+    ///
+    /// let mut ctx = Context::new_headless((320, 240))?;
+    /// let mut surface = ctx.surface();
+    /// ctx.clear_color(&mut surface, (0.0, 1.0, 0.0, 1.0));
+    /// let image = ctx.image_data(&surface);
+    /// assert_eq!(image.dimensions(), (320, 240));
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a `Context` already exists.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    pub fn new_headless(dimensions: (u32, u32)) -> Result<Self, NewContextError> {
+        Self::from_backend(Backend::initialize_headless(dimensions)?)
+    }
+
+    fn from_backend(mut backend: Backend) -> Result<Self, NewContextError> {
+        let noise_texture = match RawTexture::from_image(&mut backend, noise::noise_image()) {
+            Ok(raw) => Texture::from_raw_texture(raw),
+            Err(NewTextureError::OutOfMemory) => return Err(NewContextError::OutOfMemory),
+            Err(e) => bug!("unable to create the noise texture: {:?}", e),
+        };
         let surface = Some(WindowSurface {
             _marker: PhantomData,
         });
-        Ok(Self { backend, surface })
+        Ok(Self {
+            surface,
+            mask_texture: None,
+            noise_texture,
+            focused: true,
+            attention_requested: false,
+            platform_suspended: false,
+            on_suspend: Vec::new(),
+            on_resume: Vec::new(),
+            pre_frame: Vec::new(),
+            post_frame: Vec::new(),
+            theme: None,
+            on_theme_change: Vec::new(),
+            on_texture_copy: Vec::new(),
+            on_update_shader_globals: Vec::new(),
+            shader_globals: ShaderGlobals::default(),
+            debug_view: DebugView::Normal,
+            color_filter: None,
+            color_filter_buffer: None,
+            primitive_palette: None,
+            cursor: None,
+            cursor_position: None,
+            logical_cursor_position: None,
+            cursor_grab: CursorGrab::None,
+            mouse_delta: (0.0, 0.0),
+            draw_capture: DrawCapture::Disabled,
+            capture_label: None,
+            current_capture: Vec::new(),
+            captured_draws: Vec::new(),
+            pass_timings: PassTimings::default(),
+            last_pass_timings: PassTimings::default(),
+            profile: RenderProfile::default(),
+            last_present: None,
+            backend,
+        })
     }
 
     /// Returns the dimensions of the used window.
@@ -59,6 +144,10 @@ impl Context {
     }
 
     /// Sets the dimensions of the used window.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the context was created using `Context::new_headless`.
     pub fn resize_window(&mut self, width: u32, height: u32) {
         self.backend.resize_window(width, height)
     }
@@ -79,21 +168,235 @@ impl Context {
         self.backend.constants().max_texture_size
     }
 
+    /// Returns the limits and supported extensions of the used OpenGL implementation.
+    ///
+    /// ```rust, no_run
+    /// use crow::{Context, glutin::{window::WindowBuilder, event_loop::EventLoop}};
+    ///
+    /// let ctx = Context::new(WindowBuilder::new(), &EventLoop::new()).unwrap();
+    /// println!("texture units: {}", ctx.gl_constants().max_texture_units);
+    /// ```
+    pub fn gl_constants(&self) -> &GlConstants {
+        self.backend.constants()
+    }
+
     /// Draws the `source` onto `target`.
     ///
     /// To draw to the window, use [`Context::window_surface`] as a target.
     ///
+    /// # Panics
+    ///
+    /// This function panics if `source` or `target` was created by a different `Context`.
+    ///
     /// [`Context::window_surface`]: struct.Context.html#method.window_surface
     pub fn draw<T>(
         &mut self,
         target: &mut T,
         source: &Texture,
-        position: (i32, i32),
+        position: impl Into<(i32, i32)>,
+        config: &DrawConfig,
+    ) where
+        T: DrawTarget,
+    {
+        target.receive_draw(self, source, position.into(), config)
+    }
+
+    /// Fills `rect` by repeating `source`, starting with the pixel `uv_offset` of `source`
+    /// in the lower left corner of `rect`.
+    ///
+    /// `rect` is in pixels of `target` while `uv_offset` is in pixels of `source`. Each pixel
+    /// of `source` is scaled by `config.scale`, in case the size of `rect` is not a multiple
+    /// of the scale, the last column and row of pixels extend past `rect`. All other settings
+    /// of `config` apply to the whole area as if it was a single texture, so flipping
+    /// mirrors the whole area.
+    ///
+    /// Full textures are repeated by the GPU using a single draw call, while sections
+    /// of a larger texture, e.g. entries of an `Atlas`, require one draw call per tile.
+    /// Full textures are mirrored along each axis using `Wrap::MirroredRepeat`, either
+    /// in `TextureOptions::wrap` or `config.sampler`, and repeated otherwise.
+    ///
+    /// ```rust, no_run
+    /// use crow::{
+    ///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+    ///     Context, DrawConfig, IRect, Texture,
+    /// };
+    ///
+    /// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+    /// let clouds = Texture::load(&mut ctx, "./textures/clouds.png")?;
+    ///
+    /// let mut surface = ctx.surface();
+    /// let (width, height) = ctx.window_dimensions();
+    /// // increased each frame
+    /// let scroll = 40;
+    /// // the clouds move at half the speed of the foreground
+    /// let area = IRect::new((0, 0), (width, height));
+    /// ctx.draw_tiled(&mut surface, &clouds, area, (scroll / 2, 0), &DrawConfig::default());
+    /// ctx.present(surface)?;
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `source` or `target` was created by a different `Context`.
+    pub fn draw_tiled<T>(
+        &mut self,
+        target: &mut T,
+        source: &Texture,
+        rect: impl Into<IRect>,
+        uv_offset: impl Into<(i32, i32)>,
         config: &DrawConfig,
     ) where
         T: DrawTarget,
     {
-        target.receive_draw(self, source, position, config)
+        let rect = rect.into();
+        let uv_offset = uv_offset.into();
+        let (tile_width, tile_height) = source.dimensions();
+        if rect.is_empty() || tile_width == 0 || tile_height == 0 {
+            return;
+        }
+        // the number of pixels of `source` required to fill `rect`, rounded up,
+        // a `scale` of zero is reported when drawing
+        let texels = |size: u32, scale: u32| {
+            let scale = scale.max(1);
+            size / scale + u32::from(size % scale != 0)
+        };
+        let (width, height) = (
+            texels(rect.width, config.scale.0),
+            texels(rect.height, config.scale.1),
+        );
+
+        if source.position == (0, 0) && source.size == source.inner.dimensions {
+            let options = source.options();
+            // `Wrap::MirroredRepeat` is kept, while clamped axes are repeated instead
+            let repeat = |wrap| match wrap {
+                Wrap::ClampToEdge => Wrap::Repeat,
+                wrap => wrap,
+            };
+            let uses_wrap = config.sampler.is_none()
+                && options.wrap == (repeat(options.wrap.0), repeat(options.wrap.1))
+                && source.border_color().is_none();
+            let config = if uses_wrap {
+                Cow::Borrowed(config)
+            } else {
+                let sampler = config.sampler.unwrap_or(Sampler {
+                    min_filter: options.min_filter,
+                    mag_filter: options.mag_filter,
+                    wrap: options.wrap,
+                    ..Default::default()
+                });
+                Cow::Owned(DrawConfig {
+                    sampler: Some(Sampler {
+                        wrap: (repeat(sampler.wrap.0), repeat(sampler.wrap.1)),
+                        ..sampler
+                    }),
+                    ..config.clone()
+                })
+            };
+            let repeated = source.repeated(uv_offset, (width, height));
+            return target.receive_draw(self, &repeated, rect.position(), &config);
+        }
+
+        let (scale_x, scale_y) = (config.scale.0 as f32, config.scale.1 as f32);
+        let center = config.rotation_center((width, height));
+        let transform = config.effective_transform();
+        let start = (
+            uv_offset.0.rem_euclid(tile_width as i32) as u32,
+            uv_offset.1.rem_euclid(tile_height as i32) as u32,
+        );
+        let mut y = 0;
+        while y < height {
+            let section_y = if y == 0 { start.1 } else { 0 };
+            let tile_height = cmp::min(tile_height - section_y, height - y);
+            let mut x = 0;
+            while x < width {
+                let section_x = if x == 0 { start.0 } else { 0 };
+                let tile_width = cmp::min(tile_width - section_x, width - x);
+                let tile = source
+                    .get_section(Rect::new((section_x, section_y), (tile_width, tile_height)));
+                // flipping only changes the texture coordinates,
+                // so flipped tiles are drawn at the mirrored position
+                let offset = (
+                    if config.flip_horizontally {
+                        width - x - tile_width
+                    } else {
+                        x
+                    },
+                    if config.flip_vertically {
+                        height - y - tile_height
+                    } else {
+                        y
+                    },
+                );
+                let scaled_offset = (offset.0 as f32 * scale_x, offset.1 as f32 * scale_y);
+                // keeps the rotation and transform relative to the whole area
+                let transform = transform.map(|mut transform| {
+                    transform[0][2] += transform[0][0] * scaled_offset.0
+                        + transform[0][1] * scaled_offset.1
+                        - scaled_offset.0;
+                    transform[1][2] += transform[1][0] * scaled_offset.0
+                        + transform[1][1] * scaled_offset.1
+                        - scaled_offset.1;
+                    transform
+                });
+                let tile_config = DrawConfig {
+                    rotation_center: Some((center.0 - scaled_offset.0, center.1 - scaled_offset.1)),
+                    transform,
+                    ..config.clone()
+                };
+                let position = (
+                    rect.position().0 + (offset.0 * config.scale.0) as i32,
+                    rect.position().1 + (offset.1 * config.scale.1) as i32,
+                );
+                target.receive_draw(self, &tile, position, &tile_config);
+                x += tile_width;
+            }
+            y += tile_height;
+        }
+    }
+
+    /// Draws each sprite of `batch` onto `target`, using as few draw calls as possible.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `batch` or `target` was created by a different `Context`.
+    pub fn draw_batch<T>(&mut self, target: &mut T, batch: &SpriteBatch)
+    where
+        T: DrawTarget,
+    {
+        self.in_subsystem(Subsystem::Batch, |ctx| target.receive_batch(ctx, batch))
+    }
+
+    /// Draws the `source` onto `target`, returning an error in case the draw call
+    /// is invalid instead of ignoring it.
+    ///
+    /// When using [`Context::draw`], invalid draw calls which would not change the target,
+    /// for example drawing a texture with a size of zero, are silently ignored
+    /// while all other invalid draw calls are drawn using `DrawConfig::sanitize` in debug
+    /// builds and skipped in release builds.
+    ///
+    /// [`Context::draw`]: struct.Context.html#method.draw
+    pub fn try_draw<T>(
+        &mut self,
+        target: &mut T,
+        source: &Texture,
+        position: impl Into<(i32, i32)>,
+        config: &DrawConfig,
+    ) -> Result<(), DrawError>
+    where
+        T: DrawTarget,
+    {
+        self.backend.start_try_draw();
+        target.receive_draw(self, source, position.into(), config);
+        self.backend.finish_try_draw()
+    }
+
+    /// Calls `Texture::prepare_target` for each texture of `textures`,
+    /// stopping at the first error.
+    pub fn prepare_targets(&mut self, textures: &mut [Texture]) -> Result<(), DrawError> {
+        for texture in textures {
+            texture.prepare_target(self)?;
+        }
+        Ok(())
     }
 
     /// Draws the a line going from `from` to `to` onto `target` with the given `color`.
@@ -104,19 +407,19 @@ impl Context {
     pub fn debug_line<T>(
         &mut self,
         target: &mut T,
-        from: (i32, i32),
-        to: (i32, i32),
+        from: impl Into<(i32, i32)>,
+        to: impl Into<(i32, i32)>,
         color: (f32, f32, f32, f32),
     ) where
         T: DrawTarget,
     {
-        target.receive_line(self, from, to, color)
+        let color = self.primitive_color(color);
+        target.receive_line(self, from.into(), to.into(), color)
     }
 
-    /// Draws the bounding box of an axis-aligned rectangle specified by
-    /// its `lower_left` and `upper_right` corner.
+    /// Draws the outline of `rect`, using its outermost pixels.
     ///
-    /// In case `lower_left` is to the right or above `upper_right`, the two points will be flipped.
+    /// Nothing is drawn if `rect` is empty.
     ///
     /// To draw this rectangle to the window, use [`Context::window_surface`] as a target.
     ///
@@ -124,13 +427,69 @@ impl Context {
     pub fn debug_rectangle<T>(
         &mut self,
         target: &mut T,
-        lower_left: (i32, i32),
-        upper_right: (i32, i32),
+        rect: impl Into<IRect>,
         color: (f32, f32, f32, f32),
     ) where
         T: DrawTarget,
     {
-        target.receive_rectangle(self, lower_left, upper_right, color)
+        let color = self.primitive_color(color);
+        target.receive_rectangle(self, rect.into(), color)
+    }
+
+    /// Sets how draw calls to the window are visualized.
+    ///
+    /// Using `DebugView::Overdraw` helps with finding areas which are
+    /// drawn over multiple times, which can be expensive on weak GPUs.
+    pub fn set_debug_view(&mut self, view: DebugView) {
+        self.debug_view = view;
+    }
+
+    /// Returns how draw calls to the window are visualized.
+    pub fn debug_view(&self) -> DebugView {
+        self.debug_view
+    }
+
+    /// Sets a color matrix which is applied to the finished frame by `Context::present`,
+    /// `None` disables the filter.
+    ///
+    /// This can be used together with `color::PROTANOPIA` and the other
+    /// color blindness matrices to check how the game is perceived,
+    /// or with `color::PROTANOPIA_ASSIST` and its siblings to make it easier
+    /// to distinguish colors for affected players.
+    ///
+    /// The filter is applied to the window as a whole, so it is not affected by
+    /// the blend mode of individual draw calls. Drawing to textures, the cursor
+    /// and `DrawCapture::Overlay` are not affected by this filter.
+    pub fn set_color_filter(&mut self, filter: Option<[[f32; 4]; 4]>) {
+        self.color_filter = filter;
+    }
+
+    /// Returns the color matrix currently applied to each finished frame.
+    pub fn color_filter(&self) -> Option<[[f32; 4]; 4]> {
+        self.color_filter
+    }
+
+    /// Replaces the colors used by `Context::debug_line` and `Context::debug_rectangle`
+    /// with the closest color of `palette`, `None` uses all colors unchanged.
+    ///
+    /// See [`Palette::nearest`] for how the closest color is chosen.
+    ///
+    /// [`Palette::nearest`]: color/struct.Palette.html#method.nearest
+    pub fn set_primitive_palette(&mut self, palette: Option<color::Palette>) {
+        self.primitive_palette = palette;
+    }
+
+    /// Returns the palette used by `Context::debug_line` and `Context::debug_rectangle`.
+    pub fn primitive_palette(&self) -> Option<&color::Palette> {
+        self.primitive_palette.as_ref()
+    }
+
+    /// Applies the palette set by `Context::set_primitive_palette` to `color`.
+    fn primitive_color(&self, color: (f32, f32, f32, f32)) -> (f32, f32, f32, f32) {
+        match &self.primitive_palette {
+            Some(palette) => palette.nearest(color),
+            None => color,
+        }
     }
 
     /// Clears the color of the given [`DrawTarget`], setting each pixel to `color`
@@ -143,6 +502,20 @@ impl Context {
         target.receive_clear_color(self, color)
     }
 
+    /// Sets each pixel of the given [`DrawTarget`] inside of `rect` to `color`.
+    ///
+    /// [`DrawTarget`]: trait.DrawTarget.html
+    pub fn clear_region<T>(
+        &mut self,
+        target: &mut T,
+        rect: impl Into<IRect>,
+        color: (f32, f32, f32, f32),
+    ) where
+        T: DrawTarget,
+    {
+        target.receive_clear_region(self, rect.into(), color)
+    }
+
     /// Resets the depth buffer of the given [`DrawTarget`] to `1.0`.
     ///
     /// [`DrawTarget`]: trait.DrawTarget.html
@@ -153,6 +526,91 @@ impl Context {
         target.receive_clear_depth(self)
     }
 
+    /// Calls `f` while only allowing changes to `target` inside of the region defined by `mask`.
+    ///
+    /// Draw calls and debug primitives to `target` inside of `f` are masked,
+    /// while draw calls to other targets are unaffected.
+    /// Clearing `target` still affects the whole target.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if it is called inside of another `Context::masked`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// use crow::{glutin::{window::WindowBuilder, event_loop::EventLoop}, Context, DrawConfig, IRect, Mask, Texture};
+    ///
+    /// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+    /// let texture = Texture::load(&mut ctx, "./textures/player.png")?;
+    /// let mut target = Texture::new(&mut ctx, (64, 64))?;
+    ///
+    /// ctx.masked(&mut target, Mask::Rectangle(IRect::new((8, 8), (16, 16))), |ctx, target| {
+    ///     // only the pixels in the range `8..=23` are changed
+    ///     ctx.draw(target, &texture, (0, 0), &DrawConfig::default());
+    /// });
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    pub fn masked<T, F, R>(&mut self, target: &mut T, mask: Mask<'_>, f: F) -> R
+    where
+        T: DrawTarget,
+        F: FnOnce(&mut Context, &mut T) -> R,
+    {
+        if self.backend.masking() != Masking::Disabled {
+            panic!("Called `Context::masked` while another mask is active");
+        }
+
+        let config = DrawConfig {
+            color_mask: (false, false, false, false),
+            ..Default::default()
+        };
+
+        match mask {
+            Mask::Texture(texture, position) => {
+                self.backend.set_masking(Masking::Write(None));
+                target.receive_draw(self, texture, position, &config);
+            }
+            Mask::Rectangle(rect) => match self.mask_texture() {
+                Ok(texture) => {
+                    self.backend.set_masking(Masking::Write(None));
+                    if !rect.is_empty() {
+                        let scale = rect.size();
+                        let position = rect.position();
+                        target.receive_draw(
+                            self,
+                            &texture,
+                            position,
+                            &DrawConfig { scale, ..config },
+                        );
+                    }
+                }
+                Err(err) => self.backend.report_error(DrawError::from_new(err)),
+            },
+        }
+
+        if let Masking::Write(Some(framebuffer)) = self.backend.masking() {
+            self.backend.set_masking(Masking::Test(framebuffer));
+        } else {
+            warn!("`Context::masked` was unable to draw the mask");
+            self.backend.set_masking(Masking::Disabled);
+        }
+
+        let res = f(self, target);
+        self.backend.set_masking(Masking::Disabled);
+        res
+    }
+
+    fn mask_texture(&mut self) -> Result<Texture, NewTextureError> {
+        if let Some(texture) = &self.mask_texture {
+            Ok(texture.clone())
+        } else {
+            let mut texture = Texture::new(self, (1, 1))?;
+            self.clear_color(&mut texture, (1.0, 1.0, 1.0, 1.0));
+            self.mask_texture = Some(texture.clone());
+            Ok(texture)
+        }
+    }
+
     /// Loads the current state of a [`DrawTarget`] into an image.
     ///
     /// [`DrawTarget`]: trait.DrawTarget.html
@@ -181,44 +639,660 @@ impl Context {
     /// context.window().set_title("Running");
     /// # Ok::<(), crow::Error>(())
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the context was created using `Context::new_headless`.
     pub fn window(&self) -> &Window {
         self.backend.window()
     }
 
+    /// Returns the used window, or `None` if the context was created using `Context::new_headless`.
+    pub fn try_window(&self) -> Option<&Window> {
+        self.backend.try_window()
+    }
+
+    /// Sets the position of the IME candidate box, which should be
+    /// right next to the currently edited text field.
+    ///
+    /// Text entered using an IME is received as `WindowEvent::ReceivedCharacter`,
+    /// one event per committed character. Composition events are not
+    /// supported by the windowing library yet.
+    ///
+    /// This does nothing if the context was created using `Context::new_headless`.
+    pub fn set_ime_position(&self, position: impl Into<(i32, i32)>) {
+        let position = position.into();
+        // the origin of the window is in the top left corner
+        let height = self.window_height() as i32;
+        if let Some(window) = self.try_window() {
+            window.set_ime_position(LogicalPosition::new(position.0, height - 1 - position.1))
+        }
+    }
+
     /// Returns a handle to the window surface.
     ///
     /// This handle implements `DrawTarget` and can be used to draw to the window.
     ///
     /// Use `fn Context::present` to actually display the resulting image.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the surface is already in use, meaning that
+    /// it was not yet returned using `Context::present`. Consider using
+    /// `Context::try_surface` in case this is expected.
     pub fn surface(&mut self) -> WindowSurface {
-        if let Some(surface) = self.surface.take() {
+        if let Some(surface) = self.try_surface() {
             surface
         } else {
             panic!("Called `Context::surface` while the previous surface is still in use");
         }
     }
 
+    /// Returns a handle to the window surface, or `None` if the surface is already in use.
+    ///
+    /// This is a non-panicking version of `Context::surface`.
+    pub fn try_surface(&mut self) -> Option<WindowSurface> {
+        self.surface.take()
+    }
+
     /// Presents the current frame to the screen.
     pub fn present(&mut self, surface: WindowSurface) -> Result<(), FinalizeError> {
         self.surface = Some(surface);
-        self.backend.finalize_frame()
+        self.apply_color_filter();
+        self.finish_capture();
+        self.last_pass_timings = mem::take(&mut self.pass_timings);
+        self.draw_cursor();
+        self.limit_frame_rate();
+        let res = self.backend.finalize_frame();
+        self.mouse_delta = (0.0, 0.0);
+        self.last_present = Some(Instant::now());
+        res
+    }
+
+    /// Marks `rect` as changed during the current frame.
+    ///
+    /// If the platform supports swapping with damage, only the damaged regions are
+    /// passed to the compositor during the next `Context::present`, reducing its
+    /// workload. In case no damage is added during a frame, the whole window is
+    /// considered damaged.
+    ///
+    /// The whole window surface must still be drawn each frame, as its previous
+    /// content is not preserved.
+    pub fn add_damage(&mut self, rect: impl Into<IRect>) {
+        self.backend.add_damage(rect.into())
+    }
+
+    /// Makes sure that all previously issued commands are sent to the GPU,
+    /// without waiting for them to complete.
+    pub fn flush(&mut self) {
+        self.backend.flush()
+    }
+
+    /// Blocks until all previously issued commands have been completed by the GPU.
+    ///
+    /// To only wait for some of the commands, consider using [`Context::insert_fence`] instead.
+    ///
+    /// [`Context::insert_fence`]: struct.Context.html#method.insert_fence
+    pub fn finish(&mut self) {
+        self.backend.finish()
+    }
+
+    /// Inserts a new [`Fence`], which is signaled once all
+    /// previously issued commands have been completed by the GPU.
+    ///
+    /// [`Fence`]: struct.Fence.html
+    pub fn insert_fence(&mut self) -> Fence {
+        Fence::new(&mut self.backend)
+    }
+
+    /// Checks if any pixel of `rect` would be drawn onto `target` at the given `depth`,
+    /// taking both the depth buffer and `Context::masked` into account.
+    ///
+    /// This can be used to skip expensive draw calls, for example detailed
+    /// backgrounds, which are completely hidden behind previously drawn
+    /// occluders with a smaller `DrawConfig::depth`.
+    ///
+    /// ```rust, no_run
+    /// use crow::{
+    ///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+    ///     Context, DrawConfig, IRect, Texture,
+    /// };
+    ///
+    /// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+    /// let menu = Texture::load(&mut ctx, "./textures/menu.png")?;
+    /// let background = Texture::load(&mut ctx, "./textures/background.png")?;
+    ///
+    /// let mut surface = ctx.surface();
+    /// ctx.clear_depth(&mut surface);
+    /// let config = DrawConfig {
+    ///     depth: Some(0.2),
+    ///     ..Default::default()
+    /// };
+    /// ctx.draw(&mut surface, &menu, (0, 0), &config);
+    ///
+    /// let rect = IRect::new((0, 0), background.dimensions());
+    /// let query = ctx.query_visible(&mut surface, rect, 0.8);
+    /// if query.wait(&mut ctx) {
+    ///     let config = DrawConfig {
+    ///         depth: Some(0.8),
+    ///         ..Default::default()
+    ///     };
+    ///     ctx.draw(&mut surface, &background, (0, 0), &config);
+    /// }
+    /// ctx.present(surface)?;
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    pub fn query_visible<T>(
+        &mut self,
+        target: &mut T,
+        rect: impl Into<IRect>,
+        depth: f32,
+    ) -> QueryHandle
+    where
+        T: DrawTarget,
+    {
+        target.receive_visibility_query(self, rect.into(), depth)
+    }
+
+    /// Returns `true` if the window is not focused or the application
+    /// has been suspended by the platform.
+    ///
+    /// This is only updated by [`Context::handle_event`], which is called by [`Context::run`].
+    ///
+    /// [`Context::handle_event`]: struct.Context.html#method.handle_event
+    /// [`Context::run`]: struct.Context.html#method.run
+    pub fn is_suspended(&self) -> bool {
+        !self.focused || self.platform_suspended
+    }
+
+    /// Registers a callback which is called by [`Context::handle_event`] once
+    /// the window loses focus or the application is suspended by the platform.
+    ///
+    /// This can be used to pause the game or to mute its audio.
+    ///
+    /// [`Context::handle_event`]: struct.Context.html#method.handle_event
+    pub fn on_suspend<F>(&mut self, f: F)
+    where
+        F: 'static + FnMut(&mut Context),
+    {
+        self.on_suspend.push(SkipDebug(Box::new(f)));
+    }
+
+    /// Registers a callback which is called by [`Context::handle_event`] once
+    /// the application is no longer suspended, see [`Context::on_suspend`].
+    ///
+    /// [`Context::handle_event`]: struct.Context.html#method.handle_event
+    /// [`Context::on_suspend`]: struct.Context.html#method.on_suspend
+    pub fn on_resume<F>(&mut self, f: F)
+    where
+        F: 'static + FnMut(&mut Context),
+    {
+        self.on_resume.push(SkipDebug(Box::new(f)));
+    }
+
+    /// Returns the theme used by the system, if known.
+    ///
+    /// This is only updated by [`Context::handle_event`], which is called by [`Context::run`],
+    /// and only supported on platforms which report theme changes, currently Windows and
+    /// the web. It is `None` until the platform reported the first theme change.
+    ///
+    /// [`Context::handle_event`]: struct.Context.html#method.handle_event
+    /// [`Context::run`]: struct.Context.html#method.run
+    pub fn system_theme(&self) -> Option<&Theme> {
+        self.theme.as_ref()
+    }
+
+    /// Registers a callback which is called by [`Context::run`] once
+    /// the system theme changes, for example to update the clear color.
+    ///
+    /// [`Context::run`]: struct.Context.html#method.run
+    pub fn on_theme_change<F>(&mut self, f: F)
+    where
+        F: 'static + FnMut(&mut Context, &Theme),
+    {
+        self.on_theme_change.push(SkipDebug(Box::new(f)));
+    }
+
+    /// Registers a callback which is called each time drawing to a `Texture`
+    /// requires a copy of it, see [`TextureCopy`].
+    ///
+    /// Such copies are also logged at the `debug` level.
+    ///
+    /// [`TextureCopy`]: struct.TextureCopy.html
+    pub fn on_texture_copy<F>(&mut self, f: F)
+    where
+        F: 'static + FnMut(&TextureCopy),
+    {
+        self.on_texture_copy.push(SkipDebug(Box::new(f)));
+    }
+
+    /// Reports that a texture of the given `size` had to be copied before drawing to it.
+    pub(crate) fn report_texture_copy(
+        &mut self,
+        reason: CopyReason,
+        size: (u32, u32),
+        label: Option<&str>,
+    ) {
+        if self.on_texture_copy.is_empty() && !log_enabled!(log::Level::Debug) {
+            return;
+        }
+
+        let copy = TextureCopy {
+            reason,
+            size,
+            label: label.map(str::to_owned),
+            backtrace: Backtrace::capture(),
+        };
+        debug!(
+            "copied a {}x{} texture{} to draw to it ({:?}): {}",
+            size.0,
+            size.1,
+            label.map_or(String::new(), |label| format!(" {:?}", label)),
+            reason,
+            copy.backtrace
+        );
+        for hook in self.on_texture_copy.iter_mut() {
+            (hook.0)(&copy);
+        }
+    }
+
+    /// Returns the number of texture uploads, drawn sprites and readbacks
+    /// of the current frame, attributed to the subsystem which caused them.
+    ///
+    /// The counters are reset each time a frame is presented, use
+    /// [`Context::last_frame_counters`] to get the operations of a whole frame.
+    ///
+    /// [`Context::last_frame_counters`]: struct.Context.html#method.last_frame_counters
+    pub fn counters(&self) -> Counters {
+        *self.backend.counters()
+    }
+
+    /// Returns the operations of the last presented frame,
+    /// see [`Context::counters`] for more details.
+    ///
+    /// [`Context::counters`]: struct.Context.html#method.counters
+    pub fn last_frame_counters(&self) -> Counters {
+        *self.backend.last_frame_counters()
+    }
+
+    /// Returns the time spent on each `RenderGraph` pass executed during the
+    /// last presented frame, in the order the passes were executed.
+    ///
+    /// `PassTiming::pass` is the index of the pass in its own graph. The GPU time
+    /// of a pass is only available once the GPU is done, which usually happens
+    /// while drawing the next frame.
+    pub fn last_frame_pass_timings(&mut self) -> &[PassTiming] {
+        self.last_pass_timings.resolve(&mut self.backend)
+    }
+
+    /// Sets all counters returned by [`Context::counters`] to zero,
+    /// which is done automatically when presenting a frame.
+    ///
+    /// [`Context::counters`]: struct.Context.html#method.counters
+    pub fn reset_counters(&mut self) {
+        self.backend.reset_counters()
+    }
+
+    /// Attributes all operations of `f` to `subsystem`.
+    pub(crate) fn in_subsystem<R>(
+        &mut self,
+        subsystem: Subsystem,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let previous = self.backend.set_subsystem(subsystem);
+        let result = f(self);
+        self.backend.set_subsystem(previous);
+        result
+    }
+
+    /// Calls all hooks returned by `hooks`, hooks registered
+    /// during this call are only called the next time.
+    pub(crate) fn call_hooks<H: ?Sized>(
+        &mut self,
+        hooks: fn(&mut Self) -> &mut Vec<SkipDebug<Box<H>>>,
+        mut call: impl FnMut(&mut H, &mut Self),
+    ) {
+        let mut current = mem::take(hooks(self));
+        for hook in current.iter_mut() {
+            call(&mut hook.0, self);
+        }
+        let added = mem::replace(hooks(self), current);
+        hooks(self).extend(added);
+    }
+
+    /// Registers a callback which is called by [`Context::run`] and [`Context::frame`]
+    /// at the start of each frame, before any user code.
+    ///
+    /// This allows crates built on top of crow to extend the frame
+    /// lifecycle, for example to poll for changed assets.
+    ///
+    /// [`Context::run`]: struct.Context.html#method.run
+    /// [`Context::frame`]: struct.Context.html#method.frame
+    pub fn on_pre_frame<F>(&mut self, f: F)
+    where
+        F: 'static + FnMut(&mut Context, &mut WindowSurface),
+    {
+        self.pre_frame.push(SkipDebug(Box::new(f)));
+    }
+
+    /// Registers a callback which is called by [`Context::run`] and [`Context::frame`]
+    /// at the end of each frame, right before the surface is presented.
+    ///
+    /// This allows crates built on top of crow to extend the frame
+    /// lifecycle, for example to draw an overlay or to record the frame.
+    ///
+    /// [`Context::run`]: struct.Context.html#method.run
+    /// [`Context::frame`]: struct.Context.html#method.frame
+    pub fn on_post_frame<F>(&mut self, f: F)
+    where
+        F: 'static + FnMut(&mut Context, &mut WindowSurface),
+    {
+        self.post_frame.push(SkipDebug(Box::new(f)));
+    }
+
+    /// Draws a single frame by calling `f` with the window surface,
+    /// which is presented afterwards.
+    ///
+    /// This can be used instead of manually calling `Context::surface`
+    /// and `Context::present`, making it impossible to forget to present
+    /// the surface.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FinalizeError::SurfaceInUse` without calling `f` if the window
+    /// surface is already in use, for example when calling `Context::frame` inside of `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crow::{
+    ///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+    ///     Context,
+    /// };
+    ///
+    /// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+    ///
+    /// ctx.frame(|ctx, surface| ctx.clear_color(surface, (0.4, 0.4, 0.8, 1.0)))?;
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    pub fn frame<F, R>(&mut self, f: F) -> Result<R, FinalizeError>
+    where
+        F: FnOnce(&mut Context, &mut WindowSurface) -> R,
+    {
+        let mut surface = self.try_surface().ok_or(FinalizeError::SurfaceInUse)?;
+        self.update_shader_globals();
+        self.call_hooks(
+            |ctx| &mut ctx.pre_frame,
+            |hook, ctx| hook(ctx, &mut surface),
+        );
+        let res = f(self, &mut surface);
+        self.call_hooks(
+            |ctx| &mut ctx.post_frame,
+            |hook, ctx| hook(ctx, &mut surface),
+        );
+        self.present(surface).map(|()| res)
+    }
+
+    /// Updates the suspension state using `f` and calls the
+    /// relevant hooks in case `Context::is_suspended` changed.
+    fn update_suspended(&mut self, f: impl FnOnce(&mut Self)) {
+        let was_suspended = self.is_suspended();
+        f(self);
+        match (was_suspended, self.is_suspended()) {
+            (false, true) => self.call_hooks(|ctx| &mut ctx.on_suspend, |hook, ctx| hook(ctx)),
+            (true, false) => self.call_hooks(|ctx| &mut ctx.on_resume, |hook, ctx| hook(ctx)),
+            _ => (),
+        }
+    }
+
+    /// Runs `event_loop`, calling `frame` once per frame with all events
+    /// received since the previous frame.
+    ///
+    /// The window surface is presented after each call to `frame`.
+    /// Once `frame` returns `Flow::Exit`, this context is dropped and
+    /// the value stored in `Flow::Exit` is returned.
+    ///
+    /// While the application is suspended by the platform, no frames are drawn.
+    /// Losing focus only calls the hooks registered using [`Context::on_suspend`].
+    ///
+    /// Text input, including text entered using an IME, is received as
+    /// `WindowEvent::ReceivedCharacter`. Use [`Context::set_ime_position`]
+    /// to move the IME candidate box next to the edited text.
+    ///
+    /// This is only available on desktop platforms, as the event loop
+    /// of all other platforms never returns.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crow::{
+    ///     glutin::{
+    ///         event::{Event, WindowEvent},
+    ///         event_loop::EventLoop,
+    ///         window::WindowBuilder,
+    ///     },
+    ///     Context, DrawConfig, Flow, Texture,
+    /// };
+    ///
+    /// let event_loop = EventLoop::new();
+    /// let mut ctx = Context::new(WindowBuilder::new(), &event_loop)?;
+    ///
+    /// let texture = Texture::load(&mut ctx, "./textures/player.png")?;
+    ///
+    /// let frames = ctx.run(event_loop, {
+    ///     let mut frames = 0;
+    ///     move |ctx, surface, events| {
+    ///         ctx.clear_color(surface, (0.4, 0.4, 0.8, 1.0));
+    ///         ctx.draw(surface, &texture, (100, 150), &DrawConfig::default());
+    ///         frames += 1;
+    ///
+    ///         for event in events {
+    ///             if let Event::WindowEvent {
+    ///                 event: WindowEvent::CloseRequested,
+    ///                 ..
+    ///             } = event
+    ///             {
+    ///                 return Flow::Exit(frames);
+    ///             }
+    ///         }
+    ///
+    ///         Flow::Continue
+    ///     }
+    /// })?;
+    ///
+    /// println!("exited after {} frames", frames);
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    ///
+    /// [`Context::on_suspend`]: struct.Context.html#method.on_suspend
+    /// [`Context::set_ime_position`]: struct.Context.html#method.set_ime_position
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    pub fn run<T, R, F>(self, event_loop: EventLoop<T>, mut frame: F) -> Result<R, FinalizeError>
+    where
+        F: FnMut(&mut Context, &mut WindowSurface, &[Event<'static, T>]) -> Flow<R>,
+    {
+        self.run_with_state(event_loop, (), move |ctx, (), surface, events| {
+            frame(ctx, surface, events)
+        })
+    }
+
+    /// Runs `event_loop` like [`Context::run`], while also passing
+    /// a mutable reference to `state` to each frame.
+    ///
+    /// As `state` is owned by the event loop, `frame` does not have to capture
+    /// the whole game state, which allows it to borrow from `state` during each frame.
+    ///
+    /// `state` is dropped before this context.
+    ///
+    /// [`Context::run`]: struct.Context.html#method.run
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    pub fn run_with_state<T, S, R, F>(
+        mut self,
+        mut event_loop: EventLoop<T>,
+        mut state: S,
+        mut frame: F,
+    ) -> Result<R, FinalizeError>
+    where
+        F: FnMut(&mut Context, &mut S, &mut WindowSurface, &[Event<'static, T>]) -> Flow<R>,
+    {
+        let mut events = Vec::new();
+        let mut exit = None;
+        event_loop.run_return(|event, _window_target, control_flow| match event {
+            Event::MainEventsCleared => {
+                if *control_flow != ControlFlow::Exit {
+                    if self.platform_suspended {
+                        *control_flow = ControlFlow::Wait;
+                    } else {
+                        *control_flow = ControlFlow::Poll;
+                        self.window().request_redraw()
+                    }
+                }
+            }
+            Event::RedrawRequested(_) if !self.platform_suspended && exit.is_none() => {
+                let res = self.frame(|ctx, surface| frame(ctx, &mut state, surface, &events));
+                events.clear();
+
+                match res {
+                    Ok(Flow::Continue) => (),
+                    Ok(Flow::Exit(value)) => {
+                        exit = Some(Ok(value));
+                        *control_flow = ControlFlow::Exit;
+                    }
+                    Err(e) => {
+                        exit = Some(Err(e));
+                        *control_flow = ControlFlow::Exit;
+                    }
+                }
+            }
+            event => {
+                self.handle_event(&event);
+
+                // `WindowEvent::ScaleFactorChanged` is the only event which can not
+                // be stored, as it contains a mutable reference to the new window size.
+                if let Some(event) = event.to_static() {
+                    events.push(event);
+                }
+            }
+        });
+
+        // drop all textures before the context itself
+        mem::drop(state);
+        mem::drop(self);
+        exit.unwrap_or_else(|| bug!("the event loop stopped without an exit value"))
+    }
+
+    /// Updates the state of this context which depends on `event`, which is done for
+    /// all events while using [`Context::run`].
+    ///
+    /// This updates the cursor position, [`Context::mouse_delta`], the theme, whether the
+    /// window is focused and whether the application is suspended, calling the relevant
+    /// hooks. When using a custom event loop, this should be called for each event.
+    ///
+    /// [`Context::run`]: struct.Context.html#method.run
+    /// [`Context::mouse_delta`]: struct.Context.html#method.mouse_delta
+    pub fn handle_event<T>(&mut self, event: &Event<'_, T>) {
+        match event {
+            Event::Suspended => self.update_suspended(|ctx| ctx.platform_suspended = true),
+            Event::Resumed => self.update_suspended(|ctx| ctx.platform_suspended = false),
+            &Event::WindowEvent {
+                event: WindowEvent::Focused(focused),
+                ..
+            } => {
+                self.update_suspended(|ctx| ctx.focused = focused);
+                if focused {
+                    self.clear_attention();
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ThemeChanged(theme),
+                ..
+            } => {
+                self.theme = Some(theme.clone());
+                self.call_hooks(|ctx| &mut ctx.on_theme_change, |hook, ctx| hook(ctx, theme));
+            }
+            event => self.update_cursor(event),
+        }
     }
 
     /// Drops this context while allowing the initialization of a new one afterwards.
     ///
     /// # Safety
     ///
-    /// This method may lead to undefined behavior if a struct, for example a `Texture`, which was created using
-    /// the current context, is used with the new context.
+    /// This method is always safe, as dropping a `Context` now
+    /// allows the creation of a new one.
+    #[deprecated(note = "dropping a `Context` now allows the creation of a new one")]
     pub unsafe fn unlock_unchecked(self) {
         mem::drop(self);
+    }
+}
+
+/// The color added by each draw call while using `DebugView::Overdraw`.
+const OVERDRAW_COLOR: (f32, f32, f32, f32) = (1.0, 0.5, 0.1, 0.2);
+
+impl Context {
+    /// Applies the color filter to the finished frame, see `Context::set_color_filter`.
+    ///
+    /// The window is copied into `color_filter_buffer`, which is then drawn back
+    /// using the filter as its color modulation.
+    fn apply_color_filter(&mut self) {
+        let filter = match self.color_filter {
+            Some(filter) if self.debug_view == DebugView::Normal => filter,
+            _ => return,
+        };
 
-        let gl_error = gl::GetError();
-        if gl_error != gl::NO_ERROR {
-            bug!("unexpected error: {}", gl_error);
+        let dim = self.backend.window_dimensions();
+        let dpi = self.backend.dpi_factor();
+        let size = (dim.0 * dpi, dim.1 * dpi);
+        if self.color_filter_buffer.as_ref().map(Texture::dimensions) != Some(size) {
+            self.color_filter_buffer = match Texture::new(self, size) {
+                Ok(texture) => Some(texture),
+                Err(err) => {
+                    error!("failed to create the color filter buffer: {}", err);
+                    return;
+                }
+            };
         }
 
-        INITIALIZED.store(false, Ordering::Release);
+        let mut buffer = self.color_filter_buffer.take().unwrap();
+        if let Some(target) = buffer.prepare_as_draw_target(self) {
+            self.backend.copy_window(target.framebuffer_id, size);
+        }
+        // the buffer is drawn onto the cleared window, so that translucent pixels are kept
+        let config = DrawConfig {
+            color_modulation: filter,
+            blend_mode: BlendMode::PremultipliedAlpha,
+            ..DrawConfig::default()
+        };
+        self.backend.without_mask(|backend| {
+            backend.clear_color(0, (0.0, 0.0, 0.0, 0.0));
+            backend.draw(
+                0,
+                size,
+                1,
+                &buffer.inner,
+                buffer.position,
+                buffer.size,
+                (0, 0),
+                &config,
+            )
+        });
+        self.color_filter_buffer = Some(buffer);
     }
 }
 
@@ -233,8 +1307,25 @@ impl DrawTarget for WindowSurface {
         position: (i32, i32),
         config: &DrawConfig,
     ) {
+        ctx.capture(|| CapturedDraw::texture(texture, position, config));
         let dim = ctx.backend.window_dimensions();
         let dpi = ctx.backend.dpi_factor();
+        if ctx.debug_view == DebugView::Overdraw {
+            let config = DrawConfig {
+                blend_mode: BlendMode::Additive,
+                ..config.clone()
+            };
+            return ctx.backend.debug_quad(
+                0,
+                dim,
+                dpi,
+                texture.size,
+                position,
+                &config,
+                OVERDRAW_COLOR,
+            );
+        }
+
         ctx.backend.draw(
             0,
             dim,
@@ -247,10 +1338,48 @@ impl DrawTarget for WindowSurface {
         )
     }
 
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        if ctx.debug_view == DebugView::Overdraw {
+            for (texture, position, config) in batch.sprites() {
+                self.receive_draw(ctx, &texture, position, config);
+            }
+            return;
+        }
+
+        if ctx.draw_capture != DrawCapture::Disabled {
+            for (texture, position, config) in batch.sprites() {
+                ctx.capture(|| CapturedDraw::texture(&texture, position, config));
+            }
+        }
+
+        let dim = ctx.backend.window_dimensions();
+        let dpi = ctx.backend.dpi_factor();
+        ctx.backend
+            .draw_batch(0, dim, dpi, &batch.texture.inner, &batch.sprites)
+    }
+
     fn receive_clear_color(&mut self, ctx: &mut Context, color: (f32, f32, f32, f32)) {
+        let color = match ctx.debug_view {
+            DebugView::Normal => color,
+            DebugView::Overdraw => (0.0, 0.0, 0.0, 1.0),
+        };
         ctx.backend.clear_color(0, color)
     }
 
+    fn receive_clear_region(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        let color = match ctx.debug_view {
+            DebugView::Normal => color,
+            DebugView::Overdraw => (0.0, 0.0, 0.0, 1.0),
+        };
+        let dpi = ctx.backend.dpi_factor();
+        ctx.backend.clear_region(0, dpi, rect, color)
+    }
+
     fn receive_clear_depth(&mut self, ctx: &mut Context) {
         ctx.backend.clear_depth(0)
     }
@@ -262,22 +1391,60 @@ impl DrawTarget for WindowSurface {
         to: (i32, i32),
         color: (f32, f32, f32, f32),
     ) {
+        ctx.capture(|| CapturedDraw {
+            kind: CapturedKind::Line,
+            lower_left: from,
+            upper_right: to,
+            color,
+            label: None,
+            texture_label: None,
+        });
         let dim = ctx.backend.window_dimensions();
         let dpi = ctx.backend.dpi_factor();
         ctx.backend.debug_draw(false, 0, dim, dpi, from, to, color)
     }
 
-    fn receive_rectangle(
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32)) {
+        if rect.is_empty() {
+            return;
+        }
+
+        let lower_left = rect.position();
+        let upper_right = (rect.right() - 1, rect.top() - 1);
+        ctx.capture(|| CapturedDraw {
+            kind: CapturedKind::Rectangle,
+            lower_left,
+            upper_right,
+            color,
+            label: None,
+            texture_label: None,
+        });
+        let dim = ctx.backend.window_dimensions();
+        let dpi = ctx.backend.dpi_factor();
+        ctx.backend
+            .debug_draw(true, 0, dim, dpi, lower_left, upper_right, color)
+    }
+
+    fn receive_fullscreen(
         &mut self,
         ctx: &mut Context,
-        lower_left: (i32, i32),
-        upper_right: (i32, i32),
-        color: (f32, f32, f32, f32),
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
     ) {
         let dim = ctx.backend.window_dimensions();
         let dpi = ctx.backend.dpi_factor();
-        ctx.backend
-            .debug_draw(true, 0, dim, dpi, lower_left, upper_right, color)
+        ctx.run_shader(0, (dim.0 * dpi, dim.1 * dpi), dim, shader, uniforms)
+    }
+
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        let dim = ctx.backend.window_dimensions();
+        let dpi = ctx.backend.dpi_factor();
+        ctx.backend.query_visible(0, dim, dpi, rect, depth)
     }
 
     fn get_image_data(&self, ctx: &mut Context) -> RgbaImage {
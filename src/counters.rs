@@ -0,0 +1,146 @@
+use std::ops::{Add, AddAssign};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// A part of crow to which GPU operations are attributed in [`Counters`].
+///
+/// Operations which are not caused by one of the more specific
+/// subsystems, for example `Context::draw`, use `Subsystem::Other`.
+///
+/// [`Counters`]: struct.Counters.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Subsystem {
+    /// Everything not covered by a different subsystem.
+    Other,
+    /// `Context::draw_text`.
+    Text,
+    /// `Context::draw_batch`.
+    Batch,
+    /// `Atlas::build`.
+    Atlas,
+    /// `VideoTexture`.
+    Video,
+    /// `particles::Emitter::draw`.
+    Particles,
+    /// `tiled::Map` and `ldtk::Level`.
+    Tilemap,
+}
+
+impl Subsystem {
+    /// All subsystems, in the order used by `Counters`.
+    const ALL: [Subsystem; 7] = [
+        Subsystem::Other,
+        Subsystem::Text,
+        Subsystem::Batch,
+        Subsystem::Atlas,
+        Subsystem::Video,
+        Subsystem::Particles,
+        Subsystem::Tilemap,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The number of GPU operations of a single subsystem.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Counts {
+    /// The number of times pixel data was uploaded to a texture.
+    pub uploads: u64,
+    /// The number of drawn sprites, each of which is part of a
+    /// draw call submitted to OpenGL.
+    pub draws: u64,
+    /// The number of times a texture or the window was read back from video memory.
+    pub readbacks: u64,
+}
+
+impl Add for Counts {
+    type Output = Counts;
+
+    fn add(self, other: Counts) -> Counts {
+        Counts {
+            uploads: self.uploads + other.uploads,
+            draws: self.draws + other.draws,
+            readbacks: self.readbacks + other.readbacks,
+        }
+    }
+}
+
+impl AddAssign for Counts {
+    fn add_assign(&mut self, other: Counts) {
+        *self = *self + other;
+    }
+}
+
+/// The GPU operations of a single frame, attributed to the [`Subsystem`] which caused them.
+///
+/// This is a snapshot returned by `Context::counters` and `Context::last_frame_counters`,
+/// so it can be sent to a different thread, for example to be logged there.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, Subsystem,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+///
+/// ctx.frame(|ctx, surface| {
+///     // draw the current frame
+/// })?;
+///
+/// let counters = ctx.last_frame_counters();
+/// println!(
+///     "text: {} of {} sprites",
+///     counters.get(Subsystem::Text).draws,
+///     counters.total().draws
+/// );
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`Subsystem`]: enum.Subsystem.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Counters {
+    counts: [Counts; 7],
+    frame_arena_size: usize,
+}
+
+impl Counters {
+    /// Returns the operations caused by `subsystem`.
+    pub fn get(&self, subsystem: Subsystem) -> Counts {
+        self.counts[subsystem.index()]
+    }
+
+    /// Returns the operations of all subsystems combined.
+    pub fn total(&self) -> Counts {
+        self.counts.iter().fold(Counts::default(), |a, &b| a + b)
+    }
+
+    /// Returns an iterator over the operations of each subsystem.
+    pub fn iter(&self) -> impl Iterator<Item = (Subsystem, Counts)> + '_ {
+        Subsystem::ALL.iter().map(move |&s| (s, self.get(s)))
+    }
+
+    /// Returns the number of bytes reserved for transient allocations
+    /// while drawing, measured at the end of the last presented frame.
+    ///
+    /// These buffers are reused each frame, so this only grows
+    /// if a frame draws more sprites than all previous ones.
+    pub fn frame_arena_size(&self) -> usize {
+        self.frame_arena_size
+    }
+
+    pub(crate) fn set_frame_arena_size(&mut self, size: usize) {
+        self.frame_arena_size = size;
+    }
+
+    pub(crate) fn get_mut(&mut self, subsystem: Subsystem) -> &mut Counts {
+        &mut self.counts[subsystem.index()]
+    }
+}
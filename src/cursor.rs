@@ -0,0 +1,159 @@
+use glutin::{
+    error::ExternalError,
+    event::{DeviceEvent, Event, WindowEvent},
+};
+
+use crate::{Context, DrawConfig, Texture};
+
+/// How the mouse cursor is restricted by the window, see [`Context::set_cursor_grab`].
+///
+/// [`Context::set_cursor_grab`]: struct.Context.html#method.set_cursor_grab
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorGrab {
+    /// The cursor can freely leave the window.
+    #[default]
+    None,
+    /// The cursor can not leave the window while it is focused.
+    Confined,
+    /// The cursor is confined to the window and hidden, so the mouse
+    /// is only used through `Context::mouse_delta`.
+    ///
+    /// This can be used for aiming or panning a map by moving the mouse.
+    Relative,
+}
+
+impl Context {
+    /// Sets a texture which is drawn at the position of the mouse cursor
+    /// each time the window surface is presented.
+    ///
+    /// The cursor is drawn after everything else, right before the frame is presented,
+    /// so it always uses the most recent cursor position. It ignores `Context::masked` and
+    /// is also drawn while the OS cursor is hidden, which can be done using
+    /// `Context::window().set_cursor_visible(false)`.
+    ///
+    /// `hotspot` is the pixel of `texture` which is placed at the cursor position,
+    /// relative to the lower left corner of `texture`.
+    ///
+    /// The cursor position is updated by [`Context::handle_event`], which is called
+    /// by `Context::run`, and can also be set using [`Context::set_cursor_position`].
+    ///
+    /// [`Context::handle_event`]: struct.Context.html#method.handle_event
+    /// [`Context::set_cursor_position`]: struct.Context.html#method.set_cursor_position
+    pub fn set_cursor(&mut self, cursor: Option<(Texture, (i32, i32))>) {
+        self.cursor = cursor;
+    }
+
+    /// Returns the position of the mouse cursor inside of the window,
+    /// or `None` if the cursor is outside of the window.
+    pub fn cursor_position(&self) -> Option<(i32, i32)> {
+        self.cursor_position
+    }
+
+    /// Sets the position at which the cursor set using [`Context::set_cursor`] is drawn.
+    ///
+    /// Using `None` hides the cursor.
+    ///
+    /// [`Context::set_cursor`]: struct.Context.html#method.set_cursor
+    pub fn set_cursor_position(&mut self, position: Option<(i32, i32)>) {
+        self.cursor_position = position;
+    }
+
+    /// Returns how the mouse cursor is currently restricted by the window.
+    pub fn cursor_grab(&self) -> CursorGrab {
+        self.cursor_grab
+    }
+
+    /// Restricts the mouse cursor to the window, using `CursorGrab::Relative`
+    /// also hides the OS cursor.
+    ///
+    /// This returns an error and keeps the previous mode if the platform
+    /// does not support grabbing the cursor. Headless contexts only
+    /// store the new mode.
+    pub fn set_cursor_grab(&mut self, mode: CursorGrab) -> Result<(), ExternalError> {
+        if let Some(window) = self.try_window() {
+            window.set_cursor_grab(mode != CursorGrab::None)?;
+            window.set_cursor_visible(mode != CursorGrab::Relative);
+        }
+        self.cursor_grab = mode;
+        Ok(())
+    }
+
+    /// Returns how far the mouse has been moved during the current frame,
+    /// with a positive `y` moving it upwards.
+    ///
+    /// This is the distance the cursor moved inside of the window, using the same
+    /// units as `Context::cursor_position`. While using `CursorGrab::Relative`, the
+    /// cursor is not moved, so this is instead the raw motion reported by the mouse.
+    /// Its units depend on the mouse and the platform and are not affected by
+    /// the dpi factor of the window or mouse acceleration.
+    ///
+    /// This is updated by [`Context::handle_event`] and reset by each [`Context::present`],
+    /// which is also used by `Context::frame`.
+    ///
+    /// [`Context::handle_event`]: struct.Context.html#method.handle_event
+    /// [`Context::present`]: struct.Context.html#method.present
+    pub fn mouse_delta(&self) -> (f32, f32) {
+        self.mouse_delta
+    }
+
+    /// Updates the cursor position and the mouse delta in case `event` moved the mouse.
+    pub(crate) fn update_cursor<T>(&mut self, event: &Event<'_, T>) {
+        if let Event::DeviceEvent {
+            event: DeviceEvent::MouseMotion { delta },
+            ..
+        } = event
+        {
+            // device events are also received while the window is not focused
+            if self.focused && self.cursor_grab == CursorGrab::Relative {
+                self.mouse_delta.0 += delta.0 as f32;
+                // the y axis of raw mouse motion points downwards
+                self.mouse_delta.1 -= delta.1 as f32;
+            }
+        } else if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::CursorMoved { position, .. } => {
+                    let position = position.to_logical::<f64>(f64::from(self.backend.dpi_factor()));
+                    if let Some((x, y)) = self.logical_cursor_position {
+                        if self.cursor_grab != CursorGrab::Relative {
+                            self.mouse_delta.0 += (position.x - x) as f32;
+                            self.mouse_delta.1 -= (position.y - y) as f32;
+                        }
+                    }
+                    self.logical_cursor_position = Some((position.x, position.y));
+                    // the origin of the window is in the top left corner
+                    let height = self.window_height() as i32;
+                    self.cursor_position = Some((
+                        position.x.floor() as i32,
+                        height - 1 - position.y.floor() as i32,
+                    ));
+                }
+                WindowEvent::CursorLeft { .. } => {
+                    self.cursor_position = None;
+                    self.logical_cursor_position = None;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Draws the cursor on top of the current frame.
+    pub(crate) fn draw_cursor(&mut self) {
+        if let (Some((texture, hotspot)), Some(position)) = (&self.cursor, self.cursor_position) {
+            let dim = self.backend.window_dimensions();
+            let dpi = self.backend.dpi_factor();
+            let position = (position.0 - hotspot.0, position.1 - hotspot.1);
+            self.backend.without_mask(|backend| {
+                backend.draw(
+                    0,
+                    dim,
+                    dpi,
+                    &texture.inner,
+                    texture.position,
+                    texture.size,
+                    position,
+                    &DrawConfig::default(),
+                )
+            });
+        }
+    }
+}
@@ -1,11 +1,16 @@
 use std::{
     error,
     fmt::{self, Display, Formatter},
+    io,
 };
 
 /// The super type of every error in this crate.
 /// If this is used as a return type, the question mark operator can always be used.
+///
+/// New variants may be added in minor releases, some variants
+/// also depend on the enabled features.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Tried to create a texture with dimensions which are
     /// greater than the maximum allowed texture size or zero.
@@ -22,6 +27,32 @@ pub enum Error {
     /// Error created by `glutin::ContextWrapper::make_current`
     /// or `glutin::ContextWrapper::swap_buffers`.
     ContextError(glutin::ContextError),
+    /// Called `Context::frame` while the window surface is still in use.
+    SurfaceInUse,
+    /// Error returned by `Context::try_draw`.
+    DrawError(DrawError),
+    /// Error returned by `BitmapFont::load` which is unrelated to its textures.
+    LoadFontError(LoadFontError),
+    /// Error returned by `aseprite::Sprite::load`.
+    #[cfg(feature = "aseprite")]
+    LoadAsepriteError(LoadAsepriteError),
+    /// Error returned by `ldtk::Project::load`.
+    #[cfg(feature = "ldtk")]
+    LoadProjectError(LoadProjectError),
+    /// Error returned by `tiled::Map::load`.
+    #[cfg(feature = "tiled")]
+    LoadMapError(LoadMapError),
+    /// Error returned by `Shader::compile`.
+    ShaderError(ShaderError),
+    /// Error returned by `Context::apply_profile`.
+    VsyncError(VsyncError),
+    /// Error returned by `texture_packer::SpriteSheet::load` which is unrelated to its texture.
+    SpriteSheetError(SpriteSheetError),
+    /// OpenGL ran out of memory.
+    ///
+    /// This is only returned if the feature `strict-errors` is enabled,
+    /// otherwise running out of memory causes a panic.
+    OutOfMemory,
 }
 
 impl Display for Error {
@@ -35,19 +66,38 @@ impl Display for Error {
             Self::ImageError(err) => write!(f, "{}", err),
             Self::CreationError(err) => write!(f, "{}", err),
             Self::ContextError(err) => write!(f, "{}", err),
+            Self::SurfaceInUse => write!(f, "the window surface is already in use"),
+            Self::DrawError(err) => write!(f, "{}", err),
+            Self::LoadFontError(err) => write!(f, "{}", err),
+            #[cfg(feature = "aseprite")]
+            Self::LoadAsepriteError(err) => write!(f, "{}", err),
+            #[cfg(feature = "ldtk")]
+            Self::LoadProjectError(err) => write!(f, "{}", err),
+            #[cfg(feature = "tiled")]
+            Self::LoadMapError(err) => write!(f, "{}", err),
+            Self::ShaderError(err) => write!(f, "{}", err),
+            Self::VsyncError(err) => write!(f, "{}", err),
+            Self::SpriteSheetError(err) => write!(f, "{}", err),
+            Self::OutOfMemory => write!(f, "OpenGL is out of memory"),
         }
     }
 }
 
 impl error::Error for Error {}
 
-#[derive(Debug)]
 /// The error returned by `Context::new`.
+#[derive(Debug)]
+#[non_exhaustive]
 pub enum NewContextError {
     /// Error created by `glutin::ContextBuilder::build_windowed`.
     CreationError(glutin::CreationError),
     /// Error created by `glutin::ContextWrapper::make_current`.
     ContextError(glutin::ContextError),
+    /// OpenGL ran out of memory.
+    ///
+    /// This is only returned if the feature `strict-errors` is enabled,
+    /// otherwise running out of memory causes a panic.
+    OutOfMemory,
 }
 
 impl Display for NewContextError {
@@ -55,6 +105,7 @@ impl Display for NewContextError {
         match self {
             Self::CreationError(err) => write!(f, "{}", err),
             Self::ContextError(err) => write!(f, "{}", err),
+            Self::OutOfMemory => write!(f, "OpenGL is out of memory"),
         }
     }
 }
@@ -66,21 +117,26 @@ impl From<NewContextError> for Error {
         match e {
             NewContextError::CreationError(e) => Error::CreationError(e),
             NewContextError::ContextError(e) => Error::ContextError(e),
+            NewContextError::OutOfMemory => Error::OutOfMemory,
         }
     }
 }
 
-/// The error returned by `Context::finalize_frame`.
+/// The error returned by `Context::present` and `Context::frame`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum FinalizeError {
     /// Error created by `glutin::ContextWrapper::swap_buffers`.
     ContextError(glutin::ContextError),
+    /// Called `Context::frame` while the window surface is still in use.
+    SurfaceInUse,
 }
 
 impl Display for FinalizeError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::ContextError(err) => write!(f, "{}", err),
+            Self::SurfaceInUse => write!(f, "the window surface is already in use"),
         }
     }
 }
@@ -91,12 +147,14 @@ impl From<FinalizeError> for Error {
     fn from(e: FinalizeError) -> Self {
         match e {
             FinalizeError::ContextError(e) => Error::ContextError(e),
+            FinalizeError::SurfaceInUse => Error::SurfaceInUse,
         }
     }
 }
 
 /// The error returned by `Texture::load`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum LoadTextureError {
     /// Tried to create a texture with dimensions which are
     /// greater than the maximum allowed texture size or zero.
@@ -108,6 +166,11 @@ pub enum LoadTextureError {
     },
     /// Error created by `image::load`.
     ImageError(image::ImageError),
+    /// OpenGL ran out of memory.
+    ///
+    /// This is only returned if the feature `strict-errors` is enabled,
+    /// otherwise running out of memory causes a panic.
+    OutOfMemory,
 }
 
 impl Display for LoadTextureError {
@@ -119,6 +182,7 @@ impl Display for LoadTextureError {
                 width, height
             ),
             Self::ImageError(err) => write!(f, "{}", err),
+            Self::OutOfMemory => write!(f, "OpenGL is out of memory"),
         }
     }
 }
@@ -132,12 +196,14 @@ impl From<LoadTextureError> for Error {
                 Error::InvalidTextureSize { width, height }
             }
             LoadTextureError::ImageError(e) => Error::ImageError(e),
+            LoadTextureError::OutOfMemory => Error::OutOfMemory,
         }
     }
 }
 
 /// The error returned by `Texture::new`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum NewTextureError {
     /// Tried to create a texture with dimensions which are
     /// greater than the maximum allowed texture size or zero.
@@ -147,16 +213,22 @@ pub enum NewTextureError {
         /// The requested height.
         height: u32,
     },
+    /// OpenGL ran out of memory.
+    ///
+    /// This is only returned if the feature `strict-errors` is enabled,
+    /// otherwise running out of memory causes a panic.
+    OutOfMemory,
 }
 
 impl Display for NewTextureError {
-    fn fmt<'a>(&'a self, f: &mut Formatter<'_>) -> fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::InvalidTextureSize { width, height } => write!(
                 f,
                 "failed to create a texture of the given size: {}x{}",
                 width, height
             ),
+            Self::OutOfMemory => write!(f, "OpenGL is out of memory"),
         }
     }
 }
@@ -169,6 +241,7 @@ impl From<NewTextureError> for LoadTextureError {
             NewTextureError::InvalidTextureSize { width, height } => {
                 LoadTextureError::InvalidTextureSize { width, height }
             }
+            NewTextureError::OutOfMemory => LoadTextureError::OutOfMemory,
         }
     }
 }
@@ -179,6 +252,410 @@ impl From<NewTextureError> for Error {
             NewTextureError::InvalidTextureSize { width, height } => {
                 Error::InvalidTextureSize { width, height }
             }
+            NewTextureError::OutOfMemory => Error::OutOfMemory,
+        }
+    }
+}
+
+/// The error returned by `Context::try_draw`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum DrawError {
+    /// Tried to draw a texture with a width or height of zero.
+    ZeroSizedTexture,
+    /// Tried to draw to a target with a width or height of zero,
+    /// for example a minimized window.
+    TargetTooSmall {
+        /// The width of the target.
+        width: u32,
+        /// The height of the target.
+        height: u32,
+    },
+    /// `DrawConfig::depth` is not in the range `0.0..1.0`.
+    DepthOutOfRange(f32),
+    /// The `DrawConfig` can not result in a meaningful draw call.
+    InvalidConfig(&'static str),
+    /// OpenGL ran out of memory.
+    ///
+    /// This is only returned if the feature `strict-errors` is enabled,
+    /// otherwise running out of memory causes a panic.
+    OutOfMemory,
+    /// OpenGL was unable to create a complete framebuffer for the target.
+    ///
+    /// This is only returned if the feature `strict-errors` is enabled,
+    /// otherwise this causes a panic.
+    IncompleteFramebuffer,
+    /// A texture created by a different `Context` was used.
+    ///
+    /// Outside of `Context::try_draw` this causes a panic.
+    ForeignTexture,
+}
+
+impl DrawError {
+    /// Converts an error which occurred while creating an internal texture.
+    pub(crate) fn from_new(e: NewTextureError) -> Self {
+        match e {
+            NewTextureError::OutOfMemory => DrawError::OutOfMemory,
+            NewTextureError::InvalidTextureSize { width, height } => {
+                bug!("unexpected invalid texture size: {}x{}", width, height)
+            }
+        }
+    }
+}
+
+impl Display for DrawError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroSizedTexture => write!(f, "tried to draw a texture with a size of zero"),
+            Self::TargetTooSmall { width, height } => write!(
+                f,
+                "tried to draw to a target of size {}x{}, which does not contain any pixels",
+                width, height
+            ),
+            Self::DepthOutOfRange(depth) => write!(
+                f,
+                "`DrawConfig::depth` must be in the range `0.0..1.0`, found {}",
+                depth
+            ),
+            Self::InvalidConfig(reason) => write!(f, "invalid `DrawConfig`: {}", reason),
+            Self::OutOfMemory => write!(f, "OpenGL is out of memory"),
+            Self::IncompleteFramebuffer => write!(f, "failed to create a complete framebuffer"),
+            Self::ForeignTexture => write!(
+                f,
+                "texture from a different context, textures can only be used with the `Context` which created them"
+            ),
         }
     }
 }
+
+impl error::Error for DrawError {}
+
+impl From<DrawError> for Error {
+    fn from(e: DrawError) -> Self {
+        Error::DrawError(e)
+    }
+}
+
+/// The error returned by `BitmapFont::load`, `BitmapFont::new` and `Font::load`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoadFontError {
+    /// Failed to read the font description.
+    IoError(io::Error),
+    /// The font description is not a valid BMFont text file.
+    InvalidFont {
+        /// The line of the font description containing the error, starting at `1`.
+        ///
+        /// This is `0` if the error is not caused by a single line.
+        line: usize,
+        /// Why this line is invalid.
+        reason: &'static str,
+    },
+    /// Failed to load a page of the font.
+    LoadTextureError(LoadTextureError),
+    /// The data is not a valid TrueType or OpenType font.
+    #[cfg(feature = "ttf")]
+    TrueTypeError,
+}
+
+impl Display for LoadFontError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IoError(err) => write!(f, "{}", err),
+            Self::InvalidFont { line, reason } => {
+                write!(f, "invalid font description in line {}: {}", line, reason)
+            }
+            Self::LoadTextureError(err) => write!(f, "{}", err),
+            #[cfg(feature = "ttf")]
+            Self::TrueTypeError => write!(f, "invalid TrueType font"),
+        }
+    }
+}
+
+impl error::Error for LoadFontError {}
+
+impl From<LoadTextureError> for LoadFontError {
+    fn from(e: LoadTextureError) -> Self {
+        LoadFontError::LoadTextureError(e)
+    }
+}
+
+impl From<LoadFontError> for Error {
+    fn from(e: LoadFontError) -> Self {
+        match e {
+            LoadFontError::LoadTextureError(e) => Error::from(e),
+            e => Error::LoadFontError(e),
+        }
+    }
+}
+
+/// The error returned by `Shader::compile`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderError {
+    /// The fragment shader did not compile, contains the info log of the shader.
+    Compile(String),
+    /// The program could not be linked, contains the info log of the program.
+    Link(String),
+}
+
+impl Display for ShaderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compile(log) => write!(f, "failed to compile shader: {}", log),
+            Self::Link(log) => write!(f, "failed to link shader: {}", log),
+        }
+    }
+}
+
+impl error::Error for ShaderError {}
+
+impl From<ShaderError> for Error {
+    fn from(e: ShaderError) -> Self {
+        Error::ShaderError(e)
+    }
+}
+
+/// The error returned by `Context::apply_profile` if `RenderProfile::vsync` could not be changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VsyncError {
+    /// Changing the swap interval is not supported by the platform or by headless contexts.
+    Unsupported,
+    /// The platform rejected the new swap interval.
+    Rejected,
+}
+
+impl Display for VsyncError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "changing vsync is not supported on this platform"),
+            Self::Rejected => write!(f, "the platform rejected the new swap interval"),
+        }
+    }
+}
+
+impl error::Error for VsyncError {}
+
+impl From<VsyncError> for Error {
+    fn from(e: VsyncError) -> Self {
+        Error::VsyncError(e)
+    }
+}
+
+/// The error returned by the methods of `texture_packer::SpriteSheet`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SpriteSheetError {
+    /// Failed to read the description of the sheet.
+    #[cfg(feature = "serde1")]
+    IoError(io::Error),
+    /// The description of the sheet is not valid JSON or does not match the format of TexturePacker.
+    #[cfg(feature = "serde1")]
+    JsonError(serde_json::Error),
+    /// The image of the sheet could not be loaded or a sprite could not be created.
+    LoadTextureError(LoadTextureError),
+    /// The sheet uses data which is not valid, for example a frame outside of its image.
+    InvalidSheet {
+        /// Why the sheet is invalid.
+        reason: String,
+    },
+}
+
+impl Display for SpriteSheetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "serde1")]
+            Self::IoError(err) => write!(f, "{}", err),
+            #[cfg(feature = "serde1")]
+            Self::JsonError(err) => write!(f, "{}", err),
+            Self::LoadTextureError(err) => write!(f, "{}", err),
+            Self::InvalidSheet { reason } => write!(f, "invalid sprite sheet: {}", reason),
+        }
+    }
+}
+
+impl error::Error for SpriteSheetError {}
+
+#[cfg(feature = "serde1")]
+impl From<io::Error> for SpriteSheetError {
+    fn from(e: io::Error) -> Self {
+        SpriteSheetError::IoError(e)
+    }
+}
+
+#[cfg(feature = "serde1")]
+impl From<serde_json::Error> for SpriteSheetError {
+    fn from(e: serde_json::Error) -> Self {
+        SpriteSheetError::JsonError(e)
+    }
+}
+
+impl From<LoadTextureError> for SpriteSheetError {
+    fn from(e: LoadTextureError) -> Self {
+        SpriteSheetError::LoadTextureError(e)
+    }
+}
+
+impl From<NewTextureError> for SpriteSheetError {
+    fn from(e: NewTextureError) -> Self {
+        SpriteSheetError::LoadTextureError(e.into())
+    }
+}
+
+impl From<SpriteSheetError> for Error {
+    fn from(e: SpriteSheetError) -> Self {
+        match e {
+            SpriteSheetError::LoadTextureError(e) => e.into(),
+            e => Error::SpriteSheetError(e),
+        }
+    }
+}
+
+/// The error returned by `aseprite::Sprite::load` and `aseprite::Sprite::parse`.
+#[cfg(feature = "aseprite")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoadAsepriteError {
+    /// Failed to read the file.
+    IoError(io::Error),
+    /// The file is not a valid or supported Aseprite file.
+    InvalidFile {
+        /// Why the file is invalid.
+        reason: String,
+    },
+}
+
+#[cfg(feature = "aseprite")]
+impl Display for LoadAsepriteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IoError(err) => write!(f, "{}", err),
+            Self::InvalidFile { reason } => write!(f, "invalid Aseprite file: {}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "aseprite")]
+impl error::Error for LoadAsepriteError {}
+
+#[cfg(feature = "aseprite")]
+impl From<io::Error> for LoadAsepriteError {
+    fn from(e: io::Error) -> Self {
+        LoadAsepriteError::IoError(e)
+    }
+}
+
+#[cfg(feature = "aseprite")]
+impl From<LoadAsepriteError> for Error {
+    fn from(e: LoadAsepriteError) -> Self {
+        Error::LoadAsepriteError(e)
+    }
+}
+
+/// The error returned by `ldtk::Project::load` and `ldtk::Level::build_layer`.
+#[cfg(feature = "ldtk")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoadProjectError {
+    /// Failed to read the project or one of its external levels.
+    IoError(io::Error),
+    /// The project or one of its external levels is not valid JSON
+    /// or does not match the format of LDtk.
+    JsonError(serde_json::Error),
+    /// The project uses data which is not valid or not supported.
+    InvalidProject {
+        /// Why the project is invalid.
+        reason: String,
+    },
+}
+
+#[cfg(feature = "ldtk")]
+impl Display for LoadProjectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IoError(err) => write!(f, "{}", err),
+            Self::JsonError(err) => write!(f, "{}", err),
+            Self::InvalidProject { reason } => write!(f, "invalid project: {}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "ldtk")]
+impl error::Error for LoadProjectError {}
+
+#[cfg(feature = "ldtk")]
+impl From<io::Error> for LoadProjectError {
+    fn from(e: io::Error) -> Self {
+        LoadProjectError::IoError(e)
+    }
+}
+
+#[cfg(feature = "ldtk")]
+impl From<serde_json::Error> for LoadProjectError {
+    fn from(e: serde_json::Error) -> Self {
+        LoadProjectError::JsonError(e)
+    }
+}
+
+#[cfg(feature = "ldtk")]
+impl From<LoadProjectError> for Error {
+    fn from(e: LoadProjectError) -> Self {
+        Error::LoadProjectError(e)
+    }
+}
+
+/// The error returned by `tiled::Map::load` and `tiled::Map::parse`.
+#[cfg(feature = "tiled")]
+#[derive(Debug)]
+pub enum LoadMapError {
+    /// Failed to read the map or one of its tilesets.
+    IoError(io::Error),
+    /// The map or one of its tilesets is not a valid XML document.
+    XmlError(xml::reader::Error),
+    /// The map is not a valid or supported Tiled map.
+    InvalidMap {
+        /// The line of the file containing the error, starting at `1`.
+        ///
+        /// This is `0` if the error is not caused by a single line.
+        line: usize,
+        /// Why the map is invalid.
+        reason: String,
+    },
+}
+
+#[cfg(feature = "tiled")]
+impl Display for LoadMapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IoError(err) => write!(f, "{}", err),
+            Self::XmlError(err) => write!(f, "{}", err),
+            Self::InvalidMap { line, reason } => {
+                write!(f, "invalid map in line {}: {}", line, reason)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tiled")]
+impl error::Error for LoadMapError {}
+
+#[cfg(feature = "tiled")]
+impl From<io::Error> for LoadMapError {
+    fn from(e: io::Error) -> Self {
+        LoadMapError::IoError(e)
+    }
+}
+
+#[cfg(feature = "tiled")]
+impl From<xml::reader::Error> for LoadMapError {
+    fn from(e: xml::reader::Error) -> Self {
+        LoadMapError::XmlError(e)
+    }
+}
+
+#[cfg(feature = "tiled")]
+impl From<LoadMapError> for Error {
+    fn from(e: LoadMapError) -> Self {
+        Error::LoadMapError(e)
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::{Context, DrawTarget};
+
+/// A rolling history of frame times which can be drawn as a bar graph,
+/// for example as part of a custom debug HUD.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, Flow, FrameTimeGraph,
+/// };
+///
+/// let event_loop = EventLoop::new();
+/// let ctx = Context::new(WindowBuilder::new(), &event_loop)?;
+///
+/// let mut graph = FrameTimeGraph::new(120);
+/// ctx.run(event_loop, move |ctx, surface, _events| {
+///     graph.tick();
+///     ctx.clear_color(surface, (0.0, 0.0, 0.0, 1.0));
+///     graph.draw(ctx, surface, (10, 10), 40, Duration::from_millis(33));
+///     Flow::<()>::Continue
+/// })?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrameTimeGraph {
+    times: VecDeque<Duration>,
+    capacity: usize,
+    last_tick: Option<Instant>,
+}
+
+impl FrameTimeGraph {
+    /// Creates a new graph storing the last `capacity` frame times.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity != 0,
+            "`FrameTimeGraph` requires a capacity of at least 1"
+        );
+        FrameTimeGraph {
+            times: VecDeque::with_capacity(capacity),
+            capacity,
+            last_tick: None,
+        }
+    }
+
+    /// Records the time since the previous call to `tick`.
+    ///
+    /// This should be called exactly once per frame.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        if let Some(last_tick) = self.last_tick.replace(now) {
+            self.record(now - last_tick);
+        }
+    }
+
+    /// Manually records a frame time, removing the oldest one
+    /// in case the history is full.
+    pub fn record(&mut self, frame_time: Duration) {
+        if self.times.len() == self.capacity {
+            self.times.pop_front();
+        }
+        self.times.push_back(frame_time);
+    }
+
+    /// Returns all recorded frame times, starting with the oldest one.
+    pub fn frame_times(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.times.iter().copied()
+    }
+
+    /// Returns the average of all recorded frame times.
+    pub fn average(&self) -> Option<Duration> {
+        if self.times.is_empty() {
+            None
+        } else {
+            Some(self.times.iter().sum::<Duration>() / self.times.len() as u32)
+        }
+    }
+
+    /// Returns the longest recorded frame time.
+    pub fn max(&self) -> Option<Duration> {
+        self.times.iter().max().copied()
+    }
+
+    /// Draws the graph onto `target` using one vertical line per frame,
+    /// with the oldest frame at `position`.
+    ///
+    /// The graph is `capacity` pixels wide and `height` pixels high, where `height`
+    /// corresponds to a frame time of `budget`. Frames exceeding `budget` are drawn in red
+    /// and are cut off at the top of the graph.
+    ///
+    /// To reuse the graph, it can be drawn onto a `Texture` of the same size.
+    pub fn draw<T>(
+        &self,
+        ctx: &mut Context,
+        target: &mut T,
+        position: (i32, i32),
+        height: u32,
+        budget: Duration,
+    ) where
+        T: DrawTarget,
+    {
+        if height == 0 {
+            return;
+        }
+
+        let budget = budget.as_secs_f64();
+        for (x, time) in self.times.iter().enumerate() {
+            let fraction = time.as_secs_f64() / budget;
+            let color = if fraction > 1.0 {
+                (1.0, 0.0, 0.0, 1.0)
+            } else {
+                (0.0, 1.0, 0.0, 1.0)
+            };
+            let bar_height = (fraction.min(1.0) * f64::from(height)).ceil() as i32;
+            if bar_height > 0 {
+                let x = position.0 + x as i32;
+                ctx.debug_line(
+                    target,
+                    (x, position.1),
+                    (x, position.1 + bar_height - 1),
+                    color,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use crate::DrawConfig;
+
+/// Identifies a `DrawConfig` stored in a [`ConfigInterner`].
+///
+/// Ids are only meaningful for the interner which created them.
+///
+/// [`ConfigInterner`]: struct.ConfigInterner.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConfigId(u32);
+
+impl ConfigId {
+    /// Returns the index of the config, ids are assigned in ascending order starting at `0`.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Stores each distinct `DrawConfig` once, returning a small `Copy` id for it.
+///
+/// Comparing, hashing or sorting ids is a lot cheaper than doing the same
+/// with the configs themselves, which is useful to group draw calls using
+/// the same config, for example when building a `SpriteBatch`.
+///
+/// ```rust
+/// use crow::{ConfigInterner, DrawConfig};
+///
+/// let mut configs = ConfigInterner::new();
+/// let a = configs.intern(&DrawConfig::default());
+/// let b = configs.intern(&DrawConfig {
+///     scale: (2, 2),
+///     ..Default::default()
+/// });
+/// let c = configs.intern(&DrawConfig::default());
+///
+/// assert_ne!(a, b);
+/// assert_eq!(a, c);
+/// assert_eq!(configs.len(), 2);
+/// assert_eq!(configs.get(b).scale, (2, 2));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConfigInterner {
+    configs: Vec<DrawConfig>,
+    ids: HashMap<DrawConfig, ConfigId>,
+}
+
+impl ConfigInterner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of `config`, storing it if it has not been interned before.
+    pub fn intern(&mut self, config: &DrawConfig) -> ConfigId {
+        if let Some(&id) = self.ids.get(config) {
+            return id;
+        }
+
+        let id = ConfigId(self.configs.len() as u32);
+        self.configs.push(config.clone());
+        self.ids.insert(config.clone(), id);
+        id
+    }
+
+    /// Returns the config with the given `id`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `id` was not returned by this interner.
+    pub fn get(&self, id: ConfigId) -> &DrawConfig {
+        &self.configs[id.index()]
+    }
+
+    /// Returns the number of distinct configs.
+    pub fn len(&self) -> usize {
+        self.configs.len()
+    }
+
+    /// Returns `true` if no config has been interned.
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+
+    /// Removes all configs, invalidating all previously returned ids.
+    pub fn clear(&mut self) {
+        self.configs.clear();
+        self.ids.clear();
+    }
+}
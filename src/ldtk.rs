@@ -0,0 +1,394 @@
+//! Levels created using the [LDtk] level editor.
+//!
+//! A [`Project`] is loaded from an `.ldtk` file using `Project::load`. Only the data
+//! needed to draw the levels and to place entities is loaded, field instances of
+//! entities are ignored. Levels stored in separate files are loaded together with the project.
+//!
+//! ```rust, no_run
+//! use crow::{
+//!     glutin::{event_loop::EventLoop, window::WindowBuilder},
+//!     ldtk::Project,
+//!     Context, Texture,
+//! };
+//!
+//! let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+//!
+//! let project = Project::load("./levels/world.ldtk")?;
+//! let level = project.level("Level_0").unwrap();
+//! let mut surface = ctx.surface();
+//! // the first layer is the top one
+//! for layer in level.layers().iter().rev() {
+//!     if let Some(uid) = layer.tileset_def_uid {
+//!         let path = project.tileset(uid).unwrap().rel_path.as_ref().unwrap();
+//!         let texture = Texture::load(&mut ctx, format!("./levels/{}", path))?;
+//!         level.draw_layer(&mut ctx, &mut surface, layer, &texture)?;
+//!     }
+//!
+//!     for entity in layer.entity_instances.iter() {
+//!         println!("{} at {:?}", entity.identifier, entity.position);
+//!     }
+//! }
+//! ctx.present(surface)?;
+//! # Ok::<(), crow::Error>(())
+//! ```
+//!
+//! [LDtk]: https://ldtk.io
+//! [`Project`]: struct.Project.html
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Context, DrawConfig, DrawTarget, LoadProjectError, Rect, SpriteBatch, Subsystem, Texture,
+};
+
+/// An LDtk project, containing all levels and definitions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Project {
+    /// The definitions used by the levels of this project.
+    pub defs: Definitions,
+    /// All levels of this project.
+    pub levels: Vec<Level>,
+}
+
+impl Project {
+    /// Loads a project from the `.ldtk` file at `path`, including all external levels.
+    ///
+    /// External levels are loaded relative to the directory containing `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Project, LoadProjectError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        Project::parse(&source, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Parses the content of an `.ldtk` file.
+    ///
+    /// External levels are loaded relative to `directory`.
+    pub fn parse<P: AsRef<Path>>(source: &str, directory: P) -> Result<Project, LoadProjectError> {
+        let directory = directory.as_ref();
+        let mut project: Project = serde_json::from_str(source)?;
+        for level in project.levels.iter_mut() {
+            if let (None, Some(path)) = (&level.layer_instances, &level.external_rel_path) {
+                let external: Level =
+                    serde_json::from_str(&fs::read_to_string(directory.join(path))?)?;
+                level.layer_instances = external.layer_instances;
+            }
+        }
+        Ok(project)
+    }
+
+    /// Returns the level with the given `identifier`.
+    pub fn level(&self, identifier: &str) -> Option<&Level> {
+        self.levels.iter().find(|l| l.identifier == identifier)
+    }
+
+    /// Returns the tileset with the given `uid`.
+    pub fn tileset(&self, uid: i64) -> Option<&TilesetDefinition> {
+        self.defs.tilesets.iter().find(|t| t.uid == uid)
+    }
+}
+
+/// The definitions of a [`Project`].
+///
+/// [`Project`]: struct.Project.html
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Definitions {
+    /// All tilesets of the project.
+    #[serde(default)]
+    pub tilesets: Vec<TilesetDefinition>,
+}
+
+/// An image containing tiles.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TilesetDefinition {
+    /// The unique id of this tileset.
+    pub uid: i64,
+    /// The name of this tileset.
+    pub identifier: String,
+    /// The path of the image relative to the project file,
+    /// `None` for embedded tilesets like the internal icons of LDtk.
+    #[serde(default)]
+    pub rel_path: Option<String>,
+    /// The width of the image.
+    pub px_wid: u32,
+    /// The height of the image.
+    pub px_hei: u32,
+    /// The width and height of a single tile.
+    pub tile_grid_size: u32,
+    /// The space between adjacent tiles.
+    #[serde(default)]
+    pub spacing: u32,
+    /// The space between the tiles and the edge of the image.
+    #[serde(default)]
+    pub padding: u32,
+}
+
+/// A single level of a [`Project`].
+///
+/// [`Project`]: struct.Project.html
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Level {
+    /// The unique id of this level.
+    pub uid: i64,
+    /// The name of this level.
+    pub identifier: String,
+    /// The x coordinate of this level in the world.
+    #[serde(default)]
+    pub world_x: i32,
+    /// The y coordinate of this level in the world, with the y axis pointing downwards.
+    #[serde(default)]
+    pub world_y: i32,
+    /// The width of this level in pixels.
+    pub px_wid: u32,
+    /// The height of this level in pixels.
+    pub px_hei: u32,
+    /// All layers of this level, starting with the top one.
+    ///
+    /// This is `None` if the level is stored in a separate file
+    /// and was not loaded using `Project::load`.
+    #[serde(default)]
+    pub layer_instances: Option<Vec<LayerInstance>>,
+    /// The path of the file containing this level relative to the project
+    /// file if the project uses the option `Save levels to separate files`.
+    #[serde(default)]
+    pub external_rel_path: Option<String>,
+}
+
+impl Level {
+    /// Returns all layers of this level, starting with the top one.
+    ///
+    /// This is empty if the level is stored in a separate file
+    /// and was not loaded using `Project::load`.
+    pub fn layers(&self) -> &[LayerInstance] {
+        self.layer_instances.as_deref().unwrap_or(&[])
+    }
+
+    /// Converts a y coordinate of this level, for example the position of an entity,
+    /// to the coordinate system of crow, where the y axis points upwards.
+    pub fn flip_y(&self, y: i32) -> i32 {
+        self.px_hei as i32 - y
+    }
+
+    /// Creates a [`SpriteBatch`] containing the tiles of `layer`, including auto-layer tiles,
+    /// using the image of the tileset of `layer` as `texture`.
+    ///
+    /// The bottom left corner of this level is drawn at `(0, 0)`. The tiles use the
+    /// opacity of `layer` and the batch is empty if `layer` is not visible.
+    ///
+    /// Returns an error if a tile is outside of `texture`.
+    ///
+    /// [`SpriteBatch`]: ../struct.SpriteBatch.html
+    pub fn build_layer(
+        &self,
+        layer: &LayerInstance,
+        texture: &Texture,
+    ) -> Result<SpriteBatch, LoadProjectError> {
+        let mut batch = SpriteBatch::new(texture);
+        if !layer.visible {
+            return Ok(batch);
+        }
+
+        let size = layer.grid_size;
+        let color_modulation = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, layer.opacity],
+        ];
+        for tile in layer.grid_tiles.iter().chain(layer.auto_layer_tiles.iter()) {
+            let [x, top] = tile.source;
+            if u64::from(x) + u64::from(size) > u64::from(texture.width())
+                || u64::from(top) + u64::from(size) > u64::from(texture.height())
+            {
+                return Err(LoadProjectError::InvalidProject {
+                    reason: format!(
+                        "the tile {} of layer `{}` is outside of its tileset",
+                        tile.id, layer.identifier
+                    ),
+                });
+            }
+
+            let section = Rect::new(tile.source, (size, size));
+            let texture = texture.get_section(section.flip_y(texture.height()));
+            let x = tile.position[0] + layer.total_offset_x;
+            let y = tile.position[1] + layer.total_offset_y + size as i32;
+            let config = DrawConfig {
+                flip_horizontally: tile.flip & TileInstance::FLIP_X != 0,
+                flip_vertically: tile.flip & TileInstance::FLIP_Y != 0,
+                color_modulation,
+                ..Default::default()
+            };
+            batch.push(&texture, (x, self.flip_y(y)), &config);
+        }
+        Ok(batch)
+    }
+
+    /// Draws the tiles of `layer` onto `target` using the batch of `Level::build_layer`.
+    pub fn draw_layer<T>(
+        &self,
+        ctx: &mut Context,
+        target: &mut T,
+        layer: &LayerInstance,
+        texture: &Texture,
+    ) -> Result<(), LoadProjectError>
+    where
+        T: DrawTarget,
+    {
+        let batch = self.build_layer(layer, texture)?;
+        ctx.in_subsystem(Subsystem::Tilemap, |ctx| target.receive_batch(ctx, &batch));
+        Ok(())
+    }
+}
+
+/// The kind of a [`LayerInstance`].
+///
+/// [`LayerInstance`]: struct.LayerInstance.html
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LayerType {
+    /// A grid of integer values, which may also contain auto-layer tiles.
+    IntGrid,
+    /// A layer containing entities.
+    Entities,
+    /// A layer containing manually placed tiles.
+    #[default]
+    Tiles,
+    /// A layer containing tiles placed using rules.
+    AutoLayer,
+}
+
+/// A layer of a [`Level`].
+///
+/// [`Level`]: struct.Level.html
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerInstance {
+    /// The name of this layer.
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+    /// The kind of this layer.
+    #[serde(rename = "__type")]
+    pub layer_type: LayerType,
+    /// The width of this layer in cells.
+    #[serde(rename = "__cWid")]
+    pub grid_width: u32,
+    /// The height of this layer in cells.
+    #[serde(rename = "__cHei")]
+    pub grid_height: u32,
+    /// The width and height of a single cell in pixels.
+    #[serde(rename = "__gridSize")]
+    pub grid_size: u32,
+    /// The opacity of this layer in the range `0.0..=1.0`.
+    #[serde(rename = "__opacity")]
+    pub opacity: f32,
+    /// The horizontal offset of this layer in pixels.
+    #[serde(rename = "__pxTotalOffsetX")]
+    pub total_offset_x: i32,
+    /// The vertical offset of this layer in pixels, with the y axis pointing downwards.
+    #[serde(rename = "__pxTotalOffsetY")]
+    pub total_offset_y: i32,
+    /// The uid of the tileset used by this layer.
+    #[serde(rename = "__tilesetDefUid")]
+    pub tileset_def_uid: Option<i64>,
+    /// If this layer is visible in LDtk.
+    pub visible: bool,
+    /// The value of each cell of an `IntGrid` layer, starting with the top left one
+    /// and going from left to right and from top to bottom. Empty cells are `0`.
+    pub int_grid_csv: Vec<i32>,
+    /// The tiles of a `Tiles` layer.
+    pub grid_tiles: Vec<TileInstance>,
+    /// The tiles of an `AutoLayer` or `IntGrid` layer.
+    pub auto_layer_tiles: Vec<TileInstance>,
+    /// The entities of an `Entities` layer.
+    pub entity_instances: Vec<EntityInstance>,
+}
+
+impl Default for LayerInstance {
+    fn default() -> Self {
+        LayerInstance {
+            identifier: String::new(),
+            layer_type: LayerType::default(),
+            grid_width: 0,
+            grid_height: 0,
+            grid_size: 0,
+            opacity: 1.0,
+            total_offset_x: 0,
+            total_offset_y: 0,
+            tileset_def_uid: None,
+            visible: true,
+            int_grid_csv: Vec::new(),
+            grid_tiles: Vec::new(),
+            auto_layer_tiles: Vec::new(),
+            entity_instances: Vec::new(),
+        }
+    }
+}
+
+impl LayerInstance {
+    /// Returns the value of the cell `(x, y)` of an `IntGrid` layer,
+    /// where `(0, 0)` is the top left cell.
+    pub fn int_grid(&self, x: u32, y: u32) -> Option<i32> {
+        if x < self.grid_width && y < self.grid_height {
+            self.int_grid_csv
+                .get((y * self.grid_width + x) as usize)
+                .copied()
+        } else {
+            None
+        }
+    }
+}
+
+/// A tile of a [`LayerInstance`].
+///
+/// [`LayerInstance`]: struct.LayerInstance.html
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TileInstance {
+    /// The position of the top left corner of this tile in the layer.
+    #[serde(rename = "px")]
+    pub position: [i32; 2],
+    /// The position of the top left corner of this tile in the tileset.
+    #[serde(rename = "src")]
+    pub source: [u32; 2],
+    /// How this tile is flipped, see `TileInstance::FLIP_X` and `TileInstance::FLIP_Y`.
+    #[serde(rename = "f")]
+    pub flip: u8,
+    /// The id of this tile in the tileset.
+    #[serde(rename = "t")]
+    pub id: u32,
+}
+
+impl TileInstance {
+    /// The bit of `flip` which is set if the tile is flipped on the x axis.
+    pub const FLIP_X: u8 = 1;
+    /// The bit of `flip` which is set if the tile is flipped on the y axis.
+    pub const FLIP_Y: u8 = 2;
+}
+
+/// An entity of a [`LayerInstance`], for example a spawn point or an enemy.
+///
+/// [`LayerInstance`]: struct.LayerInstance.html
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct EntityInstance {
+    /// The name of the definition of this entity.
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+    /// The cell containing the pivot of this entity.
+    #[serde(rename = "__grid")]
+    pub grid: [i32; 2],
+    /// The pivot of this entity relative to its size, where `[0.0, 0.0]` is the top left corner.
+    #[serde(rename = "__pivot")]
+    pub pivot: [f32; 2],
+    /// The position of the pivot of this entity in the layer, with the y axis pointing downwards.
+    ///
+    /// `Level::flip_y` converts the y coordinate to the coordinate system of crow.
+    #[serde(rename = "px")]
+    pub position: [i32; 2],
+    /// The width of this entity in pixels.
+    pub width: u32,
+    /// The height of this entity in pixels.
+    pub height: u32,
+}
@@ -36,6 +36,26 @@
 //!     )
 //! }
 //! ```
+//!
+//! # Features
+//!
+//! - `serde1`: implements `Serialize` and `Deserialize` for the configuration types of this crate
+//!   and allows loading `texture_packer::SpriteSheet` from the JSON files of TexturePacker
+//!   using `SpriteSheet::load`.
+//! - `strict-errors`: returns errors instead of panicking if OpenGL runs out of memory
+//!   or fails to create a framebuffer, for applications which must not abort.
+//!   Errors which occur while drawing can be handled using `Context::try_draw`.
+//! - `leak-backtraces`: captures a backtrace when creating each texture in debug builds,
+//!   which is logged if the texture is still alive when dropping the `Context`.
+//! - `aseprite`: adds the module `aseprite`, which loads `.aseprite` files created using [Aseprite].
+//! - `ldtk`: adds the module `ldtk`, which loads projects created using the [LDtk] level editor.
+//! - `tiled`: adds the module `tiled`, which loads maps created using the [Tiled] map editor.
+//! - `ttf`: adds `text::Font`, which rasterizes TrueType fonts using [`ab_glyph`].
+//!
+//! [Aseprite]: https://www.aseprite.org
+//! [LDtk]: https://ldtk.io
+//! [Tiled]: https://www.mapeditor.org
+//! [`ab_glyph`]: https://docs.rs/ab_glyph
 // #![warn(missing_doc_code_examples)]
 #![warn(
     deprecated_in_future,
@@ -53,12 +73,37 @@
 #[macro_use]
 extern crate log;
 
-use std::{any, fmt, marker::PhantomData, rc::Rc};
+use std::{
+    any, fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    rc::Rc,
+    time::Instant,
+};
 
 use static_assertions::assert_not_impl_any;
 
-#[cfg(all(feature = "serde", not(feature = "serde1")))]
-compile_error!("Tried using the feature `serde` directly, consider enabling `serde1` instead");
+#[cfg(all(
+    any(feature = "serde", feature = "serde_json"),
+    not(feature = "serde1")
+))]
+compile_error!(
+    "Tried using the feature `serde` or `serde_json` directly, consider enabling `serde1` instead"
+);
+
+#[cfg(all(feature = "ab_glyph", not(feature = "ttf")))]
+compile_error!("Tried using the feature `ab_glyph` directly, consider enabling `ttf` instead");
+
+#[cfg(all(feature = "xml-rs", not(feature = "tiled")))]
+compile_error!("Tried using the feature `xml-rs` directly, consider enabling `tiled` instead");
+
+#[cfg(all(
+    feature = "miniz_oxide",
+    not(any(feature = "aseprite", feature = "tiled"))
+))]
+compile_error!(
+    "Tried using the feature `miniz_oxide` directly, consider enabling `aseprite` or `tiled` instead"
+);
 
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
@@ -72,35 +117,85 @@ macro_rules! bug {
     });
 }
 
+mod afterimage;
+mod animation;
+mod atlas;
+mod attention;
+mod automaton;
 mod backend;
+mod batch;
+mod capture;
+mod composite;
 mod context;
+mod counters;
+mod cursor;
 mod error;
+mod graph;
+mod intern;
+mod math;
+mod minimap;
+mod noise;
+mod offline;
+mod profile;
+mod rect;
+mod reflection;
+mod render_graph;
+mod rng;
+mod sampler;
+mod shader;
+mod shadow;
+mod sort;
 mod texture;
+mod video;
 
+#[cfg(feature = "aseprite")]
+pub mod aseprite;
 pub mod color;
+#[cfg(feature = "ldtk")]
+pub mod ldtk;
+pub mod particles;
+pub mod procgen;
 pub mod target;
-
+pub mod testing;
+pub mod text;
+pub mod texture_packer;
+#[cfg(feature = "tiled")]
+pub mod tiled;
+
+pub use afterimage::Afterimage;
+pub use animation::{Animation, AnimationFrame, AnimationState, LoopMode};
+pub use atlas::{Atlas, AtlasConfig};
+pub use attention::Attention;
+pub use automaton::{CellularAutomaton, Rule};
+pub use backend::{Extensions, Fence, GlConstants, QueryHandle};
+pub use batch::SpriteBatch;
+pub use capture::{CapturedDraw, CapturedKind, DrawCapture};
+pub use composite::{Composite, Layer};
+pub use counters::{Counters, Counts, Subsystem};
+pub use cursor::CursorGrab;
 pub use error::*;
 pub use glutin;
+pub use graph::FrameTimeGraph;
 pub use image;
-
+pub use intern::{ConfigId, ConfigInterner};
+pub use math::{IVec2, UVec2, Vec2};
+pub use minimap::Minimap;
+pub use profile::{RenderProfile, ScalingMode};
+pub use rect::{IRect, Rect};
+pub use reflection::Reflection;
+pub use render_graph::{PassInput, PassOutput, PassTiming, RenderGraph, TransientId};
+pub use sampler::{Filter, Sampler, TextureOptions, Wrap};
+pub use shader::{Shader, ShaderGlobals, UniformValue};
+pub use shadow::Shadow;
+pub use sort::{DrawList, SortKey};
+pub use texture::{CopyReason, TextureCopy};
+pub use video::VideoTexture;
+
+use glutin::window::Theme;
 use image::RgbaImage;
 
 use backend::{tex::RawTexture, Backend};
 
-trait UnwrapBug<T> {
-    fn unwrap_bug(self) -> T;
-}
-
-impl<T, E: fmt::Debug> UnwrapBug<T> for Result<T, E> {
-    fn unwrap_bug(self) -> T {
-        match self {
-            Ok(v) => v,
-            Err(e) => bug!("unexpected internal error: {:?}", e),
-        }
-    }
-}
-
 #[derive(Clone, Copy)]
 struct SkipDebug<T>(T);
 
@@ -110,6 +205,17 @@ impl<T> fmt::Debug for SkipDebug<T> {
     }
 }
 
+/// A callback registered on a `Context`.
+type Hook = SkipDebug<Box<dyn FnMut(&mut Context)>>;
+/// A callback registered on a `Context` which is called during each frame.
+type FrameHook = SkipDebug<Box<dyn FnMut(&mut Context, &mut WindowSurface)>>;
+/// A callback registered on a `Context` which is called once the system theme changes.
+type ThemeHook = SkipDebug<Box<dyn FnMut(&mut Context, &Theme)>>;
+/// A callback registered on a `Context` which is called if drawing to a texture requires a copy.
+type CopyHook = SkipDebug<Box<dyn FnMut(&TextureCopy)>>;
+/// A callback registered on a `Context` which updates the shader globals once per frame.
+type GlobalsHook = SkipDebug<Box<dyn FnMut(&mut ShaderGlobals)>>;
+
 /// A trait implemented by types upon which can be drawn.
 ///
 /// It is recommended to use the corresponding methods of `Context`
@@ -127,9 +233,21 @@ pub trait DrawTarget {
     /// Sets each pixel of `self` to `color`.
     fn receive_clear_color(&mut self, ctx: &mut Context, color: (f32, f32, f32, f32));
 
+    /// Sets each pixel inside of `rect` to `color`.
+    fn receive_clear_region(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32));
+
     /// Resets the depth buffer of `self` to `1.0`.
     fn receive_clear_depth(&mut self, ctx: &mut Context);
 
+    /// Draws each sprite of `batch` onto `self`.
+    ///
+    /// The default implementation calls `DrawTarget::receive_draw` for each sprite.
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        for (texture, position, config) in batch.sprites() {
+            self.receive_draw(ctx, &texture, position, config);
+        }
+    }
+
     /// Draws a line from `from` to `to`.
     fn receive_line(
         &mut self,
@@ -139,18 +257,26 @@ pub trait DrawTarget {
         color: (f32, f32, f32, f32),
     );
 
-    /// Draws the bounding box of an axis-aligned rectangle specified by
-    /// its `lower_left` and `upper_right` corner.
-    ///
-    /// In case `lower_left` is to the right or above `upper_right`, the two points will be flipped.
-    fn receive_rectangle(
+    /// Draws the outline of `rect`, using its outermost pixels.
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32));
+
+    /// Overwrites each pixel of `self` using `shader`.
+    fn receive_fullscreen(
         &mut self,
         ctx: &mut Context,
-        lower_left: (i32, i32),
-        upper_right: (i32, i32),
-        color: (f32, f32, f32, f32),
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
     );
 
+    /// Checks if any pixel of `rect` at the given `depth` would be drawn,
+    /// without modifying `self`.
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle;
+
     /// Returns the current state of the `DrawTarget`.
     ///
     /// For the window surface, this is a simple screenshot.
@@ -172,10 +298,23 @@ impl<T: DrawTarget> DrawTarget for &mut T {
         <T>::receive_clear_color(self, ctx, color)
     }
 
+    fn receive_clear_region(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        <T>::receive_clear_region(self, ctx, rect, color)
+    }
+
     fn receive_clear_depth(&mut self, ctx: &mut Context) {
         <T>::receive_clear_depth(self, ctx)
     }
 
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        <T>::receive_batch(self, ctx, batch)
+    }
+
     fn receive_line(
         &mut self,
         ctx: &mut Context,
@@ -186,14 +325,26 @@ impl<T: DrawTarget> DrawTarget for &mut T {
         <T>::receive_line(self, ctx, from, to, color)
     }
 
-    fn receive_rectangle(
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32)) {
+        <T>::receive_rectangle(self, ctx, rect, color)
+    }
+
+    fn receive_fullscreen(
         &mut self,
         ctx: &mut Context,
-        lower_left: (i32, i32),
-        upper_right: (i32, i32),
-        color: (f32, f32, f32, f32),
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
     ) {
-        <T>::receive_rectangle(self, ctx, lower_left, upper_right, color)
+        <T>::receive_fullscreen(self, ctx, shader, uniforms)
+    }
+
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        <T>::receive_visibility_query(self, ctx, rect, depth)
     }
 
     fn get_image_data(&self, ctx: &mut Context) -> RgbaImage {
@@ -242,8 +393,55 @@ impl<T: DrawTarget> DrawTarget for &mut T {
 /// ```
 #[derive(Debug)]
 pub struct Context {
-    backend: Backend,
     surface: Option<WindowSurface>,
+    /// A white `1x1` texture used to draw `Mask::Rectangle`.
+    mask_texture: Option<Texture>,
+    /// Available to each `Shader` as `u_noise`, see `Context::noise_texture`.
+    noise_texture: Texture,
+    focused: bool,
+    /// If `Context::request_attention` has to be cancelled once the window is focused.
+    attention_requested: bool,
+    /// Set by the platform, for example if the app was moved into the background on android.
+    platform_suspended: bool,
+    on_suspend: Vec<Hook>,
+    on_resume: Vec<Hook>,
+    pre_frame: Vec<FrameHook>,
+    post_frame: Vec<FrameHook>,
+    /// The last theme reported by the platform.
+    theme: Option<Theme>,
+    on_theme_change: Vec<ThemeHook>,
+    on_texture_copy: Vec<CopyHook>,
+    on_update_shader_globals: Vec<GlobalsHook>,
+    shader_globals: ShaderGlobals,
+    debug_view: DebugView,
+    color_filter: Option<[[f32; 4]; 4]>,
+    /// A copy of the window used to apply `color_filter`, created on first use.
+    color_filter_buffer: Option<Texture>,
+    primitive_palette: Option<color::Palette>,
+    cursor: Option<(Texture, (i32, i32))>,
+    cursor_position: Option<(i32, i32)>,
+    /// The unrounded cursor position in logical pixels, starting at the top left corner.
+    logical_cursor_position: Option<(f64, f64)>,
+    cursor_grab: CursorGrab,
+    /// The mouse motion since the start of the current frame, see `Context::mouse_delta`.
+    mouse_delta: (f32, f32),
+    draw_capture: DrawCapture,
+    capture_label: Option<String>,
+    /// The draw calls of the current frame.
+    current_capture: Vec<CapturedDraw>,
+    /// The draw calls of the last presented frame.
+    captured_draws: Vec<CapturedDraw>,
+    /// The render graph passes of the current frame.
+    pass_timings: render_graph::PassTimings,
+    /// The render graph passes of the last presented frame.
+    last_pass_timings: render_graph::PassTimings,
+    /// The current render settings, `color_grading` is stored in `color_filter` instead.
+    profile: RenderProfile,
+    /// When the last frame was presented, used to limit the frame rate.
+    last_present: Option<Instant>,
+    // The backend must be dropped after all textures owned by the context,
+    // which would otherwise be reported as leaked.
+    backend: Backend,
 }
 
 assert_not_impl_any!(Context: Send, Sync, Clone);
@@ -274,19 +472,64 @@ assert_not_impl_any!(Texture: Send, Sync);
 /// Used in `DrawConfig` to specify how
 /// each pixel should be draw onto the target.
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 #[non_exhaustive]
 pub enum BlendMode {
     /// `src_alpha * src_color + (1.0 - src_alpha) * dst_color`
+    #[default]
     Alpha,
     /// `src_alpha * src_color + 1.0 * dst_color`
     Additive,
+    /// `src_color + (1.0 - src_alpha) * dst_color`
+    ///
+    /// Used for textures which already contain premultiplied colors,
+    /// for example ones created using `Texture::from_image_premultiplied`.
+    ///
+    /// Drawing semi-transparent sprites into an intermediate texture cleared to
+    /// `(0.0, 0.0, 0.0, 0.0)` and then drawing that texture to the window using
+    /// `BlendMode::Alpha` results in dark fringes. Using this blend mode for
+    /// all these draw calls prevents this, as the intermediate texture
+    /// then also contains premultiplied colors.
+    PremultipliedAlpha,
 }
 
-impl Default for BlendMode {
-    fn default() -> Self {
-        BlendMode::Alpha
-    }
+/// Defines the visible region used by [`Context::masked`].
+///
+/// [`Context::masked`]: struct.Context.html#method.masked
+#[derive(Debug, Clone, Copy)]
+pub enum Mask<'a> {
+    /// Each pixel covered by a not fully transparent pixel
+    /// of the `texture` drawn at the given position.
+    Texture(&'a Texture, (i32, i32)),
+    /// A filled axis-aligned rectangle.
+    Rectangle(IRect),
+}
+
+/// How draw calls to the window are visualized, see [`Context::set_debug_view`].
+///
+/// [`Context::set_debug_view`]: struct.Context.html#method.set_debug_view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebugView {
+    /// Draw calls are rendered normally.
+    #[default]
+    Normal,
+    /// Each texture drawn to the window is replaced by a translucent quad which is
+    /// drawn using `BlendMode::Additive`, causing areas which are drawn
+    /// over multiple times to glow.
+    ///
+    /// Clearing the window always clears it to black while this view is active.
+    Overdraw,
+}
+
+/// Returned by each frame of [`Context::run`] to decide whether to keep running.
+///
+/// [`Context::run`]: struct.Context.html#method.run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow<R = ()> {
+    /// Continue with the next frame.
+    Continue,
+    /// Stop the event loop, causing `Context::run` to return the given value.
+    Exit(R),
 }
 
 /// How exactly should a texture be drawn?
@@ -294,6 +537,12 @@ impl Default for BlendMode {
 /// This struct has a hidden unstable field as it
 /// should only be constructed using functional record update (FRU).
 ///
+/// Floats are compared using their bits, so `NaN` is equal to itself
+/// and `0.0` is equal to `-0.0`. This allows using configs as keys of a
+/// `HashMap`, see [`ConfigInterner`] to cheaply compare many configs.
+///
+/// [`ConfigInterner`]: struct.ConfigInterner.html
+///
 /// # Examples
 ///
 /// ```rust
@@ -317,11 +566,24 @@ impl Default for BlendMode {
 pub struct DrawConfig {
     /// The scale of the drawn texture in drawn pixels per source pixel.
     pub scale: (u32, u32),
-    /// The rotation of the drawn texture in degrees.
+    /// The counterclockwise rotation of the drawn texture in degrees.
     ///
     /// Rotating by anything more precise than 90 degree steps can cause the source image
     /// to be slightly distorted, especially if it has a very low resolution.
-    pub rotation: i32,
+    pub rotation: f32,
+    /// The point around which the texture is rotated, relative to the lower left
+    /// corner of the drawn texture and already multiplied by `scale`.
+    ///
+    /// `None` rotates the texture around its center.
+    pub rotation_center: Option<(f32, f32)>,
+    /// An affine transformation applied after `scale`, `rotation` and flipping the texture.
+    ///
+    /// The matrix is multiplied with the homogeneous coordinates `(x, y, 1.0)` of each
+    /// corner of the texture, relative to `position`. It therefore uses the same
+    /// row-major layout as `color_modulation`.
+    ///
+    /// Both `None` and the identity matrix keep the pixel-perfect default behavior.
+    pub transform: Option<[[f32; 3]; 3]>,
     /// If the texture should be flipped on the y axis.
     pub flip_vertically: bool,
     /// If the texture should be flipped on the x axis.
@@ -330,7 +592,8 @@ pub struct DrawConfig {
     /// pixels with a depth smaller than `depth` will not
     /// be overwritten.
     ///
-    /// Draw calls with `depth >= 1.0` are ignored.
+    /// The depth must be in the range `0.0..1.0`, see `DrawConfig::validate`.
+    /// Draw calls with `depth >= 1.0` are ignored in release builds.
     pub depth: Option<f32>,
     /// Changes the color of the given pixel using matrix multiplication.
     pub color_modulation: [[f32; 4]; 4],
@@ -338,22 +601,239 @@ pub struct DrawConfig {
     pub invert_color: bool,
     /// How the texture should be drawn on the target.
     pub blend_mode: BlendMode,
+    /// Which color channels of the target, in the order `(red, green, blue, alpha)`,
+    /// are written to.
+    ///
+    /// Disabling all channels while setting a `depth` only updates the depth buffer.
+    pub color_mask: (bool, bool, bool, bool),
+    /// If only the silhouette of the texture should be written to the depth buffer,
+    /// leaving the color of the target untouched.
+    ///
+    /// This can be used to draw occluders which hide all later draw calls
+    /// with a greater `depth`. Has no effect if `depth` is `None`.
+    pub depth_only: bool,
+    /// Overrides how the texture is sampled, `None` uses the `TextureOptions`
+    /// or the `border_color` of the texture.
+    pub sampler: Option<Sampler>,
     // `#[non_exhaustive]` forbids FRU, so we use a hidden field instead.
     #[doc(hidden)]
     pub __non_exhaustive: (),
 }
 
+impl DrawConfig {
+    /// Returns the point around which a texture of the given size is rotated,
+    /// relative to its lower left corner.
+    pub(crate) fn rotation_center(&self, dimensions: (u32, u32)) -> (f32, f32) {
+        self.rotation_center.unwrap_or_else(|| {
+            (
+                (dimensions.0 * self.scale.0) as f32 / 2.0,
+                (dimensions.1 * self.scale.1) as f32 / 2.0,
+            )
+        })
+    }
+
+    /// Returns `transform` unless it is the identity matrix.
+    pub(crate) fn effective_transform(&self) -> Option<[[f32; 3]; 3]> {
+        self.transform
+            .filter(|&m| m != [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Returns the position of a corner of a texture with the given size relative to
+    /// the position of the draw call, mirroring the computation in `vertex.glsl`.
+    ///
+    /// `corner` is `(0.0, 0.0)` for the lower left and `(1.0, 1.0)` for the upper right corner.
+    pub(crate) fn corner(&self, dimensions: (u32, u32), corner: (f32, f32)) -> (f32, f32) {
+        let scale = (
+            (dimensions.0 * self.scale.0) as f32,
+            (dimensions.1 * self.scale.1) as f32,
+        );
+        let center = self.rotation_center(dimensions);
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (x, y) = (corner.0 * scale.0 - center.0, corner.1 * scale.1 - center.1);
+        let (x, y) = (x * cos - y * sin + center.0, x * sin + y * cos + center.1);
+        match self.effective_transform() {
+            Some(m) => (
+                m[0][0] * x + m[0][1] * y + m[0][2],
+                m[1][0] * x + m[1][1] * y + m[1][2],
+            ),
+            None => (x, y),
+        }
+    }
+}
+
+impl DrawConfig {
+    /// Returns all reasons why this config can not be used to draw a texture,
+    /// which is empty for valid configs.
+    ///
+    /// This checks that `scale` is not zero, that all floats are finite and
+    /// that `depth` is in the range `0.0..1.0`. Each draw call validates its config.
+    /// Unless it is drawn using `Context::try_draw`, invalid draw calls are
+    /// sanitized in debug builds and skipped in release builds.
+    ///
+    /// ```rust
+    /// use crow::{DrawConfig, DrawError};
+    ///
+    /// let config = DrawConfig {
+    ///     depth: Some(1.5),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(config.validate(), vec![DrawError::DepthOutOfRange(1.5)]);
+    /// assert!(config.sanitize().validate().is_empty());
+    /// ```
+    pub fn validate(&self) -> Vec<DrawError> {
+        let mut warnings = Vec::new();
+        if self.scale.0 == 0 || self.scale.1 == 0 {
+            warnings.push(DrawError::InvalidConfig(
+                "`scale` must not be zero, consider skipping this draw call instead",
+            ));
+        }
+        if !self.rotation.is_finite() {
+            warnings.push(DrawError::InvalidConfig("`rotation` must be finite"));
+        }
+        if matches!(self.rotation_center, Some((x, y)) if !x.is_finite() || !y.is_finite()) {
+            warnings.push(DrawError::InvalidConfig("`rotation_center` must be finite"));
+        }
+        if self
+            .transform
+            .iter()
+            .flatten()
+            .flatten()
+            .any(|v| !v.is_finite())
+        {
+            warnings.push(DrawError::InvalidConfig(
+                "`transform` must only contain finite values",
+            ));
+        }
+        if self
+            .color_modulation
+            .iter()
+            .flatten()
+            .any(|v| !v.is_finite())
+        {
+            warnings.push(DrawError::InvalidConfig(
+                "`color_modulation` must only contain finite values",
+            ));
+        }
+        match self.depth {
+            Some(depth) if !(0.0..1.0).contains(&depth) => {
+                warnings.push(DrawError::DepthOutOfRange(depth))
+            }
+            _ => (),
+        }
+        warnings
+    }
+
+    /// Returns a copy of this config with each problem reported
+    /// by [`DrawConfig::validate`] fixed.
+    ///
+    /// A `scale` of zero is replaced by `1`, a non-finite `rotation` by `0.0`,
+    /// a non-finite `rotation_center` or `transform` by `None` and non-finite entries
+    /// of `color_modulation` by the corresponding entry of `color::IDENTITY`.
+    /// `depth` is clamped to the range `0.0..1.0`, where `NaN` is replaced by `0.0`.
+    ///
+    /// In debug builds, this is used by draw calls with an invalid config
+    /// which are not drawn using `Context::try_draw`, which is logged as a warning.
+    ///
+    /// [`DrawConfig::validate`]: struct.DrawConfig.html#method.validate
+    pub fn sanitize(&self) -> DrawConfig {
+        let finite = |v: f32| v.is_finite();
+        let mut config = self.clone();
+        config.scale = (self.scale.0.max(1), self.scale.1.max(1));
+        if !finite(self.rotation) {
+            config.rotation = 0.0;
+        }
+        config.rotation_center = self
+            .rotation_center
+            .filter(|&(x, y)| finite(x) && finite(y));
+        config.transform = self
+            .transform
+            .filter(|m| m.iter().flatten().all(|&v| finite(v)));
+        for (i, row) in config.color_modulation.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                if !finite(*value) {
+                    *value = color::IDENTITY[i][j];
+                }
+            }
+        }
+        config.depth = self.depth.map(|depth| {
+            if depth.is_nan() {
+                0.0
+            } else {
+                // the largest `f32` smaller than `1.0`
+                depth.clamp(0.0, 1.0 - f32::EPSILON / 2.0)
+            }
+        });
+        config
+    }
+}
+
+/// Returns the bits of `v`, treating `0.0` and `-0.0` as equal.
+fn float_bits(v: f32) -> u32 {
+    if v == 0.0 {
+        0
+    } else {
+        v.to_bits()
+    }
+}
+
+impl DrawConfig {
+    /// Returns all fields with floats replaced by their bits, used by `PartialEq` and `Hash`.
+    fn key(&self) -> impl Eq + Hash {
+        let matrix3 = |m: [[f32; 3]; 3]| m.map(|row| row.map(float_bits));
+        let matrix4 = |m: [[f32; 4]; 4]| m.map(|row| row.map(float_bits));
+        (
+            (
+                self.scale,
+                float_bits(self.rotation),
+                self.rotation_center
+                    .map(|(x, y)| (float_bits(x), float_bits(y))),
+                self.transform.map(matrix3),
+                self.flip_vertically,
+                self.flip_horizontally,
+            ),
+            (
+                self.depth.map(float_bits),
+                matrix4(self.color_modulation),
+                self.invert_color,
+                self.blend_mode,
+                self.color_mask,
+                self.depth_only,
+                self.sampler,
+            ),
+        )
+    }
+}
+
+impl PartialEq for DrawConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for DrawConfig {}
+
+impl Hash for DrawConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
 impl Default for DrawConfig {
     fn default() -> Self {
         Self {
             scale: (1, 1),
-            rotation: 0,
+            rotation: 0.0,
+            rotation_center: None,
+            transform: None,
             depth: None,
             color_modulation: color::IDENTITY,
             invert_color: false,
             flip_vertically: false,
             flip_horizontally: false,
             blend_mode: BlendMode::default(),
+            color_mask: (true, true, true, true),
+            depth_only: false,
+            sampler: None,
             __non_exhaustive: (),
         }
     }
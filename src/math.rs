@@ -0,0 +1,162 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+macro_rules! vector {
+    ($(#[$attr:meta])* $name:ident($t:ty) $(, $($derive:ident),+)?) => {
+        $(#[$attr])*
+        #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+        #[derive(Debug, Clone, Copy, PartialEq, Default $(, $($derive),+)?)]
+        pub struct $name {
+            /// The horizontal component, increasing to the right.
+            pub x: $t,
+            /// The vertical component, increasing upwards.
+            pub y: $t,
+        }
+
+        impl $name {
+            /// Creates a new vector.
+            pub const fn new(x: $t, y: $t) -> Self {
+                Self { x, y }
+            }
+        }
+
+        impl From<($t, $t)> for $name {
+            fn from((x, y): ($t, $t)) -> Self {
+                Self { x, y }
+            }
+        }
+
+        impl From<$name> for ($t, $t) {
+            fn from(v: $name) -> Self {
+                (v.x, v.y)
+            }
+        }
+
+        impl From<[$t; 2]> for $name {
+            fn from([x, y]: [$t; 2]) -> Self {
+                Self { x, y }
+            }
+        }
+
+        impl From<$name> for [$t; 2] {
+            fn from(v: $name) -> Self {
+                [v.x, v.y]
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self::new(self.x + other.x, self.y + other.y)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self::new(self.x - other.x, self.y - other.y)
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+
+        impl Mul<$t> for $name {
+            type Output = Self;
+
+            fn mul(self, scalar: $t) -> Self {
+                Self::new(self.x * scalar, self.y * scalar)
+            }
+        }
+    };
+}
+
+vector! {
+    /// A two-dimensional vector of `f32`.
+    ///
+    /// Converts to and from `(f32, f32)` and `[f32; 2]`.
+    Vec2(f32)
+}
+
+vector! {
+    /// A two-dimensional vector of `i32`, used for positions.
+    ///
+    /// Converts to and from `(i32, i32)` and `[i32; 2]`, so it
+    /// can be used for all position parameters of this crate.
+    ///
+    /// ```rust, no_run
+    /// use crow::{
+    ///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+    ///     Context, DrawConfig, IVec2, Texture, UVec2,
+    /// };
+    ///
+    /// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+    /// let texture = Texture::new(&mut ctx, UVec2::new(16, 16))?;
+    ///
+    /// let player = IVec2::new(40, 20);
+    /// let velocity = IVec2::new(2, -1);
+    ///
+    /// let mut surface = ctx.surface();
+    /// ctx.draw(&mut surface, &texture, player + velocity, &DrawConfig::default());
+    /// ctx.draw(&mut surface, &texture, [0, 0], &DrawConfig::default());
+    /// ctx.present(surface)?;
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    IVec2(i32), Eq, Hash
+}
+
+vector! {
+    /// A two-dimensional vector of `u32`, used for sizes.
+    ///
+    /// Converts to and from `(u32, u32)` and `[u32; 2]`, so it
+    /// can be used for all size parameters of this crate.
+    UVec2(u32), Eq, Hash
+}
+
+impl Neg for Vec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Neg for IVec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl From<IVec2> for Vec2 {
+    fn from(v: IVec2) -> Self {
+        Self::new(v.x as f32, v.y as f32)
+    }
+}
+
+impl From<UVec2> for Vec2 {
+    fn from(v: UVec2) -> Self {
+        Self::new(v.x as f32, v.y as f32)
+    }
+}
+
+impl Vec2 {
+    /// Rounds both components to the nearest integer.
+    pub fn round(self) -> IVec2 {
+        IVec2::new(self.x.round() as i32, self.y.round() as i32)
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{Context, DrawConfig, DrawTarget, IRect, NewTextureError, Texture};
+
+/// A downscaled copy of a large world texture, which is only updated every few frames.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, Minimap, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let world = Texture::load(&mut ctx, "./textures/world.png")?;
+/// let mut minimap = Minimap::new((64, 64), 10);
+///
+/// let mut surface = ctx.surface();
+/// minimap.update(&mut ctx, &world)?;
+/// minimap.draw(&mut ctx, &mut surface, (10, 10));
+/// // the part of the world which is currently visible
+/// minimap.draw_viewport(&mut ctx, &mut surface, (10, 10), ((0, 0), (320, 240)), (1.0, 1.0, 1.0, 1.0));
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Minimap {
+    size: (u32, u32),
+    interval: u32,
+    frames_until_update: u32,
+    /// The size of the world during the last update.
+    world_size: (u32, u32),
+    texture: Option<Texture>,
+}
+
+impl Minimap {
+    /// Creates a new minimap of the given `size`, which is updated every `interval` frames.
+    ///
+    /// An `interval` of `0` or `1` updates the minimap each frame.
+    pub fn new(size: impl Into<(u32, u32)>, interval: u32) -> Self {
+        Minimap {
+            size: size.into(),
+            interval,
+            frames_until_update: 0,
+            world_size: (0, 0),
+            texture: None,
+        }
+    }
+
+    /// Returns the size of this minimap.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Downscales `world` into the minimap in case the last
+    /// update was at least `interval` frames ago.
+    ///
+    /// This should be called once per frame.
+    pub fn update(&mut self, ctx: &mut Context, world: &Texture) -> Result<(), NewTextureError> {
+        match self.frames_until_update.checked_sub(1) {
+            Some(frames) if self.texture.is_some() => {
+                self.frames_until_update = frames;
+                Ok(())
+            }
+            _ => self.render(ctx, world),
+        }
+    }
+
+    /// Immediately downscales `world` into the minimap, ignoring the update interval.
+    pub fn render(&mut self, ctx: &mut Context, world: &Texture) -> Result<(), NewTextureError> {
+        self.frames_until_update = self.interval.saturating_sub(1);
+        self.world_size = world.size;
+
+        if self.texture.is_none() {
+            self.texture = Some(Texture::new(ctx, self.size)?);
+        }
+
+        let texture = self.texture.as_mut().unwrap();
+        ctx.clear_color(texture, (0.0, 0.0, 0.0, 0.0));
+        if let Some(target) = texture.prepare_as_draw_target(ctx) {
+            ctx.backend.blit(
+                &world.inner,
+                world.position,
+                world.size,
+                target.framebuffer_id,
+                (0, 0),
+                self.size,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns the current minimap, `None` if it has not yet been rendered.
+    pub fn texture(&self) -> Option<&Texture> {
+        self.texture.as_ref()
+    }
+
+    /// Draws the minimap onto `target` with its lower left corner at `position`.
+    pub fn draw<T>(&self, ctx: &mut Context, target: &mut T, position: impl Into<(i32, i32)>)
+    where
+        T: DrawTarget,
+    {
+        if let Some(texture) = &self.texture {
+            ctx.draw(target, texture, position, &DrawConfig::default());
+        }
+    }
+
+    /// Draws the outline of `viewport`, which is in world coordinates,
+    /// onto the minimap drawn at `position`.
+    ///
+    /// This is generally used to show which part of the world is currently visible.
+    pub fn draw_viewport<T>(
+        &self,
+        ctx: &mut Context,
+        target: &mut T,
+        position: (i32, i32),
+        viewport: impl Into<IRect>,
+        color: (f32, f32, f32, f32),
+    ) where
+        T: DrawTarget,
+    {
+        let viewport = viewport.into();
+        if self.world_size.0 == 0 || self.world_size.1 == 0 || viewport.is_empty() {
+            return;
+        }
+
+        let scale = |(x, y): (i32, i32)| {
+            (
+                position.0
+                    + (i64::from(x) * i64::from(self.size.0) / i64::from(self.world_size.0)) as i32,
+                position.1
+                    + (i64::from(y) * i64::from(self.size.1) / i64::from(self.world_size.1)) as i32,
+            )
+        };
+        let upper_right = (viewport.right() - 1, viewport.top() - 1);
+        let rect = IRect::from_corners(scale(viewport.position()), scale(upper_right));
+        ctx.debug_rectangle(target, rect, color);
+    }
+}
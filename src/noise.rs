@@ -0,0 +1,134 @@
+use image::{Rgba, RgbaImage};
+
+use crate::rng::Rng;
+
+/// The width and height of the noise texture.
+pub const NOISE_SIZE: u32 = 32;
+
+/// The standard deviation of the gaussian used to find clusters and voids.
+const SIGMA: f32 = 1.5;
+
+/// The distance after which the gaussian is small enough to be ignored.
+const RADIUS: usize = 6;
+
+/// A binary pattern on a torus, together with the energy of each pixel,
+/// which is the sum of the gaussians centered at all set pixels.
+struct Pattern {
+    set: Vec<bool>,
+    energy: Vec<f32>,
+    /// The gaussian indexed using `(dy + RADIUS) * (2 * RADIUS + 1) + dx + RADIUS`.
+    kernel: Vec<f32>,
+}
+
+impl Pattern {
+    fn new() -> Self {
+        let size = NOISE_SIZE as usize;
+        let width = 2 * RADIUS + 1;
+        let mut kernel = Vec::with_capacity(width * width);
+        for dy in 0..width {
+            for dx in 0..width {
+                let x = dx as f32 - RADIUS as f32;
+                let y = dy as f32 - RADIUS as f32;
+                kernel.push((-(x * x + y * y) / (2.0 * SIGMA * SIGMA)).exp());
+            }
+        }
+
+        Pattern {
+            set: vec![false; size * size],
+            energy: vec![0.0; size * size],
+            kernel,
+        }
+    }
+
+    fn toggle(&mut self, i: usize) {
+        let size = NOISE_SIZE as usize;
+        let width = 2 * RADIUS + 1;
+        self.set[i] = !self.set[i];
+        let sign = if self.set[i] { 1.0 } else { -1.0 };
+        let (x, y) = (i % size, i / size);
+        for dy in 0..width {
+            let row = (y + size + dy - RADIUS) % size * size;
+            for dx in 0..width {
+                let column = (x + size + dx - RADIUS) % size;
+                self.energy[row + column] += sign * self.kernel[dy * width + dx];
+            }
+        }
+    }
+
+    /// The set pixel with the highest energy.
+    fn tightest_cluster(&self) -> usize {
+        self.extreme(true, |a, b| a > b)
+    }
+
+    /// The unset pixel with the lowest energy.
+    fn largest_void(&self) -> usize {
+        self.extreme(false, |a, b| a < b)
+    }
+
+    fn extreme(&self, set: bool, better: impl Fn(f32, f32) -> bool) -> usize {
+        let mut best = None;
+        for (i, &energy) in self.energy.iter().enumerate() {
+            if self.set[i] == set && best.map_or(true, |b: usize| better(energy, self.energy[b])) {
+                best = Some(i);
+            }
+        }
+        best.unwrap_or_else(|| bug!("no pixel found while generating blue noise"))
+    }
+}
+
+/// Ranks each pixel using the void-and-cluster method, resulting in blue noise.
+fn blue_noise(rng: &mut Rng) -> Vec<usize> {
+    let count = (NOISE_SIZE * NOISE_SIZE) as usize;
+    let mut pattern = Pattern::new();
+    let initial = count / 10;
+    while pattern.set.iter().filter(|&&s| s).count() < initial {
+        let i = rng.next() as usize % count;
+        if !pattern.set[i] {
+            pattern.toggle(i);
+        }
+    }
+
+    // evenly distributes the initial pixels
+    loop {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        let void = pattern.largest_void();
+        pattern.toggle(void);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0; count];
+    let initial_set = pattern.set.clone();
+    let initial_energy = pattern.energy.clone();
+    for rank in (0..initial).rev() {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        ranks[cluster] = rank;
+    }
+
+    pattern.set = initial_set;
+    pattern.energy = initial_energy;
+    for rank in initial..count {
+        let void = pattern.largest_void();
+        pattern.toggle(void);
+        ranks[void] = rank;
+    }
+    ranks
+}
+
+/// Creates the texture available to shaders as `u_noise`, containing blue noise in
+/// the red channel and independent white noise in the green and blue channel.
+pub fn noise_image() -> RgbaImage {
+    let mut rng = Rng::new(0x2545_f491);
+    let count = (NOISE_SIZE * NOISE_SIZE) as usize;
+    let ranks = blue_noise(&mut rng);
+    let mut image = RgbaImage::new(NOISE_SIZE, NOISE_SIZE);
+    for (i, pixel) in image.pixels_mut().enumerate() {
+        let blue = (ranks[i] * 256 / count) as u8;
+        let white = rng.next();
+        *pixel = Rgba([blue, white as u8, (white >> 8) as u8, 255]);
+    }
+    image
+}
@@ -0,0 +1,83 @@
+use std::{fs, mem, path::Path, time::Duration};
+
+use image::ImageError;
+
+use crate::{Context, Error, Texture};
+
+impl Context {
+    /// Renders `frames` frames of the given `dimensions` without presenting them,
+    /// saving each frame as a numbered PNG image in `directory`.
+    ///
+    /// `f` is called for each frame with its index and the time since the first
+    /// frame, assuming the video is played with `fps` frames per second. As the
+    /// time does not depend on how long rendering a frame actually takes,
+    /// the result is deterministic and can be used to render trailers at full
+    /// quality, for example by combining the frames using
+    /// `ffmpeg -framerate <fps> -i frame_%05d.png trailer.mp4`.
+    ///
+    /// Before each frame, the uniform `u_time` of all shaders is set to the time
+    /// of this frame and the callbacks added using `Context::on_update_shader_globals`
+    /// are called. After each frame, the counters and pass timings are reset,
+    /// just like when presenting a frame.
+    ///
+    /// The frames are named `frame_00000.png`, `frame_00001.png` and so on.
+    /// The hooks added using `Context::on_pre_frame` and `Context::on_post_frame`
+    /// are not called, as the frames are not drawn to the window.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `fps` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crow::{
+    ///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+    ///     Context, DrawConfig, Texture,
+    /// };
+    ///
+    /// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+    /// let texture = Texture::load(&mut ctx, "./textures/player.png")?;
+    ///
+    /// ctx.render_frames("./trailer", (320, 240), 120, 60, |ctx, target, _frame, time| {
+    ///     let x = (time.as_secs_f32() * 100.0) as i32;
+    ///     ctx.clear_color(target, (0.4, 0.4, 0.8, 1.0));
+    ///     ctx.draw(target, &texture, (x, 100), &DrawConfig::default());
+    /// })?;
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    pub fn render_frames<P, F>(
+        &mut self,
+        directory: P,
+        dimensions: (u32, u32),
+        frames: u32,
+        fps: u32,
+        mut f: F,
+    ) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&mut Context, &mut Texture, u32, Duration),
+    {
+        assert!(fps != 0, "tried to render frames with 0 fps");
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory).map_err(|e| Error::ImageError(ImageError::IoError(e)))?;
+
+        let mut target = Texture::new(self, dimensions)?;
+        // `u_time` is restored afterwards, as these frames are not presented
+        let shader_time = self.backend.time();
+        let res = (0..frames).try_for_each(|frame| {
+            let time = Duration::from_secs(1) * frame / fps;
+            self.backend.set_time(time.as_secs_f32());
+            self.update_shader_globals();
+            f(self, &mut target, frame, time);
+            let image = self.image_data(&target);
+            self.last_pass_timings = mem::take(&mut self.pass_timings);
+            self.backend.end_frame();
+            image
+                .save(directory.join(format!("frame_{:05}.png", frame)))
+                .map_err(Error::ImageError)
+        });
+        self.backend.set_time(shader_time);
+        res
+    }
+}
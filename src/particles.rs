@@ -0,0 +1,272 @@
+//! Particles simulated on the CPU and drawn using a single [`SpriteBatch`].
+//!
+//! ```rust, no_run
+//! use std::time::Duration;
+//!
+//! use crow::{
+//!     glutin::{event_loop::EventLoop, window::WindowBuilder},
+//!     particles::{Emitter, EmitterConfig},
+//!     Context, Texture,
+//! };
+//!
+//! let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+//! let spark = Texture::load(&mut ctx, "./textures/spark.png")?;
+//!
+//! let mut sparks = Emitter::new(
+//!     &spark,
+//!     EmitterConfig {
+//!         spawn_rate: 60.0,
+//!         velocity: ((-20.0, 40.0), (20.0, 80.0)),
+//!         acceleration: (0.0, -60.0),
+//!         end_color: (1.0, 0.3, 0.0, 0.0),
+//!         ..Default::default()
+//!     },
+//! );
+//! sparks.position = (160.0, 20.0);
+//!
+//! ctx.frame(|ctx, surface| {
+//!     sparks.update(Duration::from_millis(16));
+//!     sparks.draw(ctx, surface);
+//! })?;
+//! # Ok::<(), crow::Error>(())
+//! ```
+//!
+//! [`SpriteBatch`]: ../struct.SpriteBatch.html
+use std::{cmp, time::Duration};
+
+use crate::{
+    rng::Rng, BlendMode, Context, DrawConfig, DrawTarget, SpriteBatch, Subsystem, Texture,
+};
+
+/// The parameters of an [`Emitter`].
+///
+/// All ranges are given as `(min, max)` and particles use a random
+/// value inside of each range, which may be empty.
+///
+/// [`Emitter`]: struct.Emitter.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmitterConfig {
+    /// The number of particles spawned each second.
+    ///
+    /// Negative and non-finite rates do not spawn any particles.
+    pub spawn_rate: f32,
+    /// How long each particle exists.
+    pub lifetime: (Duration, Duration),
+    /// The initial velocity of each particle in pixels per second.
+    pub velocity: ((f32, f32), (f32, f32)),
+    /// The acceleration of all particles in pixels per second squared, for example gravity.
+    pub acceleration: (f32, f32),
+    /// The color the texture of new particles is multiplied with.
+    pub start_color: (f32, f32, f32, f32),
+    /// The color the texture is multiplied with at the end of the lifetime of a particle.
+    ///
+    /// The color of each particle is linearly interpolated between
+    /// `start_color` and `end_color` using `DrawConfig::color_modulation`.
+    pub end_color: (f32, f32, f32, f32),
+    /// How the particles are drawn, which is `BlendMode::Additive` by default.
+    pub blend_mode: BlendMode,
+    /// The maximum number of particles alive at the same time, further particles are not spawned.
+    pub max_particles: usize,
+    /// The seed of the random values used by particles, so the same
+    /// seed always results in the same particles.
+    pub seed: u32,
+}
+
+impl Default for EmitterConfig {
+    fn default() -> Self {
+        EmitterConfig {
+            spawn_rate: 10.0,
+            lifetime: (Duration::from_secs(1), Duration::from_secs(1)),
+            velocity: ((0.0, 0.0), (0.0, 0.0)),
+            acceleration: (0.0, 0.0),
+            start_color: (1.0, 1.0, 1.0, 1.0),
+            end_color: (1.0, 1.0, 1.0, 1.0),
+            blend_mode: BlendMode::Additive,
+            max_particles: 1024,
+            seed: 0,
+        }
+    }
+}
+
+/// A single particle of an [`Emitter`].
+///
+/// [`Emitter`]: struct.Emitter.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    /// The position of the center of this particle.
+    pub position: (f32, f32),
+    /// The velocity of this particle in pixels per second.
+    pub velocity: (f32, f32),
+    /// How long this particle exists.
+    pub age: Duration,
+    /// When this particle is removed.
+    pub lifetime: Duration,
+}
+
+impl Particle {
+    /// Returns how much of its lifetime this particle has already lived, in the range `0.0..=1.0`.
+    pub fn progress(&self) -> f32 {
+        if self.lifetime == Duration::from_secs(0) {
+            1.0
+        } else {
+            (self.age.as_secs_f32() / self.lifetime.as_secs_f32()).min(1.0)
+        }
+    }
+}
+
+/// Spawns, moves and draws particles using the same texture.
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    /// The parameters used for new particles and when drawing.
+    pub config: EmitterConfig,
+    /// The position at which new particles are spawned.
+    pub position: (f32, f32),
+    /// If new particles are spawned by `Emitter::update`, existing particles are always updated.
+    pub emitting: bool,
+    texture: Texture,
+    particles: Vec<Particle>,
+    /// The fraction of a particle which has not yet been spawned.
+    pending: f32,
+    rng: Rng,
+}
+
+impl Emitter {
+    /// Creates an emitter at `(0.0, 0.0)` which draws each particle using `texture`.
+    pub fn new(texture: &Texture, config: EmitterConfig) -> Self {
+        Emitter {
+            rng: Rng::new(config.seed),
+            config,
+            position: (0.0, 0.0),
+            emitting: true,
+            texture: texture.clone(),
+            particles: Vec::new(),
+            pending: 0.0,
+        }
+    }
+
+    /// Returns the texture used to draw each particle.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns all particles which are currently alive, starting with the oldest one.
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Removes all particles.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.pending = 0.0;
+    }
+
+    /// Immediately spawns `count` particles, ignoring `spawn_rate`.
+    pub fn burst(&mut self, count: usize) {
+        for _ in 0..count {
+            self.spawn();
+        }
+    }
+
+    fn spawn(&mut self) {
+        if self.particles.len() >= self.config.max_particles {
+            return;
+        }
+
+        let (min, max) = self.config.lifetime;
+        let lifetime = Duration::from_secs_f32(
+            self.rng
+                .range(min.as_secs_f32(), max.as_secs_f32())
+                .max(0.0),
+        );
+        let ((min_x, min_y), (max_x, max_y)) = self.config.velocity;
+        let velocity = (self.rng.range(min_x, max_x), self.rng.range(min_y, max_y));
+        self.particles.push(Particle {
+            position: self.position,
+            velocity,
+            age: Duration::from_secs(0),
+            lifetime,
+        });
+    }
+
+    /// Advances the simulation by `delta`, moving all particles, removing
+    /// the ones which reached the end of their lifetime and spawning new ones.
+    pub fn update(&mut self, delta: Duration) {
+        let seconds = delta.as_secs_f32();
+        let acceleration = self.config.acceleration;
+        for particle in self.particles.iter_mut() {
+            particle.age += delta;
+            particle.velocity.0 += acceleration.0 * seconds;
+            particle.velocity.1 += acceleration.1 * seconds;
+            particle.position.0 += particle.velocity.0 * seconds;
+            particle.position.1 += particle.velocity.1 * seconds;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        let rate = self.config.spawn_rate;
+        if self.emitting && rate.is_finite() && rate > 0.0 {
+            let pending = self.pending + rate * seconds;
+            let count = pending.floor();
+            self.pending = if count.is_finite() {
+                pending - count
+            } else {
+                0.0
+            };
+            // particles which do not fit are dropped instead of being spawned later
+            let free = self
+                .config
+                .max_particles
+                .saturating_sub(self.particles.len());
+            self.burst(cmp::min(count as usize, free));
+        }
+    }
+
+    /// Returns a batch containing all particles which are currently alive.
+    pub fn build_batch(&self) -> SpriteBatch {
+        let mut batch = SpriteBatch::new(&self.texture);
+        let (width, height) = self.texture.dimensions();
+        for particle in self.particles.iter() {
+            let position = (
+                (particle.position.0 - width as f32 / 2.0).round() as i32,
+                (particle.position.1 - height as f32 / 2.0).round() as i32,
+            );
+            let color = lerp(
+                self.config.start_color,
+                self.config.end_color,
+                particle.progress(),
+            );
+            let config = DrawConfig {
+                color_modulation: [
+                    [color.0, 0.0, 0.0, 0.0],
+                    [0.0, color.1, 0.0, 0.0],
+                    [0.0, 0.0, color.2, 0.0],
+                    [0.0, 0.0, 0.0, color.3],
+                ],
+                blend_mode: self.config.blend_mode,
+                ..Default::default()
+            };
+            batch.push(&self.texture, position, &config);
+        }
+        batch
+    }
+
+    /// Draws all particles onto `target` using a single batch.
+    pub fn draw<T>(&self, ctx: &mut Context, target: &mut T)
+    where
+        T: DrawTarget,
+    {
+        let batch = self.build_batch();
+        ctx.in_subsystem(Subsystem::Particles, |ctx| {
+            target.receive_batch(ctx, &batch)
+        })
+    }
+}
+
+fn lerp(start: (f32, f32, f32, f32), end: (f32, f32, f32, f32), t: f32) -> (f32, f32, f32, f32) {
+    (
+        start.0 + (end.0 - start.0) * t,
+        start.1 + (end.1 - start.1) * t,
+        start.2 + (end.2 - start.2) * t,
+        start.3 + (end.3 - start.3) * t,
+    )
+}
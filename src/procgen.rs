@@ -0,0 +1,183 @@
+//! Procedural texture generation on the GPU.
+//!
+//! All generators overwrite their target and only depend on their
+//! parameters, so using the same seed always results in the same texture.
+//!
+//! ```rust, no_run
+//! use crow::{
+//!     glutin::{event_loop::EventLoop, window::WindowBuilder},
+//!     procgen::{self, Noise},
+//!     Context, Texture,
+//! };
+//!
+//! let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+//!
+//! let mut clouds = Texture::new(&mut ctx, (320, 240))?;
+//! procgen::perlin_noise(&mut ctx, &mut clouds, &Noise {
+//!     seed: 42,
+//!     scale: 64.0,
+//!     octaves: 4,
+//!     low: (0.4, 0.4, 0.8, 1.0),
+//!     high: (1.0, 1.0, 1.0, 1.0),
+//!     ..Default::default()
+//! });
+//! # Ok::<(), crow::Error>(())
+//! ```
+use crate::{backend::Uniform, Context, Texture};
+
+static FRAGMENT: &str = include_str!("backend/shader/fragment_procgen.glsl");
+
+const VALUE_NOISE: u32 = 0;
+const PERLIN_NOISE: u32 = 1;
+const LINEAR_GRADIENT: u32 = 2;
+const RADIAL_GRADIENT: u32 = 3;
+
+/// The parameters used by [`value_noise`] and [`perlin_noise`].
+///
+/// [`value_noise`]: fn.value_noise.html
+/// [`perlin_noise`]: fn.perlin_noise.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Noise {
+    /// The seed of the noise, different seeds result in unrelated noise.
+    pub seed: u32,
+    /// The size of a single noise cell in pixels.
+    pub scale: f32,
+    /// The number of layers of noise, each layer having
+    /// twice the frequency of the previous one.
+    pub octaves: u32,
+    /// The amplitude of each layer relative to the previous one.
+    pub persistence: f32,
+    /// The position in the noise of the lower left corner of the target,
+    /// which can be used to generate adjacent textures.
+    pub offset: (f32, f32),
+    /// The color used for the lowest noise values.
+    pub low: (f32, f32, f32, f32),
+    /// The color used for the highest noise values.
+    pub high: (f32, f32, f32, f32),
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Noise {
+            seed: 0,
+            scale: 16.0,
+            octaves: 1,
+            persistence: 0.5,
+            offset: (0.0, 0.0),
+            low: (0.0, 0.0, 0.0, 1.0),
+            high: (1.0, 1.0, 1.0, 1.0),
+        }
+    }
+}
+
+fn run(ctx: &mut Context, target: &mut Texture, mode: u32, uniforms: &[(&str, Uniform)]) {
+    if let Some(target) = target.prepare_as_draw_target(ctx) {
+        let mut all = vec![("mode", Uniform::UInt(mode))];
+        all.extend_from_slice(uniforms);
+        ctx.backend.run_pass(
+            FRAGMENT,
+            target.framebuffer_id,
+            target.dimensions,
+            None,
+            &all,
+        );
+    }
+}
+
+fn noise(ctx: &mut Context, target: &mut Texture, mode: u32, noise: &Noise) {
+    assert!(
+        noise.scale > 0.0,
+        "`scale` must be positive: {}",
+        noise.scale
+    );
+    let (low, high) = (noise.low, noise.high);
+    run(
+        ctx,
+        target,
+        mode,
+        &[
+            ("seed", Uniform::UInt(noise.seed)),
+            ("scale", Uniform::Float(noise.scale)),
+            ("octaves", Uniform::UInt(noise.octaves)),
+            ("persistence", Uniform::Float(noise.persistence)),
+            ("offset", Uniform::Vec2(noise.offset.0, noise.offset.1)),
+            ("low", Uniform::Vec4(low.0, low.1, low.2, low.3)),
+            ("high", Uniform::Vec4(high.0, high.1, high.2, high.3)),
+        ],
+    )
+}
+
+/// Fills `target` with value noise, which interpolates between
+/// random values at the corners of each noise cell.
+///
+/// # Panics
+///
+/// This function panics if `noise.scale` is not positive.
+pub fn value_noise(ctx: &mut Context, target: &mut Texture, noise: &Noise) {
+    self::noise(ctx, target, VALUE_NOISE, noise)
+}
+
+/// Fills `target` with perlin noise, which is smoother and
+/// less blocky than value noise.
+///
+/// # Panics
+///
+/// This function panics if `noise.scale` is not positive.
+pub fn perlin_noise(ctx: &mut Context, target: &mut Texture, noise: &Noise) {
+    self::noise(ctx, target, PERLIN_NOISE, noise)
+}
+
+/// Fills `target` with a linear gradient from `start` at the position `from`
+/// to `end` at the position `to`.
+///
+/// Pixels before `from` use `start` and pixels after `to` use `end`.
+pub fn linear_gradient(
+    ctx: &mut Context,
+    target: &mut Texture,
+    from: (f32, f32),
+    to: (f32, f32),
+    start: (f32, f32, f32, f32),
+    end: (f32, f32, f32, f32),
+) {
+    run(
+        ctx,
+        target,
+        LINEAR_GRADIENT,
+        &[
+            ("offset", Uniform::Vec2(0.0, 0.0)),
+            ("from", Uniform::Vec2(from.0, from.1)),
+            ("to", Uniform::Vec2(to.0, to.1)),
+            ("low", Uniform::Vec4(start.0, start.1, start.2, start.3)),
+            ("high", Uniform::Vec4(end.0, end.1, end.2, end.3)),
+        ],
+    )
+}
+
+/// Fills `target` with a radial gradient from `inner` at `center`
+/// to `outer` at a distance of `radius` pixels.
+///
+/// # Panics
+///
+/// This function panics if `radius` is not positive.
+pub fn radial_gradient(
+    ctx: &mut Context,
+    target: &mut Texture,
+    center: (f32, f32),
+    radius: f32,
+    inner: (f32, f32, f32, f32),
+    outer: (f32, f32, f32, f32),
+) {
+    assert!(radius > 0.0, "`radius` must be positive: {}", radius);
+    run(
+        ctx,
+        target,
+        RADIAL_GRADIENT,
+        &[
+            ("offset", Uniform::Vec2(0.0, 0.0)),
+            ("from", Uniform::Vec2(center.0, center.1)),
+            ("to", Uniform::Vec2(radius, 0.0)),
+            ("low", Uniform::Vec4(inner.0, inner.1, inner.2, inner.3)),
+            ("high", Uniform::Vec4(outer.0, outer.1, outer.2, outer.3)),
+        ],
+    )
+}
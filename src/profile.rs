@@ -0,0 +1,124 @@
+use std::{thread, time::Duration};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Context, VsyncError};
+
+/// How `target::PixelPerfect` is scaled, see [`RenderProfile`].
+///
+/// [`RenderProfile`]: struct.RenderProfile.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ScalingMode {
+    /// Uses the largest integer scale which fits into the target.
+    #[default]
+    Integer,
+    /// Always uses the given scale, even if the result does not fit into the target.
+    Fixed(u32),
+}
+
+/// All render settings of a [`Context`], which can be changed at once
+/// using `Context::apply_profile`, for example by a graphics settings menu.
+///
+/// With the `serde1` feature enabled, profiles can be serialized to store
+/// the settings of the player.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     color, Context, RenderProfile,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// ctx.apply_profile(&RenderProfile {
+///     vsync: true,
+///     frame_limit: Some(60),
+///     color_grading: Some(color::PROTANOPIA_ASSIST),
+///     ..Default::default()
+/// })?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`Context`]: struct.Context.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderProfile {
+    /// If presenting a frame waits for the vertical blank of the monitor.
+    ///
+    /// Not all platforms support changing this setting, in which case
+    /// `Context::apply_profile` returns an error.
+    pub vsync: bool,
+    /// The maximum number of frames presented each second, `None` for no limit.
+    pub frame_limit: Option<u32>,
+    /// How `target::PixelPerfect` is scaled.
+    pub scaling: ScalingMode,
+    /// If optional post processing effects should be used.
+    ///
+    /// This is not used by crow itself and should be checked by the game,
+    /// for example before adding optional passes to a `RenderGraph`.
+    pub post_effects: bool,
+    /// The color matrix applied to everything drawn to the window,
+    /// see `Context::set_color_filter`.
+    pub color_grading: Option<[[f32; 4]; 4]>,
+}
+
+impl Default for RenderProfile {
+    fn default() -> Self {
+        RenderProfile {
+            vsync: false,
+            frame_limit: None,
+            scaling: ScalingMode::Integer,
+            post_effects: true,
+            color_grading: None,
+        }
+    }
+}
+
+impl Context {
+    /// Changes all render settings to the ones of `profile`.
+    ///
+    /// If `RenderProfile::vsync` could not be changed, all other settings
+    /// are still applied and `RenderProfile::vsync` keeps its previous value.
+    pub fn apply_profile(&mut self, profile: &RenderProfile) -> Result<(), VsyncError> {
+        let vsync = if profile.vsync != self.profile.vsync {
+            self.backend.set_vsync(profile.vsync)
+        } else {
+            Ok(())
+        };
+        self.profile = RenderProfile {
+            vsync: if vsync.is_ok() {
+                profile.vsync
+            } else {
+                self.profile.vsync
+            },
+            color_grading: None,
+            ..*profile
+        };
+        self.set_color_filter(profile.color_grading);
+        vsync
+    }
+
+    /// Returns the current render settings.
+    ///
+    /// `RenderProfile::color_grading` is also changed by `Context::set_color_filter`.
+    pub fn render_profile(&self) -> RenderProfile {
+        RenderProfile {
+            color_grading: self.color_filter(),
+            ..self.profile
+        }
+    }
+
+    /// Waits until the next frame may be presented according to `RenderProfile::frame_limit`.
+    pub(crate) fn limit_frame_rate(&mut self) {
+        if let Some(limit) = self.profile.frame_limit.filter(|&limit| limit != 0) {
+            let frame_time = Duration::from_secs(1) / limit;
+            if let Some(last_present) = self.last_present {
+                let elapsed = last_present.elapsed();
+                if elapsed < frame_time {
+                    thread::sleep(frame_time - elapsed);
+                }
+            }
+        }
+    }
+}
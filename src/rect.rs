@@ -0,0 +1,325 @@
+use std::cmp;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// An axis-aligned rectangle on a `DrawTarget`, used by `Context::clear_region`,
+/// `Context::debug_rectangle` and `Mask::Rectangle`.
+///
+/// The rectangle contains all pixels from `x` to `x + width - 1` and
+/// from `y` to `y + height - 1`, starting at the bottom left.
+/// A rectangle with a `width` or `height` of `0` does not contain any pixels.
+///
+/// Converts from a tuple of a position and a size, so both
+/// `IRect::new((4, 2), (8, 8))` and `((4, 2), (8, 8))` can be used
+/// to specify a rectangle.
+///
+/// ```rust
+/// use crow::IRect;
+///
+/// let a = IRect::new((0, 0), (4, 4));
+/// let b = IRect::from_corners((6, 6), (2, 2));
+/// assert_eq!(b, IRect::new((2, 2), (5, 5)));
+///
+/// assert_eq!(a.intersection(b), Some(IRect::new((2, 2), (2, 2))));
+/// assert!(a.contains((3, 3)));
+/// assert!(!a.contains((4, 3)));
+/// assert_eq!(a.inflate(1), IRect::new((-1, -1), (6, 6)));
+/// // the top row of a target with a height of 10
+/// assert_eq!(IRect::new((0, 0), (4, 1)).flip_y(10), IRect::new((0, 9), (4, 1)));
+/// ```
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct IRect {
+    /// The leftmost column of the rectangle.
+    pub x: i32,
+    /// The lowest row of the rectangle.
+    pub y: i32,
+    /// The number of columns of the rectangle.
+    pub width: u32,
+    /// The number of rows of the rectangle.
+    pub height: u32,
+}
+
+impl IRect {
+    /// Creates a rectangle with its lower left pixel at `position`.
+    pub fn new(position: impl Into<(i32, i32)>, size: impl Into<(u32, u32)>) -> Self {
+        let ((x, y), (width, height)) = (position.into(), size.into());
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Creates the smallest rectangle containing both `a` and `b`.
+    ///
+    /// The order of the two corners does not matter.
+    pub fn from_corners(a: impl Into<(i32, i32)>, b: impl Into<(i32, i32)>) -> Self {
+        let (a, b) = (a.into(), b.into());
+        let (left, bottom) = (cmp::min(a.0, b.0), cmp::min(a.1, b.1));
+        Self::new(
+            (left, bottom),
+            (
+                (a.0 - b.0).unsigned_abs() + 1,
+                (a.1 - b.1).unsigned_abs() + 1,
+            ),
+        )
+    }
+
+    /// Returns the lower left pixel of the rectangle.
+    pub fn position(self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    /// Returns the width and height of the rectangle.
+    pub fn size(self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns the leftmost column of the rectangle.
+    pub fn left(self) -> i32 {
+        self.x
+    }
+
+    /// Returns the lowest row of the rectangle.
+    pub fn bottom(self) -> i32 {
+        self.y
+    }
+
+    /// Returns the first column to the right of the rectangle.
+    pub fn right(self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    /// Returns the first row above the rectangle.
+    pub fn top(self) -> i32 {
+        self.y + self.height as i32
+    }
+
+    /// Returns `true` if the rectangle does not contain any pixels.
+    pub fn is_empty(self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns `true` if the pixel at `point` is part of the rectangle.
+    pub fn contains(self, point: impl Into<(i32, i32)>) -> bool {
+        let (x, y) = point.into();
+        self.left() <= x && x < self.right() && self.bottom() <= y && y < self.top()
+    }
+
+    /// Returns the pixels contained in both `self` and `other`,
+    /// or `None` if they do not overlap.
+    pub fn intersection(self, other: impl Into<IRect>) -> Option<Self> {
+        let other = other.into();
+        let (left, bottom) = (
+            cmp::max(self.left(), other.left()),
+            cmp::max(self.bottom(), other.bottom()),
+        );
+        let (right, top) = (
+            cmp::min(self.right(), other.right()),
+            cmp::min(self.top(), other.top()),
+        );
+        if left < right && bottom < top {
+            Some(Self::new(
+                (left, bottom),
+                ((right - left) as u32, (top - bottom) as u32),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Moves each side of the rectangle outwards by `amount`,
+    /// shrinking it if `amount` is negative.
+    ///
+    /// The size of the rectangle does not go below `0`.
+    pub fn inflate(self, amount: i32) -> Self {
+        let grow = |size: u32| cmp::max(i64::from(size) + 2 * i64::from(amount), 0) as u32;
+        Self::new(
+            (self.x - amount, self.y - amount),
+            (grow(self.width), grow(self.height)),
+        )
+    }
+
+    /// Mirrors the rectangle inside of an area with the given `height`,
+    /// converting between a bottom left and a top left origin.
+    pub fn flip_y(self, height: u32) -> Self {
+        Self::new((self.x, height as i32 - self.top()), self.size())
+    }
+
+    /// Moves the rectangle by `offset`.
+    pub fn translate(self, offset: impl Into<(i32, i32)>) -> Self {
+        let offset = offset.into();
+        Self::new((self.x + offset.0, self.y + offset.1), self.size())
+    }
+}
+
+impl<P: Into<(i32, i32)>, S: Into<(u32, u32)>> From<(P, S)> for IRect {
+    fn from((position, size): (P, S)) -> Self {
+        Self::new(position, size)
+    }
+}
+
+impl From<Rect> for IRect {
+    fn from(rect: Rect) -> Self {
+        Self::new((rect.x as i32, rect.y as i32), rect.size())
+    }
+}
+
+/// An axis-aligned section of a `Texture`, used by `Texture::get_section`.
+///
+/// The section contains all pixels from `x` to `x + width - 1` and
+/// from `y` to `y + height - 1`, starting at the bottom left.
+///
+/// Converts from a tuple of a position and a size, so both
+/// `Rect::new((4, 2), (8, 8))` and `((4, 2), (8, 8))` can be used
+/// to specify a section.
+///
+/// ```rust
+/// use crow::Rect;
+///
+/// let tile = Rect::new((16, 0), (16, 16));
+/// assert!(tile.contains((31, 15)));
+/// assert_eq!(
+///     tile.intersection(Rect::new((0, 8), (24, 24))),
+///     Some(Rect::new((16, 8), (8, 8)))
+/// );
+/// ```
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rect {
+    /// The leftmost column of the section.
+    pub x: u32,
+    /// The lowest row of the section.
+    pub y: u32,
+    /// The number of columns of the section.
+    pub width: u32,
+    /// The number of rows of the section.
+    pub height: u32,
+}
+
+impl Rect {
+    /// Creates a section with its lower left pixel at `position`.
+    pub fn new(position: impl Into<(u32, u32)>, size: impl Into<(u32, u32)>) -> Self {
+        let ((x, y), (width, height)) = (position.into(), size.into());
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Creates the smallest section containing both `a` and `b`.
+    ///
+    /// The order of the two corners does not matter.
+    pub fn from_corners(a: impl Into<(u32, u32)>, b: impl Into<(u32, u32)>) -> Self {
+        let (a, b) = (a.into(), b.into());
+        let (left, bottom) = (cmp::min(a.0, b.0), cmp::min(a.1, b.1));
+        let (right, top) = (cmp::max(a.0, b.0), cmp::max(a.1, b.1));
+        Self::new((left, bottom), (right - left + 1, top - bottom + 1))
+    }
+
+    /// Returns the lower left pixel of the section.
+    pub fn position(self) -> (u32, u32) {
+        (self.x, self.y)
+    }
+
+    /// Returns the width and height of the section.
+    pub fn size(self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns the leftmost column of the section.
+    pub fn left(self) -> u32 {
+        self.x
+    }
+
+    /// Returns the lowest row of the section.
+    pub fn bottom(self) -> u32 {
+        self.y
+    }
+
+    /// Returns the first column to the right of the section.
+    pub fn right(self) -> u32 {
+        self.x + self.width
+    }
+
+    /// Returns the first row above the section.
+    pub fn top(self) -> u32 {
+        self.y + self.height
+    }
+
+    /// Returns `true` if the section does not contain any pixels.
+    pub fn is_empty(self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns `true` if the pixel at `point` is part of the section.
+    pub fn contains(self, point: impl Into<(u32, u32)>) -> bool {
+        let (x, y) = point.into();
+        self.left() <= x && x < self.right() && self.bottom() <= y && y < self.top()
+    }
+
+    /// Returns the pixels contained in both `self` and `other`,
+    /// or `None` if they do not overlap.
+    pub fn intersection(self, other: impl Into<Rect>) -> Option<Self> {
+        let other = other.into();
+        let (left, bottom) = (
+            cmp::max(self.left(), other.left()),
+            cmp::max(self.bottom(), other.bottom()),
+        );
+        let (right, top) = (
+            cmp::min(self.right(), other.right()),
+            cmp::min(self.top(), other.top()),
+        );
+        if left < right && bottom < top {
+            Some(Self::new((left, bottom), (right - left, top - bottom)))
+        } else {
+            None
+        }
+    }
+
+    /// Mirrors the section inside of an area with the given `height`,
+    /// converting between a bottom left and a top left origin.
+    ///
+    /// This can be used to specify sections of textures using image coordinates.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the section does not fit into `height`.
+    pub fn flip_y(self, height: u32) -> Self {
+        let y = height
+            .checked_sub(self.top())
+            .expect("section does not fit into the given height");
+        Self::new((self.x, y), self.size())
+    }
+
+    /// Moves each side of the section outwards by `amount`,
+    /// shrinking it if `amount` is negative.
+    ///
+    /// Sides are not moved below `0` and the size of the
+    /// section does not go below `0`.
+    pub fn inflate(self, amount: i32) -> Self {
+        let (left, bottom) = (
+            cmp::max(i64::from(self.left()) - i64::from(amount), 0),
+            cmp::max(i64::from(self.bottom()) - i64::from(amount), 0),
+        );
+        let (right, top) = (
+            cmp::max(i64::from(self.right()) + i64::from(amount), left),
+            cmp::max(i64::from(self.top()) + i64::from(amount), bottom),
+        );
+        Self::new(
+            (left as u32, bottom as u32),
+            ((right - left) as u32, (top - bottom) as u32),
+        )
+    }
+}
+
+impl<P: Into<(u32, u32)>, S: Into<(u32, u32)>> From<(P, S)> for Rect {
+    fn from((position, size): (P, S)) -> Self {
+        Self::new(position, size)
+    }
+}
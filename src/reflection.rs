@@ -0,0 +1,94 @@
+use std::f32::consts::PI;
+
+use crate::{Context, DrawConfig, DrawTarget, Texture};
+
+/// Draws a rippling reflection of the scene above a waterline.
+///
+/// The reflection is drawn row by row, offsetting each row horizontally
+/// to create the ripples, so this is only meant to be used for
+/// low resolution scenes.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, Reflection, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let mut scene = Texture::new(&mut ctx, (320, 240))?;
+/// // draw the scene above the waterline...
+///
+/// let reflection = Reflection {
+///     opacity: 0.4,
+///     ..Reflection::new(80, 80)
+/// };
+/// let time = 1.5;
+/// let source = scene.clone();
+/// reflection.draw(&mut ctx, &mut scene, &source, time);
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reflection {
+    /// The lowest row of the scene which is reflected.
+    pub waterline: i32,
+    /// The number of rows below the waterline which contain the reflection.
+    pub height: u32,
+    /// The opacity of the reflection, in the range `0.0..=1.0`.
+    pub opacity: f32,
+    /// The maximum horizontal offset of each row in pixels.
+    pub amplitude: f32,
+    /// The number of rows covered by a single ripple.
+    pub wavelength: f32,
+    /// The number of ripples which pass each row per second.
+    pub speed: f32,
+}
+
+impl Reflection {
+    /// Creates a new reflection below `waterline` with the given `height`.
+    pub fn new(waterline: i32, height: u32) -> Self {
+        Reflection {
+            waterline,
+            height,
+            opacity: 0.5,
+            amplitude: 1.0,
+            wavelength: 8.0,
+            speed: 1.0,
+        }
+    }
+
+    /// Draws the reflection of `scene` onto `target` at the given `time` in seconds.
+    ///
+    /// `target` is generally the texture containing `scene`, in which case
+    /// `scene` should be a clone of `target`.
+    pub fn draw<T>(&self, ctx: &mut Context, target: &mut T, scene: &Texture, time: f32)
+    where
+        T: DrawTarget,
+    {
+        let config = DrawConfig {
+            color_modulation: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, self.opacity],
+            ],
+            ..Default::default()
+        };
+
+        for row in 0..self.height as i32 {
+            let source = self.waterline + row;
+            if source < 0 || source >= scene.height() as i32 {
+                continue;
+            }
+
+            let phase = (row as f32 / self.wavelength + time * self.speed) * 2.0 * PI;
+            let offset = (phase.sin() * self.amplitude).round() as i32;
+            let section = scene.get_section(((0, source as u32), (scene.width(), 1)));
+            ctx.draw(
+                target,
+                &section,
+                (offset, self.waterline - 1 - row),
+                &config,
+            );
+        }
+    }
+}
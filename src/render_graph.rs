@@ -0,0 +1,462 @@
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    backend::{Backend, TimerQuery},
+    Context, DrawTarget, NewTextureError, Shader, Texture, UniformValue,
+};
+
+/// A texture owned by a [`RenderGraph`], which only exists while executing the graph.
+///
+/// [`RenderGraph`]: struct.RenderGraph.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransientId(usize);
+
+/// A uniform of a pass of a [`RenderGraph`].
+///
+/// [`RenderGraph`]: struct.RenderGraph.html
+#[derive(Debug, Clone)]
+pub enum PassInput {
+    /// A uniform which is not a texture.
+    Value(UniformValue<'static>),
+    /// A texture which is not part of the graph.
+    Texture(Texture),
+    /// A transient texture written by a different pass.
+    Transient(TransientId),
+}
+
+/// Where a pass of a [`RenderGraph`] writes its result to.
+///
+/// [`RenderGraph`]: struct.RenderGraph.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PassOutput {
+    /// A transient texture, which must not be written by any other pass.
+    Transient(TransientId),
+    /// The target used when executing the graph, for example the window.
+    ///
+    /// Passes writing to the target are executed in the order they were added.
+    Target,
+}
+
+/// How long a single pass took during the last execution of a [`RenderGraph`].
+///
+/// [`RenderGraph`]: struct.RenderGraph.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PassTiming {
+    /// The index of the pass, counting the calls to `RenderGraph::add_pass`
+    /// since the last call to `RenderGraph::clear`.
+    pub pass: usize,
+    /// The time spent on the CPU submitting this pass.
+    pub cpu: Duration,
+    /// The time spent on the GPU executing this pass,
+    /// `None` if the GPU has not yet finished this pass.
+    pub gpu: Option<Duration>,
+}
+
+/// The timings of executed passes, together with the GPU queries whose results are not yet known.
+#[derive(Debug, Default)]
+pub(crate) struct PassTimings {
+    timings: Vec<PassTiming>,
+    queries: Vec<Option<Rc<TimerQuery>>>,
+}
+
+impl Clone for PassTimings {
+    fn clone(&self) -> Self {
+        PassTimings {
+            timings: self.timings.clone(),
+            // the GPU time of a clone is never available
+            queries: self.queries.iter().map(|_| None).collect(),
+        }
+    }
+}
+
+impl PassTimings {
+    fn clear(&mut self) {
+        self.timings.clear();
+        self.queries.clear();
+    }
+
+    fn push(&mut self, timing: PassTiming, query: Rc<TimerQuery>) {
+        self.timings.push(timing);
+        self.queries.push(Some(query));
+    }
+
+    /// Updates the GPU time of each pass whose query is available, without blocking.
+    pub(crate) fn resolve(&mut self, backend: &mut Backend) -> &[PassTiming] {
+        for (timing, query) in self.timings.iter_mut().zip(self.queries.iter_mut()) {
+            if let Some(gpu) = query.as_ref().and_then(|q| q.result(backend)) {
+                timing.gpu = Some(gpu);
+                *query = None;
+            }
+        }
+        &self.timings
+    }
+
+    /// Calls `line` with the start, end and color of each line of a bar graph of these timings,
+    /// see `RenderGraph::draw_timings`.
+    pub(crate) fn draw(
+        &self,
+        position: (i32, i32),
+        height: u32,
+        budget: Duration,
+        mut line: impl FnMut((i32, i32), (i32, i32), (f32, f32, f32, f32)),
+    ) {
+        if height == 0 {
+            return;
+        }
+
+        let budget = budget.as_secs_f64();
+        for (i, timing) in self.timings.iter().enumerate() {
+            let x = position.0 + 3 * i as i32;
+            let bars = [
+                (0, Some(timing.cpu), (0.0, 0.0, 1.0, 1.0)),
+                (1, timing.gpu, (0.0, 1.0, 0.0, 1.0)),
+            ];
+            for &(offset, time, color) in bars.iter() {
+                let fraction = time.map_or(0.0, |time| time.as_secs_f64() / budget);
+                let color = if fraction > 1.0 {
+                    (1.0, 0.0, 0.0, 1.0)
+                } else {
+                    color
+                };
+                let bar_height = (fraction.min(1.0) * f64::from(height)).ceil() as i32;
+                if bar_height > 0 {
+                    line(
+                        (x + offset, position.1),
+                        (x + offset, position.1 + bar_height - 1),
+                        color,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Pass {
+    shader: Shader,
+    inputs: Vec<(String, PassInput)>,
+    output: PassOutput,
+}
+
+impl Pass {
+    fn transient_inputs(&self) -> impl Iterator<Item = TransientId> + '_ {
+        self.inputs.iter().filter_map(|(_, input)| match *input {
+            PassInput::Transient(id) => Some(id),
+            _ => None,
+        })
+    }
+}
+
+/// A list of fullscreen shader passes which are scheduled by crow.
+///
+/// Passes read from textures and transient textures and write to either
+/// a transient texture or the target of `RenderGraph::execute`.
+/// Passes are executed after all passes writing to their inputs, passes using the same
+/// shader are executed together if possible to reduce the number of state changes.
+///
+/// Transient textures are stored by the graph and reused once all passes reading them
+/// have been executed. They are kept between executions, so a graph should be reused
+/// for each frame, using `RenderGraph::clear` to change its passes.
+///
+/// The time spent on each pass is measured and can be accessed using
+/// `RenderGraph::timings` or drawn using `RenderGraph::draw_timings`.
+/// The timings of all graphs executed during a frame are also returned by
+/// `Context::last_frame_pass_timings` and drawn by `DrawCapture::Overlay`.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, PassInput, PassOutput, RenderGraph, Shader, Texture, UniformValue,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let scene = Texture::load(&mut ctx, "./textures/scene.png")?;
+/// # let (blur, combine) = (Shader::new(""), Shader::new(""));
+///
+/// let mut graph = RenderGraph::new();
+/// let horizontal = graph.transient(scene.dimensions());
+/// let vertical = graph.transient(scene.dimensions());
+/// graph.add_pass(
+///     &blur,
+///     &[
+///         ("source", PassInput::Texture(scene.clone())),
+///         ("direction", PassInput::Value(UniformValue::Vec2(1.0, 0.0))),
+///     ],
+///     PassOutput::Transient(horizontal),
+/// );
+/// graph.add_pass(
+///     &blur,
+///     &[
+///         ("source", PassInput::Transient(horizontal)),
+///         ("direction", PassInput::Value(UniformValue::Vec2(0.0, 1.0))),
+///     ],
+///     PassOutput::Transient(vertical),
+/// );
+/// graph.add_pass(
+///     &combine,
+///     &[
+///         ("scene", PassInput::Texture(scene.clone())),
+///         ("bloom", PassInput::Transient(vertical)),
+///     ],
+///     PassOutput::Target,
+/// );
+///
+/// let mut surface = ctx.surface();
+/// graph.execute(&mut ctx, &mut surface)?;
+/// ctx.present(surface)?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RenderGraph {
+    passes: Vec<Pass>,
+    /// The dimensions of each transient texture.
+    transients: Vec<(u32, u32)>,
+    /// The textures used for transient textures, which are `None` while being drawn to.
+    slots: Vec<Option<Texture>>,
+    /// The slot containing each transient texture after the last execution,
+    /// `None` if the texture has been reused.
+    results: Vec<Option<usize>>,
+    /// The timings of the last execution, in the order the passes were executed.
+    timings: PassTimings,
+}
+
+impl RenderGraph {
+    /// Creates an empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a transient texture with the given `dimensions`.
+    pub fn transient(&mut self, dimensions: impl Into<(u32, u32)>) -> TransientId {
+        self.transients.push(dimensions.into());
+        TransientId(self.transients.len() - 1)
+    }
+
+    /// Adds a pass which overwrites `output` using `shader`, see `Context::draw_fullscreen`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `output` is already written by a different pass,
+    /// if it is also used as an input or if a transient texture is not part of this graph.
+    pub fn add_pass(&mut self, shader: &Shader, inputs: &[(&str, PassInput)], output: PassOutput) {
+        let pass = Pass {
+            shader: shader.clone(),
+            inputs: inputs
+                .iter()
+                .map(|(name, input)| (name.to_string(), input.clone()))
+                .collect(),
+            output,
+        };
+
+        for id in pass.transient_inputs() {
+            assert!(id.0 < self.transients.len(), "unknown transient texture");
+        }
+        if let PassOutput::Transient(id) = output {
+            assert!(id.0 < self.transients.len(), "unknown transient texture");
+            assert!(
+                self.passes.iter().all(|p| p.output != output),
+                "a transient texture must only be written by a single pass"
+            );
+            assert!(
+                pass.transient_inputs().all(|input| input != id),
+                "a pass must not read its own output"
+            );
+        }
+        self.passes.push(pass);
+    }
+
+    /// Removes all passes and transient textures,
+    /// keeping the allocated textures for future passes.
+    pub fn clear(&mut self) {
+        self.passes.clear();
+        self.transients.clear();
+        self.results.clear();
+        self.timings.clear();
+    }
+
+    /// Returns the transient texture `id` after the last execution of this graph.
+    ///
+    /// This is `None` if the texture has been read by a different pass,
+    /// as its texture is then reused for later passes.
+    pub fn texture(&self, id: TransientId) -> Option<&Texture> {
+        let slot = (*self.results.get(id.0)?)?;
+        self.slots[slot].as_ref()
+    }
+
+    /// Returns the indices of all passes in the order they are executed.
+    fn schedule(&self) -> Vec<usize> {
+        let writer = |id: TransientId| {
+            self.passes
+                .iter()
+                .position(|pass| pass.output == PassOutput::Transient(id))
+                .expect("a transient texture is read but never written")
+        };
+
+        let mut dependencies: Vec<Vec<usize>> = self
+            .passes
+            .iter()
+            .map(|pass| pass.transient_inputs().map(writer).collect())
+            .collect();
+        // passes writing to the target keep their order
+        let mut previous_target = None;
+        for (i, pass) in self.passes.iter().enumerate() {
+            if pass.output == PassOutput::Target {
+                dependencies[i].extend(previous_target);
+                previous_target = Some(i);
+            }
+        }
+
+        let mut done = vec![false; self.passes.len()];
+        let mut order: Vec<usize> = Vec::with_capacity(self.passes.len());
+        while order.len() < self.passes.len() {
+            let mut ready = (0..self.passes.len())
+                .filter(|&i| !done[i] && dependencies[i].iter().all(|&d| done[d]));
+            let first = ready
+                .next()
+                .expect("the passes of a render graph must not depend on each other");
+            let last_shader = order.last().map(|&i| &self.passes[i].shader);
+            let next = if last_shader == Some(&self.passes[first].shader) {
+                first
+            } else {
+                ready
+                    .find(|&i| Some(&self.passes[i].shader) == last_shader)
+                    .unwrap_or(first)
+            };
+            done[next] = true;
+            order.push(next);
+        }
+        order
+    }
+
+    /// Executes all passes of this graph, writing to `target` if a pass uses `PassOutput::Target`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a transient texture is read but never written
+    /// or if passes depend on each other.
+    pub fn execute<T>(&mut self, ctx: &mut Context, target: &mut T) -> Result<(), NewTextureError>
+    where
+        T: DrawTarget,
+    {
+        let order = self.schedule();
+        let mut last_read = vec![None; self.transients.len()];
+        for (step, &i) in order.iter().enumerate() {
+            for id in self.passes[i].transient_inputs() {
+                last_read[id.0] = Some(step);
+            }
+        }
+
+        self.timings.clear();
+        let mut free: Vec<usize> = (0..self.slots.len()).collect();
+        let mut assigned = vec![None; self.transients.len()];
+        for (step, &i) in order.iter().enumerate() {
+            let pass = &self.passes[i];
+            let output_slot = match pass.output {
+                PassOutput::Transient(id) => {
+                    let dimensions = self.transients[id.0];
+                    let slot = match free.iter().position(|&s| {
+                        self.slots[s].as_ref().map(Texture::dimensions) == Some(dimensions)
+                    }) {
+                        Some(index) => free.remove(index),
+                        None => {
+                            self.slots.push(Some(Texture::new(ctx, dimensions)?));
+                            self.slots.len() - 1
+                        }
+                    };
+                    assigned[id.0] = Some(slot);
+                    Some(slot)
+                }
+                PassOutput::Target => None,
+            };
+
+            let slots = &mut self.slots;
+            let mut output = output_slot.map(|slot| slots[slot].take().unwrap());
+            let slots = &*slots;
+            let uniforms: Vec<(&str, UniformValue<'_>)> = pass
+                .inputs
+                .iter()
+                .map(|(name, input)| {
+                    let value = match input {
+                        PassInput::Value(value) => *value,
+                        PassInput::Texture(texture) => UniformValue::Texture(texture),
+                        PassInput::Transient(id) => {
+                            let slot = assigned[id.0].unwrap();
+                            UniformValue::Texture(slots[slot].as_ref().unwrap())
+                        }
+                    };
+                    (name.as_str(), value)
+                })
+                .collect();
+
+            let query = TimerQuery::begin(&mut ctx.backend);
+            let start = Instant::now();
+            match output {
+                Some(ref mut texture) => ctx.draw_fullscreen(texture, &pass.shader, &uniforms),
+                None => ctx.draw_fullscreen(target, &pass.shader, &uniforms),
+            }
+            query.end(&mut ctx.backend);
+            let timing = PassTiming {
+                pass: i,
+                cpu: start.elapsed(),
+                gpu: None,
+            };
+            let query = Rc::new(query);
+            ctx.pass_timings.push(timing, Rc::clone(&query));
+            self.timings.push(timing, query);
+            if let (Some(slot), Some(texture)) = (output_slot, output) {
+                self.slots[slot] = Some(texture);
+            }
+
+            for id in pass.transient_inputs() {
+                if last_read[id.0] == Some(step) {
+                    // each input is only released once
+                    if let Some(slot) = assigned[id.0] {
+                        if !free.contains(&slot) {
+                            free.push(slot);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.results = (0..self.transients.len())
+            .map(|id| assigned[id].filter(|_| last_read[id].is_none()))
+            .collect();
+        Ok(())
+    }
+
+    /// Returns the time spent on each pass during the last execution,
+    /// in the order the passes were executed.
+    ///
+    /// The GPU executes passes asynchronously, so `PassTiming::gpu` is only
+    /// available once the GPU is done, which can be awaited using `Context::finish`.
+    pub fn timings(&mut self, ctx: &mut Context) -> &[PassTiming] {
+        self.timings.resolve(&mut ctx.backend)
+    }
+
+    /// Draws the timings of the last execution onto `target` as a bar graph, starting at `position`.
+    ///
+    /// Each pass uses two vertical lines, the CPU time in blue and the GPU time in green,
+    /// followed by a gap of one pixel, so the graph is `3 * passes` pixels wide.
+    /// The graph is `height` pixels high, which corresponds to a duration of `budget`.
+    /// Bars exceeding `budget` are drawn in red and are cut off at the top of the graph.
+    pub fn draw_timings<T>(
+        &mut self,
+        ctx: &mut Context,
+        target: &mut T,
+        position: (i32, i32),
+        height: u32,
+        budget: Duration,
+    ) where
+        T: DrawTarget,
+    {
+        self.timings(ctx);
+        self.timings
+            .draw(position, height, budget, |from, to, color| {
+                ctx.debug_line(target, from, to, color)
+            });
+    }
+}
@@ -0,0 +1,28 @@
+/// A xorshift random number generator, used where the results
+/// must only depend on a given seed, for example for the noise texture.
+#[derive(Debug, Clone)]
+pub struct Rng(u32);
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        // xorshift never leaves the state `0`
+        Rng(if seed == 0 { 0x2545_f491 } else { seed })
+    }
+
+    pub fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    /// Returns a value in the range `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// Returns a value in the range `min..=max`, or `max..=min` if `max` is smaller than `min`.
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// How the color between two pixels of a texture is computed.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Filter {
+    /// Uses the color of the closest pixel, keeping pixel art crisp.
+    #[default]
+    Nearest,
+    /// Interpolates between the four closest pixels.
+    Linear,
+}
+
+/// How a texture is sampled outside of its bounds.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Wrap {
+    /// Uses the color of the closest edge.
+    #[default]
+    ClampToEdge,
+    /// Repeats the texture.
+    Repeat,
+    /// Repeats the texture, mirroring every second repetition.
+    MirroredRepeat,
+}
+
+/// Overrides how a texture is sampled while drawing it, see `DrawConfig::sampler`.
+///
+/// This allows drawing the same texture with different settings, for example
+/// using `Filter::Nearest` for the game world and `Filter::Linear` for the UI.
+///
+/// Each distinct sampler is created once per `Context` and then reused.
+///
+/// ```rust
+/// use crow::{DrawConfig, Filter, Sampler};
+///
+/// let smooth = DrawConfig {
+///     scale: (3, 3),
+///     sampler: Some(Sampler {
+///         min_filter: Filter::Linear,
+///         mag_filter: Filter::Linear,
+///         anisotropy: 4,
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// ```
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sampler {
+    /// The filter used if the texture is drawn smaller than its actual size.
+    ///
+    /// This also uses the mipmaps of textures created with `TextureOptions::mipmaps`.
+    pub min_filter: Filter,
+    /// The filter used if the texture is drawn larger than its actual size.
+    pub mag_filter: Filter,
+    /// The wrap mode of the x and y axis.
+    ///
+    /// A `Texture::border_color` is still used outside of the
+    /// bounds of a section, regardless of the wrap mode.
+    pub wrap: (Wrap, Wrap),
+    /// The maximum degree of anisotropic filtering, `1` disables it.
+    ///
+    /// This is clamped to `GlConstants::max_anisotropy` and
+    /// ignored if anisotropic filtering is not supported.
+    pub anisotropy: u32,
+}
+
+impl Default for Sampler {
+    /// Uses the same settings as a texture without a sampler.
+    fn default() -> Self {
+        Sampler {
+            min_filter: Filter::Nearest,
+            mag_filter: Filter::Nearest,
+            wrap: (Wrap::ClampToEdge, Wrap::ClampToEdge),
+            anisotropy: 1,
+        }
+    }
+}
+
+impl Sampler {
+    /// Returns a sampler using `Filter::Linear` for both `min_filter` and `mag_filter`.
+    pub fn linear() -> Self {
+        Sampler {
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            ..Default::default()
+        }
+    }
+}
+
+/// How a texture is sampled by default, see `Texture::new_with`.
+///
+/// Unlike a [`Sampler`], these options are part of the texture itself,
+/// so they are used whenever the texture is drawn without a `DrawConfig::sampler`.
+///
+/// ```rust
+/// use crow::{Filter, TextureOptions};
+///
+/// let smooth = TextureOptions {
+///     min_filter: Filter::Linear,
+///     mag_filter: Filter::Linear,
+///     mipmaps: true,
+///     ..Default::default()
+/// };
+/// assert_ne!(smooth, TextureOptions::default());
+/// ```
+///
+/// [`Sampler`]: struct.Sampler.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextureOptions {
+    /// The filter used if the texture is drawn smaller than its actual size.
+    pub min_filter: Filter,
+    /// The filter used if the texture is drawn larger than its actual size.
+    pub mag_filter: Filter,
+    /// If the texture stores mipmaps, which are used while drawing it smaller than
+    /// its actual size to prevent aliasing.
+    ///
+    /// Mipmaps are generated when creating the texture from an image. They are not
+    /// updated automatically when drawing to the texture, which requires
+    /// a call to `Texture::generate_mipmaps` instead.
+    pub mipmaps: bool,
+    /// The wrap mode of the x and y axis, see `Context::draw_tiled`.
+    ///
+    /// This is ignored while the texture has a `border_color`.
+    pub wrap: (Wrap, Wrap),
+}
@@ -0,0 +1,364 @@
+use gl::types::GLuint;
+
+use crate::{backend::Uniform, Context, DrawTarget, ShaderError, SkipDebug, Texture};
+
+/// A user defined fragment shader, which computes each pixel of its target.
+///
+/// The shader must be written in GLSL 3.30 and write its result to an `out vec4`.
+/// The position of the current pixel is available using `gl_FragCoord` and relative to the
+/// size of the target using `in vec2 v_uv`, which is `(0.0, 0.0)` at the bottom left and
+/// `(1.0, 1.0)` at the top right corner. The size of the target is available
+/// using the uniform `vec2 target_dimensions` and the seconds
+/// since the context has been created using `float u_time`, see `Context::shader_time`.
+/// A tileable noise texture is available as `sampler2D u_noise`, see `Context::noise_texture`.
+///
+/// Shaders created using `Shader::new` are compiled when they are first used by a `Context`,
+/// shaders which fail to compile are skipped and the error is logged.
+/// Use `Shader::compile` to handle compilation errors instead.
+///
+/// All [`ShaderGlobals`] of the context are also available as uniforms.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, Shader, Texture, UniformValue,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+///
+/// let stripes = Shader::new(
+///     r#"#version 330
+///     out vec4 color;
+///     uniform float width;
+///     uniform vec4 stripe_color;
+///
+///     void main() {
+///         bool stripe = mod(gl_FragCoord.x, 2.0 * width) < width;
+///         color = stripe ? stripe_color : vec4(0.0);
+///     }"#,
+/// );
+///
+/// let mut texture = Texture::new(&mut ctx, (64, 64))?;
+/// ctx.draw_shader(
+///     &mut texture,
+///     &stripes,
+///     &[
+///         ("width", UniformValue::Float(4.0)),
+///         ("stripe_color", UniformValue::Vec4(1.0, 0.0, 0.0, 1.0)),
+///     ],
+/// );
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`ShaderGlobals`]: struct.ShaderGlobals.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shader {
+    fragment: String,
+}
+
+impl Shader {
+    /// Creates a new shader using the fragment shader source `fragment`.
+    pub fn new<S: Into<String>>(fragment: S) -> Self {
+        Shader {
+            fragment: fragment.into(),
+        }
+    }
+
+    /// Creates a new shader using the fragment shader source `fragment`
+    /// and compiles it right away, returning the info log of OpenGL on failure.
+    pub fn compile<S: Into<String>>(ctx: &mut Context, fragment: S) -> Result<Self, ShaderError> {
+        let fragment = fragment.into();
+        ctx.backend.pass_program(&fragment)?;
+        Ok(Shader { fragment })
+    }
+
+    /// Returns the source of the fragment shader.
+    pub fn fragment(&self) -> &str {
+        &self.fragment
+    }
+}
+
+/// The value of a named uniform of a [`Shader`].
+///
+/// [`Shader`]: struct.Shader.html
+#[derive(Debug, Clone, Copy)]
+pub enum UniformValue<'a> {
+    /// A `float`.
+    Float(f32),
+    /// A `vec2`.
+    Vec2(f32, f32),
+    /// A `vec3`.
+    Vec3(f32, f32, f32),
+    /// A `vec4`.
+    Vec4(f32, f32, f32, f32),
+    /// A `mat4`, using the same row-major layout as `DrawConfig::color_modulation`.
+    Mat4([[f32; 4]; 4]),
+    /// A `sampler2D`.
+    ///
+    /// If the texture is a section of a larger texture, for example
+    /// after using `Texture::get_section`, the whole texture is bound.
+    Texture(&'a Texture),
+}
+
+/// Uniforms which are set for every [`Shader`], for example the current time,
+/// the offset of a screen shake or the intensity of a flash.
+///
+/// Globals are usually updated once per frame by a callback registered using
+/// `Context::on_update_shader_globals`. As uniforms keep their value until they are
+/// changed, globals only cause OpenGL calls after they have been changed.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, UniformValue,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+///
+/// let mut time = 0.0;
+/// ctx.on_update_shader_globals(move |globals| {
+///     time += 1.0 / 60.0;
+///     globals.set("time", UniformValue::Float(time));
+/// });
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`Shader`]: struct.Shader.html
+#[derive(Debug, Clone, Default)]
+pub struct ShaderGlobals {
+    values: Vec<(String, UniformValue<'static>)>,
+}
+
+impl ShaderGlobals {
+    /// Sets the global `name` to `value`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `value` is a `UniformValue::Texture`.
+    pub fn set<S: Into<String>>(&mut self, name: S, value: UniformValue<'static>) {
+        assert!(
+            !matches!(value, UniformValue::Texture(_)),
+            "textures can not be used as shader globals"
+        );
+        let name = name.into();
+        match self.values.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => self.values.push((name, value)),
+        }
+    }
+
+    /// Returns the value of the global `name`.
+    pub fn get(&self, name: &str) -> Option<UniformValue<'static>> {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, value)| value)
+    }
+
+    /// Removes the global `name`, returning its previous value.
+    ///
+    /// Shaders keep using the last value of this uniform.
+    pub fn remove(&mut self, name: &str) -> Option<UniformValue<'static>> {
+        let index = self.values.iter().position(|(n, _)| n == name)?;
+        Some(self.values.remove(index).1)
+    }
+
+    /// Returns an iterator over all globals.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, UniformValue<'static>)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Returns all globals for which `used` is `false` as uniforms of a pass.
+    pub(crate) fn pass_uniforms<'a, F>(
+        &'a self,
+        used: F,
+    ) -> impl Iterator<Item = (&'a str, Uniform)>
+    where
+        F: 'a + Fn(&str) -> bool,
+    {
+        self.iter()
+            .filter(move |&(name, _)| !used(name))
+            .map(|(name, value)| {
+                let value = match value {
+                    UniformValue::Float(x) => Uniform::Float(x),
+                    UniformValue::Vec2(x, y) => Uniform::Vec2(x, y),
+                    UniformValue::Vec3(x, y, z) => Uniform::Vec3(x, y, z),
+                    UniformValue::Vec4(x, y, z, w) => Uniform::Vec4(x, y, z, w),
+                    UniformValue::Mat4(matrix) => Uniform::Mat4(matrix),
+                    UniformValue::Texture(_) => bug!("textures can not be used as shader globals"),
+                };
+                (name, value)
+            })
+    }
+}
+
+impl Context {
+    /// Registers a callback which updates the [`ShaderGlobals`], called by
+    /// [`Context::run`] and [`Context::frame`] at the start of each frame.
+    ///
+    /// [`ShaderGlobals`]: struct.ShaderGlobals.html
+    /// [`Context::run`]: struct.Context.html#method.run
+    /// [`Context::frame`]: struct.Context.html#method.frame
+    pub fn on_update_shader_globals<F>(&mut self, f: F)
+    where
+        F: 'static + FnMut(&mut ShaderGlobals),
+    {
+        self.on_update_shader_globals.push(SkipDebug(Box::new(f)));
+    }
+
+    /// Calls all callbacks registered using `Context::on_update_shader_globals`.
+    pub(crate) fn update_shader_globals(&mut self) {
+        self.call_hooks(
+            |ctx| &mut ctx.on_update_shader_globals,
+            |hook, ctx| hook(&mut ctx.shader_globals),
+        );
+    }
+
+    /// Returns the seconds between creating this context and presenting the last frame,
+    /// which is available to each `Shader` as the uniform `float u_time`.
+    ///
+    /// This value increases monotonically and is updated once per frame, so
+    /// all shaders of the same frame use the same time.
+    pub fn shader_time(&self) -> f32 {
+        self.backend.time()
+    }
+
+    /// Returns the tileable `32x32` noise texture created together with this context,
+    /// which is available to each `Shader` as the uniform `sampler2D u_noise`.
+    ///
+    /// The red channel contains blue noise, so each value is evenly spread out, which is
+    /// useful for dithering. The green and blue channel contain independent white noise,
+    /// for example for a dissolve effect. The alpha channel is always `1.0`.
+    ///
+    /// The content of this texture is the same for each context, so effects using it are deterministic.
+    /// As it covers only a small area, shaders usually sample it using
+    /// `texelFetch(u_noise, ivec2(gl_FragCoord.xy) % textureSize(u_noise, 0), 0)`.
+    pub fn noise_texture(&self) -> &Texture {
+        &self.noise_texture
+    }
+
+    /// Returns the uniforms which are set for every `Shader`.
+    pub fn shader_globals(&self) -> &ShaderGlobals {
+        &self.shader_globals
+    }
+
+    /// Returns the uniforms which are set for every `Shader`,
+    /// which can be used to change them outside of `Context::on_update_shader_globals`.
+    pub fn shader_globals_mut(&mut self) -> &mut ShaderGlobals {
+        &mut self.shader_globals
+    }
+
+    /// Overwrites each pixel of `target` using `shader`.
+    ///
+    /// This is the same as `Context::draw_fullscreen` for textures.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if more textures are used than supported by
+    /// `GlConstants::max_texture_units`. If `shader` does not compile,
+    /// nothing is drawn and the error is logged.
+    pub fn draw_shader(
+        &mut self,
+        target: &mut Texture,
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
+    ) {
+        self.draw_fullscreen(target, shader, uniforms)
+    }
+
+    /// Overwrites each pixel of `target` using `shader`, which is the building block
+    /// of post processing effects.
+    ///
+    /// Each uniform in `uniforms` and each of the [`ShaderGlobals`] is set before
+    /// running the shader, `uniforms` take precedence over globals with the same name.
+    /// Uniforms which are not used by `shader` are ignored. Uniforms
+    /// keep their value until they are changed, so unchanged values
+    /// do not cause any OpenGL calls.
+    ///
+    /// The whole target is overwritten, even when using a target adapter like `target::Offset`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if more textures are used than supported by
+    /// `GlConstants::max_texture_units`. If `shader` does not compile,
+    /// nothing is drawn and the error is logged.
+    ///
+    /// [`ShaderGlobals`]: struct.ShaderGlobals.html
+    pub fn draw_fullscreen<T>(
+        &mut self,
+        target: &mut T,
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
+    ) where
+        T: DrawTarget,
+    {
+        target.receive_fullscreen(self, shader, uniforms)
+    }
+
+    /// Runs `shader` for each pixel of the framebuffer `framebuffer_id`, using `dimensions` as
+    /// the uniform `target_dimensions`, which differs from `viewport` for hidpi windows.
+    pub(crate) fn run_shader(
+        &mut self,
+        framebuffer_id: GLuint,
+        viewport: (u32, u32),
+        dimensions: (u32, u32),
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
+    ) {
+        let noise = Some(("u_noise", UniformValue::Texture(&self.noise_texture)))
+            .filter(|_| uniforms.iter().all(|&(name, _)| name != "u_noise"));
+        let texture_count = uniforms
+            .iter()
+            .chain(noise.iter())
+            .filter(|(_, value)| matches!(value, UniformValue::Texture(_)))
+            .count();
+        assert!(
+            texture_count < self.backend.constants().max_texture_units as usize,
+            "too many textures used by a shader: {}",
+            texture_count
+        );
+
+        let mut values = Vec::with_capacity(uniforms.len() + self.shader_globals.values.len() + 3);
+        for &(name, value) in uniforms.iter() {
+            let value = match value {
+                UniformValue::Float(x) => Uniform::Float(x),
+                UniformValue::Vec2(x, y) => Uniform::Vec2(x, y),
+                UniformValue::Vec3(x, y, z) => Uniform::Vec3(x, y, z),
+                UniformValue::Vec4(x, y, z, w) => Uniform::Vec4(x, y, z, w),
+                UniformValue::Mat4(matrix) => Uniform::Mat4(matrix),
+                UniformValue::Texture(texture) => {
+                    if let Err(err) = self.backend.validate(&texture.inner) {
+                        self.backend.report_error(err);
+                        return;
+                    }
+                    Uniform::Texture(texture.inner.id)
+                }
+            };
+            values.push((name, value));
+        }
+
+        // globals are borrowed, which is fine as they are disjoint from `self.backend`
+        values.extend(
+            self.shader_globals
+                .pass_uniforms(|name| uniforms.iter().any(|&(n, _)| n == name)),
+        );
+
+        if noise.is_some() {
+            values.push(("u_noise", Uniform::Texture(self.noise_texture.inner.id)));
+        }
+
+        if uniforms.iter().all(|&(name, _)| name != "u_time") {
+            values.push(("u_time", Uniform::Float(self.backend.time())));
+        }
+
+        values.push((
+            "target_dimensions",
+            Uniform::Vec2(dimensions.0 as f32, dimensions.1 as f32),
+        ));
+
+        self.backend
+            .run_pass(&shader.fragment, framebuffer_id, viewport, None, &values);
+    }
+}
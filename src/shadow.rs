@@ -0,0 +1,112 @@
+use crate::{backend::Uniform, Context, NewTextureError, Texture};
+
+static FRAGMENT: &str = include_str!("backend/shader/fragment_blur.glsl");
+
+/// Bakes a soft shadow or ambient occlusion gradient from the
+/// silhouette of a mask texture, for example a tilemap.
+///
+/// The shadow is computed once on the GPU by blurring the alpha channel of the mask,
+/// the resulting texture can then be drawn below the sprites each frame.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, DrawConfig, Shadow, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let tilemap = Texture::load(&mut ctx, "./textures/tilemap.png")?;
+///
+/// let shadow = Shadow::new(6);
+/// let baked = shadow.bake(&mut ctx, &tilemap)?;
+///
+/// let mut surface = ctx.surface();
+/// // the shadow is drawn slightly to the bottom right of the tilemap
+/// let (x, y) = shadow.position((0, 0));
+/// ctx.draw(&mut surface, &baked, (x + 2, y - 2), &DrawConfig::default());
+/// ctx.draw(&mut surface, &tilemap, (0, 0), &DrawConfig::default());
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shadow {
+    /// The distance in pixels by which the shadow extends past the mask.
+    pub radius: u32,
+    /// The color of the shadow below fully opaque parts of the mask.
+    pub color: (f32, f32, f32, f32),
+}
+
+impl Shadow {
+    /// Creates a new black and slightly transparent shadow with the given `radius`.
+    pub fn new(radius: u32) -> Self {
+        Shadow {
+            radius,
+            color: (0.0, 0.0, 0.0, 0.6),
+        }
+    }
+
+    /// Returns the size of the shadow baked from a mask with the given `dimensions`.
+    pub fn dimensions(&self, dimensions: (u32, u32)) -> (u32, u32) {
+        (
+            dimensions.0 + 2 * self.radius,
+            dimensions.1 + 2 * self.radius,
+        )
+    }
+
+    /// Returns the position of the baked shadow for a mask drawn at `position`.
+    pub fn position(&self, position: (i32, i32)) -> (i32, i32) {
+        (
+            position.0 - self.radius as i32,
+            position.1 - self.radius as i32,
+        )
+    }
+
+    /// Bakes the shadow of `mask`, which has the size returned by `Shadow::dimensions`.
+    pub fn bake(&self, ctx: &mut Context, mask: &Texture) -> Result<Texture, NewTextureError> {
+        let dimensions = self.dimensions(mask.dimensions());
+        let radius = self.radius as i32;
+        let (r, g, b, a) = self.color;
+
+        let mut horizontal = Texture::new(ctx, dimensions)?;
+        if let Some(target) = horizontal.prepare_as_draw_target(ctx) {
+            ctx.backend.run_pass(
+                FRAGMENT,
+                target.framebuffer_id,
+                dimensions,
+                Some(&mask.inner),
+                &[
+                    ("source_position", position(mask.position)),
+                    ("source_size", position(mask.size)),
+                    ("offset", Uniform::IVec2(radius, radius)),
+                    ("direction", Uniform::IVec2(1, 0)),
+                    ("radius", Uniform::Int(radius)),
+                    ("colorize", Uniform::UInt(0)),
+                ],
+            );
+        }
+
+        let mut shadow = Texture::new(ctx, dimensions)?;
+        if let Some(target) = shadow.prepare_as_draw_target(ctx) {
+            ctx.backend.run_pass(
+                FRAGMENT,
+                target.framebuffer_id,
+                dimensions,
+                Some(&horizontal.inner),
+                &[
+                    ("source_position", Uniform::IVec2(0, 0)),
+                    ("source_size", position(dimensions)),
+                    ("offset", Uniform::IVec2(0, 0)),
+                    ("direction", Uniform::IVec2(0, 1)),
+                    ("radius", Uniform::Int(radius)),
+                    ("colorize", Uniform::UInt(1)),
+                    ("shadow_color", Uniform::Vec4(r, g, b, a)),
+                ],
+            );
+        }
+
+        Ok(shadow)
+    }
+}
+
+fn position((x, y): (u32, u32)) -> Uniform {
+    Uniform::IVec2(x as i32, y as i32)
+}
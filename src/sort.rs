@@ -0,0 +1,141 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Context, DrawConfig, DrawTarget, Texture};
+
+/// Determines the order in which the draws of a [`DrawList`] are drawn, lowest first.
+///
+/// The key is an integer, so sorting it gives the same order on every platform.
+/// Its bits are used as follows, starting with the most significant bit:
+///
+/// | bits      | content    | meaning                                                    |
+/// |-----------|------------|------------------------------------------------------------|
+/// | `48..64`  | `layer`    | all draws of a lower layer are drawn first                 |
+/// | `32..48`  | `material` | groups draws inside of a layer, for example by texture     |
+/// | `0..32`   | `depth`    | orders draws with the same layer and material              |
+///
+/// Draws with equal keys are drawn in the order they were pushed.
+///
+/// ```rust
+/// use crow::SortKey;
+///
+/// let background = SortKey::new(0, 0, 0);
+/// let player = SortKey::y_sorted(1, 0, 40);
+/// let tree = SortKey::y_sorted(1, 0, 60);
+///
+/// // the tree is further up, so it is behind the player
+/// assert!(background < tree && tree < player);
+/// assert_eq!(player.layer(), 1);
+/// ```
+///
+/// [`DrawList`]: struct.DrawList.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct SortKey(pub u64);
+
+impl SortKey {
+    /// Packs `layer`, `material` and `depth` into a single key.
+    pub fn new(layer: u16, material: u16, depth: u32) -> Self {
+        SortKey(u64::from(layer) << 48 | u64::from(material) << 32 | u64::from(depth))
+    }
+
+    /// Creates a key where draws with a greater `y` coordinate are drawn
+    /// first, so objects further down the screen are in front.
+    pub fn y_sorted(layer: u16, material: u16, y: i32) -> Self {
+        // maps `i32::MIN..=i32::MAX` to `u32::MAX..=0`
+        let depth = !((y as u32) ^ 0x8000_0000);
+        Self::new(layer, material, depth)
+    }
+
+    /// Returns the bits `48..64` of this key.
+    pub fn layer(self) -> u16 {
+        (self.0 >> 48) as u16
+    }
+
+    /// Returns the bits `32..48` of this key.
+    pub fn material(self) -> u16 {
+        (self.0 >> 32) as u16
+    }
+
+    /// Returns the bits `0..32` of this key.
+    pub fn depth(self) -> u32 {
+        self.0 as u32
+    }
+}
+
+/// Draw calls which can be pushed in any order and are drawn sorted by their [`SortKey`].
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, DrawConfig, DrawList, SortKey, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let player = Texture::load(&mut ctx, "./textures/player.png")?;
+/// let ground = Texture::load(&mut ctx, "./textures/ground.png")?;
+///
+/// let mut list = DrawList::new();
+/// list.push(SortKey::y_sorted(1, 0, 32), &player, (64, 32), &DrawConfig::default());
+/// // the ground is pushed last but drawn first
+/// list.push(SortKey::new(0, 0, 0), &ground, (0, 0), &DrawConfig::default());
+///
+/// let mut surface = ctx.surface();
+/// list.draw(&mut ctx, &mut surface);
+/// ctx.present(surface)?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`SortKey`]: struct.SortKey.html
+#[derive(Debug, Clone, Default)]
+pub struct DrawList {
+    draws: Vec<(SortKey, Texture, (i32, i32), DrawConfig)>,
+}
+
+impl DrawList {
+    /// Creates an empty draw list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a draw of `texture` at `position` using `config`.
+    pub fn push(
+        &mut self,
+        key: SortKey,
+        texture: &Texture,
+        position: impl Into<(i32, i32)>,
+        config: &DrawConfig,
+    ) {
+        self.draws
+            .push((key, texture.clone(), position.into(), config.clone()));
+    }
+
+    /// Returns the number of draws in this list.
+    pub fn len(&self) -> usize {
+        self.draws.len()
+    }
+
+    /// Returns `true` if this list does not contain any draws.
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+
+    /// Removes all draws, keeping the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.draws.clear()
+    }
+
+    /// Draws all draws of this list onto `target`, starting with the lowest key.
+    ///
+    /// The list is not cleared, so it can be drawn multiple times.
+    pub fn draw<T>(&mut self, ctx: &mut Context, target: &mut T)
+    where
+        T: DrawTarget,
+    {
+        // `sort_by_key` is stable, keeping the push order of equal keys
+        self.draws.sort_by_key(|&(key, ..)| key);
+        for (_, texture, position, config) in self.draws.iter() {
+            ctx.draw(target, texture, *position, config);
+        }
+    }
+}
@@ -1,7 +1,50 @@
 //! A collect of useful draw modifiers.
+use std::cmp;
+
+use glutin::dpi::PhysicalPosition;
 use image::RgbaImage;
 
-use crate::{Context, DrawConfig, DrawTarget, Texture};
+use crate::{
+    Context, DrawConfig, DrawTarget, IRect, NewTextureError, QueryHandle, ScalingMode, Shader,
+    SpriteBatch, Texture, UniformValue, WindowSurface,
+};
+
+/// A [`DrawTarget`] which ends up drawing to the window surface, only
+/// moving or scaling its draw calls, for example `Scaled<&mut WindowSurface>`.
+///
+/// This is used by [`Camera`] to convert between world and window coordinates.
+///
+/// [`DrawTarget`]: ../trait.DrawTarget.html
+/// [`Camera`]: struct.Camera.html
+pub trait WindowTarget: DrawTarget {
+    /// Converts a position of this target to a position of the window surface,
+    /// which uses logical pixels starting at the bottom left corner.
+    fn target_to_window(&self, position: (f64, f64)) -> (f64, f64);
+
+    /// Converts a position of the window surface to a position of this target,
+    /// the inverse of `target_to_window`.
+    fn window_to_target(&self, position: (f64, f64)) -> (f64, f64);
+}
+
+impl WindowTarget for WindowSurface {
+    fn target_to_window(&self, position: (f64, f64)) -> (f64, f64) {
+        position
+    }
+
+    fn window_to_target(&self, position: (f64, f64)) -> (f64, f64) {
+        position
+    }
+}
+
+impl<T: WindowTarget> WindowTarget for &mut T {
+    fn target_to_window(&self, position: (f64, f64)) -> (f64, f64) {
+        (**self).target_to_window(position)
+    }
+
+    fn window_to_target(&self, position: (f64, f64)) -> (f64, f64) {
+        (**self).window_to_target(position)
+    }
+}
 
 /// Can be used as a [`DrawTarget`] which modifies the scale of each draw call.
 /// This should be identical to drawing to a temporary buffer and drawing this buffer
@@ -16,14 +59,44 @@ pub struct Scaled<T> {
 
 impl<T: DrawTarget> Scaled<T> {
     /// Wraps the given `DrawTarget`, calling all draw calls by the given `scale`.
-    pub fn new(inner: T, scale: (u32, u32)) -> Self {
-        Self { inner, scale }
+    pub fn new(inner: T, scale: impl Into<(u32, u32)>) -> Self {
+        Self {
+            inner,
+            scale: scale.into(),
+        }
     }
 
     /// Consumes `self` and returns the inner `DrawTarget`.
     pub fn into_inner(self) -> T {
         self.inner
     }
+
+    /// Returns the position and config used to draw a texture onto the inner target.
+    fn scale_draw(&self, position: (i32, i32), config: &DrawConfig) -> ((i32, i32), DrawConfig) {
+        let (x, y) = (self.scale.0 as f32, self.scale.1 as f32);
+        let scale = [[x, 0.0, 0.0], [0.0, y, 0.0], [0.0, 0.0, 1.0]];
+        let inverse = [[1.0 / x, 0.0, 0.0], [0.0, 1.0 / y, 0.0], [0.0, 0.0, 1.0]];
+        (
+            (
+                position.0 * self.scale.0 as i32,
+                position.1 * self.scale.1 as i32,
+            ),
+            DrawConfig {
+                scale: (config.scale.0 * self.scale.0, config.scale.1 * self.scale.1),
+                rotation_center: config.rotation_center.map(|(cx, cy)| (cx * x, cy * y)),
+                // the transform is applied to the already scaled corners
+                transform: config.transform.map(|m| mul(mul(scale, m), inverse)),
+                ..config.clone()
+            },
+        )
+    }
+
+    fn scale_rect(&self, rect: IRect) -> IRect {
+        IRect::new(
+            (rect.x * self.scale.0 as i32, rect.y * self.scale.1 as i32),
+            (rect.width * self.scale.0, rect.height * self.scale.1),
+        )
+    }
 }
 
 impl<T: DrawTarget> DrawTarget for Scaled<T> {
@@ -34,28 +107,33 @@ impl<T: DrawTarget> DrawTarget for Scaled<T> {
         position: (i32, i32),
         config: &DrawConfig,
     ) {
-        self.inner.receive_draw(
-            ctx,
-            texture,
-            (
-                position.0 * self.scale.0 as i32,
-                position.1 * self.scale.1 as i32,
-            ),
-            &DrawConfig {
-                scale: (config.scale.0 * self.scale.0, config.scale.1 * self.scale.1),
-                ..config.clone()
-            },
-        )
+        let (position, config) = self.scale_draw(position, config);
+        self.inner.receive_draw(ctx, texture, position, &config)
     }
 
     fn receive_clear_color(&mut self, ctx: &mut Context, color: (f32, f32, f32, f32)) {
         self.inner.receive_clear_color(ctx, color)
     }
 
+    fn receive_clear_region(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        self.inner
+            .receive_clear_region(ctx, self.scale_rect(rect), color)
+    }
+
     fn receive_clear_depth(&mut self, ctx: &mut Context) {
         self.inner.receive_clear_depth(ctx)
     }
 
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        let batch = batch.map(|_, position, config| self.scale_draw(position, config));
+        self.inner.receive_batch(ctx, &batch)
+    }
+
     fn receive_line(
         &mut self,
         ctx: &mut Context,
@@ -71,25 +149,28 @@ impl<T: DrawTarget> DrawTarget for Scaled<T> {
         )
     }
 
-    fn receive_rectangle(
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32)) {
+        self.inner
+            .receive_rectangle(ctx, self.scale_rect(rect), color)
+    }
+
+    fn receive_fullscreen(
         &mut self,
         ctx: &mut Context,
-        lower_left: (i32, i32),
-        upper_right: (i32, i32),
-        color: (f32, f32, f32, f32),
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
     ) {
-        self.inner.receive_rectangle(
-            ctx,
-            (
-                lower_left.0 * self.scale.0 as i32,
-                lower_left.1 * self.scale.1 as i32,
-            ),
-            (
-                upper_right.0 * self.scale.0 as i32,
-                upper_right.1 * self.scale.1 as i32,
-            ),
-            color,
-        )
+        self.inner.receive_fullscreen(ctx, shader, uniforms)
+    }
+
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        self.inner
+            .receive_visibility_query(ctx, self.scale_rect(rect), depth)
     }
 
     fn get_image_data(&self, ctx: &mut Context) -> RgbaImage {
@@ -97,6 +178,20 @@ impl<T: DrawTarget> DrawTarget for Scaled<T> {
     }
 }
 
+impl<T: WindowTarget> WindowTarget for Scaled<T> {
+    fn target_to_window(&self, position: (f64, f64)) -> (f64, f64) {
+        let (x, y) = (f64::from(self.scale.0), f64::from(self.scale.1));
+        self.inner
+            .target_to_window((position.0 * x, position.1 * y))
+    }
+
+    fn window_to_target(&self, position: (f64, f64)) -> (f64, f64) {
+        let (x, y) = (f64::from(self.scale.0), f64::from(self.scale.1));
+        let position = self.inner.window_to_target(position);
+        (position.0 / x, position.1 / y)
+    }
+}
+
 /// Can be used as a [`DrawTarget`] which offsets the `position` of each draw call by a given `offset`.
 /// This can be thought of as changing the origin `(0, 0)` to `position`.
 ///
@@ -109,8 +204,11 @@ pub struct Offset<T> {
 
 impl<T: DrawTarget> Offset<T> {
     /// Wraps the given `DrawTarget`, moving all draw calls by the given `offset`.
-    pub fn new(inner: T, offset: (i32, i32)) -> Self {
-        Self { inner, offset }
+    pub fn new(inner: T, offset: impl Into<(i32, i32)>) -> Self {
+        Self {
+            inner,
+            offset: offset.into(),
+        }
     }
 
     /// Consumes `self` and returns the inner `DrawTarget`.
@@ -139,6 +237,28 @@ impl<T: DrawTarget> DrawTarget for Offset<T> {
         self.inner.receive_clear_color(ctx, color)
     }
 
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        let offset = self.offset;
+        let batch = batch.map(|_, position, config| {
+            (
+                (position.0 - offset.0, position.1 - offset.1),
+                config.clone(),
+            )
+        });
+        self.inner.receive_batch(ctx, &batch)
+    }
+
+    fn receive_clear_region(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        let offset = (-self.offset.0, -self.offset.1);
+        self.inner
+            .receive_clear_region(ctx, rect.translate(offset), color)
+    }
+
     fn receive_clear_depth(&mut self, ctx: &mut Context) {
         self.inner.receive_clear_depth(ctx)
     }
@@ -158,22 +278,1208 @@ impl<T: DrawTarget> DrawTarget for Offset<T> {
         )
     }
 
-    fn receive_rectangle(
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32)) {
+        let offset = (-self.offset.0, -self.offset.1);
+        self.inner
+            .receive_rectangle(ctx, rect.translate(offset), color)
+    }
+
+    fn receive_fullscreen(
         &mut self,
         ctx: &mut Context,
-        lower_left: (i32, i32),
-        upper_right: (i32, i32),
-        color: (f32, f32, f32, f32),
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
     ) {
-        self.inner.receive_rectangle(
-            ctx,
-            (lower_left.0 - self.offset.0, lower_left.1 - self.offset.1),
-            (upper_right.0 - self.offset.0, upper_right.1 - self.offset.1),
-            color,
+        self.inner.receive_fullscreen(ctx, shader, uniforms)
+    }
+
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        let offset = (-self.offset.0, -self.offset.1);
+        self.inner
+            .receive_visibility_query(ctx, rect.translate(offset), depth)
+    }
+
+    fn get_image_data(&self, ctx: &mut Context) -> RgbaImage {
+        self.inner.get_image_data(ctx)
+    }
+}
+
+impl<T: WindowTarget> WindowTarget for Offset<T> {
+    fn target_to_window(&self, position: (f64, f64)) -> (f64, f64) {
+        let (x, y) = (f64::from(self.offset.0), f64::from(self.offset.1));
+        self.inner
+            .target_to_window((position.0 - x, position.1 - y))
+    }
+
+    fn window_to_target(&self, position: (f64, f64)) -> (f64, f64) {
+        let (x, y) = (f64::from(self.offset.0), f64::from(self.offset.1));
+        let position = self.inner.window_to_target(position);
+        (position.0 + x, position.1 + y)
+    }
+}
+
+/// Can be used as a [`DrawTarget`] which uses the top left corner as its origin,
+/// with `y` increasing downwards.
+///
+/// The `position` of draw calls is the top left corner of the drawn texture, the
+/// `rotation` of draw calls is clockwise and both `rotation_center` and `transform`
+/// are relative to the top left corner of the texture. Rectangles and lines also
+/// use the flipped coordinates, while textures are still drawn upright.
+///
+/// `Context::image_data` already starts at the top left, so it is unaffected.
+/// To specify sections of textures using a top left origin, use `Rect::flip_y`.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     target::YDown,
+///     Context, DrawConfig, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let texture = Texture::load(&mut ctx, "./textures/player.png")?;
+///
+/// let height = ctx.window_height();
+/// let mut surface = YDown::new(ctx.surface(), height);
+/// // draws `texture` in the top left corner of the window
+/// ctx.draw(&mut surface, &texture, (0, 0), &DrawConfig::default());
+/// ctx.present(surface.into_inner())?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`DrawTarget`]: ../trait.DrawTarget.html
+#[derive(Debug, Clone)]
+pub struct YDown<T> {
+    inner: T,
+    height: u32,
+}
+
+impl<T: DrawTarget> YDown<T> {
+    /// Wraps the given `DrawTarget` with the given `height`.
+    pub fn new(inner: T, height: u32) -> Self {
+        Self { inner, height }
+    }
+
+    /// Consumes `self` and returns the inner `DrawTarget`.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns the position and config used to draw a texture of the given `dimensions`
+    /// onto the inner target.
+    fn flip_draw(
+        &self,
+        dimensions: (u32, u32),
+        position: (i32, i32),
+        config: &DrawConfig,
+    ) -> ((i32, i32), DrawConfig) {
+        let height = (dimensions.1 * config.scale.1) as f32;
+        // mirrors the coordinates relative to the drawn texture
+        let flip = [[1.0, 0.0, 0.0], [0.0, -1.0, height], [0.0, 0.0, 1.0]];
+        (
+            (position.0, self.height as i32 - position.1 - height as i32),
+            DrawConfig {
+                rotation: -config.rotation,
+                rotation_center: config.rotation_center.map(|(x, y)| (x, height - y)),
+                transform: config.transform.map(|m| mul(mul(flip, m), flip)),
+                ..config.clone()
+            },
         )
     }
 
+    fn flip_point(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        (x, self.height as i32 - 1 - y)
+    }
+}
+
+/// Returns the matrix product `a * b`.
+fn mul(a: [[f32; 3]; 3], b: [[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+impl<T: DrawTarget> DrawTarget for YDown<T> {
+    fn receive_draw(
+        &mut self,
+        ctx: &mut Context,
+        texture: &Texture,
+        position: (i32, i32),
+        config: &DrawConfig,
+    ) {
+        let (position, config) = self.flip_draw(texture.dimensions(), position, config);
+        self.inner.receive_draw(ctx, texture, position, &config)
+    }
+
+    fn receive_clear_color(&mut self, ctx: &mut Context, color: (f32, f32, f32, f32)) {
+        self.inner.receive_clear_color(ctx, color)
+    }
+
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        let batch =
+            batch.map(|dimensions, position, config| self.flip_draw(dimensions, position, config));
+        self.inner.receive_batch(ctx, &batch)
+    }
+
+    fn receive_clear_region(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        self.inner
+            .receive_clear_region(ctx, rect.flip_y(self.height), color)
+    }
+
+    fn receive_clear_depth(&mut self, ctx: &mut Context) {
+        self.inner.receive_clear_depth(ctx)
+    }
+
+    fn receive_line(
+        &mut self,
+        ctx: &mut Context,
+        from: (i32, i32),
+        to: (i32, i32),
+        color: (f32, f32, f32, f32),
+    ) {
+        self.inner
+            .receive_line(ctx, self.flip_point(from), self.flip_point(to), color)
+    }
+
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32)) {
+        self.inner
+            .receive_rectangle(ctx, rect.flip_y(self.height), color)
+    }
+
+    fn receive_fullscreen(
+        &mut self,
+        ctx: &mut Context,
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
+    ) {
+        self.inner.receive_fullscreen(ctx, shader, uniforms)
+    }
+
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        self.inner
+            .receive_visibility_query(ctx, rect.flip_y(self.height), depth)
+    }
+
     fn get_image_data(&self, ctx: &mut Context) -> RgbaImage {
         self.inner.get_image_data(ctx)
     }
 }
+
+/// Can be used as a [`DrawTarget`] which views the world through a camera,
+/// moving all draw calls by `-position` and then scaling them by `zoom`.
+///
+/// This is equivalent to wrapping the target in a `Scaled` and then in an `Offset`,
+/// while also being able to convert between world and screen coordinates,
+/// which is useful for mouse picking.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     target::Camera,
+///     Context, DrawConfig, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let texture = Texture::load(&mut ctx, "./textures/player.png")?;
+///
+/// let mut camera = Camera::new(ctx.surface(), (-40, -20), 2);
+/// // draws `texture` at `(80, 40)` in window coordinates
+/// ctx.draw(&mut camera, &texture, (0, 0), &DrawConfig::default());
+/// if let Some(position) = camera.cursor_world_position(&ctx) {
+///     println!("the cursor is at {:?} in the world", position);
+/// }
+/// ctx.present(camera.into_inner())?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`DrawTarget`]: ../trait.DrawTarget.html
+#[derive(Debug, Clone)]
+pub struct Camera<T> {
+    inner: T,
+    position: (i32, i32),
+    zoom: u32,
+}
+
+impl<T> Camera<T> {
+    /// Wraps the given `DrawTarget`, showing the world starting at `position`
+    /// in the lower left corner, with each world pixel being `zoom` pixels wide.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `zoom` is `0`.
+    pub fn new(inner: T, position: impl Into<(i32, i32)>, zoom: u32) -> Self {
+        assert!(zoom != 0, "the zoom of a camera must not be 0");
+        Self {
+            inner,
+            position: position.into(),
+            zoom,
+        }
+    }
+
+    /// Consumes `self` and returns the inner `DrawTarget`.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns a camera with the same position and zoom as `self`, drawing to `target`.
+    fn with_target<U>(&self, target: U) -> Camera<U> {
+        Camera {
+            inner: target,
+            position: self.position,
+            zoom: self.zoom,
+        }
+    }
+
+    /// Returns the world position shown in the lower left corner.
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    /// Moves the camera so the world position `position` is shown in the lower left corner.
+    pub fn set_position(&mut self, position: impl Into<(i32, i32)>) {
+        self.position = position.into();
+    }
+
+    /// Returns the width and height of each world pixel on screen.
+    pub fn zoom(&self) -> u32 {
+        self.zoom
+    }
+
+    /// Sets the width and height of each world pixel on screen.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `zoom` is `0`.
+    pub fn set_zoom(&mut self, zoom: u32) {
+        assert!(zoom != 0, "the zoom of a camera must not be 0");
+        self.zoom = zoom;
+    }
+
+    /// Converts a position in the world to the lower left corner
+    /// of the corresponding pixels of the inner target.
+    ///
+    /// The pixels of the inner target are only the same as the pixels of the window
+    /// if the inner target is the window surface itself. Use `Camera::world_to_window`
+    /// to account for other draw modifiers and the dpi factor of the window.
+    pub fn world_to_screen(&self, position: (i32, i32)) -> (i32, i32) {
+        let zoom = self.zoom as i32;
+        (
+            (position.0 - self.position.0) * zoom,
+            (position.1 - self.position.1) * zoom,
+        )
+    }
+
+    /// Converts a pixel of the inner target to the world position shown there.
+    pub fn screen_to_world(&self, position: (i32, i32)) -> (i32, i32) {
+        let zoom = self.zoom as i32;
+        (
+            position.0.div_euclid(zoom) + self.position.0,
+            position.1.div_euclid(zoom) + self.position.1,
+        )
+    }
+}
+
+impl<T: WindowTarget> Camera<T> {
+    /// Converts a position in the world to the lower left corner of the
+    /// corresponding pixels of the window in physical pixels, starting at the top left corner.
+    ///
+    /// Unlike `Camera::world_to_screen`, this accounts for the dpi factor of the window
+    /// and all draw modifiers between this camera and the window surface.
+    pub fn world_to_window(&self, ctx: &Context, position: (i32, i32)) -> PhysicalPosition<f64> {
+        let screen = self.world_to_screen(position);
+        let (x, y) = self
+            .inner
+            .target_to_window((f64::from(screen.0), f64::from(screen.1)));
+        let dpi = f64::from(ctx.backend.dpi_factor());
+        let height = f64::from(ctx.window_height());
+        PhysicalPosition::new(x * dpi, (height - y) * dpi)
+    }
+
+    /// Converts a position of the window in physical pixels, for example the position of
+    /// `WindowEvent::CursorMoved`, to a world position.
+    ///
+    /// This accounts for the dpi factor of the window, its origin being in the top left corner
+    /// and all draw modifiers between this camera and the window surface.
+    pub fn window_to_world(&self, ctx: &Context, position: PhysicalPosition<f64>) -> (i32, i32) {
+        let position = position.to_logical::<f64>(f64::from(ctx.backend.dpi_factor()));
+        let height = ctx.window_height() as i32;
+        self.window_pixel_to_world((
+            position.x.floor() as i32,
+            height - 1 - position.y.floor() as i32,
+        ))
+    }
+
+    /// Returns the world position below the mouse cursor, see `Context::cursor_position`.
+    pub fn cursor_world_position(&self, ctx: &Context) -> Option<(i32, i32)> {
+        ctx.cursor_position()
+            .map(|position| self.window_pixel_to_world(position))
+    }
+
+    /// Converts a pixel of the window surface to the world position shown at its center.
+    fn window_pixel_to_world(&self, position: (i32, i32)) -> (i32, i32) {
+        let center = (f64::from(position.0) + 0.5, f64::from(position.1) + 0.5);
+        let (x, y) = self.inner.window_to_target(center);
+        let zoom = f64::from(self.zoom);
+        (
+            (x / zoom).floor() as i32 + self.position.0,
+            (y / zoom).floor() as i32 + self.position.1,
+        )
+    }
+}
+
+impl<T: DrawTarget> Camera<T> {
+    fn view(&mut self) -> Offset<Scaled<&mut T>> {
+        Offset::new(
+            Scaled::new(&mut self.inner, (self.zoom, self.zoom)),
+            self.position,
+        )
+    }
+}
+
+impl<T: DrawTarget> DrawTarget for Camera<T> {
+    fn receive_draw(
+        &mut self,
+        ctx: &mut Context,
+        texture: &Texture,
+        position: (i32, i32),
+        config: &DrawConfig,
+    ) {
+        self.view().receive_draw(ctx, texture, position, config)
+    }
+
+    fn receive_clear_color(&mut self, ctx: &mut Context, color: (f32, f32, f32, f32)) {
+        self.inner.receive_clear_color(ctx, color)
+    }
+
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        self.view().receive_batch(ctx, batch)
+    }
+
+    fn receive_clear_region(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        self.view().receive_clear_region(ctx, rect, color)
+    }
+
+    fn receive_clear_depth(&mut self, ctx: &mut Context) {
+        self.inner.receive_clear_depth(ctx)
+    }
+
+    fn receive_line(
+        &mut self,
+        ctx: &mut Context,
+        from: (i32, i32),
+        to: (i32, i32),
+        color: (f32, f32, f32, f32),
+    ) {
+        self.view().receive_line(ctx, from, to, color)
+    }
+
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32)) {
+        self.view().receive_rectangle(ctx, rect, color)
+    }
+
+    fn receive_fullscreen(
+        &mut self,
+        ctx: &mut Context,
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
+    ) {
+        self.inner.receive_fullscreen(ctx, shader, uniforms)
+    }
+
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        self.view().receive_visibility_query(ctx, rect, depth)
+    }
+
+    fn get_image_data(&self, ctx: &mut Context) -> RgbaImage {
+        self.inner.get_image_data(ctx)
+    }
+}
+
+/// Can be used as a [`DrawTarget`] which only changes the pixels inside of `rect`,
+/// restricting draw calls, debug primitives, fullscreen shaders and clearing the target.
+///
+/// Unlike `Context::masked`, this uses the scissor test of the GPU, which is cheaper
+/// but only supports rectangles. `rect` is always in the pixels of the
+/// underlying texture or window, regardless of other draw modifiers wrapped by `self`.
+/// Nested clips only change the pixels inside of both rectangles.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     target::{Clipped, Offset},
+///     Context, DrawConfig, IRect, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let world = Texture::load(&mut ctx, "./textures/world.png")?;
+///
+/// let mut surface = ctx.surface();
+/// // draws the left player's view onto the left half of the window
+/// let (width, height) = ctx.window_dimensions();
+/// let half = IRect::new((0, 0), (width / 2, height));
+/// let mut left = Clipped::new(Offset::new(&mut surface, (100, 0)), half);
+/// ctx.draw(&mut left, &world, (0, 0), &DrawConfig::default());
+/// ctx.present(surface)?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`DrawTarget`]: ../trait.DrawTarget.html
+#[derive(Debug, Clone)]
+pub struct Clipped<T> {
+    inner: T,
+    rect: IRect,
+}
+
+impl<T: DrawTarget> Clipped<T> {
+    /// Wraps the given `DrawTarget`, only changing the pixels inside of `rect`.
+    pub fn new(inner: T, rect: impl Into<IRect>) -> Self {
+        Self {
+            inner,
+            rect: rect.into(),
+        }
+    }
+
+    /// Consumes `self` and returns the inner `DrawTarget`.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns the region which may be changed.
+    pub fn rect(&self) -> IRect {
+        self.rect
+    }
+
+    /// Calls `f` while clipping all changes to `self.rect`.
+    fn clipped<R>(&mut self, ctx: &mut Context, f: impl FnOnce(&mut Context, &mut T) -> R) -> R {
+        let previous = ctx.backend.clip();
+        let clip = match previous {
+            Some(previous) => previous
+                .intersection(self.rect)
+                .unwrap_or_else(|| IRect::new(self.rect.position(), (0, 0))),
+            None => self.rect,
+        };
+        ctx.backend.set_clip(Some(clip));
+        let res = f(ctx, &mut self.inner);
+        ctx.backend.set_clip(previous);
+        res
+    }
+}
+
+impl<T: DrawTarget> DrawTarget for Clipped<T> {
+    fn receive_draw(
+        &mut self,
+        ctx: &mut Context,
+        texture: &Texture,
+        position: (i32, i32),
+        config: &DrawConfig,
+    ) {
+        self.clipped(ctx, |ctx, inner| {
+            inner.receive_draw(ctx, texture, position, config)
+        })
+    }
+
+    fn receive_clear_color(&mut self, ctx: &mut Context, color: (f32, f32, f32, f32)) {
+        self.clipped(ctx, |ctx, inner| inner.receive_clear_color(ctx, color))
+    }
+
+    fn receive_clear_region(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        self.clipped(ctx, |ctx, inner| {
+            inner.receive_clear_region(ctx, rect, color)
+        })
+    }
+
+    fn receive_clear_depth(&mut self, ctx: &mut Context) {
+        self.clipped(ctx, |ctx, inner| inner.receive_clear_depth(ctx))
+    }
+
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        self.clipped(ctx, |ctx, inner| inner.receive_batch(ctx, batch))
+    }
+
+    fn receive_line(
+        &mut self,
+        ctx: &mut Context,
+        from: (i32, i32),
+        to: (i32, i32),
+        color: (f32, f32, f32, f32),
+    ) {
+        self.clipped(ctx, |ctx, inner| inner.receive_line(ctx, from, to, color))
+    }
+
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32)) {
+        self.clipped(ctx, |ctx, inner| inner.receive_rectangle(ctx, rect, color))
+    }
+
+    fn receive_fullscreen(
+        &mut self,
+        ctx: &mut Context,
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
+    ) {
+        self.clipped(ctx, |ctx, inner| {
+            inner.receive_fullscreen(ctx, shader, uniforms)
+        })
+    }
+
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        self.clipped(ctx, |ctx, inner| {
+            inner.receive_visibility_query(ctx, rect, depth)
+        })
+    }
+
+    fn get_image_data(&self, ctx: &mut Context) -> RgbaImage {
+        self.inner.get_image_data(ctx)
+    }
+}
+
+/// A texture with a fixed resolution, which is drawn onto a larger target using the
+/// largest integer scale which fits, leaving a border of `border_color` around it.
+///
+/// This can be used to render a game at a fixed resolution while keeping each
+/// pixel the same size. As the scale is computed each time `PixelPerfect::draw`
+/// is called, resizing the window is handled automatically.
+///
+/// The scale can be changed using `RenderProfile::scaling`.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     target::PixelPerfect,
+///     Context, DrawConfig, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let texture = Texture::load(&mut ctx, "./textures/player.png")?;
+///
+/// let mut game = PixelPerfect::new(&mut ctx, (320, 180))?;
+/// ctx.clear_color(&mut game, (0.3, 0.3, 0.8, 1.0));
+/// ctx.draw(&mut game, &texture, (100, 60), &DrawConfig::default());
+/// if let Some(position) = game.cursor_position(&ctx) {
+///     println!("the cursor is at {:?} in the game", position);
+/// }
+///
+/// let mut surface = ctx.surface();
+/// let dimensions = ctx.window_dimensions();
+/// game.draw(&mut ctx, &mut surface, dimensions);
+/// ctx.present(surface)?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct PixelPerfect {
+    texture: Texture,
+    border_color: (f32, f32, f32, f32),
+}
+
+impl PixelPerfect {
+    /// Creates a new texture with the given `resolution` and a black border.
+    pub fn new(
+        ctx: &mut Context,
+        resolution: impl Into<(u32, u32)>,
+    ) -> Result<Self, NewTextureError> {
+        Ok(PixelPerfect {
+            texture: Texture::new(ctx, resolution)?,
+            border_color: (0.0, 0.0, 0.0, 1.0),
+        })
+    }
+
+    /// Returns the resolution of the game.
+    pub fn resolution(&self) -> (u32, u32) {
+        self.texture.dimensions()
+    }
+
+    /// Returns the texture containing the current frame.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns the color of the area around the scaled texture.
+    pub fn border_color(&self) -> (f32, f32, f32, f32) {
+        self.border_color
+    }
+
+    /// Sets the color of the area around the scaled texture.
+    pub fn set_border_color(&mut self, color: (f32, f32, f32, f32)) {
+        self.border_color = color;
+    }
+
+    /// Returns the scale used for a target with the given `dimensions`, which is at least `1`.
+    ///
+    /// This is the largest integer scale at which the texture fits
+    /// unless `RenderProfile::scaling` is `ScalingMode::Fixed`.
+    pub fn scale(&self, ctx: &Context, dimensions: (u32, u32)) -> u32 {
+        let (width, height) = self.resolution();
+        let scale = match ctx.render_profile().scaling {
+            ScalingMode::Integer => cmp::min(dimensions.0 / width, dimensions.1 / height),
+            ScalingMode::Fixed(scale) => scale,
+        };
+        cmp::max(scale, 1)
+    }
+
+    /// Returns the position of the lower left corner of the scaled texture,
+    /// centered inside of a target with the given `dimensions`.
+    pub fn offset(&self, ctx: &Context, dimensions: (u32, u32)) -> (i32, i32) {
+        let (width, height) = self.resolution();
+        let scale = self.scale(ctx, dimensions);
+        (
+            (dimensions.0 as i32 - (width * scale) as i32) / 2,
+            (dimensions.1 as i32 - (height * scale) as i32) / 2,
+        )
+    }
+
+    /// Fills `target` with the border color and draws the scaled texture in its center.
+    ///
+    /// `dimensions` are the dimensions of `target`, for example `Context::window_dimensions`.
+    pub fn draw<T>(&self, ctx: &mut Context, target: &mut T, dimensions: (u32, u32))
+    where
+        T: DrawTarget,
+    {
+        let scale = self.scale(ctx, dimensions);
+        let offset = self.offset(ctx, dimensions);
+        ctx.clear_color(target, self.border_color);
+        ctx.draw(
+            target,
+            &self.texture,
+            offset,
+            &DrawConfig {
+                scale: (scale, scale),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Converts a pixel of a target with the given `dimensions` to the pixel
+    /// of the game shown there, or `None` if the pixel is part of the border.
+    pub fn screen_to_game(
+        &self,
+        ctx: &Context,
+        position: (i32, i32),
+        dimensions: (u32, u32),
+    ) -> Option<(i32, i32)> {
+        let (width, height) = self.resolution();
+        let scale = self.scale(ctx, dimensions) as i32;
+        let offset = self.offset(ctx, dimensions);
+        let x = (position.0 - offset.0).div_euclid(scale);
+        let y = (position.1 - offset.1).div_euclid(scale);
+        if (0..width as i32).contains(&x) && (0..height as i32).contains(&y) {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the pixel of the game below the mouse cursor, see `Context::cursor_position`,
+    /// assuming that `self` is drawn onto the whole window.
+    ///
+    /// This is `None` if the cursor is outside of the window or above the border.
+    pub fn cursor_position(&self, ctx: &Context) -> Option<(i32, i32)> {
+        let dimensions = ctx.window_dimensions();
+        ctx.cursor_position()
+            .and_then(|position| self.screen_to_game(ctx, position, dimensions))
+    }
+}
+
+impl DrawTarget for PixelPerfect {
+    fn receive_draw(
+        &mut self,
+        ctx: &mut Context,
+        texture: &Texture,
+        position: (i32, i32),
+        config: &DrawConfig,
+    ) {
+        self.texture.receive_draw(ctx, texture, position, config)
+    }
+
+    fn receive_clear_color(&mut self, ctx: &mut Context, color: (f32, f32, f32, f32)) {
+        self.texture.receive_clear_color(ctx, color)
+    }
+
+    fn receive_clear_region(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        self.texture.receive_clear_region(ctx, rect, color)
+    }
+
+    fn receive_clear_depth(&mut self, ctx: &mut Context) {
+        self.texture.receive_clear_depth(ctx)
+    }
+
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        self.texture.receive_batch(ctx, batch)
+    }
+
+    fn receive_line(
+        &mut self,
+        ctx: &mut Context,
+        from: (i32, i32),
+        to: (i32, i32),
+        color: (f32, f32, f32, f32),
+    ) {
+        self.texture.receive_line(ctx, from, to, color)
+    }
+
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32)) {
+        self.texture.receive_rectangle(ctx, rect, color)
+    }
+
+    fn receive_fullscreen(
+        &mut self,
+        ctx: &mut Context,
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
+    ) {
+        self.texture.receive_fullscreen(ctx, shader, uniforms)
+    }
+
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        self.texture.receive_visibility_query(ctx, rect, depth)
+    }
+
+    fn get_image_data(&self, ctx: &mut Context) -> RgbaImage {
+        self.texture.get_image_data(ctx)
+    }
+}
+
+/// A texture showing the world through its own camera, which can be drawn like any other
+/// texture, for example for security camera screens, portals or map previews.
+///
+/// The resolution of a view is independent of the window and the world is drawn into it
+/// using `View::render`, which clears the texture and then moves and scales all
+/// draw calls according to the camera of the view.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     target::View,
+///     Context, DrawConfig, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let world = Texture::load(&mut ctx, "./textures/world.png")?;
+///
+/// let mut minimap = View::new(&mut ctx, (64, 64))?;
+/// minimap.set_position((320, 160));
+/// minimap.render(&mut ctx, |ctx, camera| {
+///     ctx.draw(camera, &world, (0, 0), &DrawConfig::default());
+/// });
+///
+/// let mut surface = ctx.surface();
+/// ctx.draw(&mut surface, &world, (0, 0), &DrawConfig::default());
+/// ctx.draw(&mut surface, minimap.texture(), (8, 8), &DrawConfig::default());
+/// ctx.present(surface)?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct View {
+    texture: Texture,
+    camera: Camera<()>,
+    clear_color: (f32, f32, f32, f32),
+}
+
+impl View {
+    /// Creates a view with the given `resolution`, showing the world starting at `(0, 0)`
+    /// with a zoom of `1` on a transparent background.
+    pub fn new(
+        ctx: &mut Context,
+        resolution: impl Into<(u32, u32)>,
+    ) -> Result<Self, NewTextureError> {
+        Ok(View {
+            texture: Texture::new(ctx, resolution)?,
+            camera: Camera::new((), (0, 0), 1),
+            clear_color: (0.0, 0.0, 0.0, 0.0),
+        })
+    }
+
+    /// Returns the resolution of the view.
+    pub fn resolution(&self) -> (u32, u32) {
+        self.texture.dimensions()
+    }
+
+    /// Changes the resolution of the view, keeping its camera.
+    ///
+    /// This replaces the texture of the view if `resolution` differs from the current one,
+    /// so the view has to be rendered again afterwards.
+    pub fn set_resolution(
+        &mut self,
+        ctx: &mut Context,
+        resolution: impl Into<(u32, u32)>,
+    ) -> Result<(), NewTextureError> {
+        let resolution = resolution.into();
+        if resolution != self.resolution() {
+            let options = self.texture.options();
+            self.texture = Texture::new_with(ctx, resolution, options)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the texture containing the last rendered frame of the view.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns the world position shown in the lower left corner.
+    pub fn position(&self) -> (i32, i32) {
+        self.camera.position()
+    }
+
+    /// Moves the camera so the world position `position` is shown in the lower left corner.
+    pub fn set_position(&mut self, position: impl Into<(i32, i32)>) {
+        self.camera.set_position(position);
+    }
+
+    /// Returns the width and height of each world pixel in the view.
+    pub fn zoom(&self) -> u32 {
+        self.camera.zoom()
+    }
+
+    /// Sets the width and height of each world pixel in the view.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `zoom` is `0`.
+    pub fn set_zoom(&mut self, zoom: u32) {
+        self.camera.set_zoom(zoom);
+    }
+
+    /// Returns the color used to clear the view before rendering it.
+    pub fn clear_color(&self) -> (f32, f32, f32, f32) {
+        self.clear_color
+    }
+
+    /// Sets the color used to clear the view before rendering it.
+    pub fn set_clear_color(&mut self, color: (f32, f32, f32, f32)) {
+        self.clear_color = color;
+    }
+
+    /// Clears the view and calls `f` with a camera, which draws onto the texture of the view.
+    pub fn render<R>(
+        &mut self,
+        ctx: &mut Context,
+        f: impl FnOnce(&mut Context, &mut Camera<&mut Texture>) -> R,
+    ) -> R {
+        ctx.clear_color(&mut self.texture, self.clear_color);
+        let mut camera = self.camera.with_target(&mut self.texture);
+        f(ctx, &mut camera)
+    }
+
+    /// Converts a pixel of the view to the world position shown there.
+    pub fn view_to_world(&self, position: (i32, i32)) -> (i32, i32) {
+        self.camera.screen_to_world(position)
+    }
+}
+
+/// How the viewports of a [`SplitScreen`] are arranged, starting with the first
+/// player in the top left corner.
+///
+/// [`SplitScreen`]: struct.SplitScreen.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SplitLayout {
+    /// All viewports are next to each other, each using the full height.
+    Columns,
+    /// All viewports are above each other, each using the full width.
+    Rows,
+    /// The viewports are arranged in a grid with at least as many columns as rows,
+    /// for example two columns and two rows for three or four players.
+    #[default]
+    Grid,
+}
+
+/// Divides a target into one viewport for each player, each of which
+/// views the world using its own camera.
+///
+/// Viewports never overlap and drawing to a view only changes the pixels of its viewport.
+/// If an `aspect_ratio` is set, each viewport is the largest rectangle with this aspect
+/// ratio which is centered in its cell of the layout, leaving letterboxes around it.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     target::{SplitLayout, SplitScreen},
+///     Context, DrawConfig, Texture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let world = Texture::load(&mut ctx, "./textures/world.png")?;
+///
+/// let mut split = SplitScreen::new(2, ctx.window_dimensions(), SplitLayout::Columns);
+/// split.set_camera_position(1, (200, 0));
+///
+/// let mut surface = ctx.surface();
+/// split.clear(&mut ctx, &mut surface, (0.3, 0.3, 0.8, 1.0));
+/// for player in 0..split.players() {
+///     let mut view = split.view(player, &mut surface);
+///     ctx.draw(&mut view, &world, (0, 0), &DrawConfig::default());
+/// }
+/// ctx.present(surface)?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SplitScreen {
+    dimensions: (u32, u32),
+    layout: SplitLayout,
+    aspect_ratio: Option<(u32, u32)>,
+    letterbox_color: (f32, f32, f32, f32),
+    views: Vec<Camera<()>>,
+}
+
+impl SplitScreen {
+    /// Divides a target with the given `dimensions` into `players` viewports,
+    /// with all cameras starting at `(0, 0)` with a zoom of `1`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `players` is `0`.
+    pub fn new(players: usize, dimensions: impl Into<(u32, u32)>, layout: SplitLayout) -> Self {
+        assert!(players != 0, "a split screen requires at least one player");
+        Self {
+            dimensions: dimensions.into(),
+            layout,
+            aspect_ratio: None,
+            letterbox_color: (0.0, 0.0, 0.0, 1.0),
+            views: vec![Camera::new((), (0, 0), 1); players],
+        }
+    }
+
+    /// Returns the number of players.
+    pub fn players(&self) -> usize {
+        self.views.len()
+    }
+
+    /// Returns the dimensions of the divided target.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.dimensions
+    }
+
+    /// Changes the dimensions of the divided target, which should be called
+    /// after the window has been resized.
+    pub fn resize(&mut self, dimensions: impl Into<(u32, u32)>) {
+        self.dimensions = dimensions.into();
+    }
+
+    /// Returns how the viewports are arranged.
+    pub fn layout(&self) -> SplitLayout {
+        self.layout
+    }
+
+    /// Changes how the viewports are arranged.
+    pub fn set_layout(&mut self, layout: SplitLayout) {
+        self.layout = layout;
+    }
+
+    /// Returns the aspect ratio of each viewport, `None` if viewports fill their cell.
+    pub fn aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.aspect_ratio
+    }
+
+    /// Restricts all viewports to the given aspect ratio, letterboxing them inside of their cell.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if either the width or the height of `aspect_ratio` is `0`.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: Option<(u32, u32)>) {
+        if let Some((width, height)) = aspect_ratio {
+            assert!(
+                width != 0 && height != 0,
+                "invalid aspect ratio: {}:{}",
+                width,
+                height
+            );
+        }
+        self.aspect_ratio = aspect_ratio;
+    }
+
+    /// Returns the color used by `SplitScreen::clear` outside of the viewports.
+    pub fn letterbox_color(&self) -> (f32, f32, f32, f32) {
+        self.letterbox_color
+    }
+
+    /// Sets the color used by `SplitScreen::clear` outside of the viewports.
+    pub fn set_letterbox_color(&mut self, color: (f32, f32, f32, f32)) {
+        self.letterbox_color = color;
+    }
+
+    /// Returns the world position shown in the lower left corner of the viewport of `player`.
+    pub fn camera_position(&self, player: usize) -> (i32, i32) {
+        self.views[player].position()
+    }
+
+    /// Moves the camera of `player` so `position` is shown in the lower left corner of its viewport.
+    pub fn set_camera_position(&mut self, player: usize, position: impl Into<(i32, i32)>) {
+        self.views[player].set_position(position);
+    }
+
+    /// Returns the width and height of each world pixel in the viewport of `player`.
+    pub fn zoom(&self, player: usize) -> u32 {
+        self.views[player].zoom()
+    }
+
+    /// Sets the width and height of each world pixel in the viewport of `player`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `zoom` is `0`.
+    pub fn set_zoom(&mut self, player: usize, zoom: u32) {
+        self.views[player].set_zoom(zoom);
+    }
+
+    /// Returns the number of columns and rows of the layout.
+    fn grid(&self) -> (u32, u32) {
+        let players = self.views.len() as u32;
+        match self.layout {
+            SplitLayout::Columns => (players, 1),
+            SplitLayout::Rows => (1, players),
+            SplitLayout::Grid => {
+                let mut columns = 1;
+                while columns * columns < players {
+                    columns += 1;
+                }
+                (columns, (players + columns - 1) / columns)
+            }
+        }
+    }
+
+    /// Returns the region of the target which shows the view of `player`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `player` is not less than `self.players()`.
+    pub fn viewport(&self, player: usize) -> IRect {
+        assert!(
+            player < self.views.len(),
+            "invalid player {} for a split screen with {} players",
+            player,
+            self.views.len()
+        );
+        let (columns, rows) = self.grid();
+        let (width, height) = self.dimensions;
+        let column = player as u32 % columns;
+        // the first row is at the top of the target
+        let row = rows - 1 - player as u32 / columns;
+        // computing the bounds of each cell this way distributes the remaining
+        // pixels between the cells without leaving gaps
+        let bound = |total: u32, parts: u32, i: u32| {
+            (u64::from(total) * u64::from(i) / u64::from(parts)) as u32
+        };
+        let (left, right) = (
+            bound(width, columns, column),
+            bound(width, columns, column + 1),
+        );
+        let (bottom, top) = (bound(height, rows, row), bound(height, rows, row + 1));
+        let cell = (right - left, top - bottom);
+
+        let size = match self.aspect_ratio {
+            Some((ratio_width, ratio_height)) => {
+                let (cell_width, cell_height) = (u64::from(cell.0), u64::from(cell.1));
+                let (ratio_width, ratio_height) = (u64::from(ratio_width), u64::from(ratio_height));
+                (
+                    cmp::min(cell_width, cell_height * ratio_width / ratio_height) as u32,
+                    cmp::min(cell_height, cell_width * ratio_height / ratio_width) as u32,
+                )
+            }
+            None => cell,
+        };
+        IRect::new(
+            (
+                (left + (cell.0 - size.0) / 2) as i32,
+                (bottom + (cell.1 - size.1) / 2) as i32,
+            ),
+            size,
+        )
+    }
+
+    /// Returns a draw target which views the world through the camera of `player`,
+    /// only changing the pixels of its viewport.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `player` is not less than `self.players()`.
+    pub fn view<'a, T: DrawTarget>(
+        &self,
+        player: usize,
+        target: &'a mut T,
+    ) -> Clipped<Camera<Offset<&'a mut T>>> {
+        let viewport = self.viewport(player);
+        let (x, y) = viewport.position();
+        Clipped::new(
+            self.views[player].with_target(Offset::new(target, (-x, -y))),
+            viewport,
+        )
+    }
+
+    /// Clears all viewports of `target` with `color` and the
+    /// remaining pixels with the `letterbox_color`.
+    pub fn clear<T: DrawTarget>(
+        &self,
+        ctx: &mut Context,
+        target: &mut T,
+        color: (f32, f32, f32, f32),
+    ) {
+        ctx.clear_color(target, self.letterbox_color);
+        for player in 0..self.views.len() {
+            ctx.clear_region(target, self.viewport(player), color);
+        }
+    }
+
+    /// Converts a pixel of the target to the player whose viewport contains it
+    /// and the world position shown there.
+    ///
+    /// Returns `None` if the pixel is not inside of any viewport.
+    pub fn screen_to_world(&self, position: (i32, i32)) -> Option<(usize, (i32, i32))> {
+        (0..self.views.len()).find_map(|player| {
+            let viewport = self.viewport(player);
+            if viewport.contains(position) {
+                let (x, y) = viewport.position();
+                let position = (position.0 - x, position.1 - y);
+                Some((player, self.views[player].screen_to_world(position)))
+            } else {
+                None
+            }
+        })
+    }
+}
@@ -0,0 +1,284 @@
+//! Utilities to compare images, for example screenshots taken during tests.
+//!
+//! The functions comparing images do not require a `Context`,
+//! so they can also be used in build scripts.
+//!
+//! ```rust
+//! use crow::{image::RgbaImage, testing};
+//!
+//! let expected = RgbaImage::from_pixel(4, 4, [0, 0, 0, 255].into());
+//! let mut actual = expected.clone();
+//! actual.put_pixel(1, 2, [10, 0, 0, 255].into());
+//!
+//! assert!(testing::compare(&expected, &actual, 10).is_match());
+//!
+//! let report = testing::compare(&expected, &actual, 5);
+//! assert_eq!(report.differing_pixels, 1);
+//! assert_eq!(report.max_difference, 10);
+//! ```
+//!
+//! [`GoldenTest`] runs each test using a fresh `Context` and compares
+//! the resulting image to an expected image stored on disk.
+//!
+//! [`GoldenTest`]: struct.GoldenTest.html
+use std::{
+    error,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use glutin::{dpi::LogicalSize, event_loop::EventLoop, window::WindowBuilder};
+use image::{ImageError, Rgba, RgbaImage};
+
+use crate::{Context, Error};
+
+/// The result of comparing two images.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DiffReport {
+    /// Whether both images have the same dimensions.
+    pub same_dimensions: bool,
+    /// The number of pixels for which at least one channel differs by more than the tolerance.
+    ///
+    /// In case the dimensions differ, every pixel only contained in one image differs.
+    pub differing_pixels: usize,
+    /// The greatest difference of a single channel between the two images.
+    pub max_difference: u8,
+    /// An image highlighting all differing pixels in red, brighter pixels differ by more.
+    ///
+    /// Its size is the maximum width and height of both images.
+    pub heat_map: RgbaImage,
+}
+
+impl DiffReport {
+    /// Returns `true` if both images are equal within the given tolerance.
+    pub fn is_match(&self) -> bool {
+        self.same_dimensions && self.differing_pixels == 0
+    }
+
+    /// Saves `self.heat_map` to `path`.
+    pub fn save_heat_map<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
+        self.heat_map.save(path)
+    }
+}
+
+/// Compares `a` and `b`, pixels only differ if at least one channel
+/// differs by more than `tolerance`.
+pub fn compare(a: &RgbaImage, b: &RgbaImage, tolerance: u8) -> DiffReport {
+    let same_dimensions = a.dimensions() == b.dimensions();
+    let width = a.width().max(b.width());
+    let height = a.height().max(b.height());
+
+    let mut differing_pixels = 0;
+    let mut max_difference = 0;
+    let heat_map = RgbaImage::from_fn(width, height, |x, y| {
+        let difference = match (pixel(a, x, y), pixel(b, x, y)) {
+            (Some(a), Some(b)) => {
+                a.0.iter()
+                    .zip(b.0.iter())
+                    .map(|(&a, &b)| a.abs_diff(b))
+                    .max()
+                    .unwrap_or(0)
+            }
+            _ => u8::MAX,
+        };
+
+        max_difference = max_difference.max(difference);
+        if difference > tolerance {
+            differing_pixels += 1;
+            // make sure that even small differences are clearly visible
+            Rgba([128 + difference / 2, 0, 0, 255])
+        } else {
+            Rgba([0, 0, 0, 255])
+        }
+    });
+
+    DiffReport {
+        same_dimensions,
+        differing_pixels,
+        max_difference,
+        heat_map,
+    }
+}
+
+/// Loads the images at `a` and `b` and compares them using [`compare`].
+///
+/// [`compare`]: fn.compare.html
+pub fn compare_files<P, Q>(a: P, b: Q, tolerance: u8) -> Result<DiffReport, ImageError>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let a = image::open(a)?.to_rgba8();
+    let b = image::open(b)?.to_rgba8();
+    Ok(compare(&a, &b, tolerance))
+}
+
+fn pixel(image: &RgbaImage, x: u32, y: u32) -> Option<Rgba<u8>> {
+    if x < image.width() && y < image.height() {
+        Some(*image.get_pixel(x, y))
+    } else {
+        None
+    }
+}
+
+/// Compares the images created by tests to expected images stored in `expected_dir`.
+///
+/// The expected image of the test `name` is `{expected_dir}/{name}.png`. If the image of a
+/// test does not match, it is saved as `{actual_dir}/{name}.png` together with the heat map
+/// of the differences as `{actual_dir}/{name}_diff.png`.
+///
+/// ```rust, no_run
+/// use crow::{glutin::event_loop::EventLoop, testing::GoldenTest, Texture};
+///
+/// let golden = GoldenTest {
+///     tolerance: 2,
+///     ..Default::default()
+/// };
+/// golden.run(&EventLoop::new(), "red", |ctx| {
+///     let mut texture = Texture::new(ctx, (16, 16))?;
+///     ctx.clear_color(&mut texture, (1.0, 0.0, 0.0, 1.0));
+///     Ok(ctx.image_data(&texture))
+/// })?;
+/// # Ok::<(), crow::testing::GoldenError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenTest {
+    /// The directory containing the expected images, `tests/expected` by default.
+    pub expected_dir: PathBuf,
+    /// The directory in which differing images are saved, `tests/actual` by default.
+    pub actual_dir: PathBuf,
+    /// The maximum difference of each channel, see [`compare`].
+    ///
+    /// [`compare`]: fn.compare.html
+    pub tolerance: u8,
+    /// The logical size of the window of the contexts used by each test, `(720, 480)` by default.
+    pub dimensions: (u32, u32),
+}
+
+impl Default for GoldenTest {
+    fn default() -> Self {
+        GoldenTest {
+            expected_dir: PathBuf::from("tests/expected"),
+            actual_dir: PathBuf::from("tests/actual"),
+            tolerance: 0,
+            dimensions: (720, 480),
+        }
+    }
+}
+
+impl GoldenTest {
+    /// Compares `actual` to the expected image of the test `name`,
+    /// saving it and its heat map in case they differ.
+    pub fn check(&self, name: &str, actual: &RgbaImage) -> Result<(), GoldenError> {
+        let expected = image::open(self.expected_dir.join(format!("{}.png", name)))
+            .map_err(GoldenError::MissingExpected)?
+            .to_rgba8();
+        let report = compare(&expected, actual, self.tolerance);
+        if report.is_match() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.actual_dir)
+            .map_err(|e| GoldenError::SaveError(ImageError::IoError(e)))?;
+        actual
+            .save(self.actual_dir.join(format!("{}.png", name)))
+            .map_err(GoldenError::SaveError)?;
+        report
+            .save_heat_map(self.actual_dir.join(format!("{}_diff.png", name)))
+            .map_err(GoldenError::SaveError)?;
+        Err(GoldenError::Mismatch(report))
+    }
+
+    /// Runs the test `name` using a new `Context` with a hidden window
+    /// and compares the returned image using `GoldenTest::check`.
+    ///
+    /// The context is dropped before this function returns,
+    /// so tests can not influence each other.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a `Context` already exists.
+    pub fn run<T, F>(&self, event_loop: &EventLoop<T>, name: &str, f: F) -> Result<(), GoldenError>
+    where
+        F: FnOnce(&mut Context) -> Result<RgbaImage, Error>,
+    {
+        let window = WindowBuilder::new()
+            .with_inner_size(LogicalSize::new(self.dimensions.0, self.dimensions.1))
+            .with_visible(false);
+        let mut ctx = Context::new(window, event_loop).map_err(|e| GoldenError::Error(e.into()))?;
+        self.run_with(&mut ctx, name, f)
+    }
+
+    /// Runs the test `name` using a new context created by `Context::new_headless`,
+    /// which does not require a display server.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a `Context` already exists.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    pub fn run_headless<F>(&self, name: &str, f: F) -> Result<(), GoldenError>
+    where
+        F: FnOnce(&mut Context) -> Result<RgbaImage, Error>,
+    {
+        let mut ctx =
+            Context::new_headless(self.dimensions).map_err(|e| GoldenError::Error(e.into()))?;
+        self.run_with(&mut ctx, name, f)
+    }
+
+    fn run_with<F>(&self, ctx: &mut Context, name: &str, f: F) -> Result<(), GoldenError>
+    where
+        F: FnOnce(&mut Context) -> Result<RgbaImage, Error>,
+    {
+        let actual = f(ctx).map_err(GoldenError::Error)?;
+        self.check(name, &actual)
+    }
+}
+
+/// The error returned by the methods of [`GoldenTest`].
+///
+/// [`GoldenTest`]: struct.GoldenTest.html
+#[derive(Debug)]
+pub enum GoldenError {
+    /// Creating the context failed or the test returned an error.
+    Error(Error),
+    /// The expected image could not be loaded.
+    MissingExpected(ImageError),
+    /// The image of the test differs from the expected image.
+    Mismatch(DiffReport),
+    /// Saving the image of the test or its heat map failed.
+    SaveError(ImageError),
+}
+
+impl Display for GoldenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(err) => write!(f, "runtime error: {}", err),
+            Self::MissingExpected(err) => write!(f, "expected image not found: {}", err),
+            Self::Mismatch(report) if !report.same_dimensions => {
+                write!(f, "the dimensions differ from the expected image")
+            }
+            Self::Mismatch(report) => write!(
+                f,
+                "{} pixels differ from the expected image by up to {}",
+                report.differing_pixels, report.max_difference
+            ),
+            Self::SaveError(err) => write!(f, "unable to save the actual image: {}", err),
+        }
+    }
+}
+
+impl error::Error for GoldenError {}
+
+impl From<Error> for GoldenError {
+    fn from(e: Error) -> Self {
+        GoldenError::Error(e)
+    }
+}
@@ -0,0 +1,431 @@
+//! Text rendering using bitmap fonts in the [BMFont] text format.
+//!
+//! Each glyph is a section of one of the pages of the font, so drawing text
+//! only requires a few draw calls as long as the font uses a single page.
+//!
+//! ```rust, no_run
+//! use crow::{
+//!     glutin::{event_loop::EventLoop, window::WindowBuilder},
+//!     text::{BitmapFont, TextConfig},
+//!     Context, DrawConfig,
+//! };
+//!
+//! let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+//! let font = BitmapFont::load(&mut ctx, "./fonts/pixel.fnt")?;
+//!
+//! let mut surface = ctx.surface();
+//! ctx.clear_color(&mut surface, (0.0, 0.0, 0.0, 1.0));
+//! ctx.draw_text(
+//!     &mut surface,
+//!     &font,
+//!     "hello\nworld",
+//!     (20, 200),
+//!     &TextConfig {
+//!         draw_config: DrawConfig {
+//!             scale: (2, 2),
+//!             ..Default::default()
+//!         },
+//!         ..Default::default()
+//!     },
+//! );
+//! ctx.present(surface)?;
+//! # Ok::<(), crow::Error>(())
+//! ```
+//!
+//! With the `ttf` feature, TrueType fonts can be rasterized into a `BitmapFont`
+//! using [`Font`].
+//!
+//! [BMFont]: https://www.angelcode.com/products/bmfont/doc/file_format.html
+//! [`Font`]: struct.Font.html
+use std::{collections::HashMap, fs, path::Path};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Context, DrawConfig, DrawTarget, LoadFontError, Rect, Subsystem, Texture};
+
+#[cfg(feature = "ttf")]
+mod ttf;
+
+#[cfg(feature = "ttf")]
+pub use ttf::{Font, RasterConfig};
+
+/// A single character of a [`BitmapFont`].
+///
+/// [`BitmapFont`]: struct.BitmapFont.html
+#[derive(Debug, Clone)]
+pub struct Glyph {
+    /// The image of this glyph, `None` for glyphs without any visible pixels, like spaces.
+    pub texture: Option<Texture>,
+    /// The offset from the current position to the upper left corner of the glyph,
+    /// with positive values moving right and down.
+    pub offset: (i32, i32),
+    /// How far the current position is moved after drawing this glyph.
+    pub advance: i32,
+}
+
+/// How text should be drawn by `Context::draw_text`.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TextConfig {
+    /// Used for each glyph.
+    ///
+    /// The `scale` is also applied to the distances between
+    /// glyphs and lines, while `rotation`, flipping and
+    /// `transform` only change each glyph on its own.
+    pub draw_config: DrawConfig,
+    /// Additional space between two glyphs in pixels of the font.
+    pub letter_spacing: i32,
+    /// Additional space between two lines in pixels of the font.
+    pub line_spacing: i32,
+}
+
+/// A font consisting of textures containing each glyph,
+/// loaded from the [BMFont] text format.
+///
+/// [BMFont]: https://www.angelcode.com/products/bmfont/doc/file_format.html
+#[derive(Debug, Clone)]
+pub struct BitmapFont {
+    line_height: u32,
+    base: u32,
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), i32>,
+    /// The rasterized font, used for all pairs which are not in `kerning`.
+    #[cfg(feature = "ttf")]
+    ttf_kerning: Option<ttf::Kerning>,
+}
+
+/// Splits a line of a font description into its tag and its `key=value` pairs.
+fn parse_line(line: &str) -> (&str, Vec<(&str, &str)>) {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_at(line.find(char::is_whitespace).unwrap_or(line.len()));
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        let key_end = match rest.find('=') {
+            Some(i) => i,
+            None => break,
+        };
+        let key = rest[..key_end].trim();
+        rest = &rest[key_end + 1..];
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            rest = quoted.get(end + 1..).unwrap_or("");
+            &quoted[..end]
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let value = &rest[..end];
+            rest = &rest[end..];
+            value
+        };
+        attributes.push((key, value));
+    }
+
+    (tag, attributes)
+}
+
+impl BitmapFont {
+    /// Loads the font description at `path` together with all its pages,
+    /// which are located relative to the font description.
+    pub fn load<P: AsRef<Path>>(ctx: &mut Context, path: P) -> Result<Self, LoadFontError> {
+        let path = path.as_ref();
+        let description = fs::read_to_string(path).map_err(LoadFontError::IoError)?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let mut pages = Vec::new();
+        for (i, line) in description.lines().enumerate() {
+            let (tag, attributes) = parse_line(line);
+            if tag != "page" {
+                continue;
+            }
+
+            let invalid = |reason| LoadFontError::InvalidFont {
+                line: i + 1,
+                reason,
+            };
+            let mut id = None;
+            let mut file = None;
+            for (key, value) in attributes {
+                match key {
+                    "id" => {
+                        id = Some(
+                            value
+                                .parse::<usize>()
+                                .map_err(|_| invalid("invalid page id"))?,
+                        )
+                    }
+                    "file" => file = Some(value),
+                    _ => (),
+                }
+            }
+
+            match (id, file) {
+                (Some(id), Some(file)) if id == pages.len() => {
+                    pages.push(Texture::load(ctx, directory.join(file))?)
+                }
+                (Some(_), Some(_)) => {
+                    return Err(invalid("the page ids must be consecutive, starting at 0"))
+                }
+                _ => return Err(invalid("a page requires both an `id` and a `file`")),
+            }
+        }
+
+        Self::new(&description, pages)
+    }
+
+    /// Creates a font from a font description in the BMFont text format,
+    /// using `pages` instead of the files mentioned in the description.
+    pub fn new(description: &str, pages: Vec<Texture>) -> Result<Self, LoadFontError> {
+        let mut font = BitmapFont {
+            line_height: 0,
+            base: 0,
+            glyphs: HashMap::new(),
+            kerning: HashMap::new(),
+            #[cfg(feature = "ttf")]
+            ttf_kerning: None,
+        };
+        let mut has_common = false;
+
+        for (i, line) in description.lines().enumerate() {
+            let invalid = |reason| LoadFontError::InvalidFont {
+                line: i + 1,
+                reason,
+            };
+            let (tag, attributes) = parse_line(line);
+            let get = |key| -> Result<i32, LoadFontError> {
+                attributes
+                    .iter()
+                    .find(|&&(k, _)| k == key)
+                    .ok_or_else(|| invalid("missing attribute"))?
+                    .1
+                    .parse()
+                    .map_err(|_| invalid("attributes must be integers"))
+            };
+
+            match tag {
+                "common" => {
+                    let line_height = get("lineHeight")?;
+                    let base = get("base")?;
+                    if line_height < 0 || base < 0 {
+                        return Err(invalid("`lineHeight` and `base` must not be negative"));
+                    }
+                    font.line_height = line_height as u32;
+                    font.base = base as u32;
+                    has_common = true;
+                }
+                "char" => {
+                    let id = get("id")?;
+                    let character = std::char::from_u32(id as u32)
+                        .ok_or_else(|| invalid("invalid character id"))?;
+                    let (x, y, width, height) =
+                        (get("x")?, get("y")?, get("width")?, get("height")?);
+                    let page = pages
+                        .get(get("page")? as usize)
+                        .ok_or_else(|| invalid("missing page"))?;
+                    if x < 0 || y < 0 || width < 0 || height < 0 {
+                        return Err(invalid("the bounds of a glyph must not be negative"));
+                    }
+
+                    let (x, y, width, height) = (x as u32, y as u32, width as u32, height as u32);
+                    let (page_width, page_height) = page.dimensions();
+                    let fits = |start: u32, size: u32, max: u32| matches!(start.checked_add(size), Some(end) if end <= max);
+                    if !fits(x, width, page_width) || !fits(y, height, page_height) {
+                        return Err(invalid("glyph outside of its page"));
+                    }
+
+                    let texture =
+                        if width == 0 || height == 0 {
+                            None
+                        } else {
+                            // textures start at the bottom left while the glyphs start at the top left
+                            Some(page.get_section(
+                                Rect::new((x, y), (width, height)).flip_y(page_height),
+                            ))
+                        };
+                    font.glyphs.insert(
+                        character,
+                        Glyph {
+                            texture,
+                            offset: (get("xoffset")?, get("yoffset")?),
+                            advance: get("xadvance")?,
+                        },
+                    );
+                }
+                "kerning" => {
+                    let first = std::char::from_u32(get("first")? as u32);
+                    let second = std::char::from_u32(get("second")? as u32);
+                    match (first, second) {
+                        (Some(first), Some(second)) => {
+                            font.kerning.insert((first, second), get("amount")?);
+                        }
+                        _ => return Err(invalid("invalid character id")),
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if has_common {
+            Ok(font)
+        } else {
+            Err(LoadFontError::InvalidFont {
+                line: 0,
+                reason: "missing `common` line",
+            })
+        }
+    }
+
+    /// Returns the distance between the baselines of two lines.
+    pub fn line_height(&self) -> u32 {
+        self.line_height
+    }
+
+    /// Returns the distance from the top of a line to its baseline.
+    pub fn base(&self) -> u32 {
+        self.base
+    }
+
+    /// Returns the glyph used for `c`, if it exists.
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c)
+    }
+
+    /// Returns the adjustment of the advance of `first` if it is followed by `second`.
+    pub fn kerning(&self, first: char, second: char) -> i32 {
+        if let Some(&amount) = self.kerning.get(&(first, second)) {
+            return amount;
+        }
+
+        #[cfg(feature = "ttf")]
+        if let Some(kerning) = &self.ttf_kerning {
+            return kerning.get(first, second);
+        }
+
+        0
+    }
+
+    /// Returns how far the current position is moved after drawing `c`,
+    /// ignoring kerning, or `None` if the font does not contain `c`.
+    pub fn advance(&self, c: char) -> Option<i32> {
+        self.glyphs.get(&c).map(|glyph| glyph.advance)
+    }
+
+    /// Returns the size of `text` if it is drawn using `config`, without drawing it.
+    ///
+    /// The width is the sum of the advances of the longest line, including kerning
+    /// and `letter_spacing`, while the height is the distance from the top of the first line
+    /// to the bottom of the last line. Both are multiplied by the `scale` of `config`.
+    ///
+    /// As `Context::draw_text` starts at the baseline of the first line, the top of the
+    /// measured area is `base() * scale.1` pixels above the position of the text.
+    ///
+    /// ```rust
+    /// # use crow::text::{BitmapFont, TextConfig};
+    /// # fn center(font: &BitmapFont, label: &str, button: (u32, u32)) -> (i32, i32) {
+    /// let config = TextConfig::default();
+    /// let (width, height) = font.measure(label, &config);
+    /// (
+    ///     (button.0 as i32 - width as i32) / 2,
+    ///     (button.1 as i32 - height as i32) / 2 + height as i32 - font.base() as i32,
+    /// )
+    /// # }
+    /// ```
+    pub fn measure(&self, text: &str, config: &TextConfig) -> (u32, u32) {
+        let mut width = 0;
+        for line in text.split('\n') {
+            let mut x = 0;
+            let mut previous = None;
+            for c in line.chars() {
+                let glyph = if let Some(glyph) = self.glyphs.get(&c) {
+                    glyph
+                } else {
+                    continue;
+                };
+                if let Some(previous) = previous {
+                    x += self.kerning(previous, c) + config.letter_spacing;
+                }
+                previous = Some(c);
+                x += glyph.advance;
+            }
+            width = width.max(x);
+        }
+
+        let lines = text.split('\n').count() as i32;
+        let height =
+            (lines - 1) * (self.line_height as i32 + config.line_spacing) + self.line_height as i32;
+        (
+            width.max(0) as u32 * config.draw_config.scale.0,
+            height.max(0) as u32 * config.draw_config.scale.1,
+        )
+    }
+
+    /// Returns the position of each visible glyph of `text`,
+    /// relative to the start of the baseline of the first line.
+    fn layout<'a>(
+        &'a self,
+        text: &'a str,
+        config: &'a TextConfig,
+    ) -> impl Iterator<Item = (&'a Texture, (i32, i32))> + 'a {
+        let (scale_x, scale_y) = (
+            config.draw_config.scale.0 as i32,
+            config.draw_config.scale.1 as i32,
+        );
+        let line_height = self.line_height as i32 + config.line_spacing;
+        let (mut x, mut y) = (0, 0);
+        let mut previous = None;
+        text.chars().filter_map(move |c| {
+            if c == '\n' {
+                x = 0;
+                y -= line_height * scale_y;
+                previous = None;
+                return None;
+            }
+
+            let glyph = self.glyphs.get(&c)?;
+            if let Some(previous) = previous {
+                x += (self.kerning(previous, c) + config.letter_spacing) * scale_x;
+            }
+            previous = Some(c);
+
+            let position = glyph.texture.as_ref().map(|texture| {
+                let height = texture.dimensions().1 as i32;
+                (
+                    x + glyph.offset.0 * scale_x,
+                    y + (self.base as i32 - glyph.offset.1 - height) * scale_y,
+                )
+            });
+            x += glyph.advance * scale_x;
+            Some((glyph.texture.as_ref()?, position?))
+        })
+    }
+}
+
+impl Context {
+    /// Draws `text` onto `target` using `font`, starting at the left end of the
+    /// baseline of the first line at `position`.
+    ///
+    /// Each `'\n'` starts a new line below the previous one, characters
+    /// without a glyph in `font` are skipped.
+    pub fn draw_text<T>(
+        &mut self,
+        target: &mut T,
+        font: &BitmapFont,
+        text: &str,
+        position: impl Into<(i32, i32)>,
+        config: &TextConfig,
+    ) where
+        T: DrawTarget,
+    {
+        let position = position.into();
+        self.in_subsystem(Subsystem::Text, |ctx| {
+            for (texture, (x, y)) in font.layout(text, config) {
+                target.receive_draw(
+                    ctx,
+                    texture,
+                    (position.0 + x, position.1 + y),
+                    &config.draw_config,
+                );
+            }
+        })
+    }
+}
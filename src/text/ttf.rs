@@ -0,0 +1,219 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use ab_glyph::{point, Font as _, FontArc, PxScale, PxScaleFont, ScaleFont};
+
+use image::{Rgba, RgbaImage};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    text::{BitmapFont, Glyph},
+    Atlas, AtlasConfig, Context, LoadFontError, NewTextureError,
+};
+
+/// How a [`Font`] is turned into a [`BitmapFont`] by `Font::rasterize`.
+///
+/// [`Font`]: struct.Font.html
+/// [`BitmapFont`]: struct.BitmapFont.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RasterConfig {
+    /// The distance between the highest ascender and the lowest
+    /// descender of the font in pixels.
+    pub size: u32,
+    /// Whether pixels which are only partially covered by a glyph are partially transparent.
+    ///
+    /// If this is `false`, each pixel is either fully opaque or fully transparent,
+    /// which is generally desired for pixel art.
+    pub antialiasing: bool,
+    /// Whether the outline of each glyph is moved by less than a pixel, so that
+    /// its top left corner is aligned to whole pixels.
+    ///
+    /// This keeps the strokes of fonts designed for a pixel grid crisp,
+    /// but slightly changes the shape of other fonts.
+    pub hinting: bool,
+}
+
+impl Default for RasterConfig {
+    fn default() -> Self {
+        RasterConfig {
+            size: 16,
+            antialiasing: false,
+            hinting: true,
+        }
+    }
+}
+
+/// A TrueType or OpenType font, which must be rasterized into a
+/// [`BitmapFont`] using `Font::rasterize` before drawing it.
+///
+/// This requires the `ttf` feature.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     text::{Font, RasterConfig, TextConfig},
+///     Context,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let font = Font::load("./fonts/pixel.ttf")?;
+/// let font = font.rasterize(
+///     &mut ctx,
+///     ' '..='~',
+///     &RasterConfig {
+///         size: 8,
+///         ..RasterConfig::default()
+///     },
+/// )?;
+///
+/// let mut surface = ctx.surface();
+/// ctx.clear_color(&mut surface, (0.0, 0.0, 0.0, 1.0));
+/// ctx.draw_text(&mut surface, &font, "hello world", (20, 200), &TextConfig::default());
+/// ctx.present(surface)?;
+/// # Ok::<(), crow::Error>(())
+/// ```
+///
+/// [`BitmapFont`]: struct.BitmapFont.html
+#[derive(Clone)]
+pub struct Font {
+    inner: FontArc,
+}
+
+impl std::fmt::Debug for Font {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Font")
+            .field("glyph_count", &self.inner.glyph_count())
+            .finish()
+    }
+}
+
+/// The kerning of a rasterized [`Font`], which is computed when needed
+/// instead of storing the kerning of each pair of characters.
+///
+/// [`Font`]: struct.Font.html
+#[derive(Debug, Clone)]
+pub(crate) struct Kerning {
+    font: PxScaleFont<FontArc>,
+}
+
+impl Kerning {
+    pub(crate) fn get(&self, first: char, second: char) -> i32 {
+        let font = &self.font;
+        font.kern(font.glyph_id(first), font.glyph_id(second))
+            .round() as i32
+    }
+}
+
+impl Font {
+    /// Loads the font file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LoadFontError> {
+        let bytes = fs::read(path).map_err(LoadFontError::IoError)?;
+        Self::from_bytes(bytes)
+    }
+
+    /// Parses the content of a font file.
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Result<Self, LoadFontError> {
+        FontArc::try_from_vec(bytes.into())
+            .map(|inner| Font { inner })
+            .map_err(|_| LoadFontError::TrueTypeError)
+    }
+
+    /// Draws each of the given `characters` into a single texture,
+    /// returning a `BitmapFont` containing them.
+    ///
+    /// All glyphs and the baseline are aligned to whole pixels and the advance and kerning
+    /// of each glyph are rounded, so text drawn using the result stays crisp when scaled.
+    /// Characters which are not part of the font are skipped.
+    pub fn rasterize(
+        &self,
+        ctx: &mut Context,
+        characters: impl IntoIterator<Item = char>,
+        config: &RasterConfig,
+    ) -> Result<BitmapFont, NewTextureError> {
+        let font = self
+            .inner
+            .clone()
+            .into_scaled(PxScale::from(config.size as f32));
+        let base = font.ascent().round().max(0.0);
+        let line_height = (font.ascent() - font.descent() + font.line_gap())
+            .round()
+            .max(0.0);
+
+        let mut characters = characters
+            .into_iter()
+            .filter(|&c| font.glyph_id(c).0 != 0)
+            .collect::<Vec<_>>();
+        characters.sort_unstable();
+        characters.dedup();
+
+        let mut atlas = Atlas::new(AtlasConfig {
+            padding: 1,
+            extrusion: 0,
+        });
+        let mut glyphs = Vec::new();
+        for &c in characters.iter() {
+            let id = font.glyph_id(c);
+            let advance = font.h_advance(id).round() as i32;
+            // the baseline is positioned at a whole pixel, relative to the top of the line
+            let mut position = point(0.0, base);
+            if config.hinting {
+                if let Some(outline) = font.font.outline(id) {
+                    let factor = font.scale_factor();
+                    let left = outline.bounds.min.x * factor.horizontal;
+                    let top = base - outline.bounds.min.y * factor.vertical;
+                    position = point(left.round() - left, base + top.round() - top);
+                }
+            }
+
+            let glyph = id.with_scale_and_position(font.scale, position);
+            let (entry, offset) = match font.outline_glyph(glyph) {
+                Some(glyph) => {
+                    let bounds = glyph.px_bounds();
+                    let width = (bounds.max.x - bounds.min.x) as u32;
+                    let height = (bounds.max.y - bounds.min.y) as u32;
+                    let mut image = RgbaImage::new(width, height);
+                    glyph.draw(|x, y, coverage| {
+                        let alpha = if config.antialiasing {
+                            (coverage * 255.0).round() as u8
+                        } else if coverage >= 0.5 {
+                            255
+                        } else {
+                            0
+                        };
+                        image.put_pixel(x, y, Rgba([255, 255, 255, alpha]));
+                    });
+                    let offset = (bounds.min.x as i32, bounds.min.y as i32);
+                    (Some(atlas.push(image)), offset)
+                }
+                None => (None, (0, 0)),
+            };
+            glyphs.push((c, entry, offset, advance));
+        }
+
+        let textures = atlas.build(ctx)?;
+        let glyphs = glyphs
+            .into_iter()
+            .map(|(c, entry, offset, advance)| {
+                let texture = entry.map(|i| textures[i].clone());
+                (
+                    c,
+                    Glyph {
+                        texture,
+                        offset,
+                        advance,
+                    },
+                )
+            })
+            .collect();
+
+        Ok(BitmapFont {
+            line_height: line_height as u32,
+            base: base as u32,
+            glyphs,
+            kerning: HashMap::new(),
+            ttf_kerning: Some(Kerning { font }),
+        })
+    }
+}
@@ -1,14 +1,55 @@
-use std::{path::Path, rc::Rc};
+use std::{backtrace::Backtrace, path::Path, rc::Rc, time::Duration};
 
-use image::RgbaImage;
+use image::{
+    error::{ImageError, ParameterError, ParameterErrorKind},
+    RgbaImage,
+};
 
 use crate::{
-    backend::tex::RawTexture, Context, DrawConfig, DrawTarget, LoadTextureError, NewTextureError,
-    Texture, UnwrapBug,
+    backend::{tex::RawTexture, QueryHandle},
+    Animation, AnimationFrame, Context, DrawConfig, DrawError, DrawTarget, IRect, LoadTextureError,
+    LoopMode, NewTextureError, Rect, Shader, SpriteBatch, Texture, TextureOptions, UniformValue,
 };
 
+/// Why drawing to a texture required a copy of it, see [`TextureCopy`].
+///
+/// [`TextureCopy`]: struct.TextureCopy.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CopyReason {
+    /// The texture is only a section of a larger texture, for example
+    /// one returned by `Texture::get_section` or `Atlas::build`.
+    Section,
+    /// The content of the texture is shared with other textures,
+    /// for example after cloning it.
+    Shared,
+}
+
+/// A copy of a texture which was required to draw to it,
+/// passed to the callbacks registered using [`Context::on_texture_copy`].
+///
+/// As `Texture`s are copy-on-write, drawing to a texture which is a section of
+/// another texture or which has been cloned first copies its content. Drawing to the
+/// same texture afterwards does not require another copy, but unexpectedly copying
+/// textures each frame can be expensive.
+///
+/// [`Context::on_texture_copy`]: struct.Context.html#method.on_texture_copy
+#[derive(Debug)]
+pub struct TextureCopy {
+    /// Why the texture had to be copied.
+    pub reason: CopyReason,
+    /// The dimensions of the copied texture.
+    pub size: (u32, u32),
+    /// The label of the copied texture, see `Texture::set_label`.
+    pub label: Option<String>,
+    /// Where the texture was copied.
+    ///
+    /// This is only captured if backtraces are enabled using
+    /// the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variable.
+    pub backtrace: Backtrace,
+}
+
 impl Texture {
-    fn from_raw(raw: RawTexture) -> Self {
+    pub(crate) fn from_raw_texture(raw: RawTexture) -> Self {
         let size = raw.dimensions;
 
         Texture {
@@ -21,17 +62,116 @@ impl Texture {
     /// Creates a new texture with the given `dimensions`.
     ///
     /// The content of the texture is undefined after its creation.
-    pub fn new(ctx: &mut Context, dimensions: (u32, u32)) -> Result<Self, NewTextureError> {
+    pub fn new(
+        ctx: &mut Context,
+        dimensions: impl Into<(u32, u32)>,
+    ) -> Result<Self, NewTextureError> {
+        let dimensions = dimensions.into();
         let raw = RawTexture::new(&mut ctx.backend, dimensions)?;
 
-        Ok(Self::from_raw(raw))
+        Ok(Self::from_raw_texture(raw))
+    }
+
+    /// Creates a new texture with the given `dimensions` which is sampled using `options`.
+    ///
+    /// The content of the texture is undefined after its creation.
+    pub fn new_with(
+        ctx: &mut Context,
+        dimensions: impl Into<(u32, u32)>,
+        options: TextureOptions,
+    ) -> Result<Self, NewTextureError> {
+        let dimensions = dimensions.into();
+        let raw = RawTexture::new_with(&mut ctx.backend, dimensions, options)?;
+
+        Ok(Self::from_raw_texture(raw))
     }
 
     /// Creates a new texture from the given `image`.
     pub fn from_image(ctx: &mut Context, image: RgbaImage) -> Result<Self, NewTextureError> {
         let raw = RawTexture::from_image(&mut ctx.backend, image)?;
 
-        Ok(Self::from_raw(raw))
+        Ok(Self::from_raw_texture(raw))
+    }
+
+    /// Creates a new texture from the given `image` which is sampled using `options`.
+    ///
+    /// If `options.mipmaps` is set, all mipmaps are generated from `image`.
+    ///
+    /// ```rust, no_run
+    /// use crow::{
+    ///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+    ///     Context, Filter, Texture, TextureOptions,
+    /// };
+    ///
+    /// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+    /// let image = image::open("./textures/player.png")?.to_rgba8();
+    /// let options = TextureOptions {
+    ///     min_filter: Filter::Linear,
+    ///     mag_filter: Filter::Linear,
+    ///     mipmaps: true,
+    ///     ..Default::default()
+    /// };
+    /// let player = Texture::from_image_with(&mut ctx, image, options)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_image_with(
+        ctx: &mut Context,
+        image: RgbaImage,
+        options: TextureOptions,
+    ) -> Result<Self, NewTextureError> {
+        let raw = RawTexture::from_image_with(&mut ctx.backend, image, options)?;
+
+        Ok(Self::from_raw_texture(raw))
+    }
+
+    /// Returns the options used to sample this texture.
+    ///
+    /// Sections and copies of a texture share its options.
+    pub fn options(&self) -> TextureOptions {
+        self.inner.options
+    }
+
+    /// Recomputes the mipmaps of this texture from its current content.
+    ///
+    /// Mipmaps are not updated while drawing to a texture, so this should be called
+    /// after drawing to a texture with `TextureOptions::mipmaps`. Does nothing for
+    /// textures without mipmaps.
+    pub fn generate_mipmaps(&self, ctx: &mut Context) {
+        self.inner.generate_mipmaps(&mut ctx.backend)
+    }
+
+    /// Creates a new texture from RGBA pixels with 8 bits per channel,
+    /// starting with the top left pixel and going row by row.
+    ///
+    /// Returns `ImageError::Parameter` if the length of `data` is not `width * height * 4`.
+    pub fn from_raw(
+        ctx: &mut Context,
+        dimensions: impl Into<(u32, u32)>,
+        data: &[u8],
+    ) -> Result<Self, LoadTextureError> {
+        let (width, height) = dimensions.into();
+        let expected = u64::from(width) * u64::from(height) * 4;
+        if data.len() as u64 != expected {
+            return Err(LoadTextureError::ImageError(ImageError::Parameter(
+                ParameterError::from_kind(ParameterErrorKind::DimensionMismatch),
+            )));
+        }
+
+        let image = RgbaImage::from_raw(width, height, data.to_vec()).unwrap();
+        Ok(Self::from_image(ctx, image)?)
+    }
+
+    /// Creates a new texture from the given `image`, multiplying
+    /// the color of each pixel with its alpha value.
+    ///
+    /// The resulting texture should be drawn using `BlendMode::PremultipliedAlpha`.
+    pub fn from_image_premultiplied(
+        ctx: &mut Context,
+        image: RgbaImage,
+    ) -> Result<Self, NewTextureError> {
+        let raw = RawTexture::from_image_premultiplied(&mut ctx.backend, image)?;
+
+        Ok(Self::from_raw_texture(raw))
     }
 
     /// Loads a texture from an image located at `path`.
@@ -40,15 +180,40 @@ impl Texture {
 
         let raw = RawTexture::from_image(&mut ctx.backend, image.to_rgba8())?;
 
-        Ok(Self::from_raw(raw))
+        Ok(Self::from_raw_texture(raw))
+    }
+
+    /// Loads a texture from an image file stored in `bytes`, for example
+    /// one embedded using `include_bytes!`.
+    ///
+    /// The format is detected automatically and can be any format supported by `image`.
+    ///
+    /// ```rust, no_run
+    /// use crow::{
+    ///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+    ///     Context, Texture,
+    /// };
+    ///
+    /// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+    /// let player = Texture::load_from_memory(&mut ctx, include_bytes!("../textures/player.png"))?;
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    pub fn load_from_memory(ctx: &mut Context, bytes: &[u8]) -> Result<Texture, LoadTextureError> {
+        let image = image::load_from_memory(bytes).map_err(LoadTextureError::ImageError)?;
+
+        let raw = RawTexture::from_image(&mut ctx.backend, image.to_rgba8())?;
+
+        Ok(Self::from_raw_texture(raw))
     }
 
-    /// Returns the part of `self` specified by `position` and `size` as a `Texture`.
+    /// Returns the part of `self` inside of `section` as a `Texture`.
     ///
     /// # Panics
     ///
     /// This function panics if part of the requested section would be outside of the original texture.
-    pub fn get_section(&self, position: (u32, u32), size: (u32, u32)) -> Texture {
+    pub fn get_section(&self, section: impl Into<Rect>) -> Texture {
+        let section = section.into();
+        let (position, size) = (section.position(), section.size());
         assert!(
             position.0 + size.0 <= self.size.0,
             "invalid section width: {} + {} > {}",
@@ -71,6 +236,39 @@ impl Texture {
         }
     }
 
+    /// Returns a texture with the given `size` showing `self` starting at `offset`, which
+    /// continues outside of `self` depending on the wrap mode used while drawing it.
+    ///
+    /// Unlike all other textures, the result is not a section of the underlying texture,
+    /// so it is only used by `Context::draw_tiled` to draw `self`. `offset` is wrapped
+    /// around twice the dimensions of `self`, which does not change the result of
+    /// `Wrap::Repeat` and `Wrap::MirroredRepeat`.
+    pub(crate) fn repeated(&self, offset: (i32, i32), size: (u32, u32)) -> Texture {
+        debug_assert!(self.position == (0, 0) && self.size == self.inner.dimensions);
+        let (width, height) = self.size;
+        Texture {
+            inner: Rc::clone(&self.inner),
+            position: (
+                offset.0.rem_euclid(width as i32 * 2) as u32,
+                offset.1.rem_euclid(height as i32 * 2) as u32,
+            ),
+            size,
+        }
+    }
+
+    /// Creates a copy of `self` which does not share its content with any other texture.
+    ///
+    /// Cloning a `Texture` only copies its content once one of the clones is drawn to,
+    /// see `Context::on_texture_copy`. This instead always copies it right away, which
+    /// can be used to take a snapshot of a texture before drawing to it again.
+    /// Copying a section only copies the content of the section itself.
+    ///
+    /// The copy keeps the options, border color and label of `self`.
+    pub fn deep_clone(&self, ctx: &mut Context) -> Result<Texture, DrawError> {
+        ctx.backend.validate(&self.inner)?;
+        Ok(Self::from_raw_texture(self.copy_raw(ctx)?))
+    }
+
     /// Returns the dimensions of this texture.
     pub fn dimensions(&self) -> (u32, u32) {
         self.size
@@ -86,31 +284,297 @@ impl Texture {
         self.size.1
     }
 
-    fn prepare_as_draw_target<'a>(&'a mut self, ctx: &mut Context) -> &'a mut RawTexture {
-        if self.position != (0, 0) || self.size != self.inner.dimensions {
-            let mut inner = RawTexture::new(&mut ctx.backend, self.size).unwrap_bug();
-            inner.add_framebuffer(&mut ctx.backend);
-            ctx.backend.draw(
-                inner.framebuffer_id,
-                self.size,
-                1,
-                &self.inner,
-                self.position,
-                self.size,
-                (0, 0),
-                &DrawConfig::default(),
-            );
+    /// Sets the color used for the parts of a draw call which lie outside of this texture.
+    ///
+    /// Scaled, rotated or transformed draw calls may sample slightly outside of the
+    /// drawn texture. By default this samples the neighboring part of the underlying
+    /// texture, causing adjacent entries of a texture atlas to bleed into each other,
+    /// or repeats the closest edge of the underlying texture. Using an invisible border
+    /// color, e.g. `Some((0.0, 0.0, 0.0, 0.0))`, prevents these artifacts.
+    ///
+    /// Sections created after calling this method share its border color. In case
+    /// the underlying texture is still used by other textures, `self` is copied first,
+    /// leaving the border color of these textures unchanged.
+    pub fn set_border_color(&mut self, ctx: &mut Context, color: Option<(f32, f32, f32, f32)>) {
+        if self.inner.border_color == color && ctx.backend.validate(&self.inner).is_ok() {
+            return;
+        }
 
-            self.inner = Rc::new(inner);
+        match self.make_unique(ctx) {
+            Ok(inner) => inner.set_border_color(&mut ctx.backend, color),
+            Err(err) => ctx.backend.report_error(err),
+        }
+    }
+
+    /// Returns the border color set by `Texture::set_border_color`.
+    pub fn border_color(&self) -> Option<(f32, f32, f32, f32)> {
+        self.inner.border_color
+    }
+
+    /// Names this texture, so it can be identified in debug output.
+    ///
+    /// The label is included in `TextureCopy`, `CapturedDraw` and the error message
+    /// when using a texture of a different `Context`. If `GL_KHR_debug` is supported,
+    /// it is also used as the name of the underlying OpenGL objects in graphics debuggers.
+    ///
+    /// The label is kept when the texture has to be copied to draw to it. Like
+    /// `Texture::set_border_color`, this copies `self` in case the underlying texture
+    /// is still used by other textures.
+    pub fn set_label(&mut self, ctx: &mut Context, label: &str) {
+        if self.inner.label.as_deref() == Some(label) && ctx.backend.validate(&self.inner).is_ok() {
+            return;
+        }
+
+        match self.make_unique(ctx) {
+            Ok(inner) => inner.set_label(&mut ctx.backend, label),
+            Err(err) => ctx.backend.report_error(err),
+        }
+    }
+
+    /// Returns the label set by `Texture::set_label`.
+    pub fn label(&self) -> Option<String> {
+        self.inner.label.clone()
+    }
+
+    /// Replaces the pixels of `self` covered by `image` with the pixels of `image`,
+    /// where `position` is the bottom left corner of `image` inside of `self`.
+    ///
+    /// This uploads `image` directly without drawing it, which is useful to
+    /// stream small changes, for example of a minimap, into an existing texture.
+    /// The parts of `image` outside of `self` are ignored.
+    ///
+    /// Like drawing to `self`, this first copies the texture in case it is a
+    /// section of a larger texture or shares its content with other textures.
+    pub fn write_section(
+        &mut self,
+        ctx: &mut Context,
+        position: impl Into<(i32, i32)>,
+        image: &RgbaImage,
+    ) {
+        let position = position.into();
+        let region = match IRect::new(position, image.dimensions())
+            .intersection(IRect::new((0, 0), self.size))
+        {
+            Some(region) => region,
+            None => return,
+        };
+
+        let target = if let Some(target) = self.prepare_as_draw_target(ctx) {
+            target
+        } else {
+            return;
+        };
+
+        // the rows of the region starting at the bottom, as images start at the top
+        let (width, height) = (region.width as usize, region.height as usize);
+        let left = (region.left() - position.0) as usize;
+        let top = image.height() as usize - (region.top() - position.1) as usize;
+        let row_len = image.width() as usize * 4;
+        let mut data = Vec::with_capacity(width * height * 4);
+        for row in image.as_raw().chunks(row_len).skip(top).take(height).rev() {
+            data.extend_from_slice(&row[left * 4..(left + width) * 4]);
+        }
+
+        let offset = (region.left() as u32, region.bottom() as u32);
+        target.write_region(&mut ctx.backend, offset, region.size(), &data);
+    }
+
+    /// Creates everything required to draw to `self`, so that the first draw call
+    /// to this texture does not have to.
+    ///
+    /// This allocates the framebuffer together with its depth and stencil buffer and
+    /// copies the texture in case it is a section of a larger texture or shares its
+    /// content with other textures. Calling this while loading a level prevents
+    /// stalls during the first frame which draws to the texture.
+    ///
+    /// Does nothing if `self` can already be used as a draw target.
+    pub fn prepare_target(&mut self, ctx: &mut Context) -> Result<(), DrawError> {
+        self.try_prepare_as_draw_target(ctx)
+    }
+
+    /// Renders `frame_count` frames of the given `frame_size` into a single texture,
+    /// returning each frame as a section of this texture.
+    ///
+    /// `f` is called once for each frame with the index of the frame and a transparent
+    /// texture of `frame_size`. This can be used to bake procedural animations or
+    /// characters composed of multiple textures, which are afterwards
+    /// cheap to draw.
+    ///
+    /// The frames are arranged in rows, starting at the bottom left of the texture.
+    ///
+    /// ```rust, no_run
+    /// use crow::{
+    ///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+    ///     Context, DrawConfig, Texture,
+    /// };
+    ///
+    /// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+    /// let body = Texture::load(&mut ctx, "./textures/player.png")?;
+    ///
+    /// let frames = Texture::bake_frames(&mut ctx, (32, 32), 8, |ctx, frame, i| {
+    ///     let config = DrawConfig {
+    ///         rotation: i as f32 * 45.0,
+    ///         ..Default::default()
+    ///     };
+    ///     ctx.draw(frame, &body, (0, 0), &config);
+    /// })?;
+    /// # Ok::<(), crow::Error>(())
+    /// ```
+    pub fn bake_frames<F>(
+        ctx: &mut Context,
+        frame_size: (u32, u32),
+        frame_count: u32,
+        mut f: F,
+    ) -> Result<Vec<Texture>, NewTextureError>
+    where
+        F: FnMut(&mut Context, &mut Texture, u32),
+    {
+        if frame_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let columns = (ctx.maximum_texture_size().0 / frame_size.0.max(1))
+            .min(frame_count)
+            .max(1);
+        let rows = (frame_count + columns - 1) / columns;
+        let positions = (0..frame_count)
+            .map(|i| ((i % columns) * frame_size.0, (i / columns) * frame_size.1))
+            .collect::<Vec<_>>();
+
+        let mut texture = Texture::new(ctx, (columns * frame_size.0, rows * frame_size.1))?;
+        ctx.clear_color(&mut texture, (0.0, 0.0, 0.0, 0.0));
+        let mut frame = Texture::new(ctx, frame_size)?;
+        for (i, &position) in positions.iter().enumerate() {
+            ctx.clear_color(&mut frame, (0.0, 0.0, 0.0, 0.0));
+            f(ctx, &mut frame, i as u32);
+
+            if let Some(target) = texture.prepare_as_draw_target(ctx) {
+                let position = (position.0 as i32, position.1 as i32);
+                ctx.backend.blit(
+                    &frame.inner,
+                    frame.position,
+                    frame.size,
+                    target.framebuffer_id,
+                    position,
+                    frame_size,
+                );
+            }
+        }
+
+        Ok(positions
+            .into_iter()
+            .map(|position| texture.get_section((position, frame_size)))
+            .collect())
+    }
+
+    /// Renders `frame_count` frames using [`Texture::bake_frames`] and combines them
+    /// into an [`Animation`] which shows each frame for `frame_duration`.
+    ///
+    /// Returns `Ok(None)` if `frame_count` is zero.
+    ///
+    /// [`Texture::bake_frames`]: struct.Texture.html#method.bake_frames
+    /// [`Animation`]: struct.Animation.html
+    pub fn bake_animation<F>(
+        ctx: &mut Context,
+        frame_size: (u32, u32),
+        frame_count: u32,
+        frame_duration: Duration,
+        mode: LoopMode,
+        f: F,
+    ) -> Result<Option<Animation>, NewTextureError>
+    where
+        F: FnMut(&mut Context, &mut Texture, u32),
+    {
+        let frames = Texture::bake_frames(ctx, frame_size, frame_count, f)?
+            .into_iter()
+            .map(|texture| AnimationFrame {
+                texture,
+                duration: frame_duration,
+            })
+            .collect();
+        Ok(Animation::new(frames, mode))
+    }
+
+    /// Prepares `self` to be used as a draw target, reporting errors to the backend.
+    pub(crate) fn prepare_as_draw_target<'a>(
+        &'a mut self,
+        ctx: &mut Context,
+    ) -> Option<&'a mut RawTexture> {
+        match self.try_prepare_as_draw_target(ctx) {
+            Ok(()) => Some(Rc::get_mut(&mut self.inner).unwrap()),
+            Err(err) => {
+                ctx.backend.report_error(err);
+                None
+            }
+        }
+    }
+
+    /// Copies the content of `self` into a new texture with a framebuffer,
+    /// keeping its options, border color and label.
+    fn copy_raw(&self, ctx: &mut Context) -> Result<RawTexture, DrawError> {
+        let mut inner = RawTexture::new_with(&mut ctx.backend, self.size, self.inner.options)
+            .map_err(DrawError::from_new)?;
+        inner.add_framebuffer(&mut ctx.backend)?;
+        // blitting copies translucent texels unchanged, which is not the case
+        // when drawing onto the uninitialized texture
+        ctx.backend.blit(
+            &self.inner,
+            self.position,
+            self.size,
+            inner.framebuffer_id,
+            (0, 0),
+            self.size,
+        );
+        inner.generate_mipmaps(&mut ctx.backend);
+
+        inner.set_border_color(&mut ctx.backend, self.inner.border_color);
+        if let Some(label) = self.label() {
+            inner.set_label(&mut ctx.backend, &label);
+        }
+        Ok(inner)
+    }
+
+    /// Returns the underlying texture of `self`, copying `self`
+    /// in case the underlying texture is shared with other textures.
+    fn make_unique(&mut self, ctx: &mut Context) -> Result<&mut RawTexture, DrawError> {
+        ctx.backend.validate(&self.inner)?;
+        // queued draw calls using `self` as their source would otherwise require a copy
+        ctx.backend.flush_draws_using(&self.inner);
+        if Rc::get_mut(&mut self.inner).is_none() {
+            ctx.report_texture_copy(CopyReason::Shared, self.size, self.label().as_deref());
+            self.inner = Rc::new(self.copy_raw(ctx)?);
+            self.position = (0, 0);
+        }
+
+        Ok(Rc::get_mut(&mut self.inner).unwrap_or_else(|| bug!("copied texture is still shared")))
+    }
+
+    fn try_prepare_as_draw_target(&mut self, ctx: &mut Context) -> Result<(), DrawError> {
+        ctx.backend.validate(&self.inner)?;
+        // queued draw calls using `self` as their source would otherwise require a copy
+        ctx.backend.flush_draws_using(&self.inner);
+        let border_color = self.inner.border_color;
+        let label = self.label();
+
+        if self.position != (0, 0) || self.size != self.inner.dimensions {
+            ctx.report_texture_copy(CopyReason::Section, self.size, label.as_deref());
+            self.inner = Rc::new(self.copy_raw(ctx)?);
+            self.position = (0, 0);
         } else if let Some(inner) = Rc::get_mut(&mut self.inner) {
             if !inner.has_framebuffer {
-                inner.add_framebuffer(&mut ctx.backend);
+                inner.add_framebuffer(&mut ctx.backend)?;
             }
         } else {
-            self.inner = Rc::new(RawTexture::clone_as_target(&self.inner, &mut ctx.backend));
+            ctx.report_texture_copy(CopyReason::Shared, self.size, label.as_deref());
+            let mut inner = RawTexture::clone_as_target(&self.inner, &mut ctx.backend)?;
+            inner.set_border_color(&mut ctx.backend, border_color);
+            if let Some(label) = &label {
+                inner.set_label(&mut ctx.backend, label);
+            }
+            self.inner = Rc::new(inner);
         }
 
-        Rc::get_mut(&mut self.inner).unwrap()
+        Ok(())
     }
 }
 
@@ -131,7 +595,11 @@ impl DrawTarget for Texture {
         position: (i32, i32),
         config: &DrawConfig,
     ) {
-        let target = self.prepare_as_draw_target(ctx);
+        let target = if let Some(target) = self.prepare_as_draw_target(ctx) {
+            target
+        } else {
+            return;
+        };
 
         ctx.backend.draw(
             target.framebuffer_id,
@@ -145,13 +613,52 @@ impl DrawTarget for Texture {
         )
     }
 
+    fn receive_batch(&mut self, ctx: &mut Context, batch: &SpriteBatch) {
+        let target = if let Some(target) = self.prepare_as_draw_target(ctx) {
+            target
+        } else {
+            return;
+        };
+
+        ctx.backend.draw_batch(
+            target.framebuffer_id,
+            target.dimensions,
+            1,
+            &batch.texture.inner,
+            &batch.sprites,
+        )
+    }
+
     fn receive_clear_color(&mut self, ctx: &mut Context, color: (f32, f32, f32, f32)) {
-        let target = self.prepare_as_draw_target(ctx);
+        let target = if let Some(target) = self.prepare_as_draw_target(ctx) {
+            target
+        } else {
+            return;
+        };
         ctx.backend.clear_color(target.framebuffer_id, color)
     }
 
+    fn receive_clear_region(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        color: (f32, f32, f32, f32),
+    ) {
+        let target = if let Some(target) = self.prepare_as_draw_target(ctx) {
+            target
+        } else {
+            return;
+        };
+        ctx.backend
+            .clear_region(target.framebuffer_id, 1, rect, color)
+    }
+
     fn receive_clear_depth(&mut self, ctx: &mut Context) {
-        let target = self.prepare_as_draw_target(ctx);
+        let target = if let Some(target) = self.prepare_as_draw_target(ctx) {
+            target
+        } else {
+            return;
+        };
         ctx.backend.clear_depth(target.framebuffer_id)
     }
 
@@ -162,7 +669,11 @@ impl DrawTarget for Texture {
         to: (i32, i32),
         color: (f32, f32, f32, f32),
     ) {
-        let target = self.prepare_as_draw_target(ctx);
+        let target = if let Some(target) = self.prepare_as_draw_target(ctx) {
+            target
+        } else {
+            return;
+        };
 
         ctx.backend.debug_draw(
             false,
@@ -175,41 +686,86 @@ impl DrawTarget for Texture {
         )
     }
 
-    fn receive_rectangle(
-        &mut self,
-        ctx: &mut Context,
-        lower_left: (i32, i32),
-        upper_right: (i32, i32),
-        color: (f32, f32, f32, f32),
-    ) {
-        let target = self.prepare_as_draw_target(ctx);
+    fn receive_rectangle(&mut self, ctx: &mut Context, rect: IRect, color: (f32, f32, f32, f32)) {
+        if rect.is_empty() {
+            return;
+        }
+
+        let target = if let Some(target) = self.prepare_as_draw_target(ctx) {
+            target
+        } else {
+            return;
+        };
 
         ctx.backend.debug_draw(
             true,
             target.framebuffer_id,
             target.dimensions,
             1,
-            lower_left,
-            upper_right,
+            rect.position(),
+            (rect.right() - 1, rect.top() - 1),
             color,
         )
     }
 
+    fn receive_fullscreen(
+        &mut self,
+        ctx: &mut Context,
+        shader: &Shader,
+        uniforms: &[(&str, UniformValue<'_>)],
+    ) {
+        let dimensions = self.dimensions();
+        if let Some(target) = self.prepare_as_draw_target(ctx) {
+            let framebuffer = target.framebuffer_id;
+            ctx.run_shader(framebuffer, dimensions, dimensions, shader, uniforms);
+        }
+    }
+
+    fn receive_visibility_query(
+        &mut self,
+        ctx: &mut Context,
+        rect: IRect,
+        depth: f32,
+    ) -> QueryHandle {
+        match self.prepare_as_draw_target(ctx) {
+            Some(target) => {
+                let (framebuffer, dimensions) = (target.framebuffer_id, target.dimensions);
+                ctx.backend
+                    .query_visible(framebuffer, dimensions, 1, rect, depth)
+            }
+            None => QueryHandle::visible(&ctx.backend),
+        }
+    }
+
     fn get_image_data(&self, ctx: &mut Context) -> RgbaImage {
-        let _ = ctx;
+        if self.position.0 + self.size.0 > self.inner.dimensions.0
+            || self.position.1 + self.size.1 > self.inner.dimensions.1
+        {
+            // a texture returned by `Texture::repeated` has to be drawn to apply its wrap mode
+            let mut copy = match Texture::new(ctx, self.size) {
+                Ok(copy) => copy,
+                Err(err) => {
+                    ctx.backend.report_error(DrawError::from_new(err));
+                    return RgbaImage::new(self.size.0, self.size.1);
+                }
+            };
+            ctx.clear_color(&mut copy, (0.0, 0.0, 0.0, 0.0));
+            ctx.draw(&mut copy, self, (0, 0), &DrawConfig::default());
+            return copy.get_image_data(ctx);
+        }
 
         let data = ctx.backend.get_image_data(&self.inner);
 
-        let (width, height) = self.inner.dimensions;
-        let skip_above = height - (self.position.1 + self.size.1);
+        let width = self.inner.dimensions.0;
         let skip_vertical = self.position.0 * 4;
         let take_vertical = self.size.0 * 4;
 
+        // `data` starts with the bottom row while images start at the top
         let image_data = data
             .chunks(width as usize * 4)
-            .skip(skip_above as usize)
-            .rev()
             .skip(self.position.1 as usize)
+            .take(self.size.1 as usize)
+            .rev()
             .flat_map(|row| {
                 row.iter()
                     .skip(skip_vertical as usize)
@@ -0,0 +1,414 @@
+//! Sprite sheets described by the JSON format of [TexturePacker].
+//!
+//! Both the `JSON (Hash)` and the `JSON (Array)` variant are supported.
+//! With the `serde1` feature enabled, [`SpriteSheet`] can be loaded
+//! from these files using `SpriteSheet::load`.
+//!
+//! The same format is used when exporting a sprite sheet from [Aseprite],
+//! which additionally stores the duration of each frame and its tags.
+//! These can be used to create a [`TaggedAnimation`] using `SpriteSheet::build_animation`.
+//!
+//! ```rust, no_run
+//! use crow::{
+//!     glutin::{event_loop::EventLoop, window::WindowBuilder},
+//!     texture_packer::SpriteSheet,
+//!     Context,
+//! };
+//!
+//! # #[cfg(feature = "serde1")]
+//! # fn main() -> Result<(), crow::Error> {
+//! let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+//!
+//! let sprites = SpriteSheet::load_sprites(&mut ctx, "./sprites.json")?;
+//! let player = &sprites["player.png"];
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "serde1"))]
+//! # fn main() {}
+//! ```
+//!
+//! [TexturePacker]: https://www.codeandweb.com/texturepacker
+//! [Aseprite]: https://www.aseprite.org
+//! [`SpriteSheet`]: struct.SpriteSheet.html
+//! [`TaggedAnimation`]: struct.TaggedAnimation.html
+use std::{collections::HashMap, time::Duration};
+#[cfg(feature = "serde1")]
+use std::{fs, path::Path};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Animation, AnimationFrame, BlendMode, Context, DrawConfig, LoopMode, Rect, SpriteSheetError,
+    Texture,
+};
+
+/// A rectangle with its origin at the top left of the image.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FrameRect {
+    /// The distance between the left edge of the rectangle and the left edge of the image.
+    pub x: u32,
+    /// The distance between the top edge of the rectangle and the top edge of the image.
+    pub y: u32,
+    /// The width of the rectangle.
+    pub w: u32,
+    /// The height of the rectangle.
+    pub h: u32,
+}
+
+/// The dimensions of a sprite.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FrameSize {
+    /// The width of the sprite.
+    pub w: u32,
+    /// The height of the sprite.
+    pub h: u32,
+}
+
+/// A single sprite of a [`SpriteSheet`].
+///
+/// [`SpriteSheet`]: struct.SpriteSheet.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "camelCase"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Frame {
+    /// The name of the sprite, only used by the `JSON (Array)` format.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub filename: Option<String>,
+    /// The location of the sprite in the sprite sheet.
+    ///
+    /// The size does not take `rotated` into account.
+    pub frame: FrameRect,
+    /// Whether the sprite is stored rotated by 90 degrees clockwise.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub rotated: bool,
+    /// Whether transparent pixels around the sprite have been removed.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub trimmed: bool,
+    /// The location of the stored part of the sprite inside of the original sprite.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub sprite_source_size: FrameRect,
+    /// The dimensions of the original sprite, before trimming it.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub source_size: FrameSize,
+    /// How long this frame is shown in milliseconds, only used by Aseprite.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub duration: Option<u32>,
+}
+
+/// The sprites of a [`SpriteSheet`], stored either by name or in a list.
+///
+/// [`SpriteSheet`]: struct.SpriteSheet.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(untagged))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frames {
+    /// The `JSON (Hash)` format.
+    Hash(HashMap<String, Frame>),
+    /// The `JSON (Array)` format, using the `filename` of each frame as its name.
+    Array(Vec<Frame>),
+}
+
+impl Default for Frames {
+    fn default() -> Self {
+        Frames::Hash(HashMap::new())
+    }
+}
+
+/// The order in which the frames of a [`FrameTag`] are played.
+///
+/// [`FrameTag`]: struct.FrameTag.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Direction {
+    /// From the first to the last frame.
+    #[default]
+    #[cfg_attr(feature = "serde1", serde(rename = "forward"))]
+    Forward,
+    /// From the last to the first frame.
+    #[cfg_attr(feature = "serde1", serde(rename = "reverse"))]
+    Reverse,
+    /// From the first to the last frame and back again.
+    #[cfg_attr(feature = "serde1", serde(rename = "pingpong"))]
+    PingPong,
+    /// From the last to the first frame and back again.
+    #[cfg_attr(feature = "serde1", serde(rename = "pingpong_reverse"))]
+    PingPongReverse,
+}
+
+/// A named range of frames, created by Aseprite.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FrameTag {
+    /// The name of the tag.
+    pub name: String,
+    /// The index of the first frame of the tag.
+    pub from: usize,
+    /// The index of the last frame of the tag, which is part of the tag.
+    pub to: usize,
+    /// The order in which the frames are played.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub direction: Direction,
+}
+
+impl FrameTag {
+    /// Returns the indices of the frames of one cycle of this tag in the order they are played.
+    ///
+    /// ```rust
+    /// use crow::texture_packer::{Direction, FrameTag};
+    ///
+    /// let tag = FrameTag {
+    ///     name: "walk".to_string(),
+    ///     from: 2,
+    ///     to: 5,
+    ///     direction: Direction::PingPong,
+    /// };
+    /// assert_eq!(tag.sequence(), vec![2, 3, 4, 5, 4, 3]);
+    /// ```
+    pub fn sequence(&self) -> Vec<usize> {
+        let forward = self.from..=self.to;
+        // the first and last frame are not repeated when changing directions
+        let back = (self.from + 1..self.to).rev();
+        match self.direction {
+            Direction::Forward => forward.collect(),
+            Direction::Reverse => forward.rev().collect(),
+            Direction::PingPong => forward.chain(back).collect(),
+            Direction::PingPongReverse => forward.rev().chain(back.rev()).collect(),
+        }
+    }
+}
+
+/// Additional information about a [`SpriteSheet`].
+///
+/// [`SpriteSheet`]: struct.SpriteSheet.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "camelCase"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Meta {
+    /// The file name of the image containing all sprites.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub image: Option<String>,
+    /// The dimensions of the image containing all sprites.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub size: Option<FrameSize>,
+    /// The tags of the animation, only used by Aseprite.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub frame_tags: Vec<FrameTag>,
+}
+
+/// The frames and tags of an animation exported by Aseprite,
+/// created using `SpriteSheet::build_animation`.
+#[derive(Debug, Clone)]
+pub struct TaggedAnimation {
+    /// All frames of the animation in order.
+    pub frames: Vec<AnimationFrame>,
+    /// Named ranges of `frames`.
+    pub tags: Vec<FrameTag>,
+}
+
+impl TaggedAnimation {
+    /// Returns the tag with the given `name`.
+    pub fn tag(&self, name: &str) -> Option<&FrameTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    /// Returns an [`Animation`] looping the frames of the tag with the given `name`
+    /// in the order given by its `direction`, or all frames if `name` is `None`.
+    ///
+    /// Returns `None` if there is no such tag or if it does not contain any frames.
+    ///
+    /// [`Animation`]: ../struct.Animation.html
+    pub fn animation(&self, name: Option<&str>) -> Option<Animation> {
+        let sequence = match name {
+            Some(name) => self.tag(name)?.sequence(),
+            None => (0..self.frames.len()).collect(),
+        };
+        let frames = sequence
+            .into_iter()
+            .filter_map(|i| self.frames.get(i).cloned())
+            .collect::<Vec<_>>();
+        Animation::new(frames, LoopMode::Loop)
+    }
+}
+
+/// The description of a sprite sheet created by TexturePacker.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SpriteSheet {
+    /// The sprites of this sheet.
+    pub frames: Frames,
+    /// Additional information about this sheet.
+    #[cfg_attr(feature = "serde1", serde(default))]
+    pub meta: Meta,
+}
+
+impl SpriteSheet {
+    /// Loads the description of a sprite sheet from the JSON file at `path`.
+    #[cfg(feature = "serde1")]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SpriteSheet, SpriteSheetError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Loads the description of a sprite sheet from the JSON file at `path` together
+    /// with its image `meta.image`, which is relative to the directory containing `path`,
+    /// and returns each sprite by name, see `SpriteSheet::build`.
+    #[cfg(feature = "serde1")]
+    pub fn load_sprites<P: AsRef<Path>>(
+        ctx: &mut Context,
+        path: P,
+    ) -> Result<HashMap<String, Texture>, SpriteSheetError> {
+        let path = path.as_ref();
+        let sheet = SpriteSheet::load(path)?;
+        let image = sheet
+            .meta
+            .image
+            .as_ref()
+            .ok_or_else(|| SpriteSheetError::InvalidSheet {
+                reason: "the sheet does not contain the name of its image".to_string(),
+            })?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let texture = Texture::load(ctx, directory.join(image))?;
+        sheet.build(ctx, &texture)
+    }
+
+    /// Returns each sprite of `texture` by name.
+    ///
+    /// Sprites which are neither trimmed nor rotated are sections of `texture`.
+    /// All other sprites are copied into a new texture with the size of
+    /// the original sprite, restoring its transparent border and orientation.
+    ///
+    /// Returns an error if a frame is outside of `texture`.
+    pub fn build(
+        &self,
+        ctx: &mut Context,
+        texture: &Texture,
+    ) -> Result<HashMap<String, Texture>, SpriteSheetError> {
+        let frames: Vec<(&str, &Frame)> = match &self.frames {
+            Frames::Hash(frames) => frames
+                .iter()
+                .map(|(name, frame)| (name.as_str(), frame))
+                .collect(),
+            Frames::Array(frames) => frames
+                .iter()
+                .map(|frame| (frame.filename.as_deref().unwrap_or(""), frame))
+                .collect(),
+        };
+
+        let mut sprites = HashMap::new();
+        for (name, frame) in frames {
+            let sprite = Self::sprite(ctx, texture, name, frame)?;
+            sprites.insert(name.to_string(), sprite);
+        }
+        Ok(sprites)
+    }
+
+    /// Returns each sprite of `texture` as a frame of an animation, together with
+    /// the `frame_tags` of the sheet.
+    ///
+    /// Frames without a duration are shown for `100` milliseconds, just like in Aseprite.
+    /// When using the `JSON (Hash)` format, whose frames are unordered, frames are
+    /// ordered using the last number in their name, as Aseprite names them
+    /// `{title} {frame}.{extension}` by default.
+    ///
+    /// Returns an error if a frame is outside of `texture`.
+    pub fn build_animation(
+        &self,
+        ctx: &mut Context,
+        texture: &Texture,
+    ) -> Result<TaggedAnimation, SpriteSheetError> {
+        let frames: Vec<(&str, &Frame)> = match &self.frames {
+            Frames::Hash(frames) => {
+                let mut frames = frames.iter().collect::<Vec<_>>();
+                frames.sort_by_key(|&(name, _)| (frame_number(name), name));
+                frames
+                    .into_iter()
+                    .map(|(name, frame)| (name.as_str(), frame))
+                    .collect()
+            }
+            Frames::Array(frames) => frames
+                .iter()
+                .map(|frame| (frame.filename.as_deref().unwrap_or(""), frame))
+                .collect(),
+        };
+
+        let frames = frames
+            .into_iter()
+            .map(|(name, frame)| {
+                Ok(AnimationFrame {
+                    texture: Self::sprite(ctx, texture, name, frame)?,
+                    duration: Duration::from_millis(u64::from(frame.duration.unwrap_or(100))),
+                })
+            })
+            .collect::<Result<_, SpriteSheetError>>()?;
+        Ok(TaggedAnimation {
+            frames,
+            tags: self.meta.frame_tags.clone(),
+        })
+    }
+
+    fn sprite(
+        ctx: &mut Context,
+        texture: &Texture,
+        name: &str,
+        frame: &Frame,
+    ) -> Result<Texture, SpriteSheetError> {
+        let FrameRect { x, y, w, h } = frame.frame;
+        let stored = if frame.rotated { (h, w) } else { (w, h) };
+        if u64::from(x) + u64::from(stored.0) > u64::from(texture.width())
+            || u64::from(y) + u64::from(stored.1) > u64::from(texture.height())
+        {
+            return Err(SpriteSheetError::InvalidSheet {
+                reason: format!("the frame `{}` is outside of the image", name),
+            });
+        }
+        let section = texture.get_section(Rect::new((x, y), stored).flip_y(texture.height()));
+        if !frame.trimmed && !frame.rotated {
+            return Ok(section);
+        }
+
+        let (size, offset) = if frame.trimmed {
+            let FrameSize { w, h } = frame.source_size;
+            let trim = frame.sprite_source_size;
+            (
+                (w, h),
+                (trim.x as i32, h as i32 - trim.y as i32 - trim.h as i32),
+            )
+        } else {
+            ((w, h), (0, 0))
+        };
+
+        let mut sprite = Texture::new(ctx, size)?;
+        ctx.clear_color(&mut sprite, (0.0, 0.0, 0.0, 0.0));
+        // as `sprite` is fully transparent, this copies each pixel unchanged
+        let blend_mode = BlendMode::PremultipliedAlpha;
+        let config = if frame.rotated {
+            // rotates the stored sprite counterclockwise around the center
+            // of its lower square, moving it back to the origin
+            let center = stored.1 as f32 / 2.0;
+            DrawConfig {
+                rotation: 90.0,
+                rotation_center: Some((center, center)),
+                blend_mode,
+                ..Default::default()
+            }
+        } else {
+            DrawConfig {
+                blend_mode,
+                ..Default::default()
+            }
+        };
+        ctx.draw(&mut sprite, &section, offset, &config);
+        Ok(sprite)
+    }
+}
+
+/// Returns the last number in `name`, for example `12` for `player 12.aseprite`.
+fn frame_number(name: &str) -> Option<u64> {
+    let end = name.rfind(|c: char| c.is_ascii_digit())? + 1;
+    let start = name[..end]
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    name[start..end].parse().ok()
+}
@@ -0,0 +1,857 @@
+//! Maps created using the [Tiled] map editor.
+//!
+//! Only orthogonal maps with a fixed size are supported. Tile layers can use any
+//! encoding except `zstd` compression and each tileset must use a single image.
+//! Tilesets stored in a separate `.tsx` file are loaded together with the map.
+//!
+//! The layers of groups are added to the map in order, ignoring the group itself.
+//! Image layers are not supported and are skipped.
+//!
+//! ```rust, no_run
+//! use crow::{
+//!     glutin::{event_loop::EventLoop, window::WindowBuilder},
+//!     tiled::{Layer, Map},
+//!     Context,
+//! };
+//!
+//! let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+//!
+//! let map = Map::load("./levels/forest.tmx")?;
+//! let textures = map.load_textures(&mut ctx)?;
+//! let mut surface = ctx.surface();
+//! for layer in map.layers.iter() {
+//!     match layer {
+//!         Layer::Tiles(layer) => map.draw_layer(&mut ctx, &mut surface, layer, &textures)?,
+//!         Layer::Objects(layer) => {
+//!             for object in layer.objects.iter() {
+//!                 println!("{} at ({}, {})", object.name, object.x, object.y);
+//!             }
+//!         }
+//!     }
+//! }
+//! ctx.present(surface)?;
+//! # Ok::<(), crow::Error>(())
+//! ```
+//!
+//! [Tiled]: https://www.mapeditor.org
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use xml::{
+    common::Position,
+    reader::{EventReader, XmlEvent},
+};
+
+use crate::{
+    Context, DrawConfig, DrawTarget, LoadMapError, LoadTextureError, Rect, SpriteBatch, Subsystem,
+    Texture,
+};
+
+/// The custom properties of a map, layer or object.
+///
+/// The values are stored as strings regardless of their type in Tiled,
+/// for example `true`, `4.5` or `#ff00ff00`.
+pub type Properties = HashMap<String, String>;
+
+/// A map created using Tiled.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    /// The width of the map in tiles.
+    pub width: u32,
+    /// The height of the map in tiles.
+    pub height: u32,
+    /// The width of a single tile in pixels.
+    pub tile_width: u32,
+    /// The height of a single tile in pixels.
+    pub tile_height: u32,
+    /// All tilesets used by this map, ordered by their `first_gid`.
+    pub tilesets: Vec<Tileset>,
+    /// All layers of this map, starting with the bottom one.
+    pub layers: Vec<Layer>,
+    /// The custom properties of this map.
+    pub properties: Properties,
+}
+
+/// An image containing the tiles of a [`Map`].
+///
+/// [`Map`]: struct.Map.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tileset {
+    /// The global id of the first tile of this tileset.
+    pub first_gid: u32,
+    /// The name of this tileset.
+    pub name: String,
+    /// The width of a single tile in pixels.
+    pub tile_width: u32,
+    /// The height of a single tile in pixels.
+    pub tile_height: u32,
+    /// The space between adjacent tiles in pixels.
+    pub spacing: u32,
+    /// The space between the tiles and the edge of the image in pixels.
+    pub margin: u32,
+    /// The number of tiles in this tileset.
+    pub tile_count: u32,
+    /// The number of tiles in each row of the image.
+    pub columns: u32,
+    /// The path of the image, relative to the current working directory.
+    pub image: PathBuf,
+}
+
+/// A single layer of a [`Map`].
+///
+/// [`Map`]: struct.Map.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layer {
+    /// A grid of tiles.
+    Tiles(TileLayer),
+    /// Objects placed at arbitrary positions, for example spawn points or triggers.
+    Objects(ObjectLayer),
+}
+
+/// A layer containing a grid of tiles.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileLayer {
+    /// The name of this layer.
+    pub name: String,
+    /// The width of this layer in tiles.
+    pub width: u32,
+    /// The height of this layer in tiles.
+    pub height: u32,
+    /// If this layer is visible in Tiled.
+    pub visible: bool,
+    /// The opacity of this layer in the range `0.0..=1.0`.
+    pub opacity: f32,
+    /// The tiles of this layer, starting with the top left one and going from
+    /// left to right and from top to bottom. Empty cells are `None`.
+    pub tiles: Vec<Option<Tile>>,
+    /// The custom properties of this layer.
+    pub properties: Properties,
+}
+
+impl TileLayer {
+    /// Returns the tile at `(x, y)`, where `(0, 0)` is the top left corner of the layer.
+    pub fn get(&self, x: u32, y: u32) -> Option<Tile> {
+        if x < self.width && y < self.height {
+            self.tiles[(y * self.width + x) as usize]
+        } else {
+            None
+        }
+    }
+}
+
+/// A tile of a [`TileLayer`] or a tile object.
+///
+/// [`TileLayer`]: struct.TileLayer.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tile {
+    /// The global id of this tile, which is never `0`.
+    pub gid: u32,
+    /// If the tile is flipped on the x axis.
+    pub flip_horizontally: bool,
+    /// If the tile is flipped on the y axis.
+    pub flip_vertically: bool,
+    /// If the x and y axis of the tile are swapped, which
+    /// happens before flipping it horizontally and vertically.
+    pub flip_diagonally: bool,
+}
+
+impl Tile {
+    const FLIP_HORIZONTALLY: u32 = 1 << 31;
+    const FLIP_VERTICALLY: u32 = 1 << 30;
+    const FLIP_DIAGONALLY: u32 = 1 << 29;
+    /// Also includes the bit used to rotate hexagonal tiles, which are not supported.
+    const FLAGS: u32 = 0xf000_0000;
+
+    /// Converts a global id as stored by Tiled, which also contains
+    /// how the tile is flipped, returning `None` for empty cells.
+    fn from_raw(raw: u32) -> Option<Tile> {
+        let gid = raw & !Self::FLAGS;
+        if gid == 0 {
+            None
+        } else {
+            Some(Tile {
+                gid,
+                flip_horizontally: raw & Self::FLIP_HORIZONTALLY != 0,
+                flip_vertically: raw & Self::FLIP_VERTICALLY != 0,
+                flip_diagonally: raw & Self::FLIP_DIAGONALLY != 0,
+            })
+        }
+    }
+
+    /// Returns a config which draws this tile with the given dimensions.
+    fn draw_config(self, (width, height): (u32, u32)) -> DrawConfig {
+        let (width, height) = (width as f32, height as f32);
+        if self.flip_diagonally {
+            // swapping the axes before flipping the tile is the same
+            // as swapping them after flipping it along the other axis.
+            //
+            // As crow uses a y-up coordinate system, swapping the
+            // axes is a reflection along the other diagonal.
+            DrawConfig {
+                flip_horizontally: self.flip_vertically,
+                flip_vertically: self.flip_horizontally,
+                transform: Some([[0.0, -1.0, height], [-1.0, 0.0, width], [0.0, 0.0, 1.0]]),
+                ..Default::default()
+            }
+        } else {
+            DrawConfig {
+                flip_horizontally: self.flip_horizontally,
+                flip_vertically: self.flip_vertically,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// A layer containing objects.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectLayer {
+    /// The name of this layer.
+    pub name: String,
+    /// If this layer is visible in Tiled.
+    pub visible: bool,
+    /// The opacity of this layer in the range `0.0..=1.0`.
+    pub opacity: f32,
+    /// The objects of this layer.
+    pub objects: Vec<Object>,
+    /// The custom properties of this layer.
+    pub properties: Properties,
+}
+
+/// An object of an [`ObjectLayer`].
+///
+/// The position and size of objects are in pixels and use the coordinate system of Tiled,
+/// where `(0.0, 0.0)` is the top left corner of the map and the y axis points downwards.
+/// `Map::flip_y` converts the y coordinate to the coordinate system of crow.
+///
+/// [`ObjectLayer`]: struct.ObjectLayer.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    /// The unique id of this object.
+    pub id: u32,
+    /// The name of this object.
+    pub name: String,
+    /// The type of this object, which is called class since Tiled 1.9.
+    pub kind: String,
+    /// The x coordinate of this object.
+    pub x: f32,
+    /// The y coordinate of this object.
+    ///
+    /// This is the top of rectangles and ellipses and the bottom of tile objects.
+    pub y: f32,
+    /// The width of this object, which is `0.0` for points.
+    pub width: f32,
+    /// The height of this object, which is `0.0` for points.
+    pub height: f32,
+    /// The clockwise rotation around the position of this object in degrees.
+    pub rotation: f32,
+    /// The tile of this object if it is a tile object.
+    pub tile: Option<Tile>,
+    /// If this object is visible in Tiled.
+    pub visible: bool,
+    /// The shape of this object.
+    pub shape: Shape,
+    /// The custom properties of this object.
+    pub properties: Properties,
+}
+
+/// The shape of an [`Object`].
+///
+/// [`Object`]: struct.Object.html
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// A rectangle, which is also used for tile and text objects.
+    Rectangle,
+    /// An ellipse filling the bounds of the object.
+    Ellipse,
+    /// A single point.
+    Point,
+    /// A closed polygon using points relative to the position of the object.
+    Polygon(Vec<(f32, f32)>),
+    /// A line using points relative to the position of the object.
+    Polyline(Vec<(f32, f32)>),
+}
+
+impl Map {
+    /// Loads a map from the `.tmx` file at `path`, including all external tilesets.
+    ///
+    /// Relative paths in the map are relative to the directory containing `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Map, LoadMapError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        Map::parse(&source, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Parses the content of a `.tmx` file.
+    ///
+    /// External tilesets and images are loaded relative to `directory`.
+    pub fn parse<P: AsRef<Path>>(source: &str, directory: P) -> Result<Map, LoadMapError> {
+        let directory = directory.as_ref();
+        let map = Element::parse(source)?;
+        if map.name != "map" {
+            return Err(map.invalid("the root element must be a `map`"));
+        }
+        if matches!(map.attr("orientation"), Some(o) if o != "orthogonal") {
+            return Err(map.invalid("only orthogonal maps are supported"));
+        }
+        if map.attr("infinite") == Some("1") {
+            return Err(map.invalid("infinite maps are not supported"));
+        }
+
+        let mut tilesets = Vec::new();
+        let mut layers = Vec::new();
+        for child in map.children.iter() {
+            match child.name.as_str() {
+                "tileset" => {
+                    let first_gid = child.required("firstgid")?;
+                    if let Some(source) = child.attr("source") {
+                        let path = directory.join(source);
+                        let tileset = Element::parse(&fs::read_to_string(&path)?)?;
+                        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+                        tilesets.push(Tileset::from_element(&tileset, first_gid, directory)?);
+                    } else {
+                        tilesets.push(Tileset::from_element(child, first_gid, directory)?);
+                    }
+                }
+                _ => parse_layer(child, &mut layers)?,
+            }
+        }
+        tilesets.sort_by_key(|tileset: &Tileset| tileset.first_gid);
+
+        let map = Map {
+            width: map.required("width")?,
+            height: map.required("height")?,
+            tile_width: map.required("tilewidth")?,
+            tile_height: map.required("tileheight")?,
+            tilesets,
+            layers,
+            properties: map.properties()?,
+        };
+
+        for layer in map.layers.iter() {
+            if let Layer::Tiles(layer) = layer {
+                if layer
+                    .tiles
+                    .iter()
+                    .flatten()
+                    .any(|tile| map.tile(tile.gid).is_none())
+                {
+                    return Err(LoadMapError::InvalidMap {
+                        line: 0,
+                        reason: format!("layer `{}` uses a tile without a tileset", layer.name),
+                    });
+                }
+            }
+        }
+
+        Ok(map)
+    }
+
+    /// Returns the index of the tileset containing `gid` together with the
+    /// id of the tile in this tileset, or `None` if no tileset contains `gid`.
+    pub fn tile(&self, gid: u32) -> Option<(usize, u32)> {
+        let index = self.tilesets.iter().rposition(|t| t.first_gid <= gid)?;
+        let id = gid - self.tilesets[index].first_gid;
+        if id < self.tilesets[index].tile_count {
+            Some((index, id))
+        } else {
+            None
+        }
+    }
+
+    /// Converts the y coordinate of an object to the coordinate system used by crow.
+    pub fn flip_y(&self, y: f32) -> f32 {
+        (self.height * self.tile_height) as f32 - y
+    }
+
+    /// Loads the image of each tileset in order.
+    pub fn load_textures(&self, ctx: &mut Context) -> Result<Vec<Texture>, LoadTextureError> {
+        ctx.in_subsystem(Subsystem::Tilemap, |ctx| {
+            self.tilesets
+                .iter()
+                .map(|tileset| Texture::load(ctx, &tileset.image))
+                .collect()
+        })
+    }
+
+    /// Creates a [`SpriteBatch`] for each tileset containing all tiles of `layer`
+    /// which use this tileset, in the order of the tilesets of this map.
+    ///
+    /// `textures` contains the image of each tileset, for example loaded using `Map::load_textures`.
+    /// The bottom left corner of `layer` is drawn at `(0, 0)`, tiles
+    /// which are larger than a cell extend to the top and to the right.
+    ///
+    /// Returns an error if `layer` uses a tile which is not part of a tileset
+    /// or which is outside of the texture of its tileset.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of `textures` is not the same as the number of tilesets.
+    ///
+    /// [`SpriteBatch`]: ../struct.SpriteBatch.html
+    pub fn build_layer(
+        &self,
+        layer: &TileLayer,
+        textures: &[Texture],
+    ) -> Result<Vec<SpriteBatch>, LoadMapError> {
+        assert_eq!(
+            textures.len(),
+            self.tilesets.len(),
+            "each tileset requires exactly one texture"
+        );
+        let mut batches: Vec<_> = textures.iter().map(SpriteBatch::new).collect();
+        for (i, tile) in layer.tiles.iter().enumerate() {
+            let tile = match tile {
+                Some(tile) => tile,
+                None => continue,
+            };
+
+            let (index, id) = self
+                .tile(tile.gid)
+                .ok_or_else(|| LoadMapError::InvalidMap {
+                    line: 0,
+                    reason: format!("the tile {} is not part of a tileset", tile.gid),
+                })?;
+            let tileset = &self.tilesets[index];
+            let texture = tileset.tile(&textures[index], id)?;
+            let (x, y) = (i as u32 % layer.width, i as u32 / layer.width);
+            let position = (
+                (x * self.tile_width) as i32,
+                ((layer.height - 1 - y) * self.tile_height) as i32,
+            );
+            let config = tile.draw_config(texture.dimensions());
+            batches[index].push(&texture, position, &config);
+        }
+        Ok(batches)
+    }
+
+    /// Draws all tiles of `layer` onto `target` using the batches of `Map::build_layer`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of `textures` is not the same as the number of tilesets.
+    pub fn draw_layer<T>(
+        &self,
+        ctx: &mut Context,
+        target: &mut T,
+        layer: &TileLayer,
+        textures: &[Texture],
+    ) -> Result<(), LoadMapError>
+    where
+        T: DrawTarget,
+    {
+        let batches = self.build_layer(layer, textures)?;
+        ctx.in_subsystem(Subsystem::Tilemap, |ctx| {
+            for batch in batches.iter() {
+                target.receive_batch(ctx, batch);
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Tileset {
+    fn from_element(
+        tileset: &Element,
+        first_gid: u32,
+        directory: &Path,
+    ) -> Result<Tileset, LoadMapError> {
+        let image = tileset
+            .children
+            .iter()
+            .find(|child| child.name == "image")
+            .ok_or_else(|| tileset.invalid("tilesets without a single image are not supported"))?;
+        let tile_width: u32 = tileset.required("tilewidth")?;
+        let tile_height: u32 = tileset.required("tileheight")?;
+        if tile_width == 0 || tile_height == 0 {
+            return Err(tileset.invalid("tiles must not be empty"));
+        }
+        let spacing: u32 = tileset.optional("spacing")?.unwrap_or(0);
+        let margin: u32 = tileset.optional("margin")?.unwrap_or(0);
+        let columns = match tileset.optional("columns")? {
+            Some(columns) => columns,
+            None => {
+                let width: u32 = image.required("width")?;
+                let width = u64::from(width).saturating_sub(2 * u64::from(margin));
+                let columns =
+                    (width + u64::from(spacing)) / (u64::from(tile_width) + u64::from(spacing));
+                columns as u32
+            }
+        };
+        if columns == 0 {
+            return Err(tileset.invalid("tilesets must have at least one column"));
+        }
+
+        Ok(Tileset {
+            first_gid,
+            name: tileset.attr("name").unwrap_or_default().to_owned(),
+            tile_width,
+            tile_height,
+            spacing,
+            margin,
+            tile_count: tileset.required("tilecount")?,
+            columns,
+            image: directory.join(image.required::<String>("source")?),
+        })
+    }
+
+    /// Returns the section of `texture` containing the tile `id`,
+    /// where `0` is the top left tile of this tileset.
+    ///
+    /// Returns an error if `id` is not smaller than `tile_count`
+    /// or if the tile is outside of `texture`.
+    pub fn tile(&self, texture: &Texture, id: u32) -> Result<Texture, LoadMapError> {
+        let invalid = |reason| LoadMapError::InvalidMap {
+            line: 0,
+            reason: format!("the tile {} {} `{}`", id, reason, self.name),
+        };
+        if id >= self.tile_count || self.columns == 0 {
+            return Err(invalid("is not part of the tileset"));
+        }
+
+        let (column, row) = (id % self.columns, id / self.columns);
+        let offset = |index: u32, size: u32| {
+            u64::from(self.margin) + u64::from(index) * (u64::from(size) + u64::from(self.spacing))
+        };
+        let (x, top) = (
+            offset(column, self.tile_width),
+            offset(row, self.tile_height),
+        );
+        if x + u64::from(self.tile_width) > u64::from(texture.width())
+            || top + u64::from(self.tile_height) > u64::from(texture.height())
+        {
+            return Err(invalid("is outside of the texture of the tileset"));
+        }
+
+        let section = Rect::new((x as u32, top as u32), (self.tile_width, self.tile_height));
+        Ok(texture.get_section(section.flip_y(texture.height())))
+    }
+}
+
+/// Adds the layer `element` to `layers`, ignoring elements which are not a layer.
+fn parse_layer(element: &Element, layers: &mut Vec<Layer>) -> Result<(), LoadMapError> {
+    match element.name.as_str() {
+        "layer" => layers.push(Layer::Tiles(TileLayer::from_element(element)?)),
+        "objectgroup" => layers.push(Layer::Objects(ObjectLayer::from_element(element)?)),
+        "group" => {
+            for child in element.children.iter() {
+                parse_layer(child, layers)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+impl TileLayer {
+    fn from_element(layer: &Element) -> Result<TileLayer, LoadMapError> {
+        let width: u32 = layer.required("width")?;
+        let height: u32 = layer.required("height")?;
+        let data = layer
+            .children
+            .iter()
+            .find(|child| child.name == "data")
+            .ok_or_else(|| layer.invalid("missing `data` of a layer"))?;
+        if data.children.iter().any(|child| child.name == "chunk") {
+            return Err(data.invalid("infinite maps are not supported"));
+        }
+
+        let raw = match data.attr("encoding") {
+            None => data
+                .children
+                .iter()
+                .filter(|child| child.name == "tile")
+                .map(|tile| Ok(tile.optional("gid")?.unwrap_or(0)))
+                .collect::<Result<Vec<u32>, LoadMapError>>()?,
+            Some("csv") => data
+                .text
+                .split(',')
+                .map(|gid| {
+                    gid.trim()
+                        .parse()
+                        .map_err(|_| data.invalid("invalid csv data"))
+                })
+                .collect::<Result<Vec<u32>, LoadMapError>>()?,
+            Some("base64") => {
+                let bytes =
+                    decode_base64(&data.text).ok_or_else(|| data.invalid("invalid base64 data"))?;
+                let bytes = match data.attr("compression") {
+                    None => bytes,
+                    Some("zlib") => miniz_oxide::inflate::decompress_to_vec_zlib(&bytes)
+                        .map_err(|_| data.invalid("invalid zlib data"))?,
+                    Some("gzip") => {
+                        decompress_gzip(&bytes).ok_or_else(|| data.invalid("invalid gzip data"))?
+                    }
+                    Some(_) => return Err(data.invalid("unsupported compression")),
+                };
+                bytes
+                    .chunks(4)
+                    .map(|gid| match *gid {
+                        [a, b, c, d] => Ok(u32::from_le_bytes([a, b, c, d])),
+                        _ => Err(data.invalid("the length of the data is not a multiple of 4")),
+                    })
+                    .collect::<Result<Vec<u32>, LoadMapError>>()?
+            }
+            Some(_) => return Err(data.invalid("unsupported encoding")),
+        };
+
+        let size = width
+            .checked_mul(height)
+            .ok_or_else(|| layer.invalid("the layer is too large"))?;
+        if raw.len() != size as usize {
+            return Err(data.invalid("the number of tiles does not match the size of the layer"));
+        }
+
+        Ok(TileLayer {
+            name: layer.attr("name").unwrap_or_default().to_owned(),
+            width,
+            height,
+            visible: layer.attr("visible") != Some("0"),
+            opacity: layer.optional("opacity")?.unwrap_or(1.0),
+            tiles: raw.into_iter().map(Tile::from_raw).collect(),
+            properties: layer.properties()?,
+        })
+    }
+}
+
+impl ObjectLayer {
+    fn from_element(layer: &Element) -> Result<ObjectLayer, LoadMapError> {
+        Ok(ObjectLayer {
+            name: layer.attr("name").unwrap_or_default().to_owned(),
+            visible: layer.attr("visible") != Some("0"),
+            opacity: layer.optional("opacity")?.unwrap_or(1.0),
+            objects: layer
+                .children
+                .iter()
+                .filter(|child| child.name == "object")
+                .map(Object::from_element)
+                .collect::<Result<_, _>>()?,
+            properties: layer.properties()?,
+        })
+    }
+}
+
+impl Object {
+    fn from_element(object: &Element) -> Result<Object, LoadMapError> {
+        let mut shape = Shape::Rectangle;
+        for child in object.children.iter() {
+            shape = match child.name.as_str() {
+                "ellipse" => Shape::Ellipse,
+                "point" => Shape::Point,
+                "polygon" => Shape::Polygon(child.points()?),
+                "polyline" => Shape::Polyline(child.points()?),
+                _ => continue,
+            };
+        }
+
+        Ok(Object {
+            id: object.optional("id")?.unwrap_or(0),
+            name: object.attr("name").unwrap_or_default().to_owned(),
+            kind: object
+                .attr("type")
+                .or_else(|| object.attr("class"))
+                .unwrap_or_default()
+                .to_owned(),
+            x: object.optional("x")?.unwrap_or(0.0),
+            y: object.optional("y")?.unwrap_or(0.0),
+            width: object.optional("width")?.unwrap_or(0.0),
+            height: object.optional("height")?.unwrap_or(0.0),
+            rotation: object.optional("rotation")?.unwrap_or(0.0),
+            tile: object.optional("gid")?.and_then(Tile::from_raw),
+            visible: object.attr("visible") != Some("0"),
+            shape,
+            properties: object.properties()?,
+        })
+    }
+}
+
+/// An element of a XML document.
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    /// The text directly contained in this element.
+    text: String,
+    /// The line of the start of this element, starting at `1`.
+    line: usize,
+}
+
+impl Element {
+    /// Parses a XML document, returning its root element.
+    fn parse(source: &str) -> Result<Element, LoadMapError> {
+        let mut reader = EventReader::from_str(source);
+        let mut stack: Vec<Element> = Vec::new();
+        loop {
+            let line = reader.position().row as usize + 1;
+            match reader.next()? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => stack.push(Element {
+                    name: name.local_name,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|a| (a.name.local_name, a.value))
+                        .collect(),
+                    children: Vec::new(),
+                    text: String::new(),
+                    line,
+                }),
+                XmlEvent::EndElement { .. } => {
+                    let element = stack.pop().unwrap();
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text);
+                    }
+                }
+                XmlEvent::EndDocument => {
+                    return Err(LoadMapError::InvalidMap {
+                        line,
+                        reason: "missing root element".to_owned(),
+                    })
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn invalid(&self, reason: &str) -> LoadMapError {
+        LoadMapError::InvalidMap {
+            line: self.line,
+            reason: format!("{} in `{}`", reason, self.name),
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn optional<T: FromStr>(&self, name: &str) -> Result<Option<T>, LoadMapError> {
+        self.attr(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| self.invalid(&format!("invalid attribute `{}`", name)))
+            })
+            .transpose()
+    }
+
+    fn required<T: FromStr>(&self, name: &str) -> Result<T, LoadMapError> {
+        self.optional(name)?
+            .ok_or_else(|| self.invalid(&format!("missing attribute `{}`", name)))
+    }
+
+    /// Parses the attribute `points` of a polygon or polyline.
+    fn points(&self) -> Result<Vec<(f32, f32)>, LoadMapError> {
+        let points: String = self.required("points")?;
+        points
+            .split_whitespace()
+            .map(|point| {
+                let mut coords = point.split(',').map(str::parse);
+                match (coords.next(), coords.next(), coords.next()) {
+                    (Some(Ok(x)), Some(Ok(y)), None) => Ok((x, y)),
+                    _ => Err(self.invalid("invalid attribute `points`")),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the custom properties of this element.
+    fn properties(&self) -> Result<Properties, LoadMapError> {
+        let mut properties = Properties::new();
+        for list in self
+            .children
+            .iter()
+            .filter(|child| child.name == "properties")
+        {
+            for property in list
+                .children
+                .iter()
+                .filter(|child| child.name == "property")
+            {
+                // multiline strings are stored as text instead of using `value`
+                let value = match property.attr("value") {
+                    Some(value) => value.to_owned(),
+                    None => property.text.clone(),
+                };
+                properties.insert(property.required("name")?, value);
+            }
+        }
+        Ok(properties)
+    }
+}
+
+/// Decodes base64 using the standard alphabet, ignoring whitespace.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        buffer = buffer << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Decompresses gzip data, which is deflate data with an additional header.
+fn decompress_gzip(bytes: &[u8]) -> Option<Vec<u8>> {
+    const EXTRA: u8 = 1 << 2;
+    const NAME: u8 = 1 << 3;
+    const COMMENT: u8 = 1 << 4;
+    const HEADER_CRC: u8 = 1 << 1;
+
+    if bytes.len() < 10 || bytes[..3] != [0x1f, 0x8b, 8] {
+        return None;
+    }
+    let flags = bytes[3];
+    let mut rest = &bytes[10..];
+    if flags & EXTRA != 0 {
+        let len = usize::from(u16::from_le_bytes([*rest.first()?, *rest.get(1)?]));
+        rest = rest.get(2 + len..)?;
+    }
+    for &flag in [NAME, COMMENT].iter() {
+        if flags & flag != 0 {
+            let end = rest.iter().position(|&b| b == 0)?;
+            rest = &rest[end + 1..];
+        }
+    }
+    if flags & HEADER_CRC != 0 {
+        rest = rest.get(2..)?;
+    }
+    miniz_oxide::inflate::decompress_to_vec(rest).ok()
+}
@@ -0,0 +1,212 @@
+use std::rc::Rc;
+
+use crate::{
+    backend::{tex::RawTexture, Uniform},
+    Context, DrawError, NewTextureError, Subsystem, Texture,
+};
+
+static FRAGMENT: &str = include_str!("backend/shader/fragment_video.glsl");
+
+/// A texture which is updated with raw frames, for example
+/// to play cutscenes decoded by an external video decoder.
+///
+/// ```rust, no_run
+/// use crow::{
+///     glutin::{event_loop::EventLoop, window::WindowBuilder},
+///     Context, DrawConfig, VideoTexture,
+/// };
+///
+/// let mut ctx = Context::new(WindowBuilder::new(), &EventLoop::new())?;
+/// let mut video = VideoTexture::new(&mut ctx, (320, 240))?;
+///
+/// // a single frame returned by the video decoder
+/// let frame = vec![0; 320 * 240 * 4];
+/// video.push_rgba(&mut ctx, &frame);
+///
+/// let mut surface = ctx.surface();
+/// ctx.draw(&mut surface, video.texture(), (0, 0), &DrawConfig::default());
+/// # Ok::<(), crow::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct VideoTexture {
+    texture: Texture,
+    /// The uploaded frame or plane.
+    buffer: Vec<u8>,
+    /// The `y`, `u` and `v` planes used by `push_yuv420`, created on first use.
+    planes: Vec<RawTexture>,
+    frame_count: u64,
+}
+
+impl VideoTexture {
+    /// Creates a new video texture for frames of the given `dimensions`.
+    ///
+    /// The content of the texture is undefined until the first frame is pushed.
+    pub fn new(
+        ctx: &mut Context,
+        dimensions: impl Into<(u32, u32)>,
+    ) -> Result<Self, NewTextureError> {
+        Ok(VideoTexture {
+            texture: Texture::new(ctx, dimensions)?,
+            buffer: Vec::new(),
+            planes: Vec::new(),
+            frame_count: 0,
+        })
+    }
+
+    /// Returns the texture containing the current frame.
+    ///
+    /// Clones of this texture keep showing the frame which was current when they were cloned.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns the dimensions of each frame.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.texture.dimensions()
+    }
+
+    /// Returns the number of frames pushed so far.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Replaces the current frame with `frame`, which contains 8 bit RGBA pixels
+    /// starting with the top left corner, just like an `image::RgbaImage`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `frame` does not contain exactly one frame or
+    /// if this video texture was created by a different `Context`.
+    pub fn push_rgba(&mut self, ctx: &mut Context, frame: &[u8]) {
+        let (width, height) = self.dimensions();
+        let row = width as usize * 4;
+        assert_eq!(
+            frame.len(),
+            row * height as usize,
+            "invalid frame size for a {}x{} video",
+            width,
+            height
+        );
+
+        // open gl stores images upside down
+        self.buffer.clear();
+        for line in frame.chunks(row).rev() {
+            self.buffer.extend_from_slice(line);
+        }
+
+        ctx.in_subsystem(Subsystem::Video, |ctx| {
+            match frame_target(&mut self.texture, ctx) {
+                Ok(target) => target.write(&mut ctx.backend, &self.buffer),
+                Err(err) => ctx.backend.report_error(err),
+            }
+        });
+        self.frame_count += 1;
+    }
+
+    /// Replaces the current frame with a frame in the planar `I420` format,
+    /// which is the most common output of video decoders.
+    ///
+    /// `y` contains one byte for each pixel, while `u` and `v` have half
+    /// the width and height of the frame, rounded up. All planes start with
+    /// the top left corner and are converted to RGBA on the GPU using the BT.601 color space.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a plane has an unexpected size or if
+    /// this video texture was created by a different `Context`.
+    pub fn push_yuv420(&mut self, ctx: &mut Context, y: &[u8], u: &[u8], v: &[u8]) {
+        let (width, height) = self.dimensions();
+        let (width, height) = (width as usize, height as usize);
+        let chroma = (width / 2 + width % 2, height / 2 + height % 2);
+        assert_eq!(y.len(), width * height, "invalid size of the `y` plane");
+        assert_eq!(
+            u.len(),
+            chroma.0 * chroma.1,
+            "invalid size of the `u` plane"
+        );
+        assert_eq!(
+            v.len(),
+            chroma.0 * chroma.1,
+            "invalid size of the `v` plane"
+        );
+
+        ctx.in_subsystem(Subsystem::Video, |ctx| {
+            if let Err(err) = self.convert_yuv420(ctx, [(y, width), (u, chroma.0), (v, chroma.0)]) {
+                ctx.backend.report_error(err);
+            }
+        });
+        self.frame_count += 1;
+    }
+
+    /// Uploads the planes and converts them into the current frame.
+    fn convert_yuv420(
+        &mut self,
+        ctx: &mut Context,
+        planes: [(&[u8], usize); 3],
+    ) -> Result<(), DrawError> {
+        if self.planes.is_empty() {
+            for &(plane, width) in planes.iter() {
+                // each texel contains 4 bytes of the plane
+                let texels = width / 4 + usize::from(width % 4 != 0);
+                let dimensions = (texels as u32, (plane.len() / width) as u32);
+                let raw =
+                    RawTexture::new(&mut ctx.backend, dimensions).map_err(DrawError::from_new)?;
+                self.planes.push(raw);
+            }
+        }
+
+        for (raw, &(plane, width)) in self.planes.iter().zip(planes.iter()) {
+            // rows are padded to whole texels
+            let padded = raw.dimensions.0 as usize * 4;
+            self.buffer.clear();
+            for row in plane.chunks(width) {
+                self.buffer.extend_from_slice(row);
+                self.buffer.resize(self.buffer.len() + padded - width, 0);
+            }
+            raw.write(&mut ctx.backend, &self.buffer);
+        }
+
+        let planes = [self.planes[0].id, self.planes[1].id, self.planes[2].id];
+        let target = frame_target(&mut self.texture, ctx)?;
+        if !target.has_framebuffer {
+            target.add_framebuffer(&mut ctx.backend)?;
+        }
+        let (framebuffer_id, dimensions) = (target.framebuffer_id, target.dimensions);
+        ctx.backend.run_pass(
+            FRAGMENT,
+            framebuffer_id,
+            dimensions,
+            None,
+            &[
+                ("y_plane", Uniform::Texture(planes[0])),
+                ("u_plane", Uniform::Texture(planes[1])),
+                ("v_plane", Uniform::Texture(planes[2])),
+                ("frame_height", Uniform::Int(dimensions.1 as i32)),
+            ],
+        );
+        Ok(())
+    }
+}
+
+/// Returns the texture of the current frame, which is replaced by a new
+/// texture if the previous frame is still used by clones or queued draw calls.
+///
+/// As each frame overwrites the whole texture, the previous frame is not copied.
+fn frame_target<'a>(
+    texture: &'a mut Texture,
+    ctx: &mut Context,
+) -> Result<&'a mut RawTexture, DrawError> {
+    ctx.backend.validate(&texture.inner)?;
+    if Rc::get_mut(&mut texture.inner).is_none() {
+        let previous = &texture.inner;
+        let mut raw = RawTexture::new_with(&mut ctx.backend, previous.dimensions, previous.options)
+            .map_err(DrawError::from_new)?;
+        raw.set_border_color(&mut ctx.backend, previous.border_color);
+        if let Some(label) = texture.label() {
+            raw.set_label(&mut ctx.backend, &label);
+        }
+        texture.inner = Rc::new(raw);
+    }
+
+    Ok(Rc::get_mut(&mut texture.inner).unwrap())
+}
@@ -1,45 +1,43 @@
-use std::{fs, io::ErrorKind, ops::Deref};
+use std::{cell::RefCell, collections::HashMap, env, fs, io::ErrorKind, rc::Rc, time::Duration};
 
-use image::RgbaImage;
+use image::{Rgba, RgbaImage};
 
 use rand::prelude::*;
 
 use crow::{
-    glutin::{dpi::LogicalSize, event_loop::EventLoop, window::WindowBuilder},
-    target::{Offset, Scaled},
-    Context, DrawConfig, Texture,
+    color,
+    glutin::{
+        dpi::PhysicalPosition,
+        event::{DeviceEvent, DeviceId, Event, ModifiersState, WindowEvent},
+        event_loop::EventLoop,
+        window::WindowId,
+    },
+    particles::{Emitter, EmitterConfig},
+    procgen,
+    target::{
+        Camera, Clipped, Offset, PixelPerfect, Scaled, SplitLayout, SplitScreen, View, YDown,
+    },
+    testing::{GoldenError, GoldenTest},
+    text::{BitmapFont, TextConfig},
+    texture_packer::{Direction, Frame, FrameRect, FrameSize, FrameTag, Frames, Meta, SpriteSheet},
+    Afterimage, Animation, AnimationState, Atlas, AtlasConfig, BlendMode, CellularAutomaton,
+    Composite, Context, CopyReason, Counts, CursorGrab, DrawCapture, DrawConfig, DrawError,
+    DrawList, Filter, FinalizeError, IRect, Layer, LoopMode, Mask, Minimap, PassInput, PassOutput,
+    Rect, Reflection, RenderGraph, RenderProfile, Rule, Sampler, ScalingMode, Shader, Shadow,
+    SortKey, SpriteBatch, SpriteSheetError, Subsystem, Texture, TextureOptions, UniformValue,
+    VideoTexture, VsyncError, Wrap,
 };
 
-type TestFn = fn(&mut Context) -> Result<RgbaImage, crow::Error>;
-
-pub fn test(ctx: &mut Context, name: &str, f: TestFn) -> Result<(), ()> {
-    let res = f(ctx);
-
-    let actual_image = match res {
-        Ok(image) => image,
-        Err(_e) => {
-            eprintln!("TEST FAILED (runtime error): {}", name);
-            return Err(());
-        }
-    };
-
-    let expected = if let Ok(image) = image::open(format!("tests/expected/{}.png", name)) {
-        image.to_rgba8()
-    } else {
-        eprintln!("TEST FAILED (expected image not found): {}", name);
-        return Err(());
-    };
+#[cfg(feature = "aseprite")]
+use crow::aseprite::Sprite;
+#[cfg(feature = "ldtk")]
+use crow::ldtk::Project;
+#[cfg(feature = "ttf")]
+use crow::text::{Font, RasterConfig};
+#[cfg(feature = "tiled")]
+use crow::tiled::{self, Map, Shape};
 
-    if actual_image.deref() != expected.deref() {
-        eprintln!("TEST FAILED (invalid return image): {}", name);
-        actual_image
-            .save(format!("tests/actual/{}.png", name))
-            .unwrap();
-        Err(())
-    } else {
-        Ok(())
-    }
-}
+type TestFn = fn(&mut Context) -> Result<RgbaImage, crow::Error>;
 
 fn simple(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     let mut a = Texture::new(ctx, (32, 32))?;
@@ -70,6 +68,246 @@ fn from_image(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     Ok(ctx.image_data(&a))
 }
 
+fn from_raw(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let cat = Texture::load_from_memory(ctx, include_bytes!("../textures/cat1.png"))?;
+    let expected = Texture::load(ctx, "textures/cat1.png")?;
+    assert_eq!(ctx.image_data(&cat), ctx.image_data(&expected));
+
+    let data = [
+        0, 0, 255, 255, 255, 255, 0, 255, 0, 255, 255, 255, 0, 0, 0, 255,
+    ];
+    let texture = Texture::from_raw(ctx, (2, 2), &data)?;
+    // both too short and too long buffers are rejected
+    assert!(Texture::from_raw(ctx, (2, 2), &data[..12]).is_err());
+    assert!(Texture::from_raw(ctx, (2, 1), &data).is_err());
+    let mut a = Texture::new(ctx, (5, 5))?;
+    ctx.clear_color(&mut a, (1.0, 0.0, 0.0, 1.0));
+    ctx.draw(&mut a, &texture, (1, 1), &DrawConfig::default());
+
+    Ok(ctx.image_data(&a))
+}
+
+fn texture_options(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let linear = TextureOptions {
+        min_filter: Filter::Linear,
+        mag_filter: Filter::Linear,
+        ..Default::default()
+    };
+    let mut image = RgbaImage::new(2, 1);
+    image.put_pixel(0, 0, Rgba([0, 0, 0, 255]));
+    image.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+    let gradient = Texture::from_image_with(ctx, image, linear)?;
+    assert_eq!(gradient.options(), linear);
+    assert_eq!(
+        gradient.get_section(Rect::new((0, 0), (1, 1))).options(),
+        linear
+    );
+
+    let mipmapped = TextureOptions {
+        mipmaps: true,
+        ..linear
+    };
+    let mut blue = Texture::new_with(ctx, (4, 4), mipmapped)?;
+    ctx.clear_color(&mut blue, (0.0, 0.0, 1.0, 1.0));
+    blue.generate_mipmaps(ctx);
+
+    // copying a texture when drawing to it also copies its mipmaps
+    let mut copy = blue.clone();
+    ctx.debug_line(&mut copy, (0, 0), (0, 1), (0.0, 0.0, 1.0, 1.0));
+    let mut minified = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut minified, (0.0, 0.0, 0.0, 0.0));
+    ctx.draw(
+        &mut minified,
+        &copy,
+        (0, 0),
+        &DrawConfig {
+            transform: Some([[0.25, 0.0, 0.0], [0.0, 0.25, 0.0], [0.0, 0.0, 1.0]]),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        *ctx.image_data(&minified).get_pixel(0, 3),
+        Rgba([0, 0, 255, 255])
+    );
+
+    // samplers also use the mipmaps of the texture
+    let checker = RgbaImage::from_fn(4, 4, |x, y| {
+        if (x + y) % 2 == 0 {
+            Rgba([255, 0, 0, 255])
+        } else {
+            Rgba([0, 255, 0, 255])
+        }
+    });
+    let checker = Texture::from_image_with(ctx, checker, mipmapped)?;
+    ctx.draw(
+        &mut minified,
+        &checker,
+        (0, 0),
+        &DrawConfig {
+            sampler: Some(Sampler::default()),
+            transform: Some([[0.25, 0.0, 0.0], [0.0, 0.25, 0.0], [0.0, 0.0, 1.0]]),
+            ..Default::default()
+        },
+    );
+    let pixel = *ctx.image_data(&minified).get_pixel(0, 3);
+    assert!(pixel[0] > 0 && pixel[1] > 0, "{:?}", pixel);
+
+    let mut a = Texture::new(ctx, (8, 8))?;
+    ctx.clear_color(&mut a, (1.0, 0.0, 0.0, 1.0));
+    ctx.draw(
+        &mut a,
+        &gradient,
+        (0, 4),
+        &DrawConfig {
+            scale: (4, 4),
+            ..Default::default()
+        },
+    );
+    ctx.draw(&mut a, &blue, (0, 0), &DrawConfig::default());
+
+    Ok(ctx.image_data(&a))
+}
+
+fn draw_tiled(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut image = RgbaImage::new(4, 2);
+    image.put_pixel(0, 0, Rgba([0, 0, 255, 255]));
+    image.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+    image.put_pixel(0, 1, Rgba([255, 0, 0, 255]));
+    image.put_pixel(1, 1, Rgba([0, 255, 0, 255]));
+    let atlas = Texture::from_image(ctx, image.clone())?;
+    let section = atlas.get_section(Rect::new((0, 0), (2, 2)));
+    let full = Texture::from_image(
+        ctx,
+        image::imageops::crop(&mut image, 0, 0, 2, 2).to_image(),
+    )?;
+
+    let config = DrawConfig {
+        flip_horizontally: true,
+        ..Default::default()
+    };
+    let mut a = Texture::new(ctx, (6, 8))?;
+    ctx.clear_color(&mut a, (0.0, 0.0, 0.0, 1.0));
+    ctx.draw_tiled(&mut a, &full, IRect::new((0, 0), (5, 3)), (1, -1), &config);
+    ctx.draw_tiled(
+        &mut a,
+        &section,
+        IRect::new((0, 4), (5, 3)),
+        (1, -1),
+        &config,
+    );
+    assert_eq!(
+        ctx.image_data(&a.get_section(Rect::new((0, 0), (5, 3)))),
+        ctx.image_data(&a.get_section(Rect::new((0, 4), (5, 3))))
+    );
+
+    // `rect` is in pixels of the target, regardless of the scale
+    let mut scaled = Texture::new(ctx, (6, 4))?;
+    ctx.clear_color(&mut scaled, (0.0, 0.0, 0.0, 0.0));
+    let config = DrawConfig {
+        scale: (2, 2),
+        ..Default::default()
+    };
+    ctx.draw_tiled(
+        &mut scaled,
+        &full,
+        IRect::new((0, 0), (4, 4)),
+        (0, 0),
+        &config,
+    );
+    let scaled = ctx.image_data(&scaled);
+    assert_eq!(scaled.get_pixel(3, 0)[3], 255);
+    assert_eq!(scaled.get_pixel(4, 0)[3], 0);
+
+    Ok(ctx.image_data(&a))
+}
+
+fn render_view(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut pixel = Texture::new(ctx, (1, 1))?;
+    ctx.clear_color(&mut pixel, (1.0, 1.0, 1.0, 1.0));
+
+    let mut view = View::new(ctx, (2, 2))?;
+    view.set_resolution(ctx, (6, 4))?;
+    assert_eq!(view.resolution(), (6, 4));
+    view.set_position((1, 1));
+    view.set_zoom(2);
+    view.set_clear_color((0.0, 0.0, 1.0, 1.0));
+    assert_eq!(view.view_to_world((3, 2)), (2, 2));
+    view.render(ctx, |ctx, camera| {
+        ctx.draw(camera, &pixel, (1, 1), &DrawConfig::default());
+        ctx.draw(camera, &pixel, (3, 2), &DrawConfig::default());
+    });
+
+    let mut a = Texture::new(ctx, (8, 8))?;
+    ctx.clear_color(&mut a, (1.0, 0.0, 0.0, 1.0));
+    ctx.draw(&mut a, view.texture(), (1, 2), &DrawConfig::default());
+
+    Ok(ctx.image_data(&a))
+}
+
+fn texture_wrap(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut image = RgbaImage::new(2, 2);
+    image.put_pixel(0, 0, Rgba([0, 0, 255, 255]));
+    image.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+    image.put_pixel(0, 1, Rgba([255, 0, 0, 255]));
+    image.put_pixel(1, 1, Rgba([0, 255, 0, 255]));
+    let options = TextureOptions {
+        wrap: (Wrap::Repeat, Wrap::MirroredRepeat),
+        ..Default::default()
+    };
+    let mut texture = Texture::from_image_with(ctx, image, options)?;
+    // removing the border color restores the wrap mode
+    texture.set_border_color(ctx, Some((0.0, 0.0, 0.0, 0.0)));
+    texture.set_border_color(ctx, None);
+
+    let mut a = Texture::new(ctx, (6, 5))?;
+    ctx.clear_color(&mut a, (0.0, 0.0, 0.0, 1.0));
+    let area = IRect::new((0, 0), (5, 4));
+    ctx.draw_tiled(&mut a, &texture, area, (1, -1), &DrawConfig::default());
+    // the wrap mode of a sampler is used instead of the one of the texture
+    let mut b = Texture::new(ctx, (6, 5))?;
+    ctx.clear_color(&mut b, (0.0, 0.0, 0.0, 1.0));
+    let sampler = DrawConfig {
+        sampler: Some(Sampler {
+            wrap: (Wrap::Repeat, Wrap::MirroredRepeat),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let image = ctx.image_data(&texture);
+    let plain = Texture::from_image(ctx, image)?;
+    ctx.draw_tiled(&mut b, &plain, area, (1, -1), &sampler);
+    assert_eq!(ctx.image_data(&a), ctx.image_data(&b));
+
+    Ok(ctx.image_data(&a))
+}
+
+fn split_screen(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut split = SplitScreen::new(3, (16, 8), SplitLayout::Grid);
+    split.set_aspect_ratio(Some((1, 1)));
+    split.set_letterbox_color((0.0, 0.0, 0.0, 1.0));
+    split.set_camera_position(1, (-1, -1));
+    split.set_zoom(2, 2);
+    assert_eq!(split.viewport(0), IRect::new((2, 4), (4, 4)));
+    assert_eq!(split.viewport(1), IRect::new((10, 4), (4, 4)));
+    assert_eq!(split.viewport(2), IRect::new((2, 0), (4, 4)));
+    assert_eq!(split.screen_to_world((11, 5)), Some((1, (0, 0))));
+    assert_eq!(split.screen_to_world((5, 3)), Some((2, (1, 1))));
+    assert_eq!(split.screen_to_world((0, 0)), None);
+
+    let mut pixel = Texture::new(ctx, (1, 1))?;
+    ctx.clear_color(&mut pixel, (1.0, 1.0, 1.0, 1.0));
+    let mut a = Texture::new(ctx, (16, 8))?;
+    split.clear(ctx, &mut a, (1.0, 0.0, 0.0, 1.0));
+    for player in 0..split.players() {
+        let mut view = split.view(player, &mut a);
+        ctx.draw(&mut view, &pixel, (0, 0), &DrawConfig::default());
+        // outside of the viewport
+        ctx.draw(&mut view, &pixel, (4, 0), &DrawConfig::default());
+    }
+
+    Ok(ctx.image_data(&a))
+}
+
 fn color_modulation(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     let mut a = Texture::new(ctx, (32, 32))?;
     let mut b = Texture::new(ctx, (32, 32))?;
@@ -93,11 +331,191 @@ fn color_modulation(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     Ok(ctx.image_data(&a))
 }
 
+fn color_mask(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut a = Texture::new(ctx, (32, 32))?;
+    let mut b = Texture::new(ctx, (32, 32))?;
+    ctx.clear_color(&mut a, (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_color(&mut b, (0.0, 1.0, 0.0, 1.0));
+    ctx.draw(
+        &mut a,
+        &b,
+        (16, 16),
+        &DrawConfig {
+            color_mask: (false, true, false, true),
+            ..Default::default()
+        },
+    );
+
+    Ok(ctx.image_data(&a))
+}
+
+fn depth_only(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut a = Texture::new(ctx, (32, 32))?;
+    let mut b = Texture::new(ctx, (32, 32))?;
+    let mut c = Texture::new(ctx, (32, 32))?;
+    ctx.clear_color(&mut a, (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_color(&mut b, (0.0, 1.0, 0.0, 1.0));
+    ctx.clear_color(&mut c, (0.0, 0.0, 1.0, 1.0));
+    ctx.draw(
+        &mut a,
+        &b,
+        (16, 16),
+        &DrawConfig {
+            depth: Some(0.2),
+            depth_only: true,
+            ..Default::default()
+        },
+    );
+    ctx.draw(
+        &mut a,
+        &c,
+        (0, 0),
+        &DrawConfig {
+            depth: Some(0.5),
+            ..Default::default()
+        },
+    );
+
+    Ok(ctx.image_data(&a))
+}
+
+fn occlusion_query(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut red = Texture::new(ctx, (2, 1))?;
+    ctx.clear_color(&mut red, (1.0, 0.0, 0.0, 1.0));
+    let mut green = Texture::new(ctx, (4, 1))?;
+    ctx.clear_color(&mut green, (0.0, 1.0, 0.0, 1.0));
+
+    let mut target = Texture::new(ctx, (4, 1))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    ctx.clear_depth(&mut target);
+    let depth = |depth| DrawConfig {
+        depth: Some(depth),
+        ..Default::default()
+    };
+    ctx.draw(&mut target, &red, (0, 0), &depth(0.2));
+
+    let hidden = ctx.query_visible(&mut target, ((0, 0), (2, 1)), 0.8);
+    let partially_hidden = ctx.query_visible(&mut target, ((1, 0), (2, 1)), 0.8);
+    let in_front = ctx.query_visible(&mut target, ((0, 0), (2, 1)), 0.1);
+    let offset = ctx.query_visible(
+        &mut Offset::new(&mut target, (-2, 0)),
+        ((0, 0), (2, 1)),
+        0.8,
+    );
+    assert!(!hidden.wait(ctx));
+    assert!(partially_hidden.wait(ctx));
+    assert!(in_front.wait(ctx));
+    assert!(offset.wait(ctx));
+    assert_eq!(offset.is_visible(ctx), Some(true));
+
+    // queries do not modify the depth buffer
+    ctx.draw(&mut target, &green, (0, 0), &depth(0.8));
+    Ok(ctx.image_data(&target))
+}
+
+fn masked_rectangle(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut target = Texture::new(ctx, (10, 10))?;
+    let mut source = Texture::new(ctx, (10, 10))?;
+    ctx.clear_color(&mut target, (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_color(&mut source, (0.0, 1.0, 0.0, 1.0));
+
+    ctx.masked(
+        &mut target,
+        Mask::Rectangle(IRect::from_corners((5, 4), (2, 2))),
+        |ctx, target| ctx.draw(target, &source, (0, 0), &DrawConfig::default()),
+    );
+
+    // the window has a stencil buffer as well, even for headless contexts
+    let mut surface = ctx.surface();
+    ctx.clear_color(&mut surface, (1.0, 0.0, 0.0, 1.0));
+    ctx.masked(
+        &mut surface,
+        Mask::Rectangle(IRect::from_corners((5, 4), (2, 2))),
+        |ctx, target| ctx.draw(target, &source, (0, 0), &DrawConfig::default()),
+    );
+    let window = ctx.image_data(&surface);
+    let height = window.height();
+    assert_eq!(window.get_pixel(3, height - 4).0, [0, 255, 0, 255]);
+    assert_eq!(window.get_pixel(1, height - 4).0, [255, 0, 0, 255]);
+    assert_eq!(window.get_pixel(3, height - 6).0, [255, 0, 0, 255]);
+
+    Ok(ctx.image_data(&target))
+}
+
+fn try_draw(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut target = Texture::new(ctx, (10, 10))?;
+    let mut source = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut target, (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_color(&mut source, (0.0, 1.0, 0.0, 1.0));
+
+    let res = ctx.try_draw(
+        &mut target,
+        &source,
+        (0, 0),
+        &DrawConfig {
+            depth: Some(1.5),
+            ..Default::default()
+        },
+    );
+    assert_eq!(res, Err(DrawError::DepthOutOfRange(1.5)));
+    let res = ctx.try_draw(
+        &mut target,
+        &source.get_section(Rect::new((0, 0), (0, 3))),
+        (0, 0),
+        &DrawConfig::default(),
+    );
+    assert_eq!(res, Err(DrawError::ZeroSizedTexture));
+    ctx.try_draw(&mut target, &source, (2, 3), &DrawConfig::default())?;
+
+    Ok(ctx.image_data(&target))
+}
+
+fn sanitize_config(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut target = Texture::new(ctx, (4, 2))?;
+    let mut source = Texture::new(ctx, (2, 2))?;
+    ctx.clear_color(&mut target, (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_color(&mut source, (0.0, 1.0, 0.0, 1.0));
+
+    let mut color_modulation = color::IDENTITY;
+    color_modulation[0][0] = f32::NAN;
+    let config = DrawConfig {
+        scale: (0, 1),
+        color_modulation,
+        depth: Some(-0.5),
+        ..Default::default()
+    };
+    assert_eq!(config.validate().len(), 3);
+    assert_eq!(config.validate()[2], DrawError::DepthOutOfRange(-0.5));
+    let res = ctx.try_draw(&mut target, &source, (0, 0), &config);
+    assert_eq!(res, Err(config.validate()[0]));
+
+    let sanitized = config.sanitize();
+    assert_eq!(sanitized.validate(), vec![]);
+    assert_eq!(sanitized.scale, (1, 1));
+    assert_eq!(sanitized.color_modulation, color::IDENTITY);
+    assert_eq!(sanitized.depth, Some(0.0));
+    ctx.try_draw(&mut target, &source, (0, 0), &sanitized)?;
+
+    // invalid draw calls are sanitized in debug builds and skipped in release builds
+    let mut drawn = Texture::new(ctx, (4, 2))?;
+    ctx.clear_color(&mut drawn, (1.0, 0.0, 0.0, 1.0));
+    let skipped = ctx.image_data(&drawn);
+    ctx.draw(&mut drawn, &source, (0, 0), &config);
+    let expected = if cfg!(debug_assertions) {
+        ctx.image_data(&target)
+    } else {
+        skipped
+    };
+    assert_eq!(ctx.image_data(&drawn), expected);
+
+    Ok(ctx.image_data(&target))
+}
+
 fn flip_vertically(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     let big = Texture::new(ctx, (48, 16))?;
-    let mut a = big.get_section((0, 0), (16, 16));
-    let mut b = big.get_section((16, 0), (16, 16));
-    let mut c = big.get_section((32, 0), (16, 16));
+    let mut a = big.get_section(Rect::new((0, 0), (16, 16)));
+    let mut b = big.get_section(Rect::new((16, 0), (16, 16)));
+    let mut c = big.get_section(Rect::new((32, 0), (16, 16)));
 
     ctx.clear_color(&mut a, (1.0, 0.0, 0.0, 1.0));
     ctx.clear_color(&mut b, (0.0, 1.0, 0.0, 1.0));
@@ -122,7 +540,7 @@ fn section_drawing(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
 
     let source = Texture::load(ctx, "textures/section_test.png")?;
-    let source = source.get_section((3, 4), (3, 2));
+    let source = source.get_section(Rect::new((3, 4), (3, 2)));
 
     ctx.draw(&mut target, &source, (3, 5), &DrawConfig::default());
 
@@ -134,7 +552,7 @@ fn section_offset(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
 
     let source = Texture::load(ctx, "textures/section_test.png")?;
-    let source = source.get_section((3, 4), (3, 2));
+    let source = source.get_section(Rect::new((3, 4), (3, 2)));
 
     ctx.draw(
         &mut Offset::new(&mut target, (-2, -3)),
@@ -151,7 +569,7 @@ fn section_flipped(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
 
     let source = Texture::load(ctx, "textures/section_test.png")?;
-    let source = source.get_section((3, 4), (3, 2));
+    let source = source.get_section(Rect::new((3, 4), (3, 2)));
 
     ctx.draw(
         &mut target,
@@ -172,7 +590,7 @@ fn section_scaled(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
 
     let source = Texture::load(ctx, "textures/section_test.png")?;
-    let source = source.get_section((3, 4), (3, 2));
+    let source = source.get_section(Rect::new((3, 4), (3, 2)));
 
     ctx.draw(
         &mut Scaled::new(&mut target, (2, 3)),
@@ -193,7 +611,7 @@ fn zero_section(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
 
     let source = Texture::load(ctx, "textures/section_test.png")?;
-    let source = source.get_section((3, 4), (0, 0));
+    let source = source.get_section(Rect::new((3, 4), (0, 0)));
 
     ctx.draw(&mut target, &source, (3, 5), &DrawConfig::default());
 
@@ -214,7 +632,11 @@ fn debug_rectangle(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     let mut target = Texture::new(ctx, (10, 10))?;
     ctx.clear_color(&mut target, (1.0, 0.0, 0.0, 1.0));
 
-    ctx.debug_rectangle(&mut target, (1, 1), (4, 3), (0.0, 1.0, 0.0, 1.0));
+    ctx.debug_rectangle(
+        &mut target,
+        IRect::new((1, 1), (4, 3)),
+        (0.0, 1.0, 0.0, 1.0),
+    );
 
     Ok(ctx.image_data(&target))
 }
@@ -230,34 +652,1901 @@ fn lines_offset(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
     Ok(ctx.image_data(&image))
 }
 
-#[derive(Default)]
-struct TestRunner(Vec<(&'static str, TestFn)>);
+fn bake_frames(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let frames = Texture::bake_frames(ctx, (3, 2), 3, |ctx, frame, i| {
+        let color = [
+            (1.0, 0.0, 0.0, 1.0),
+            (0.0, 1.0, 0.0, 1.0),
+            (0.0, 0.0, 1.0, 1.0),
+        ];
+        let y = i as i32 % 2;
+        ctx.debug_line(frame, (0, y), (2, y), color[i as usize]);
+    })?;
 
-impl TestRunner {
-    fn add(&mut self, name: &'static str, f: TestFn) {
-        self.0.push((name, f))
+    let mut target = Texture::new(ctx, (10, 10))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    for (i, frame) in frames.iter().enumerate() {
+        ctx.draw(
+            &mut target,
+            frame,
+            (1, 1 + 3 * i as i32),
+            &DrawConfig::default(),
+        );
     }
 
-    fn run(mut self) -> i32 {
-        // randomize test order
-        println!("\nrunning {} tests", self.0.len());
+    let frame_duration = Duration::from_millis(100);
+    let animation =
+        Texture::bake_animation(ctx, (3, 2), 3, frame_duration, LoopMode::Once, |_, _, _| {})?
+            .unwrap();
+    assert_eq!(animation.frames().len(), 3);
+    assert_eq!(animation.mode(), LoopMode::Once);
+    assert_eq!(animation.cycle_duration(), frame_duration * 3);
+    assert_eq!(animation.frames()[0].texture.dimensions(), (3, 2));
+    assert!(
+        Texture::bake_animation(ctx, (3, 2), 0, frame_duration, LoopMode::Loop, |_, _, _| {})?
+            .is_none()
+    );
 
-        self.0.shuffle(&mut rand::thread_rng());
-        let mut ctx = Context::new(
-            WindowBuilder::new()
-                .with_inner_size(LogicalSize::new(720, 480))
-                .with_visible(false),
-            &EventLoop::new(),
-        )
-        .unwrap();
+    Ok(ctx.image_data(&target))
+}
 
-        let mut success = 0;
-        let mut failed = 0;
+fn composite(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let white = Texture::from_image(ctx, RgbaImage::from_pixel(4, 4, Rgba([255; 4])))?;
 
-        for (name, f) in self.0 {
-            match test(&mut ctx, name, f) {
+    let mut composite = Composite::new((4, 4)).with_layer(Layer::new(white.clone()));
+    let mut target = Texture::new(ctx, (6, 6))?;
+    // the cached texture must be updated after changing a layer
+    let texture = composite.texture(ctx)?;
+    ctx.draw(&mut target, texture, (0, 0), &DrawConfig::default());
+    composite.layer_mut(0).unwrap().color_modulation = color::RED;
+    composite.push_layer(Layer {
+        offset: (1, 1),
+        color_modulation: color::GREEN,
+        ..Layer::new(white.get_section(Rect::new((0, 0), (2, 2))))
+    });
+
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    let texture = composite.texture(ctx)?;
+    ctx.draw(&mut target, texture, (1, 1), &DrawConfig::default());
+
+    Ok(ctx.image_data(&target))
+}
+
+fn minimap(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let colors = [
+        [Rgba([255, 0, 0, 255]), Rgba([0, 255, 0, 255])],
+        [Rgba([0, 0, 255, 255]), Rgba([255, 255, 255, 255])],
+    ];
+    let world = RgbaImage::from_fn(4, 4, |x, y| colors[y as usize / 2][x as usize / 2]);
+    let world = Texture::from_image(ctx, world)?;
+
+    let mut minimap = Minimap::new((2, 2), 10);
+    minimap.update(ctx, &world)?;
+
+    let mut target = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    minimap.draw(ctx, &mut target, (1, 1));
+
+    Ok(ctx.image_data(&target))
+}
+
+fn reflection(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut scene = Texture::new(ctx, (4, 6))?;
+    ctx.clear_color(&mut scene, (0.0, 0.0, 0.0, 1.0));
+    ctx.debug_line(&mut scene, (0, 3), (3, 3), (1.0, 0.0, 0.0, 1.0));
+    ctx.debug_line(&mut scene, (0, 4), (1, 4), (0.0, 0.0, 1.0, 1.0));
+    ctx.debug_line(&mut scene, (1, 5), (2, 5), (0.0, 1.0, 0.0, 1.0));
+
+    let reflection = Reflection {
+        opacity: 1.0,
+        amplitude: 1.0,
+        wavelength: 4.0,
+        ..Reflection::new(3, 3)
+    };
+    let source = scene.clone();
+    reflection.draw(ctx, &mut scene, &source, 0.0);
+
+    Ok(ctx.image_data(&scene))
+}
+
+fn afterimage(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut afterimage = Afterimage::new();
+    afterimage.enable(0.5);
+    for i in 0..3 {
+        afterimage.render(ctx, (4, 4), (0.0, 0.0, 0.0, 1.0), |ctx, frame| {
+            ctx.debug_line(frame, (i, 0), (i, 3), (1.0, 1.0, 1.0, 1.0));
+        })?;
+    }
+
+    let mut target = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 1.0, 1.0));
+    let frame = afterimage.render(ctx, (4, 4), (0.0, 0.0, 0.0, 1.0), |_, _| ())?;
+    ctx.draw(&mut target, frame, (0, 0), &DrawConfig::default());
+
+    Ok(ctx.image_data(&target))
+}
+
+fn clear_region(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut target = Texture::new(ctx, (10, 10))?;
+    ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
+
+    ctx.clear_region(
+        &mut Scaled::new(&mut target, (2, 2)),
+        IRect::new((1, 1), (2, 1)),
+        (1.0, 0.0, 0.0, 1.0),
+    );
+    ctx.clear_region(
+        &mut target,
+        IRect::from_corners((7, 7), (5, 9)),
+        (0.0, 0.0, 1.0, 1.0),
+    );
+
+    Ok(ctx.image_data(&target))
+}
+
+fn contrast(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut source = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut source, (0.25, 0.25, 0.25, 1.0));
+    ctx.clear_region(
+        &mut source,
+        IRect::new((2, 0), (2, 4)),
+        (0.75, 0.75, 0.75, 1.0),
+    );
+
+    let mut target = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 0.0));
+    let config = DrawConfig {
+        color_modulation: color::contrast(2.0),
+        ..Default::default()
+    };
+    ctx.draw(&mut target, &source, (0, 0), &config);
+
+    Ok(ctx.image_data(&target))
+}
+
+fn rotation_center(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut source = Texture::new(ctx, (4, 2))?;
+    ctx.clear_color(&mut source, (1.0, 0.0, 0.0, 1.0));
+
+    let mut target = Texture::new(ctx, (8, 8))?;
+    ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
+    let config = DrawConfig {
+        rotation: 90.0,
+        rotation_center: Some((0.0, 0.0)),
+        ..Default::default()
+    };
+    ctx.draw(&mut target, &source, (4, 4), &config);
+
+    Ok(ctx.image_data(&target))
+}
+
+fn transform(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut source = Texture::new(ctx, (2, 2))?;
+    ctx.clear_color(&mut source, (1.0, 0.0, 0.0, 1.0));
+
+    let mut target = Texture::new(ctx, (8, 8))?;
+    ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
+    let config = DrawConfig {
+        transform: Some([[2.0, 0.0, 1.0], [0.0, 1.0, 1.0], [0.0, 0.0, 1.0]]),
+        ..Default::default()
+    };
+    ctx.draw(&mut target, &source, (2, 2), &config);
+
+    // `Scaled` scales the rotation center and the translation of the transform
+    let config = DrawConfig {
+        rotation: 90.0,
+        rotation_center: Some((0.0, 0.0)),
+        transform: Some([[1.0, 0.0, 1.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+        ..Default::default()
+    };
+    let mut small = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut small, (0.0, 0.0, 0.0, 1.0));
+    ctx.draw(&mut small, &source, (1, 1), &config);
+    let mut expected = Texture::new(ctx, (8, 8))?;
+    let upscale = DrawConfig {
+        scale: (2, 2),
+        ..Default::default()
+    };
+    ctx.draw(&mut expected, &small, (0, 0), &upscale);
+    let mut scaled = Texture::new(ctx, (8, 8))?;
+    ctx.clear_color(&mut scaled, (0.0, 0.0, 0.0, 1.0));
+    ctx.draw(
+        &mut Scaled::new(&mut scaled, (2, 2)),
+        &source,
+        (1, 1),
+        &config,
+    );
+    assert_eq!(ctx.image_data(&scaled), ctx.image_data(&expected));
+
+    Ok(ctx.image_data(&target))
+}
+
+fn video_texture(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut target = Texture::new(ctx, (4, 2))?;
+    ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
+
+    let mut video = VideoTexture::new(ctx, (2, 2))?;
+    video.push_yuv420(ctx, &[235, 235, 16, 16], &[128], &[128]);
+    ctx.draw(&mut target, video.texture(), (0, 0), &DrawConfig::default());
+
+    let previous = video.texture().clone();
+    let red = [255, 0, 0, 255];
+    let blue = [0, 0, 255, 255];
+    video.push_rgba(ctx, &[red, red, blue, blue].concat());
+    ctx.draw(&mut target, video.texture(), (2, 0), &DrawConfig::default());
+    // clones keep their frame
+    let image = ctx.image_data(&previous);
+    assert_eq!(image.get_pixel(0, 0).0, [255; 4]);
+    assert_eq!(image.get_pixel(0, 1).0, [0, 0, 0, 255]);
+
+    // the planes of frames with an odd width are padded
+    let mut odd = VideoTexture::new(ctx, (5, 1))?;
+    odd.push_yuv420(ctx, &[16, 235, 16, 235, 235], &[128; 3], &[128, 128, 255]);
+    let image = ctx.image_data(odd.texture());
+    let pixels: Vec<_> = image.pixels().map(|p| p.0).collect();
+    assert_eq!(
+        pixels[..4],
+        [[0, 0, 0, 255], [255; 4], [0, 0, 0, 255], [255; 4]]
+    );
+    assert_eq!(pixels[4][0], 255);
+    assert!(pixels[4][1] < 200);
+
+    Ok(ctx.image_data(&target))
+}
+
+fn premultiplied_alpha(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let sprite = RgbaImage::from_pixel(1, 1, Rgba([255, 0, 0, 128]));
+    let sprite = Texture::from_image_premultiplied(ctx, sprite)?;
+    let config = DrawConfig {
+        blend_mode: BlendMode::PremultipliedAlpha,
+        ..Default::default()
+    };
+
+    let mut layer = Texture::new(ctx, (2, 1))?;
+    ctx.clear_color(&mut layer, (0.0, 0.0, 0.0, 0.0));
+    ctx.draw(&mut layer, &sprite, (0, 0), &config);
+
+    let mut target = Texture::new(ctx, (2, 1))?;
+    ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
+    ctx.draw(&mut target, &layer, (0, 0), &config);
+
+    Ok(ctx.image_data(&target))
+}
+
+fn linear_gradient(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut target = Texture::new(ctx, (4, 2))?;
+    procgen::linear_gradient(
+        ctx,
+        &mut target,
+        (0.5, 0.0),
+        (3.5, 0.0),
+        (0.0, 0.0, 0.0, 1.0),
+        (1.0, 1.0, 1.0, 1.0),
+    );
+
+    Ok(ctx.image_data(&target))
+}
+
+type NoiseFn = fn(&mut Context, &mut Texture, &procgen::Noise);
+
+fn generate_noise(
+    ctx: &mut Context,
+    generate: NoiseFn,
+    size: (u32, u32),
+    noise: &procgen::Noise,
+) -> Result<RgbaImage, crow::Error> {
+    let mut target = Texture::new(ctx, size)?;
+    generate(ctx, &mut target, noise);
+    Ok(ctx.image_data(&target))
+}
+
+/// Checks the properties shared by all noise functions and returns an 8x8 noise image.
+fn check_noise(ctx: &mut Context, generate: NoiseFn) -> Result<RgbaImage, crow::Error> {
+    let noise = procgen::Noise {
+        seed: 7,
+        scale: 4.0,
+        octaves: 2,
+        low: (0.0, 0.0, 0.5, 1.0),
+        high: (1.0, 0.0, 0.5, 1.0),
+        ..Default::default()
+    };
+    let image = generate_noise(ctx, generate, (8, 8), &noise)?;
+    assert_eq!(image, generate_noise(ctx, generate, (8, 8), &noise)?);
+    assert!(image.pixels().all(|pixel| pixel.0[1..] == [0, 128, 255]));
+    // the noise is not uniform
+    assert!(image
+        .pixels()
+        .any(|pixel| pixel.0[0] != image.get_pixel(0, 0).0[0]));
+
+    let other_seed = procgen::Noise {
+        seed: 8,
+        ..noise.clone()
+    };
+    assert_ne!(image, generate_noise(ctx, generate, (8, 8), &other_seed)?);
+
+    // `offset` continues the noise of an adjacent texture
+    let right = procgen::Noise {
+        offset: (4.0, 0.0),
+        ..noise.clone()
+    };
+    let right = generate_noise(ctx, generate, (4, 8), &right)?;
+    for (x, y, pixel) in right.enumerate_pixels() {
+        assert_eq!(pixel, image.get_pixel(x + 4, y));
+    }
+
+    Ok(image)
+}
+
+fn value_noise(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    check_noise(ctx, procgen::value_noise)
+}
+
+fn perlin_noise(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    check_noise(ctx, procgen::perlin_noise)
+}
+
+fn game_of_life(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut life = CellularAutomaton::new(ctx, (5, 5), Rule::GameOfLife)?;
+    // a blinker
+    for &cell in [(1, 2), (2, 2), (3, 2)].iter() {
+        life.set_cell(ctx, cell, (1.0, 1.0, 1.0, 1.0));
+    }
+    life.step(ctx);
+
+    let invalid = Rule::Custom(String::from("vec4 rule() { return missing; }"));
+    assert!(matches!(
+        CellularAutomaton::new(ctx, (2, 2), invalid.clone()),
+        Err(crow::Error::ShaderError(_))
+    ));
+    assert!(life.set_rule(ctx, invalid).is_err());
+    assert_eq!(life.rule(), &Rule::GameOfLife);
+
+    // cells move up and to the right, wrapping around the edges
+    let shift = Rule::Custom(String::from(
+        "vec4 rule() {
+            return cell(ivec2(-1)).r > 0.5 ? vec4(1.0) : vec4(0.0, 0.0, 0.0, 1.0);
+        }",
+    ));
+    let mut wrapped = CellularAutomaton::new(ctx, (3, 3), shift)?;
+    wrapped.set_cell(ctx, (2, 2), (1.0, 1.0, 1.0, 1.0));
+    wrapped.step(ctx);
+    let image = ctx.image_data(wrapped.state());
+    assert_eq!(image.get_pixel(0, 2).0, [255; 4]);
+    assert_eq!(image.pixels().filter(|p| p.0 == [255; 4]).count(), 1);
+
+    // shader globals are available to rules
+    let fill = Rule::Custom(String::from(
+        "uniform vec4 fill;
+        vec4 rule() {
+            return fill;
+        }",
+    ));
+    wrapped.set_rule(ctx, fill)?;
+    ctx.shader_globals_mut()
+        .set("fill", UniformValue::Vec4(1.0, 0.0, 0.0, 1.0));
+    wrapped.step(ctx);
+    ctx.shader_globals_mut().remove("fill");
+    let image = ctx.image_data(wrapped.state());
+    assert!(image.pixels().all(|p| p.0 == [255, 0, 0, 255]));
+
+    Ok(ctx.image_data(life.state()))
+}
+
+fn shadow(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut mask = Texture::new(ctx, (2, 2))?;
+    ctx.clear_color(&mut mask, (1.0, 1.0, 1.0, 1.0));
+
+    let shadow = Shadow {
+        radius: 1,
+        color: (1.0, 0.0, 0.0, 1.0),
+    };
+    let baked = shadow.bake(ctx, &mask)?;
+    assert_eq!(baked.dimensions(), shadow.dimensions(mask.dimensions()));
+
+    Ok(ctx.image_data(&baked))
+}
+
+fn custom_shader(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let shader = Shader::new(
+        r#"#version 330
+        out vec4 color;
+        uniform sampler2D mask;
+        uniform vec4 fill;
+
+        void main() {
+            color = fill * texelFetch(mask, ivec2(gl_FragCoord.xy), 0).a;
+        }"#,
+    );
+
+    let mut mask = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut mask, (0.0, 0.0, 0.0, 0.0));
+    ctx.clear_region(&mut mask, IRect::new((1, 1), (2, 3)), (1.0, 1.0, 1.0, 1.0));
+
+    let mut target = Texture::new(ctx, (4, 4))?;
+    let uniforms = [
+        ("mask", UniformValue::Texture(&mask)),
+        ("fill", UniformValue::Vec4(1.0, 0.0, 0.0, 1.0)),
+    ];
+    ctx.draw_shader(&mut target, &shader, &uniforms);
+    // the cached uniforms are reused
+    ctx.draw_shader(&mut target, &shader, &uniforms);
+
+    let invalid = "#version 330\nout vec4 color;\nvoid main() { color = missing; }";
+    assert!(matches!(
+        Shader::compile(ctx, invalid),
+        Err(crow::ShaderError::Compile(_))
+    ));
+    // shaders which do not compile are skipped
+    ctx.draw_shader(&mut target, &Shader::new(invalid), &uniforms);
+    assert_eq!(Shader::compile(ctx, shader.fragment()), Ok(shader));
+
+    Ok(ctx.image_data(&target))
+}
+
+fn shader_globals(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let shader = Shader::new(
+        r#"#version 330
+        out vec4 color;
+        uniform vec4 tint;
+        uniform float flash;
+
+        void main() {
+            color = gl_FragCoord.x < 1.0 ? tint : vec4(vec3(flash), 1.0);
+        }"#,
+    );
+
+    ctx.shader_globals_mut()
+        .set("tint", UniformValue::Vec4(1.0, 0.0, 0.0, 1.0));
+    ctx.shader_globals_mut()
+        .set("flash", UniformValue::Float(1.0));
+    assert!(matches!(
+        ctx.shader_globals().get("flash"),
+        Some(UniformValue::Float(_))
+    ));
+
+    let mut a = Texture::new(ctx, (2, 1))?;
+    let mut b = Texture::new(ctx, (2, 1))?;
+    ctx.draw_shader(&mut a, &shader, &[]);
+    // uniforms take precedence over globals
+    ctx.draw_shader(
+        &mut b,
+        &shader,
+        &[("tint", UniformValue::Vec4(0.0, 1.0, 0.0, 1.0))],
+    );
+    ctx.shader_globals_mut().remove("tint");
+    ctx.shader_globals_mut().remove("flash");
+    assert_eq!(ctx.shader_globals().iter().count(), 0);
+
+    let mut target = Texture::new(ctx, (2, 2))?;
+    ctx.draw(&mut target, &a, (0, 1), &DrawConfig::default());
+    ctx.draw(&mut target, &b, (0, 0), &DrawConfig::default());
+    Ok(ctx.image_data(&target))
+}
+
+fn shader_time(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let shader = Shader::new(
+        r#"#version 330
+        out vec4 color;
+        uniform float u_time;
+        uniform float expected;
+
+        void main() {
+            color = vec4(float(u_time == expected), float(u_time == 100.0), 0.0, 1.0);
+        }"#,
+    );
+
+    let mut a = Texture::new(ctx, (1, 1))?;
+    let mut b = Texture::new(ctx, (1, 1))?;
+    let time = ctx.shader_time();
+    ctx.draw_shader(&mut a, &shader, &[("expected", UniformValue::Float(time))]);
+    // `u_time` can be overwritten
+    ctx.draw_shader(
+        &mut b,
+        &shader,
+        &[
+            ("expected", UniformValue::Float(100.0)),
+            ("u_time", UniformValue::Float(100.0)),
+        ],
+    );
+
+    // `u_time` is updated after each frame and also used when drawing to the window
+    ctx.frame(|_, _| ())?;
+    let time = ctx.shader_time();
+    assert!(time > 0.0);
+    let window = ctx.frame(|ctx, surface| {
+        ctx.draw_fullscreen(surface, &shader, &[("expected", UniformValue::Float(time))]);
+        ctx.image_data(surface)
+    })?;
+    assert_eq!(window.get_pixel(0, 0).0, [255, 0, 0, 255]);
+
+    let mut target = Texture::new(ctx, (2, 1))?;
+    ctx.draw(&mut target, &a, (0, 0), &DrawConfig::default());
+    ctx.draw(&mut target, &b, (1, 0), &DrawConfig::default());
+    Ok(ctx.image_data(&target))
+}
+
+fn render_graph(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let fill = Shader::new(
+        r#"#version 330
+        out vec4 color;
+        uniform vec4 fill_color;
+
+        void main() {
+            color = fill_color;
+        }"#,
+    );
+    let add = Shader::new(
+        r#"#version 330
+        out vec4 color;
+        uniform sampler2D a;
+        uniform sampler2D b;
+
+        void main() {
+            ivec2 position = ivec2(gl_FragCoord.xy);
+            color = texelFetch(a, position, 0) + texelFetch(b, position, 0);
+        }"#,
+    );
+    let fill_color = |r, g, b| PassInput::Value(UniformValue::Vec4(r, g, b, 1.0));
+
+    let mut graph = RenderGraph::new();
+    let red = graph.transient((2, 1));
+    let green = graph.transient((2, 1));
+    let blue = graph.transient((2, 1));
+    // passes are executed once their inputs are ready
+    graph.add_pass(
+        &add,
+        &[
+            ("a", PassInput::Transient(red)),
+            ("b", PassInput::Transient(green)),
+        ],
+        PassOutput::Target,
+    );
+    graph.add_pass(
+        &fill,
+        &[("fill_color", fill_color(1.0, 0.0, 0.0))],
+        PassOutput::Transient(red),
+    );
+    graph.add_pass(
+        &fill,
+        &[("fill_color", fill_color(0.0, 1.0, 0.0))],
+        PassOutput::Transient(green),
+    );
+    graph.add_pass(
+        &fill,
+        &[("fill_color", fill_color(0.0, 0.0, 1.0))],
+        PassOutput::Transient(blue),
+    );
+
+    let mut target = Texture::new(ctx, (2, 1))?;
+    graph.execute(ctx, &mut target)?;
+    assert!(graph.texture(red).is_none());
+    ctx.finish();
+    // passes using the same shader are executed together
+    let timings = graph.timings(ctx);
+    let order: Vec<usize> = timings.iter().map(|timing| timing.pass).collect();
+    assert_eq!(order, [1, 2, 3, 0]);
+    assert!(timings.iter().all(|timing| timing.gpu.is_some()));
+    let blue = graph.texture(blue).unwrap().clone();
+
+    // the timings of all passes of the last frame are also returned by the context,
+    // passes executed outside of `Context::frame` count towards the next presented frame
+    ctx.frame(|_, _| ())?;
+    assert_eq!(ctx.last_frame_pass_timings().len(), 4);
+    ctx.set_draw_capture(DrawCapture::Overlay);
+    let black = (0.0, 0.0, 0.0, 1.0);
+    ctx.frame(|ctx, surface| {
+        ctx.clear_color(surface, black);
+        graph.execute(ctx, surface)
+    })??;
+    ctx.finish();
+    let timings = ctx.last_frame_pass_timings();
+    assert_eq!(timings.len(), 4);
+    assert!(timings.iter().all(|timing| timing.gpu.is_some()));
+    // and drawn in the lower left corner of the overlay during the next frame
+    ctx.frame(|ctx, surface| ctx.clear_color(surface, black))?;
+    let window = ctx.frame(|ctx, surface| ctx.image_data(surface))?;
+    assert_ne!(window.get_pixel(0, window.height() - 1).0, [0, 0, 0, 255]);
+    ctx.set_draw_capture(DrawCapture::Disabled);
+
+    let mut result = Texture::new(ctx, (2, 2))?;
+    ctx.draw(&mut result, &target, (0, 1), &DrawConfig::default());
+    ctx.draw(&mut result, &blue, (0, 0), &DrawConfig::default());
+    Ok(ctx.image_data(&result))
+}
+
+fn draw_fullscreen(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let shader = Shader::new(
+        r#"#version 330
+        in vec2 v_uv;
+        out vec4 color;
+
+        void main() {
+            color = vec4(v_uv, 0.0, 1.0);
+        }"#,
+    );
+
+    let mut target = Texture::new(ctx, (4, 2))?;
+    // target adapters do not change the area covered by the shader
+    ctx.draw_fullscreen(&mut Offset::new(&mut target, (3, 3)), &shader, &[]);
+    Ok(ctx.image_data(&target))
+}
+
+fn shader_noise(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let expected = ctx.noise_texture().clone();
+    let noise = ctx.image_data(&expected);
+    assert_eq!(noise.dimensions(), (32, 32));
+    // the red channel is blue noise, so each value is used equally often
+    let mut histogram = [0; 256];
+    for pixel in noise.pixels() {
+        histogram[usize::from(pixel[0])] += 1;
+    }
+    assert!(histogram.iter().all(|&count| count == 4));
+
+    let shader = Shader::new(
+        r#"#version 330
+        out vec4 color;
+        uniform sampler2D u_noise;
+        uniform sampler2D expected;
+
+        void main() {
+            ivec2 position = ivec2(gl_FragCoord.xy);
+            bool equal = texelFetch(u_noise, position, 0) == texelFetch(expected, position, 0);
+            color = vec4(0.0, float(equal), 0.0, 1.0);
+        }"#,
+    );
+
+    let mut target = Texture::new(ctx, (32, 32))?;
+    ctx.draw_shader(
+        &mut target,
+        &shader,
+        &[("expected", UniformValue::Texture(&expected))],
+    );
+    Ok(ctx.image_data(&target))
+}
+
+fn border_color(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut atlas = Texture::new(ctx, (4, 1))?;
+    ctx.clear_region(&mut atlas, IRect::new((0, 0), (2, 1)), (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_region(&mut atlas, IRect::new((2, 0), (2, 1)), (0.0, 1.0, 0.0, 1.0));
+    let mut red = atlas.get_section(Rect::new((0, 0), (2, 1)));
+    red.set_border_color(ctx, Some((0.0, 0.0, 0.0, 0.0)));
+    assert_eq!(red.border_color(), Some((0.0, 0.0, 0.0, 0.0)));
+    // `red` is copied as the underlying texture is still used by `atlas`
+    assert_eq!(atlas.border_color(), None);
+
+    let mut target = Texture::new(ctx, (4, 1))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 1.0, 1.0));
+    // the leftmost pixel samples the right edge of `red`, which
+    // would otherwise be the first pixel of the green section
+    ctx.draw(
+        &mut target,
+        &red,
+        (0, 0),
+        &DrawConfig {
+            flip_horizontally: true,
+            transform: Some([[1.0, 0.0, 0.5], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+            ..Default::default()
+        },
+    );
+
+    Ok(ctx.image_data(&target))
+}
+
+fn sprite_batch(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut atlas = Texture::new(ctx, (4, 2))?;
+    ctx.clear_region(&mut atlas, IRect::new((0, 0), (2, 2)), (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_region(&mut atlas, IRect::new((0, 0), (1, 1)), (1.0, 1.0, 1.0, 1.0));
+    ctx.clear_region(&mut atlas, IRect::new((2, 0), (2, 2)), (0.0, 1.0, 0.0, 1.0));
+    let red = atlas.get_section(Rect::new((0, 0), (2, 2)));
+    let green = atlas.get_section(Rect::new((2, 0), (2, 2)));
+
+    let sprites = [
+        (&red, (0, 0), DrawConfig::default()),
+        (
+            &green,
+            (4, 0),
+            DrawConfig {
+                scale: (2, 2),
+                ..Default::default()
+            },
+        ),
+        (
+            &red,
+            (0, 4),
+            DrawConfig {
+                flip_horizontally: true,
+                rotation: 90.0,
+                ..Default::default()
+            },
+        ),
+        (
+            &green,
+            (4, 5),
+            DrawConfig {
+                blend_mode: BlendMode::Additive,
+                color_modulation: color::GREYSCALE,
+                ..Default::default()
+            },
+        ),
+        (
+            &red,
+            (2, 2),
+            DrawConfig {
+                invert_color: true,
+                ..Default::default()
+            },
+        ),
+    ];
+
+    let mut batch = SpriteBatch::new(&atlas);
+    let mut expected = Texture::new(ctx, (8, 8))?;
+    ctx.clear_color(&mut expected, (0.0, 0.0, 0.5, 1.0));
+    for (texture, position, config) in sprites.iter() {
+        batch.push(texture, *position, config);
+        ctx.draw(&mut expected, texture, *position, config);
+        // prevents the draw calls from being combined
+        ctx.flush();
+    }
+    assert_eq!(batch.len(), sprites.len());
+
+    let mut target = Texture::new(ctx, (8, 8))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.5, 1.0));
+    ctx.draw_batch(&mut target, &batch);
+
+    let image = ctx.image_data(&target);
+    assert_eq!(image, ctx.image_data(&expected));
+
+    // draw modifiers forward batches with the same result as individual draw calls
+    let mut batched = Texture::new(ctx, (20, 20))?;
+    let mut drawn = Texture::new(ctx, (20, 20))?;
+    ctx.clear_color(&mut batched, (0.0, 0.0, 0.5, 1.0));
+    ctx.clear_color(&mut drawn, (0.0, 0.0, 0.5, 1.0));
+    ctx.draw_batch(
+        &mut Offset::new(
+            YDown::new(Camera::new(&mut batched, (-1, 0), 2), 10),
+            (1, 0),
+        ),
+        &batch,
+    );
+    let mut target = Offset::new(YDown::new(Camera::new(&mut drawn, (-1, 0), 2), 10), (1, 0));
+    for (texture, position, config) in sprites.iter() {
+        ctx.draw(&mut target, texture, *position, config);
+    }
+    assert_eq!(ctx.image_data(&batched), ctx.image_data(&drawn));
+    Ok(image)
+}
+
+fn atlas_extrusion(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut red = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]));
+    red.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+    let green = RgbaImage::from_pixel(2, 2, Rgba([0, 255, 0, 255]));
+
+    let mut atlas = Atlas::new(AtlasConfig {
+        padding: 0,
+        extrusion: 1,
+    });
+    let red_index = atlas.push(red.clone());
+    let green_index = atlas.push(green.clone());
+    let textures = atlas.build(ctx)?;
+    assert_eq!(ctx.image_data(&textures[red_index]), red);
+    assert_eq!(ctx.image_data(&textures[green_index]), green);
+
+    let mut target = Texture::new(ctx, (4, 2))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 1.0, 1.0));
+    // the leftmost column samples the right edge of the section,
+    // which is an extruded copy instead of the neighboring entry
+    ctx.draw(
+        &mut target,
+        &textures[red_index],
+        (0, 0),
+        &DrawConfig {
+            flip_horizontally: true,
+            transform: Some([[1.0, 0.0, 0.5], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+            ..Default::default()
+        },
+    );
+
+    Ok(ctx.image_data(&target))
+}
+
+fn queued_draws(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut atlas = Texture::new(ctx, (2, 1))?;
+    ctx.clear_color(&mut atlas, (1.0, 0.0, 0.0, 1.0));
+    let mut target = Texture::new(ctx, (6, 6))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 1.0, 1.0));
+
+    for y in 0..3 {
+        ctx.draw(&mut target, &atlas, (0, y), &DrawConfig::default());
+    }
+
+    // modifying the source after queuing draw calls must not change their result
+    ctx.clear_region(&mut atlas, IRect::new((1, 0), (1, 1)), (0.0, 1.0, 0.0, 1.0));
+    for y in 3..6 {
+        ctx.draw(&mut target, &atlas, (2, y), &DrawConfig::default());
+    }
+
+    // draw calls to dropped textures are discarded
+    let mut dropped = Texture::new(ctx, (2, 2))?;
+    ctx.draw(&mut dropped, &atlas, (0, 0), &DrawConfig::default());
+    drop(dropped);
+    // the framebuffer of a new texture may reuse the name of the dropped one
+    let mut reused = Texture::new(ctx, (2, 2))?;
+    ctx.clear_color(&mut reused, (0.0, 0.0, 0.0, 1.0));
+    assert!(ctx
+        .image_data(&reused)
+        .pixels()
+        .all(|&pixel| pixel == Rgba([0, 0, 0, 255])));
+
+    ctx.draw(
+        &mut target,
+        &atlas.get_section(Rect::new((1, 0), (1, 1))),
+        (5, 0),
+        &DrawConfig::default(),
+    );
+    Ok(ctx.image_data(&target))
+}
+
+fn sampler(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut source = Texture::new(ctx, (2, 1))?;
+    ctx.clear_color(&mut source, (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_region(
+        &mut source,
+        IRect::new((1, 0), (1, 1)),
+        (0.0, 1.0, 0.0, 1.0),
+    );
+    let mut target = Texture::new(ctx, (8, 3))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 1.0, 1.0));
+
+    let linear = DrawConfig {
+        scale: (4, 1),
+        sampler: Some(Sampler::linear()),
+        ..Default::default()
+    };
+    let nearest = DrawConfig {
+        scale: (4, 1),
+        ..Default::default()
+    };
+    ctx.draw(&mut target, &source, (0, 0), &linear);
+    ctx.draw(&mut target, &source, (0, 1), &nearest);
+    ctx.flush();
+    ctx.draw(&mut target, &source, (0, 2), &linear);
+
+    let image = ctx.image_data(&target);
+    assert!((0..8).all(|x| image.get_pixel(x, 0) == image.get_pixel(x, 2)));
+    Ok(image)
+}
+
+fn bitmap_font(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let font = BitmapFont::load(ctx, "textures/font.fnt")?;
+    assert_eq!(font.kerning('A', 'b'), -1);
+    let page = Texture::new(ctx, (4, 4))?;
+    let huge = "common lineHeight=4 base=3\n\
+        char id=65 x=2147483647 y=0 width=2147483647 height=1 xoffset=0 yoffset=0 xadvance=1 page=0";
+    assert!(matches!(
+        BitmapFont::new(huge, vec![page]),
+        Err(crow::LoadFontError::InvalidFont { line: 2, .. })
+    ));
+    assert_eq!((font.advance('A'), font.advance('c')), (Some(3), None));
+    assert_eq!(font.measure("Ab.\n b", &TextConfig::default()), (7, 8));
+    let config = TextConfig {
+        draw_config: DrawConfig {
+            scale: (2, 1),
+            ..Default::default()
+        },
+        letter_spacing: 1,
+        line_spacing: -1,
+    };
+    assert_eq!(font.measure("Ab.\n b", &config), (18, 7));
+
+    let mut target = Texture::new(ctx, (10, 8))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 1.0, 1.0));
+    ctx.draw_text(
+        &mut target,
+        &font,
+        "Ab.\n b",
+        (1, 4),
+        &TextConfig::default(),
+    );
+
+    Ok(ctx.image_data(&target))
+}
+
+/// Checks the input and focus handling of a headless context, which has a dpi factor of `1`.
+fn window_events(ctx: &mut Context) -> Result<(), crow::Error> {
+    // SAFETY: the ids are only used to create events
+    let (window_id, device_id) = unsafe { (WindowId::dummy(), DeviceId::dummy()) };
+    #[allow(deprecated)]
+    let moved = |x, y| -> Event<'static, ()> {
+        Event::WindowEvent {
+            window_id,
+            event: WindowEvent::CursorMoved {
+                device_id,
+                position: PhysicalPosition::new(x, y),
+                modifiers: ModifiersState::empty(),
+            },
+        }
+    };
+    let motion = |x, y| -> Event<'static, ()> {
+        Event::DeviceEvent {
+            device_id,
+            event: DeviceEvent::MouseMotion { delta: (x, y) },
+        }
+    };
+
+    let height = ctx.window_height() as i32;
+    ctx.handle_event(&moved(2.5, 4.0));
+    assert_eq!(ctx.cursor_position(), Some((2, height - 5)));
+    // raw motion is ignored unless the cursor is grabbed
+    ctx.handle_event(&motion(100.0, 100.0));
+    ctx.handle_event(&moved(5.0, 3.0));
+    assert_eq!(ctx.mouse_delta(), (2.5, 1.0));
+
+    ctx.set_cursor_grab(CursorGrab::Relative).unwrap();
+    assert_eq!(ctx.cursor_grab(), CursorGrab::Relative);
+    ctx.handle_event(&moved(7.0, 3.0));
+    ctx.handle_event(&motion(3.0, -2.0));
+    assert_eq!(ctx.mouse_delta(), (5.5, 3.0));
+    ctx.set_cursor_grab(CursorGrab::None).unwrap();
+
+    // the delta is reset by each presented frame, even without using `Context::frame`
+    let surface = ctx.surface();
+    ctx.present(surface)?;
+    assert_eq!(ctx.mouse_delta(), (0.0, 0.0));
+    ctx.handle_event(&moved(6.0, 3.0));
+    assert_eq!(ctx.mouse_delta(), (-1.0, 0.0));
+    ctx.frame(|_, _| ())?;
+    assert_eq!(ctx.mouse_delta(), (0.0, 0.0));
+
+    // the window is assumed to be focused until told otherwise
+    assert!(!ctx.is_suspended());
+    let focused = |focused| -> Event<'static, ()> {
+        Event::WindowEvent {
+            window_id,
+            event: WindowEvent::Focused(focused),
+        }
+    };
+    ctx.handle_event(&focused(false));
+    assert!(ctx.is_suspended());
+    ctx.request_attention(crow::Attention::Critical);
+    ctx.handle_event(&focused(true));
+    assert!(!ctx.is_suspended());
+    Ok(())
+}
+
+fn input(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    // requests which require a window are ignored by headless contexts
+    if ctx.try_window().is_none() {
+        ctx.set_ime_position((1, 1));
+        ctx.request_attention(crow::Attention::Critical);
+        window_events(ctx)?;
+    }
+
+    let mut target = Texture::new(ctx, (1, 1))?;
+    ctx.clear_color(&mut target, (1.0, 0.0, 0.0, 1.0));
+    Ok(ctx.image_data(&target))
+}
+
+fn camera(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut red = Texture::new(ctx, (1, 1))?;
+    ctx.clear_color(&mut red, (1.0, 0.0, 0.0, 1.0));
+
+    let mut image = Texture::new(ctx, (6, 6))?;
+    let mut camera = Camera::new(&mut image, (-1, -1), 2);
+    ctx.clear_color(&mut camera, (0.0, 1.0, 0.0, 1.0));
+    ctx.draw(&mut camera, &red, (0, 0), &DrawConfig::default());
+    ctx.clear_region(
+        &mut camera,
+        IRect::new((1, 1), (1, 1)),
+        (0.0, 0.0, 1.0, 1.0),
+    );
+
+    assert_eq!(camera.world_to_screen((1, 1)), (4, 4));
+    assert_eq!(camera.screen_to_world((5, 4)), (1, 1));
+    assert_eq!(camera.screen_to_world((-1, 0)), (-2, -1));
+
+    // window conversions account for draw modifiers and the dpi factor
+    let mut surface = ctx.surface();
+    let camera = Camera::new(Scaled::new(&mut surface, (2, 2)), (-1, -1), 3);
+    let window = camera.world_to_window(ctx, (1, 1));
+    let height = f64::from(ctx.window_height());
+    assert_eq!(window.x * (height - 12.0), window.y * 12.0);
+    let below = PhysicalPosition::new(window.x, window.y - 1.0);
+    assert_eq!(camera.window_to_world(ctx, below), (1, 1));
+    ctx.set_cursor_position(Some((12, 12)));
+    assert_eq!(camera.cursor_world_position(ctx), Some((1, 1)));
+    ctx.set_cursor_position(Some((11, 12)));
+    assert_eq!(camera.cursor_world_position(ctx), Some((0, 1)));
+    ctx.set_cursor_position(None);
+    ctx.present(surface)?;
+
+    Ok(ctx.image_data(&image))
+}
+
+fn clipped(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut red = Texture::new(ctx, (6, 1))?;
+    ctx.clear_color(&mut red, (1.0, 0.0, 0.0, 1.0));
+
+    let mut image = Texture::new(ctx, (6, 6))?;
+    ctx.clear_color(&mut image, (0.0, 0.0, 0.0, 1.0));
+    let mut target = Clipped::new(&mut image, ((1, 1), (3, 3)));
+    ctx.clear_color(&mut target, (0.0, 1.0, 0.0, 1.0));
+    ctx.draw(&mut target, &red, (0, 2), &DrawConfig::default());
+    ctx.debug_line(&mut target, (0, 3), (5, 3), (0.0, 0.0, 1.0, 1.0));
+    let mut nested = Clipped::new(&mut target, ((3, 0), (3, 6)));
+    ctx.clear_color(&mut nested, (1.0, 1.0, 0.0, 1.0));
+
+    // the clip only applies while drawing to `target`
+    ctx.debug_line(&mut image, (0, 5), (5, 5), (1.0, 1.0, 1.0, 1.0));
+    Ok(ctx.image_data(&image))
+}
+
+fn pixel_perfect(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut game = PixelPerfect::new(ctx, (2, 1))?;
+    game.set_border_color((0.0, 0.0, 1.0, 1.0));
+    ctx.clear_color(&mut game, (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_region(&mut game, ((1, 0), (1, 1)), (0.0, 1.0, 0.0, 1.0));
+
+    let dimensions = (7, 6);
+    let mut target = Texture::new(ctx, dimensions)?;
+    game.draw(ctx, &mut target, dimensions);
+
+    assert_eq!(game.scale(ctx, dimensions), 3);
+    assert_eq!(game.offset(ctx, dimensions), (0, 1));
+    assert_eq!(game.screen_to_game(ctx, (3, 1), dimensions), Some((1, 0)));
+    assert_eq!(game.screen_to_game(ctx, (2, 3), dimensions), Some((0, 0)));
+    assert_eq!(game.screen_to_game(ctx, (0, 0), dimensions), None);
+    assert_eq!(game.screen_to_game(ctx, (6, 1), dimensions), None);
+
+    Ok(ctx.image_data(&target))
+}
+
+fn render_profile(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let profile = RenderProfile {
+        frame_limit: Some(1000),
+        scaling: ScalingMode::Fixed(2),
+        post_effects: false,
+        color_grading: Some(color::PROTANOPIA),
+        ..Default::default()
+    };
+    ctx.apply_profile(&profile)?;
+    assert_eq!(ctx.render_profile(), profile);
+    assert_eq!(ctx.color_filter(), Some(color::PROTANOPIA));
+
+    // headless contexts do not support vsync, which does not prevent applying the other settings
+    if ctx.try_window().is_none() {
+        let res = ctx.apply_profile(&RenderProfile {
+            vsync: true,
+            frame_limit: Some(500),
+            ..profile
+        });
+        assert_eq!(res, Err(VsyncError::Unsupported));
+        assert!(!ctx.render_profile().vsync);
+        assert_eq!(ctx.render_profile().frame_limit, Some(500));
+        ctx.apply_profile(&profile)?;
+    }
+
+    let mut game = PixelPerfect::new(ctx, (2, 1))?;
+    ctx.clear_color(&mut game, (1.0, 0.0, 0.0, 1.0));
+    let dimensions = (7, 6);
+    let mut target = Texture::new(ctx, dimensions)?;
+    game.draw(ctx, &mut target, dimensions);
+    assert_eq!(game.offset(ctx, dimensions), (1, 2));
+
+    // the filter is applied to the finished frame, so negative
+    // values are not clamped for each draw call
+    ctx.set_color_filter(Some([
+        [1.0, -1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]));
+    let mut red = Texture::new(ctx, (1, 1))?;
+    ctx.clear_color(&mut red, (1.0, 0.0, 0.0, 1.0));
+    let mut surface = ctx.surface();
+    ctx.clear_color(&mut surface, (0.0, 1.0, 0.0, 1.0));
+    let config = DrawConfig {
+        blend_mode: BlendMode::Additive,
+        ..DrawConfig::default()
+    };
+    ctx.draw(&mut surface, &red, (0, 0), &config);
+    let window = ctx.image_data(&surface);
+    assert_eq!(
+        window.get_pixel(0, window.height() - 1).0,
+        [255, 255, 0, 255]
+    );
+    ctx.present(surface)?;
+    // the content of the window is only preserved by headless contexts
+    if ctx.try_window().is_none() {
+        let surface = ctx.surface();
+        let window = ctx.image_data(&surface);
+        assert_eq!(window.get_pixel(0, window.height() - 1).0, [0, 255, 0, 255]);
+        ctx.present(surface)?;
+    }
+
+    ctx.set_color_filter(None);
+    assert_eq!(ctx.render_profile().color_grading, None);
+    Ok(ctx.image_data(&target))
+}
+
+fn y_down(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut bar = Texture::new(ctx, (2, 1))?;
+    ctx.clear_color(&mut bar, (1.0, 1.0, 1.0, 1.0));
+    let mut corner = Texture::new(ctx, (2, 2))?;
+    ctx.clear_color(&mut corner, (0.0, 1.0, 0.0, 1.0));
+    ctx.clear_region(
+        &mut corner,
+        IRect::new((0, 1), (1, 1)),
+        (1.0, 0.0, 0.0, 1.0),
+    );
+
+    let mut image = Texture::new(ctx, (6, 6))?;
+    ctx.clear_color(&mut image, (0.0, 0.0, 1.0, 1.0));
+    let mut target = YDown::new(&mut image, 6);
+    ctx.draw(&mut target, &bar, (0, 0), &DrawConfig::default());
+    ctx.clear_region(
+        &mut target,
+        IRect::new((4, 0), (2, 2)),
+        (1.0, 1.0, 0.0, 1.0),
+    );
+    // rotates clockwise, moving the red pixel from the top left to the top right
+    ctx.draw(
+        &mut target,
+        &corner,
+        (0, 2),
+        &DrawConfig {
+            rotation: 90.0,
+            ..Default::default()
+        },
+    );
+    ctx.debug_rectangle(
+        &mut target,
+        IRect::new((3, 3), (3, 3)),
+        (1.0, 1.0, 1.0, 1.0),
+    );
+    ctx.debug_line(&mut target, (0, 5), (2, 5), (1.0, 0.0, 0.0, 1.0));
+
+    Ok(ctx.image_data(&image))
+}
+
+fn texture_copy(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let copies = Rc::new(RefCell::new(Vec::new()));
+    let hook_copies = Rc::clone(&copies);
+    ctx.on_texture_copy(move |copy| hook_copies.borrow_mut().push((copy.reason, copy.size)));
+
+    let mut atlas = Texture::new(ctx, (4, 2))?;
+    ctx.clear_color(&mut atlas, (1.0, 0.0, 0.0, 1.0));
+    let mut shared = atlas.clone();
+    ctx.clear_color(&mut shared, (0.0, 1.0, 0.0, 1.0));
+    // `shared` is no longer shared with `atlas`
+    ctx.clear_color(&mut shared, (0.0, 1.0, 0.0, 1.0));
+    let mut section = atlas.get_section(Rect::new((2, 0), (2, 2)));
+    ctx.draw(&mut section, &shared, (-1, 0), &DrawConfig::default());
+    assert_eq!(
+        *copies.borrow(),
+        [(CopyReason::Shared, (4, 2)), (CopyReason::Section, (2, 2))]
+    );
+
+    let mut target = Texture::new(ctx, (4, 2))?;
+    ctx.draw(&mut target, &atlas, (0, 0), &DrawConfig::default());
+    ctx.draw(&mut target, &section, (2, 0), &DrawConfig::default());
+    assert_eq!(copies.borrow().len(), 2);
+
+    // preparing the targets performs the copies ahead of time
+    let mut frames = [
+        atlas.get_section(Rect::new((0, 0), (1, 2))),
+        atlas.get_section(Rect::new((1, 0), (1, 2))),
+    ];
+    ctx.prepare_targets(&mut frames)?;
+    assert_eq!(copies.borrow().len(), 4);
+    ctx.clear_color(&mut frames[1], (0.0, 0.0, 0.0, 1.0));
+    target.prepare_target(ctx)?;
+    assert_eq!(copies.borrow().len(), 4);
+    Ok(ctx.image_data(&target))
+}
+
+fn write_section(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut texture = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut texture, (1.0, 0.0, 0.0, 1.0));
+    let original = texture.clone();
+
+    let (green, blue) = (Rgba([0, 255, 0, 255]), Rgba([0, 0, 255, 255]));
+    let stripes = RgbaImage::from_fn(3, 2, |_, y| if y == 0 { green } else { blue });
+    // only the top row is inside of the texture
+    texture.write_section(ctx, (2, -1), &stripes);
+    let column = RgbaImage::from_fn(1, 2, |_, y| {
+        if y == 0 {
+            Rgba([255, 255, 255, 255])
+        } else {
+            blue
+        }
+    });
+    texture.write_section(ctx, (0, 2), &column);
+    texture.write_section(ctx, (4, 0), &column);
+
+    assert!(ctx
+        .image_data(&original)
+        .pixels()
+        .all(|&p| p == Rgba([255, 0, 0, 255])));
+    Ok(ctx.image_data(&texture))
+}
+
+fn draw_list(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut colors = Texture::new(ctx, (3, 2))?;
+    ctx.clear_color(&mut colors, (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_region(
+        &mut colors,
+        IRect::new((2, 0), (1, 2)),
+        (0.0, 1.0, 0.0, 1.0),
+    );
+    let red = colors.get_section(Rect::new((0, 0), (2, 2)));
+    let green = colors.get_section(Rect::new((2, 0), (1, 2)));
+    let mut blue = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut blue, (0.0, 0.0, 1.0, 1.0));
+
+    let mut list = DrawList::new();
+    let config = DrawConfig::default();
+    let wide = DrawConfig {
+        scale: (2, 1),
+        ..Default::default()
+    };
+    list.push(SortKey::y_sorted(1, 0, 1), &green, (1, 1), &wide);
+    // equal keys are drawn in the order they are pushed
+    list.push(SortKey::new(2, 0, 0), &green, (3, 3), &config);
+    list.push(SortKey::y_sorted(1, 0, 0), &red, (0, 0), &config);
+    list.push(SortKey::new(2, 0, 0), &red, (3, 3), &config);
+    list.push(SortKey::new(0, 0, 0), &blue, (0, 0), &config);
+    assert_eq!(list.len(), 5);
+
+    let mut target = Texture::new(ctx, (4, 4))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    list.draw(ctx, &mut target);
+    Ok(ctx.image_data(&target))
+}
+
+fn deep_clone(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let copies = Rc::new(RefCell::new(0));
+    let hook_copies = Rc::clone(&copies);
+    ctx.on_texture_copy(move |_| *hook_copies.borrow_mut() += 1);
+
+    let mut a = Texture::new(ctx, (4, 4))?;
+    a.set_label(ctx, "a");
+    ctx.clear_color(&mut a, (1.0, 0.0, 0.0, 1.0));
+    let snapshot = a.deep_clone(ctx)?;
+    assert_eq!(snapshot.label().as_deref(), Some("a"));
+    ctx.clear_region(&mut a, IRect::new((0, 0), (2, 4)), (0.0, 1.0, 0.0, 1.0));
+    let section = a.get_section(Rect::new((1, 1), (2, 2))).deep_clone(ctx)?;
+    assert_eq!(section.dimensions(), (2, 2));
+    ctx.clear_region(&mut a, IRect::new((0, 0), (4, 1)), (0.0, 0.0, 1.0, 1.0));
+    // neither `a` nor `snapshot` share their content
+    assert_eq!(*copies.borrow(), 0);
+
+    // translucent texels are copied without blending
+    let mut translucent = Texture::new(ctx, (1, 1))?;
+    ctx.clear_color(&mut translucent, (1.0, 0.0, 0.0, 0.5));
+    let expected = ctx.image_data(&translucent);
+    let copy = translucent.deep_clone(ctx)?;
+    assert_eq!(ctx.image_data(&copy), expected);
+
+    let mut b = Texture::new(ctx, (8, 4))?;
+    ctx.clear_color(&mut b, (0.0, 0.0, 0.0, 1.0));
+    ctx.draw(&mut b, &snapshot, (0, 0), &DrawConfig::default());
+    ctx.draw(&mut b, &section, (4, 0), &DrawConfig::default());
+    Ok(ctx.image_data(&b))
+}
+
+fn texture_label(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let labels = Rc::new(RefCell::new(Vec::new()));
+    let hook_labels = Rc::clone(&labels);
+    ctx.on_texture_copy(move |copy| hook_labels.borrow_mut().push(copy.label.clone()));
+
+    let mut atlas = Texture::new(ctx, (4, 2))?;
+    assert_eq!(atlas.label(), None);
+    atlas.set_label(ctx, "atlas");
+    ctx.clear_color(&mut atlas, (1.0, 0.0, 0.0, 1.0));
+    let mut section = atlas.get_section(Rect::new((2, 0), (2, 2)));
+    assert_eq!(section.label().as_deref(), Some("atlas"));
+    // the copied section keeps its label
+    ctx.clear_color(&mut section, (0.0, 0.0, 1.0, 1.0));
+    assert_eq!(*labels.borrow(), [Some(String::from("atlas"))]);
+    section.set_label(ctx, "section");
+    assert_eq!(section.label().as_deref(), Some("section"));
+    assert_eq!(atlas.label().as_deref(), Some("atlas"));
+    // labeling a shared texture copies it
+    let mut clone = atlas.clone();
+    clone.set_label(ctx, "clone");
+    assert_eq!(atlas.label().as_deref(), Some("atlas"));
+    assert_eq!(clone.label().as_deref(), Some("clone"));
+    assert_eq!(labels.borrow().len(), 2);
+    Ok(ctx.image_data(&section))
+}
+
+fn animation(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut image = RgbaImage::new(2, 2);
+    image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+    image.put_pixel(1, 0, Rgba([0, 255, 0, 255]));
+    image.put_pixel(0, 1, Rgba([0, 0, 255, 255]));
+    image.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+    let sheet = Texture::from_image(ctx, image)?;
+
+    let frame = Duration::from_millis(100);
+    let ping_pong = Animation::from_grid(&sheet, (1, 1), frame, LoopMode::PingPong).unwrap();
+    assert_eq!(ping_pong.frames().len(), 4);
+    assert_eq!(ping_pong.cycle_duration(), frame * 6);
+    let mut once = Animation::new(ping_pong.frames().to_vec(), LoopMode::Loop).unwrap();
+    once.set_mode(LoopMode::Once);
+    assert_eq!(once.mode(), LoopMode::Once);
+    assert!(Animation::new(Vec::new(), LoopMode::Loop).is_none());
+    assert!(Animation::from_grid(&sheet, (3, 1), frame, LoopMode::Loop).is_none());
+
+    let mut target = Texture::new(ctx, (5, 1))?;
+    let mut state = AnimationState::new();
+    let mut draw = |ctx: &mut Context, texture: &Texture, x| {
+        ctx.draw(&mut target, texture, (x, 0), &DrawConfig::default())
+    };
+    draw(ctx, state.current(&ping_pong), 0);
+    draw(ctx, state.tick(&ping_pong, Duration::from_millis(250)), 1);
+    // the third frame is shown again while going back
+    draw(ctx, state.tick(&ping_pong, Duration::from_millis(200)), 2);
+    draw(ctx, state.tick(&ping_pong, Duration::from_millis(100)), 3);
+    assert!(!state.is_finished(&ping_pong));
+
+    state.reset();
+    assert_eq!(state.time(), Duration::from_secs(0));
+    assert!(!state.is_finished(&once));
+    state.tick(&once, Duration::from_millis(450));
+    assert!(state.is_finished(&once));
+    draw(ctx, state.current(&once), 4);
+
+    Ok(ctx.image_data(&target))
+}
+
+fn texture_packer(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut sheet = Texture::new(ctx, (4, 3))?;
+    ctx.clear_color(&mut sheet, (0.0, 0.0, 0.0, 0.0));
+    ctx.clear_region(&mut sheet, ((0, 2), (2, 1)), (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_region(&mut sheet, ((2, 1), (1, 2)), (0.0, 1.0, 0.0, 1.0));
+    ctx.clear_region(&mut sheet, ((0, 0), (1, 1)), (0.0, 0.0, 1.0, 1.0));
+    ctx.clear_region(&mut sheet, ((1, 0), (1, 1)), (1.0, 1.0, 1.0, 1.0));
+
+    let frame = |x, y, w, h| FrameRect { x, y, w, h };
+    let mut frames = HashMap::new();
+    frames.insert(
+        "a".to_string(),
+        Frame {
+            frame: frame(0, 0, 2, 1),
+            ..Default::default()
+        },
+    );
+    frames.insert(
+        "b".to_string(),
+        Frame {
+            frame: frame(2, 0, 1, 2),
+            trimmed: true,
+            sprite_source_size: frame(1, 0, 1, 2),
+            source_size: FrameSize { w: 3, h: 3 },
+            ..Default::default()
+        },
+    );
+    frames.insert(
+        "c".to_string(),
+        Frame {
+            frame: frame(0, 2, 1, 2),
+            rotated: true,
+            ..Default::default()
+        },
+    );
+    let description = SpriteSheet {
+        frames: Frames::Hash(frames),
+        meta: Meta::default(),
+    };
+    let sprites = description.build(ctx, &sheet)?;
+
+    let mut invalid = description.clone();
+    if let Frames::Hash(frames) = &mut invalid.frames {
+        frames.get_mut("a").unwrap().frame.x = 3;
+    }
+    assert!(matches!(
+        invalid.build(ctx, &sheet),
+        Err(SpriteSheetError::InvalidSheet { .. })
+    ));
+
+    // `sprites.json` describes the same sheet, which is stored in `sprites.png`
+    #[cfg(feature = "serde1")]
+    let sprites = {
+        let loaded = SpriteSheet::load("textures/sprites.json")?;
+        assert_eq!(loaded.meta.image.as_deref(), Some("sprites.png"));
+        match (&loaded.frames, &description.frames) {
+            (Frames::Hash(loaded), Frames::Hash(frames)) => assert_eq!(loaded["b"], frames["b"]),
+            _ => panic!("expected the `JSON (Hash)` format"),
+        }
+        drop(sprites);
+        SpriteSheet::load_sprites(ctx, "textures/sprites.json")?
+    };
+    assert_eq!(sprites["b"].dimensions(), (3, 3));
+    assert_eq!(sprites["c"].dimensions(), (1, 2));
+
+    let mut target = Texture::new(ctx, (8, 3))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    ctx.draw(&mut target, &sprites["a"], (0, 0), &DrawConfig::default());
+    ctx.draw(&mut target, &sprites["b"], (2, 0), &DrawConfig::default());
+    ctx.draw(&mut target, &sprites["c"], (6, 0), &DrawConfig::default());
+    Ok(ctx.image_data(&target))
+}
+
+fn frame(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut target = Texture::new(ctx, (1, 1))?;
+    let nested = ctx.frame(|ctx, _| {
+        ctx.clear_color(&mut target, (1.0, 0.0, 0.0, 1.0));
+        ctx.frame(|_, _| ())
+    })?;
+    assert!(matches!(nested, Err(FinalizeError::SurfaceInUse)));
+
+    let surface = ctx.surface();
+    assert!(matches!(
+        ctx.frame(|_, _| unreachable!()),
+        Err(FinalizeError::SurfaceInUse)
+    ));
+    ctx.present(surface)?;
+
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let pre = Rc::clone(&calls);
+    ctx.on_pre_frame(move |_, _| pre.borrow_mut().push("pre"));
+    let post = Rc::clone(&calls);
+    ctx.on_post_frame(move |_, _| post.borrow_mut().push("post"));
+    ctx.frame(|_, _| calls.borrow_mut().push("frame"))?;
+    assert_eq!(*calls.borrow(), ["pre", "frame", "post"]);
+
+    // offline rendering does not call the hooks, but updates the shader globals and
+    // sets `u_time` to the time of each frame, resetting the counters after each frame
+    let updates = Rc::new(RefCell::new(0));
+    let update = Rc::clone(&updates);
+    ctx.on_update_shader_globals(move |_| *update.borrow_mut() += 1);
+    let shader_time = ctx.shader_time();
+    let mut frames = Vec::new();
+    let directory = env::temp_dir().join("crow_frame_hooks");
+    ctx.render_frames(&directory, (1, 1), 3, 2, |ctx, frame_target, _, _| {
+        calls.borrow_mut().push("offline");
+        frames.push((ctx.shader_time(), ctx.counters().total()));
+        ctx.draw(frame_target, &target, (0, 0), &DrawConfig::default());
+    })?;
+    let _ = fs::remove_dir_all(&directory);
+    assert_eq!(
+        *calls.borrow(),
+        ["pre", "frame", "post", "offline", "offline", "offline"]
+    );
+    assert_eq!(*updates.borrow(), 3);
+    assert_eq!(
+        frames,
+        [
+            (0.0, Counts::default()),
+            (0.5, Counts::default()),
+            (1.0, Counts::default())
+        ]
+    );
+    assert_eq!(ctx.shader_time(), shader_time);
+    Ok(ctx.image_data(&target))
+}
+
+fn counters(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    ctx.reset_counters();
+    let mut atlas = Atlas::new(AtlasConfig {
+        padding: 0,
+        extrusion: 0,
+    });
+    let red = atlas.push(RgbaImage::from_pixel(1, 1, Rgba([255, 0, 0, 255])));
+    let green = atlas.push(RgbaImage::from_pixel(1, 1, Rgba([0, 255, 0, 255])));
+    let textures = atlas.build(ctx)?;
+    let blue = Texture::from_image(ctx, RgbaImage::from_pixel(1, 1, Rgba([0, 0, 255, 255])))?;
+
+    let mut target = Texture::new(ctx, (4, 1))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    let mut batch = SpriteBatch::new(&textures[0]);
+    batch.push(&textures[red], (0, 0), &DrawConfig::default());
+    batch.push(&textures[green], (1, 0), &DrawConfig::default());
+    ctx.draw_batch(&mut target, &batch);
+    ctx.draw(&mut target, &blue, (2, 0), &DrawConfig::default());
+    let image = ctx.image_data(&target);
+
+    let counters = ctx.counters();
+    let counts = |uploads, draws, readbacks| Counts {
+        uploads,
+        draws,
+        readbacks,
+    };
+    assert_eq!(counters.get(Subsystem::Atlas), counts(1, 0, 0));
+    assert_eq!(counters.get(Subsystem::Batch), counts(0, 2, 0));
+    assert_eq!(counters.get(Subsystem::Other), counts(1, 1, 1));
+    assert_eq!(counters.get(Subsystem::Text), counts(0, 0, 0));
+    assert_eq!(counters.total(), counts(2, 3, 1));
+    ctx.reset_counters();
+    assert_eq!(ctx.counters().total(), counts(0, 0, 0));
+
+    // the counters are reset after each frame
+    ctx.frame(|ctx, surface| ctx.draw(surface, &blue, (0, 0), &DrawConfig::default()))?;
+    assert_eq!(ctx.counters().total(), counts(0, 0, 0));
+    assert_eq!(ctx.last_frame_counters().total(), counts(0, 1, 0));
+    Ok(image)
+}
+
+fn aseprite_animation(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut sheet = Texture::new(ctx, (3, 1))?;
+    ctx.clear_region(&mut sheet, ((0, 0), (1, 1)), (1.0, 0.0, 0.0, 1.0));
+    ctx.clear_region(&mut sheet, ((1, 0), (1, 1)), (0.0, 1.0, 0.0, 1.0));
+    ctx.clear_region(&mut sheet, ((2, 0), (1, 1)), (0.0, 0.0, 1.0, 1.0));
+
+    let frame = |x, duration| Frame {
+        frame: FrameRect {
+            x,
+            y: 0,
+            w: 1,
+            h: 1,
+        },
+        duration,
+        ..Default::default()
+    };
+    let mut frames = HashMap::new();
+    frames.insert("walk 8.png".to_string(), frame(0, Some(100)));
+    frames.insert("walk 9.png".to_string(), frame(1, Some(50)));
+    frames.insert("walk 10.png".to_string(), frame(2, None));
+    let animation = SpriteSheet {
+        frames: Frames::Hash(frames),
+        meta: Meta {
+            frame_tags: vec![FrameTag {
+                name: "bounce".to_string(),
+                from: 0,
+                to: 2,
+                direction: Direction::PingPong,
+            }],
+            ..Default::default()
+        },
+    }
+    .build_animation(ctx, &sheet)?;
+    assert_eq!(animation.frames.len(), 3);
+    assert_eq!(animation.frames[2].duration, Duration::from_millis(100));
+    assert!(animation.tag("idle").is_none());
+    assert!(animation.animation(Some("idle")).is_none());
+    let bounce = animation.animation(Some("bounce")).unwrap();
+    assert_eq!(bounce.cycle_duration(), Duration::from_millis(300));
+
+    let mut target = Texture::new(ctx, (6, 1))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    for (x, &time) in [0, 120, 200, 260, 310].iter().enumerate() {
+        let frame = bounce.frame_at(Duration::from_millis(time));
+        ctx.draw(
+            &mut target,
+            &frame.texture,
+            (x as i32, 0),
+            &DrawConfig::default(),
+        );
+    }
+    let frame = animation
+        .animation(None)
+        .unwrap()
+        .frame_at(Duration::from_millis(130))
+        .texture
+        .clone();
+    ctx.draw(&mut target, &frame, (5, 0), &DrawConfig::default());
+    Ok(ctx.image_data(&target))
+}
+
+fn primitive_palette(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let mut image = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 255]));
+    image.put_pixel(1, 0, Rgba([255, 0, 0, 255]));
+    image.put_pixel(1, 1, Rgba([0, 0, 255, 255]));
+    let palette = color::Palette::from_image(&image);
+    assert_eq!(
+        palette.colors(),
+        &[
+            (0.0, 0.0, 0.0, 1.0),
+            (1.0, 0.0, 0.0, 1.0),
+            (0.0, 0.0, 1.0, 1.0)
+        ]
+    );
+
+    let mut target = Texture::new(ctx, (4, 2))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    ctx.set_primitive_palette(Some(palette));
+    ctx.debug_line(&mut target, (0, 0), (3, 0), (0.8, 0.1, 0.2, 1.0));
+    ctx.debug_rectangle(&mut target, ((0, 1), (2, 1)), (0.1, 0.2, 0.7, 1.0));
+    ctx.set_primitive_palette(None);
+    ctx.debug_rectangle(&mut target, ((2, 1), (2, 1)), (0.0, 1.0, 0.0, 1.0));
+    Ok(ctx.image_data(&target))
+}
+
+fn particles(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let pixel = Texture::from_image(ctx, RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 255])))?;
+    let mut emitter = Emitter::new(
+        &pixel,
+        EmitterConfig {
+            spawn_rate: 2.0,
+            lifetime: (Duration::from_secs(2), Duration::from_secs(2)),
+            velocity: ((4.0, 2.0), (4.0, 2.0)),
+            end_color: (1.0, 0.0, 0.0, 1.0),
+            ..Default::default()
+        },
+    );
+    emitter.position = (0.5, 0.5);
+    emitter.burst(1);
+    emitter.update(Duration::from_millis(500));
+    assert_eq!(emitter.particles().len(), 2);
+    assert_eq!(emitter.particles()[0].progress(), 0.25);
+
+    let mut target = Texture::new(ctx, (4, 2))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    ctx.reset_counters();
+    emitter.draw(ctx, &mut target);
+    assert_eq!(ctx.counters().get(Subsystem::Particles).draws, 2);
+    assert_eq!(ctx.counters().get(Subsystem::Batch).draws, 0);
+
+    emitter.emitting = false;
+    emitter.update(Duration::from_secs(2));
+    assert!(emitter.particles().is_empty());
+
+    // spawning stops at `max_particles` and non-finite rates are ignored
+    emitter.emitting = true;
+    emitter.config.max_particles = 3;
+    emitter.config.spawn_rate = f32::INFINITY;
+    emitter.update(Duration::from_millis(1));
+    assert!(emitter.particles().is_empty());
+    emitter.config.spawn_rate = f32::MAX;
+    emitter.update(Duration::from_secs(1));
+    assert_eq!(emitter.particles().len(), 3);
+    Ok(ctx.image_data(&target))
+}
+
+#[cfg(feature = "ldtk")]
+fn ldtk_level(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let project = Project::load("textures/level.ldtk")?;
+    let tileset = project.tileset(1).unwrap();
+    assert_eq!(tileset.rel_path.as_deref(), Some("tiles.png"));
+    let tiles = Texture::load(ctx, "textures/tiles.png")?;
+
+    // the layers are stored in `level_0.ldtkl`
+    let level = project.level("Level_0").unwrap();
+    assert_eq!(level.layers().len(), 3);
+    let player = &level.layers()[0].entity_instances[0];
+    assert_eq!(player.identifier, "Player");
+    assert_eq!(level.flip_y(player.position[1]), 1);
+
+    let mut ground = level.layers()[2].clone();
+    ground.grid_tiles[0].source = [6, 0];
+    assert!(level.build_layer(&ground, &tiles).is_err());
+
+    ground.grid_tiles.truncate(1);
+    ground.grid_tiles[0].source = [4, 0];
+    ground.opacity = 0.5;
+    let mut transparent = Texture::new(ctx, (8, 6))?;
+    ctx.clear_color(&mut transparent, (0.0, 0.0, 0.0, 1.0));
+    ctx.draw_batch(&mut transparent, &level.build_layer(&ground, &tiles)?);
+    let transparent = ctx.image_data(&transparent);
+    assert_eq!(transparent.get_pixel(1, 3).0[..3], [0, 128, 0]);
+
+    let mut target = Texture::new(ctx, (8, 6))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    ctx.reset_counters();
+    for layer in level.layers().iter().rev() {
+        level.draw_layer(ctx, &mut target, layer, &tiles)?;
+    }
+    assert_eq!(
+        ctx.counters().get(Subsystem::Tilemap).draws,
+        ctx.counters().total().draws
+    );
+    Ok(ctx.image_data(&target))
+}
+
+#[cfg(feature = "aseprite")]
+fn aseprite_file(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let sprite = Sprite::load("textures/walk.aseprite")?;
+    assert_eq!((sprite.width, sprite.height), (2, 2));
+    let durations: Vec<_> = sprite.frames.iter().map(|f| f.duration).collect();
+    assert_eq!(
+        durations,
+        [100, 50, 200]
+            .iter()
+            .map(|&ms| Duration::from_millis(ms))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(sprite.tags[1].name, "loop");
+    assert_eq!(
+        (
+            sprite.tags[1].from,
+            sprite.tags[1].to,
+            sprite.tags[1].direction
+        ),
+        (0, 2, Direction::PingPong)
+    );
+
+    // hidden layers and layers inside of hidden groups are ignored
+    let first = &sprite.frames[0].image;
+    assert_eq!(first.get_pixel(0, 0).0, [255, 0, 0, 255]);
+    let blended = first.get_pixel(1, 0).0;
+    assert!((127..=128).contains(&blended[0]) && (127..=128).contains(&blended[2]));
+    assert_eq!([blended[1], blended[3]], [0, 255]);
+    // the cel of the second frame only covers its bottom row
+    assert_eq!(sprite.frames[1].image.get_pixel(0, 0).0, [0; 4]);
+    // the third frame links to the cel of the first frame on the base layer
+    assert!(sprite.frames[2]
+        .image
+        .pixels()
+        .all(|pixel| pixel.0 == [255, 0, 0, 255]));
+
+    assert!(Sprite::parse(&[0; 16]).is_err());
+
+    let animation = sprite.build_animation(ctx)?;
+    let start = animation.animation(Some("start")).unwrap();
+    assert_eq!(start.frames().len(), 2);
+    let mut target = Texture::new(ctx, (6, 2))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    for (i, frame) in animation.frames.iter().enumerate() {
+        ctx.draw(
+            &mut target,
+            &frame.texture,
+            (i as i32 * 2, 0),
+            &DrawConfig::default(),
+        );
+    }
+    Ok(ctx.image_data(&target))
+}
+
+#[cfg(feature = "tiled")]
+fn tiled_map(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let map = Map::load("textures/map.tmx")?;
+    assert_eq!((map.width, map.height, map.tile_width), (3, 2, 4));
+    assert_eq!(map.properties["music"], "forest.ogg");
+    assert_eq!(
+        map.tilesets[0].image,
+        std::path::Path::new("textures/tiles.png")
+    );
+    assert_eq!(map.layers.len(), 3);
+    let objects = match &map.layers[2] {
+        tiled::Layer::Objects(layer) => &layer.objects,
+        tiled::Layer::Tiles(_) => panic!("expected an object layer"),
+    };
+    assert_eq!(
+        (objects[0].name.as_str(), objects[0].kind.as_str()),
+        ("spawn", "player")
+    );
+    assert_eq!(
+        (objects[0].shape.clone(), map.flip_y(objects[0].y)),
+        (Shape::Point, 0.0)
+    );
+    assert_eq!(objects[0].properties["health"], "3");
+    assert_eq!(
+        objects[1].shape,
+        Shape::Polygon(vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)])
+    );
+
+    ctx.reset_counters();
+    let textures = map.load_textures(ctx)?;
+    assert_eq!(ctx.counters().get(Subsystem::Tilemap).uploads, 1);
+    let mut target = Texture::new(ctx, (12, 8))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 0.0, 1.0));
+    for layer in map.layers.iter() {
+        if let tiled::Layer::Tiles(layer) = layer {
+            map.draw_layer(ctx, &mut target, layer, &textures)?;
+        }
+    }
+    let counters = ctx.counters();
+    assert!(counters.get(Subsystem::Tilemap).draws > 0);
+    assert_eq!(counters.get(Subsystem::Batch).draws, 0);
+
+    // invalid tilesets and tiles are errors instead of panics
+    let source = |columns: u32, gid: u32| {
+        format!(
+            r#"<map orientation="orthogonal" width="1" height="1" tilewidth="4" tileheight="4">
+                <tileset firstgid="1" name="tiles" tilewidth="4" tileheight="4" tilecount="8" columns="{}">
+                    <image source="tiles.png" width="8" height="8"/>
+                </tileset>
+                <layer name="ground" width="1" height="1"><data encoding="csv">{}</data></layer>
+            </map>"#,
+            columns, gid
+        )
+    };
+    assert!(Map::parse(&source(0, 1), "textures").is_err());
+    let outside = Map::parse(&source(2, 7), "textures")?;
+    if let tiled::Layer::Tiles(layer) = &outside.layers[0] {
+        assert!(outside.build_layer(layer, &textures).is_err());
+    }
+    let huge = r#"<map width="1" height="1" tilewidth="4" tileheight="4">
+        <layer width="65536" height="65536"><data encoding="csv">0</data></layer>
+    </map>"#;
+    assert!(Map::parse(huge, "textures").is_err());
+
+    Ok(ctx.image_data(&target))
+}
+
+#[cfg(feature = "ttf")]
+fn ttf_font(ctx: &mut Context) -> Result<RgbaImage, crow::Error> {
+    let font = Font::load("textures/font.ttf")?;
+    let font = font.rasterize(
+        ctx,
+        "AB C".chars(),
+        &RasterConfig {
+            size: 10,
+            ..RasterConfig::default()
+        },
+    )?;
+    assert_eq!((font.line_height(), font.base()), (10, 8));
+    assert_eq!(font.kerning('A', 'B'), -1);
+    assert!(matches!(
+        Font::from_bytes(vec![0; 4]),
+        Err(crow::LoadFontError::TrueTypeError)
+    ));
+    assert!(font.glyph(' ').is_some() && font.glyph('C').is_none());
+
+    let mut target = Texture::new(ctx, (12, 20))?;
+    ctx.clear_color(&mut target, (0.0, 0.0, 1.0, 1.0));
+    ctx.draw_text(
+        &mut target,
+        &font,
+        "AB\n A",
+        (1, 12),
+        &TextConfig::default(),
+    );
+
+    Ok(ctx.image_data(&target))
+}
+
+#[derive(Default)]
+struct TestRunner(Vec<(&'static str, TestFn)>);
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+fn run_headless(golden: &GoldenTest, name: &str, f: TestFn) -> Result<(), GoldenError> {
+    golden.run_headless(name, f)
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+)))]
+fn run_headless(_: &GoldenTest, _: &str, _: TestFn) -> Result<(), GoldenError> {
+    panic!("`CROW_HEADLESS` is only supported on linux and the BSDs")
+}
+
+impl TestRunner {
+    fn add(&mut self, name: &'static str, f: TestFn) {
+        self.0.push((name, f))
+    }
+
+    fn run(mut self) -> i32 {
+        // randomize test order
+        println!("\nrunning {} tests", self.0.len());
+
+        self.0.shuffle(&mut rand::thread_rng());
+        // `CROW_HEADLESS` runs the tests without a display server, for example on CI
+        let event_loop = if env::var_os("CROW_HEADLESS").is_some() {
+            None
+        } else {
+            Some(EventLoop::new())
+        };
+
+        let golden = GoldenTest::default();
+        let mut success = 0;
+        let mut failed = 0;
+
+        for (name, f) in self.0 {
+            // each test uses a new context, so tests can not influence each other
+            let res = if let Some(event_loop) = &event_loop {
+                golden.run(event_loop, name, f)
+            } else {
+                run_headless(&golden, name, f)
+            };
+
+            match res {
                 Ok(()) => success += 1,
-                Err(()) => failed += 1,
+                Err(e) => {
+                    eprintln!("TEST FAILED ({}): {}", e, name);
+                    failed += 1
+                }
             }
         }
 
@@ -288,7 +2577,20 @@ fn main() {
     let mut runner = TestRunner::default();
     runner.add("simple", simple);
     runner.add("from_image", from_image);
+    runner.add("from_raw", from_raw);
+    runner.add("texture_options", texture_options);
+    runner.add("split_screen", split_screen);
+    runner.add("texture_wrap", texture_wrap);
+    runner.add("render_view", render_view);
+    runner.add("draw_tiled", draw_tiled);
+    runner.add("deep_clone", deep_clone);
     runner.add("color_modulation", color_modulation);
+    runner.add("color_mask", color_mask);
+    runner.add("depth_only", depth_only);
+    runner.add("occlusion_query", occlusion_query);
+    runner.add("masked_rectangle", masked_rectangle);
+    runner.add("try_draw", try_draw);
+    runner.add("sanitize_config", sanitize_config);
     runner.add("flip_vertically", flip_vertically);
     runner.add("section_drawing", section_drawing);
     runner.add("section_offset", section_offset);
@@ -298,6 +2600,59 @@ fn main() {
     runner.add("debug_lines", debug_lines);
     runner.add("debug_rectangle", debug_rectangle);
     runner.add("lines_offset", lines_offset);
+    runner.add("bake_frames", bake_frames);
+    runner.add("composite", composite);
+    runner.add("minimap", minimap);
+    runner.add("reflection", reflection);
+    runner.add("afterimage", afterimage);
+    runner.add("clear_region", clear_region);
+    runner.add("contrast", contrast);
+    runner.add("rotation_center", rotation_center);
+    runner.add("transform", transform);
+    runner.add("video_texture", video_texture);
+    runner.add("premultiplied_alpha", premultiplied_alpha);
+    runner.add("linear_gradient", linear_gradient);
+    runner.add("value_noise", value_noise);
+    runner.add("perlin_noise", perlin_noise);
+    runner.add("game_of_life", game_of_life);
+    runner.add("shadow", shadow);
+    runner.add("custom_shader", custom_shader);
+    runner.add("shader_globals", shader_globals);
+    runner.add("shader_time", shader_time);
+    runner.add("shader_noise", shader_noise);
+    runner.add("draw_fullscreen", draw_fullscreen);
+    runner.add("render_graph", render_graph);
+    runner.add("border_color", border_color);
+    runner.add("sprite_batch", sprite_batch);
+    runner.add("atlas_extrusion", atlas_extrusion);
+    runner.add("queued_draws", queued_draws);
+    runner.add("sampler", sampler);
+    runner.add("bitmap_font", bitmap_font);
+    runner.add("y_down", y_down);
+    runner.add("input", input);
+    runner.add("camera", camera);
+    runner.add("clipped", clipped);
+    runner.add("pixel_perfect", pixel_perfect);
+    runner.add("render_profile", render_profile);
+    runner.add("texture_copy", texture_copy);
+    runner.add("texture_label", texture_label);
+    runner.add("draw_list", draw_list);
+    runner.add("write_section", write_section);
+    runner.add("animation", animation);
+    runner.add("texture_packer", texture_packer);
+    runner.add("frame", frame);
+    runner.add("counters", counters);
+    runner.add("aseprite_animation", aseprite_animation);
+    runner.add("primitive_palette", primitive_palette);
+    runner.add("particles", particles);
+    #[cfg(feature = "aseprite")]
+    runner.add("aseprite_file", aseprite_file);
+    #[cfg(feature = "ldtk")]
+    runner.add("ldtk_level", ldtk_level);
+    #[cfg(feature = "tiled")]
+    runner.add("tiled_map", tiled_map);
+    #[cfg(feature = "ttf")]
+    runner.add("ttf_font", ttf_font);
 
     std::process::exit(runner.run())
 }
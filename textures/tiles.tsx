@@ -0,0 +1,4 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" tiledversion="1.10.2" name="tiles" tilewidth="4" tileheight="4" tilecount="4" columns="2">
+ <image source="tiles.png" width="8" height="8"/>
+</tileset>